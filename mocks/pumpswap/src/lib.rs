@@ -0,0 +1,251 @@
+// ---------- Mock PumpSwap (local testing only) ----------
+//
+// Just enough of the PumpSwap AMM for CTO Pools' buy-and-burn leg to run against
+// solana-test-validator. Load it at the real PumpSwap address so the pool's program check passes:
+//
+//   solana-test-validator --bpf-program pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA mock_pumpswap.so
+//
+// What matches the real program: the `buy` discriminator, the 17-account order, vaults owned by
+// the pool account, and constant-product pricing with a quote-side fee. What doesn't: global
+// config, creator fees, LP tokens, events. `set_fail_buys` forces `buy` to error so the
+// swap-failure path (the crank reverts, burn lamports stay pending) can be exercised.
+
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+
+declare_id!("pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA");
+
+const BPS_DENOMINATOR: u128 = 10_000;
+
+#[program]
+pub mod mock_pumpswap {
+    use super::*;
+
+    /// Creates a pool and its two vaults. Seed liquidity by minting/transferring into the vaults.
+    pub fn create_pool(ctx: Context<CreatePool>, fee_bps: u16) -> Result<()> {
+        require!((fee_bps as u128) < BPS_DENOMINATOR, MockError::InvalidFee);
+        let pool = &mut ctx.accounts.pool;
+        pool.base_mint = ctx.accounts.base_mint.key();
+        pool.quote_mint = ctx.accounts.quote_mint.key();
+        pool.base_vault = ctx.accounts.base_vault.key();
+        pool.quote_vault = ctx.accounts.quote_vault.key();
+        pool.fee_bps = fee_bps;
+        pool.fail_buys = false;
+        pool.bump = ctx.bumps.pool;
+        Ok(())
+    }
+
+    /// Makes every subsequent `buy` on this pool fail (or succeed again).
+    pub fn set_fail_buys(ctx: Context<SetFailBuys>, fail: bool) -> Result<()> {
+        ctx.accounts.pool.fail_buys = fail;
+        Ok(())
+    }
+
+    /// Buys exactly `base_amount_out` base tokens for at most `max_quote_amount_in` quote
+    /// (fee included). Same signature and account order as PumpSwap `buy`.
+    pub fn buy(ctx: Context<Buy>, base_amount_out: u64, max_quote_amount_in: u64) -> Result<()> {
+        let pool = &ctx.accounts.pool;
+        require!(!pool.fail_buys, MockError::ForcedFailure);
+        require!(base_amount_out > 0, MockError::ZeroAmount);
+
+        let base_reserve = ctx.accounts.pool_base_token_account.amount as u128;
+        let quote_reserve = ctx.accounts.pool_quote_token_account.amount as u128;
+        require!(base_reserve > base_amount_out as u128, MockError::InsufficientLiquidity);
+
+        // x * y = k, rounded up against the buyer
+        let numerator = (base_amount_out as u128)
+            .checked_mul(quote_reserve)
+            .ok_or(MockError::MathOverflow)?;
+        let denominator = base_reserve - base_amount_out as u128;
+        let quote_in = numerator.div_ceil(denominator);
+        let fee = (quote_in * pool.fee_bps as u128).div_ceil(BPS_DENOMINATOR);
+        let total_in = quote_in.checked_add(fee).ok_or(MockError::MathOverflow)?;
+        require!(total_in <= max_quote_amount_in as u128, MockError::ExceededSlippage);
+
+        let quote_in = quote_in as u64;
+        let fee = fee as u64;
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.quote_token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.user_quote_token_account.to_account_info(),
+                    to: ctx.accounts.pool_quote_token_account.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            ),
+            quote_in,
+        )?;
+        if fee > 0 {
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.quote_token_program.to_account_info(),
+                    token::Transfer {
+                        from: ctx.accounts.user_quote_token_account.to_account_info(),
+                        to: ctx.accounts.protocol_fee_recipient_token_account.to_account_info(),
+                        authority: ctx.accounts.user.to_account_info(),
+                    },
+                ),
+                fee,
+            )?;
+        }
+
+        let base_mint = pool.base_mint;
+        let quote_mint = pool.quote_mint;
+        let seeds: &[&[u8]] = &[b"pool", base_mint.as_ref(), quote_mint.as_ref(), &[pool.bump]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.base_token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.pool_base_token_account.to_account_info(),
+                    to: ctx.accounts.user_base_token_account.to_account_info(),
+                    authority: ctx.accounts.pool.to_account_info(),
+                },
+                &[seeds],
+            ),
+            base_amount_out,
+        )?;
+
+        msg!("mock buy: base_out={} quote_in={} fee={}", base_amount_out, quote_in, fee);
+        Ok(())
+    }
+}
+
+// ============= Accounts =============
+
+#[derive(Accounts)]
+pub struct CreatePool<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + MockPool::SIZE,
+        seeds = [b"pool", base_mint.key().as_ref(), quote_mint.key().as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, MockPool>,
+
+    pub base_mint: Account<'info, Mint>,
+    pub quote_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"base_vault", pool.key().as_ref()],
+        bump,
+        token::mint = base_mint,
+        token::authority = pool
+    )]
+    pub base_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"quote_vault", pool.key().as_ref()],
+        bump,
+        token::mint = quote_mint,
+        token::authority = pool
+    )]
+    pub quote_vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetFailBuys<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, MockPool>,
+}
+
+/// Field order mirrors PumpSwap `buy`; CTO Pools passes these positionally.
+#[derive(Accounts)]
+pub struct Buy<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, MockPool>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: not modelled by the mock
+    pub global_config: UncheckedAccount<'info>,
+
+    #[account(address = pool.base_mint)]
+    pub base_mint: Account<'info, Mint>,
+
+    #[account(address = pool.quote_mint)]
+    pub quote_mint: Account<'info, Mint>,
+
+    #[account(mut, token::mint = base_mint)]
+    pub user_base_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, token::mint = quote_mint)]
+    pub user_quote_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = pool.base_vault)]
+    pub pool_base_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = pool.quote_vault)]
+    pub pool_quote_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: not modelled by the mock
+    pub protocol_fee_recipient: UncheckedAccount<'info>,
+
+    #[account(mut, token::mint = quote_mint)]
+    pub protocol_fee_recipient_token_account: Account<'info, TokenAccount>,
+
+    pub base_token_program: Program<'info, Token>,
+    pub quote_token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// CHECK: not modelled by the mock
+    pub event_authority: UncheckedAccount<'info>,
+
+    /// CHECK: self
+    pub program: UncheckedAccount<'info>,
+}
+
+// ============= State =============
+
+#[account]
+pub struct MockPool {
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub base_vault: Pubkey,
+    pub quote_vault: Pubkey,
+    pub fee_bps: u16,
+    pub fail_buys: bool,
+    pub bump: u8,
+}
+
+impl MockPool {
+    pub const SIZE: usize = 32 // base_mint
+        + 32 // quote_mint
+        + 32 // base_vault
+        + 32 // quote_vault
+        + 2 // fee_bps
+        + 1 // fail_buys
+        + 1; // bump
+}
+
+// ============= Errors =============
+
+#[error_code]
+pub enum MockError {
+    #[msg("Fee must be below 100%")]
+    InvalidFee,
+    #[msg("Buy forced to fail")]
+    ForcedFailure,
+    #[msg("Amount must be greater than zero")]
+    ZeroAmount,
+    #[msg("Not enough base liquidity")]
+    InsufficientLiquidity,
+    #[msg("Quote required exceeds max_quote_amount_in")]
+    ExceededSlippage,
+    #[msg("Math overflow")]
+    MathOverflow,
+}
@@ -5,44 +5,148 @@ use anchor_lang::solana_program::{
     instruction::{AccountMeta, Instruction},
     program::{invoke, invoke_signed},
     system_instruction,
+    sysvar::instructions as sysvar_instructions,
 };
 use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::token::{self, Mint, Token, TokenAccount};
+use anchor_spl::token_interface;
 
 // SPL Stake Pool (used for SOL<->LST)
 use spl_stake_pool::{instruction as stake_pool_ix, state::StakePool};
 
 declare_id!("4T9SkpDeDyC8KWKrcGsVQ6wG14H46og6f9pBFNc1Csje");
 
+#[cfg(all(feature = "devnet", feature = "mainnet"))]
+compile_error!("features `devnet` and `mainnet` are mutually exclusive; build devnet with `--no-default-features --features devnet`");
+
+#[cfg(feature = "client")]
+pub mod replay;
+
 // ============= Constants =============
 
 /// 1% protocol fee
 const PROTOCOL_FEE_BPS: u16 = 100;
-/// 30% quorum
-const QUORUM_BPS: u16 = 3000;
+/// Quorum bounds for `create_pool` and ConfigChange proposals (10%..100%)
+const MIN_QUORUM_BPS: u16 = 1000;
+const MAX_QUORUM_BPS: u16 = 10_000;
+/// Lamports in one SOL (burn-auction prices are quoted per SOL)
+const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
 /// 1 SOL minimum proposer value
 const MIN_PROPOSER_DEPOSIT_LAMPORTS: u64 = 1_000_000_000;
-/// 20% voting cap per wallet
-const MAX_VOTER_BPS: u16 = 2000;
+/// 0.1 SOL minimum `donate_sol_for` credit from a payer other than the beneficiary: a credit
+/// restarts the beneficiary's proposer delay and vote ramp, so griefing them costs a real gift
+const MIN_THIRD_PARTY_CREDIT_LAMPORTS: u64 = 100_000_000;
+/// Per-wallet voting cap bounds for `create_pool` (5%..100%; 100% disables the cap)
+const MIN_VOTER_CAP_BPS: u16 = 500;
+const MAX_VOTER_CAP_BPS: u16 = 10_000;
+/// 30% of pool LST is the default ceiling for a single proposal
+const MAX_PROPOSAL_BPS: u16 = 3000;
+/// 0.1 SOL default floor for a single proposal
+const MIN_PROPOSAL_LAMPORTS: u64 = 100_000_000;
+
+/// Current layout versions, stored as the first byte after the discriminator. Bump the constant
+/// and add an `upgrade_layout` step whenever a released layout changes. Version 0 is the
+/// unversioned launch layout (`PoolV0`, `DonorV0`, `ProposalV0`), recognized by its length.
+const POOL_VERSION: u8 = 1;
+const DONOR_VERSION: u8 = 1;
+const PROPOSAL_VERSION: u8 = 1;
+
+/// Shares per LST base unit for a pool's first donation. Keeps proportional mints into large
+/// pools from losing meaningful value to integer division.
+const SHARE_SCALE: u64 = 1_000;
+
+/// Virtual LST backing every pool's share price (0.001 LST), paired with
+/// VIRTUAL_POOL_TOKENS * share_scale virtual shares. Makes first-depositor inflation
+/// (mint dust shares, then top up the LST account directly) unprofitable.
+const VIRTUAL_POOL_TOKENS: u64 = 1_000_000;
+
+/// Decimals of a pool's share mint; at SHARE_SCALE one share token is worth about one LST
+const SHARE_MINT_DECIMALS: u8 = 12;
+
+/// Minimum shares minted by a single donation (0.001 LST at SHARE_SCALE); Donor balances below this are dust
+const MIN_SHARES_MINTED: u64 = 1_000_000 * SHARE_SCALE;
+
+/// Minimum interval between folds of rounding dust back into withdrawable TVL (1 day)
+const DUST_FOLD_INTERVAL_SECONDS: i64 = 24 * 60 * 60;
 
 /// Proposal buffer: 50 bps (0.50%)
 const PROPOSAL_BUFFER_BPS: u64 = 50;
 
-/// NOTE: restore to ~216_000 for production (~1 day)
-const MIN_PROPOSAL_DELAY_SLOTS: u64 = 18_000; // ~2 hours at ~400ms/slot
+/// Minimum share age before a donor can propose. Selected by the `devnet` feature (mainnet otherwise).
+#[cfg(not(feature = "devnet"))]
+const MIN_PROPOSAL_DELAY_SLOTS: u64 = 216_000; // ~1 day at ~400ms/slot
+#[cfg(feature = "devnet")]
+const MIN_PROPOSAL_DELAY_SLOTS: u64 = 150; // ~1 minute, for test cycles
+/// Longest share age a ConfigChange can require before proposing (~30 days at ~400ms/slot)
+const MAX_PROPOSAL_DELAY_SLOTS: u64 = 6_480_000;
+
+/// Highest protocol fee a ConfigChange can set (10%)
+const MAX_PROTOCOL_FEE_BPS: u16 = 1000;
 
 /// Minimum delay before an abort vote is eligible (~2 hours at ~400ms/slot)
 const MIN_ABORTER_DELAY_SLOTS: u64 = 18_000;
 
+/// Longest vote-weight ramp a pool can configure for time-weighted voting (~30 days at ~400ms/slot)
+const MAX_VOTE_RAMP_SLOTS: u64 = 6_480_000;
+
 /// Minimum time after proposal creation before abort can be finalized (4 hours)
 const MIN_ABORT_REVIEW_SECONDS: i64 = 4 * 60 * 60;
 
 /// Cooldown period after an abort before new proposals can be created (6 hours)
 const COOLDOWN_AFTER_ABORT_SECONDS: i64 = 6 * 60 * 60;
 
+/// Default gap between a proposal finalizing and the next one being created (1 hour)
+const DEFAULT_PROPOSAL_GAP_SECONDS: i64 = 60 * 60;
+
+/// Base cooldown before a destination can be targeted again after a failed proposal (3 days).
+/// Doubles with each further failure for the same destination.
+const DESTINATION_COOLDOWN_SECONDS: i64 = 3 * 24 * 60 * 60;
+/// Cap on the cooldown doubling (3 days * 2^5 = 96 days)
+const MAX_DESTINATION_COOLDOWN_SHIFT: u16 = 5;
+
+/// After this long past a deadline, anyone may close a resolved vote record or recovery
+/// (rent still goes to its payer) (30 days)
+const CLOSE_GRACE_SECONDS: i64 = 30 * 24 * 60 * 60;
+
 /// Minimum time after proposal creation before execution is allowed (4 hours)
 const MIN_EXECUTE_DELAY_SECONDS: i64 = 4 * 60 * 60;
 
+/// Time after the deadline that a proposal which may still pass is left for `execute_proposal`
+/// before `finalize_expired_proposal` can fail it (3 days)
+const EXECUTION_WINDOW_SECONDS: i64 = 3 * 24 * 60 * 60;
+
+/// Longest execution timelock a pool can configure (14 days)
+const MAX_TIMELOCK_SECONDS: i64 = 14 * 24 * 60 * 60;
+
+/// Most tranches a milestone payout can be split into
+const MAX_MILESTONE_TRANCHES: u8 = 12;
+
+/// How long a confirm-vote milestone tranche waits for confirmation before
+/// `cancel_milestone_payout` can release the rest of the escrow (30 days)
+const MILESTONE_CONFIRM_WINDOW_SECONDS: i64 = 30 * 24 * 60 * 60;
+
+/// Longest vesting period a stream payout can run for (2 years)
+const MAX_STREAM_SECONDS: i64 = 2 * 365 * 24 * 60 * 60;
+
+/// Number of amount-based quorum tiers a pool can configure
+pub const QUORUM_TIER_COUNT: usize = 3;
+
+/// Share checkpoints kept per Donor; votes snapshotted before the oldest one fall back to
+/// all-or-nothing eligibility
+pub const SHARE_CHECKPOINT_COUNT: usize = 8;
+
+/// Lowest quorum a pool can set for fast-tracked small payouts (5%)
+const MIN_FAST_TRACK_QUORUM_BPS: u16 = 500;
+
+/// Bounds on the period of a recurring budget (1 day to 90 days)
+const MIN_BUDGET_PERIOD_SECONDS: i64 = 24 * 60 * 60;
+const MAX_BUDGET_PERIOD_SECONDS: i64 = 90 * 24 * 60 * 60;
+
+/// Most co-sponsors a pool can require per proposal
+const MAX_REQUIRED_SPONSORS: u8 = 10;
+/// Time a proposal has to collect its co-sponsors before it expires unvoted (3 days)
+const SPONSOR_WINDOW_SECONDS: i64 = 3 * 24 * 60 * 60;
+
 // Buy & burn slippage tolerance (bps). Larger means more tolerant (less likely to fail), but weaker price protection.
 const MAX_SLIPPAGE_BPS: u64 = 1500; // 15%
 
@@ -50,8 +154,33 @@ const MAX_SLIPPAGE_BPS: u64 = 1500; // 15%
 // Keep conservative while upgrade authority is retained.
 const PUMPSWAP_FEE_BPS_ESTIMATE: u64 = 50; // 0.50%
 
-// Legacy Raydium swap constants (kept optional)
-const RAYDIUM_SWAP_INSTRUCTION: u8 = 9;
+// Compute units a single swap leg needs (wrap + buy + settle). A best-effort burn with less
+// remaining budget is skipped up front instead of exhausting the transaction mid-CPI.
+const MIN_SWAP_LEG_COMPUTE_UNITS: u64 = 120_000;
+
+// Estimated Raydium CPMM trade fee (bps); fee tiers run 0.25%-4%, memecoin pools mostly 1%.
+const RAYDIUM_CPMM_FEE_BPS_ESTIMATE: u64 = 100; // 1.00%
+
+/// Anchor discriminator of Pyth's PriceUpdateV2 account.
+const PYTH_PRICE_UPDATE_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+/// Oldest oracle price accepted for valuing a donation (seconds)
+const MAX_ORACLE_AGE_SECONDS: i64 = 60;
+/// Widest oracle confidence interval accepted, relative to price (2%)
+const MAX_ORACLE_CONF_BPS: u64 = 200;
+/// Widest band accepted by `configure_oracle_guards` (50%)
+const MAX_ORACLE_GUARD_BAND_BPS: u16 = 5_000;
+
+/// Deepest proof accepted by `vote_merkle` (2^24 leaves).
+const MAX_MERKLE_PROOF_LEN: usize = 24;
+
+/// Size of a stake account (`StakeStateV2`).
+const STAKE_ACCOUNT_SPACE: usize = 200;
+/// Size of an SPL Token account.
+const TOKEN_ACCOUNT_SPACE: usize = 165;
+
+/// Stake program instruction indices (bincode u32 enum tags).
+const STAKE_IX_WITHDRAW: u32 = 4;
+const STAKE_IX_DEACTIVATE: u32 = 5;
 
 /// BPS denominator
 const BPS_DENOM: u64 = 10_000;
@@ -63,6 +192,17 @@ pub const WSOL_MINT: Pubkey = pubkey!("So111111111111111111111111111111111111111
 /// IMPORTANT: Treat this as a protocol dependency. Keep upgrade authority during beta to respond to upstream changes.
 pub const PUMPSWAP_PROGRAM_ID: Pubkey = pubkey!("pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA");
 
+/// Pyth Solana receiver program; owns the PriceUpdateV2 accounts read by `read_pyth_price`.
+/// Native stake program (WithdrawStake fallback accounts are owned by it).
+pub const STAKE_PROGRAM_ID: Pubkey = pubkey!("Stake11111111111111111111111111111111111111");
+
+pub const PYTH_RECEIVER_PROGRAM_ID: Pubkey = pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+
+/// Squads v4 multisig program; vaults are PDAs ['multisig', multisig, 'vault', index] under it.
+pub const SQUADS_V4_PROGRAM_ID: Pubkey = pubkey!("SQDS4ep65T869zMMBKyuUq6aD6EgTu8psMjkvj52pCf");
+/// Anchor discriminator of Squads v4's Multisig account.
+const SQUADS_MULTISIG_DISCRIMINATOR: [u8; 8] = [224, 116, 121, 186, 68, 161, 79, 236];
+
 /// PumpSwap `buy` discriminator (Anchor-style 8-byte discriminator).
 /// Args: (base_amount_out: u64, max_quote_amount_in: u64)
 ///
@@ -70,6 +210,63 @@ pub const PUMPSWAP_PROGRAM_ID: Pubkey = pubkey!("pAMMBay6oceH9fJKBRHGP5D4bD4sWpm
 /// If PumpSwap updates, this may change; keep upgrade authority until stable.
 const PUMPSWAP_BUY_DISCRIMINATOR: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
 
+/// Orca Whirlpool program id (concentrated-liquidity venue some graduated tokens migrate to).
+pub const WHIRLPOOL_PROGRAM_ID: Pubkey = pubkey!("whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc");
+
+/// Whirlpool `swap` discriminator.
+/// Args: (amount: u64, other_amount_threshold: u64, sqrt_price_limit: u128,
+///        amount_specified_is_input: bool, a_to_b: bool)
+const WHIRLPOOL_SWAP_DISCRIMINATOR: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+
+/// Whirlpool sqrt-price bounds (Q64.64), passed as the limit so a swap is bounded by min-out only.
+const WHIRLPOOL_MIN_SQRT_PRICE: u128 = 4_295_048_016;
+const WHIRLPOOL_MAX_SQRT_PRICE: u128 = 79_226_673_515_401_279_992_447_579_055;
+
+/// Raydium CPMM (constant-product AMM that replaced AMM v4 for new pools) program id.
+pub const RAYDIUM_CPMM_PROGRAM_ID: Pubkey = pubkey!("CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C");
+
+/// Raydium CPMM `swap_base_input` discriminator.
+/// Args: (amount_in: u64, minimum_amount_out: u64)
+const RAYDIUM_CPMM_SWAP_BASE_INPUT_DISCRIMINATOR: [u8; 8] = [143, 190, 90, 218, 196, 30, 51, 222];
+
+/// Raydium CPMM `deposit` discriminator.
+/// Args: (lp_token_amount: u64, maximum_token_0_amount: u64, maximum_token_1_amount: u64)
+const RAYDIUM_CPMM_DEPOSIT_DISCRIMINATOR: [u8; 8] = [242, 35, 198, 137, 82, 225, 242, 182];
+
+/// Raydium CPMM `withdraw` discriminator.
+/// Args: (lp_token_amount: u64, minimum_token_0_amount: u64, minimum_token_1_amount: u64)
+const RAYDIUM_CPMM_WITHDRAW_DISCRIMINATOR: [u8; 8] = [183, 18, 70, 156, 148, 109, 161, 34];
+
+/// Byte offsets of the vaults in a Raydium CPMM `PoolState` account (zero-copy).
+const RAYDIUM_CPMM_TOKEN_0_VAULT_OFFSET: usize = 72;
+const RAYDIUM_CPMM_TOKEN_1_VAULT_OFFSET: usize = 104;
+const RAYDIUM_CPMM_LP_MINT_OFFSET: usize = 136;
+
+/// SPL Memo program; Raydium CPMM `withdraw` takes it for Token-2022 memo-required accounts.
+pub const SPL_MEMO_PROGRAM_ID: Pubkey = pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+/// Meteora DLMM (`lb_clmm`) program id.
+pub const METEORA_DLMM_PROGRAM_ID: Pubkey = pubkey!("LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo");
+
+/// Meteora DLMM `swap` discriminator (same Anchor name as Whirlpool's).
+/// Args: (amount_in: u64, min_amount_out: u64)
+const METEORA_DLMM_SWAP_DISCRIMINATOR: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+
+// Estimated DLMM base + variable fee (bps), for min-out modelling only (like PUMPSWAP_FEE_BPS_ESTIMATE).
+const METEORA_DLMM_FEE_BPS_ESTIMATE: u64 = 100; // 1.00%
+
+/// Byte offsets into a Meteora `LbPair` account (zero-copy, after the 8-byte discriminator).
+const METEORA_ACTIVE_ID_OFFSET: usize = 76; // i32
+const METEORA_BIN_STEP_OFFSET: usize = 80; // u16, bps
+const METEORA_RESERVE_X_OFFSET: usize = 153;
+const METEORA_RESERVE_Y_OFFSET: usize = 185;
+
+/// Byte offsets into a Whirlpool account (after the 8-byte discriminator).
+const WHIRLPOOL_FEE_RATE_OFFSET: usize = 45; // u16, hundredths of a bp
+const WHIRLPOOL_SQRT_PRICE_OFFSET: usize = 65; // u128, Q64.64
+const WHIRLPOOL_TOKEN_VAULT_A_OFFSET: usize = 133;
+const WHIRLPOOL_TOKEN_VAULT_B_OFFSET: usize = 213;
+
 // Jito Stake Pool references
 pub const JITO_MAINNET_STAKE_POOL_PROGRAM: Pubkey =
     pubkey!("SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy");
@@ -84,20 +281,99 @@ pub const JITO_DEVNET_STAKE_POOL: Pubkey = pubkey!("JitoY5pcAxWX6iyP2QdFwTznGb8A
 pub const JITO_DEVNET_JITOSOL_MINT: Pubkey =
     pubkey!("J1tos8mqbhdGcF3pgj4PCKyVjzWSURcpLZU7pPGHxSYi");
 
+// Stake pool whitelist for the cluster this build targets. A devnet build rejects the mainnet
+// pool and vice versa, so a test build can't be pointed at real funds.
+#[cfg(not(feature = "devnet"))]
+pub const STAKE_POOL_PROGRAM: Pubkey = JITO_MAINNET_STAKE_POOL_PROGRAM;
+#[cfg(not(feature = "devnet"))]
+pub const STAKE_POOL: Pubkey = JITO_MAINNET_STAKE_POOL;
+#[cfg(not(feature = "devnet"))]
+pub const JITOSOL_MINT: Pubkey = JITO_MAINNET_JITOSOL_MINT;
+#[cfg(feature = "devnet")]
+pub const STAKE_POOL_PROGRAM: Pubkey = JITO_DEVNET_STAKE_POOL_PROGRAM;
+#[cfg(feature = "devnet")]
+pub const STAKE_POOL: Pubkey = JITO_DEVNET_STAKE_POOL;
+#[cfg(feature = "devnet")]
+pub const JITOSOL_MINT: Pubkey = JITO_DEVNET_JITOSOL_MINT;
+
 // ============= Constants for Security Fixes =============
 
+
 /// H-05 FIX: PumpSwap version tracking
 const PUMPSWAP_EXPECTED_VERSION: u8 = 1;
 
 /// Proposal duration: 24 hours
 const PROPOSAL_DURATION_SECONDS: i64 = 24 * 60 * 60;
 
+/// Inactivity (no donations, proposals or votes) after which a pool can be sunset (365 days)
+const ABANDONED_POOL_SECONDS: i64 = 365 * 24 * 60 * 60;
+/// Withdrawal-only period between starting a sunset and closing the pool (180 days)
+const SUNSET_CLOSE_GRACE_SECONDS: i64 = 180 * 24 * 60 * 60;
+/// How long a destination has to pull a credited payout before it may return to the pool (90 days)
+const PAYOUT_CLAIM_EXPIRY_SECONDS: i64 = 90 * 24 * 60 * 60;
+
+/// Smallest non-zero dead-man switch window, in epochs (~2 days each)
+const MIN_DEAD_MAN_EPOCHS: u64 = 5;
+
+/// Default bounds for the proposer-selected voting window (12 hours to 7 days)
+const DEFAULT_MIN_VOTING_SECONDS: i64 = 12 * 60 * 60;
+const DEFAULT_MAX_VOTING_SECONDS: i64 = 7 * 24 * 60 * 60;
+/// Hard ceiling for any pool's maximum voting window (30 days)
+const MAX_VOTING_WINDOW_SECONDS: i64 = 30 * 24 * 60 * 60;
+
+/// Default tolerance applied when a donor passes 0 as their minimum out (1%)
+const DEFAULT_DONOR_SLIPPAGE_BPS: u16 = 100;
+/// Highest tolerance a pool may configure for derived donor minimums (10%)
+const MAX_DONOR_SLIPPAGE_BPS: u16 = 1_000;
+
+/// Largest referral bonus a pool may configure, as bps of the referred donation's shares (5%)
+const MAX_REFERRAL_BPS: u16 = 500;
+
+/// Highest match ratio a matching campaign may offer (3 lamports per donated lamport)
+const MAX_MATCH_BPS: u16 = 30_000;
+/// Longest match window of a matching campaign (30 days)
+const MAX_MATCH_WINDOW_SECONDS: i64 = 30 * 24 * 60 * 60;
+
+/// Bounds on the reveal window of a commit–reveal proposal (1 hour to 3 days)
+const MIN_REVEAL_SECONDS: i64 = 60 * 60;
+const MAX_REVEAL_SECONDS: i64 = 3 * 24 * 60 * 60;
+
+/// One-time voting extension for a tied proposal under TieBreakRule::ExtendVoting (12 hours)
+const TIE_EXTENSION_SECONDS: i64 = 12 * 60 * 60;
+
+/// A majority-Yes proposal whose participation is within this many bps of quorum gets one grace extension
+const QUORUM_GRACE_BPS: u16 = 200;
+/// Length of the near-quorum grace extension (12 hours)
+const QUORUM_GRACE_EXTENSION_SECONDS: i64 = 12 * 60 * 60;
+
+/// Final stretch of voting in which a ballot that flips the outcome extends the deadline (1 hour)
+const SNIPE_WINDOW_SECONDS: i64 = 60 * 60;
+/// Bounds on an anti-sniping extension (10 minutes to 24 hours), and on how many one proposal gets
+const MIN_SNIPE_EXTENSION_SECONDS: i64 = 10 * 60;
+const MAX_SNIPE_EXTENSION_SECONDS: i64 = 24 * 60 * 60;
+const MAX_SNIPE_EXTENSIONS: u8 = 10;
+
+/// Quadratic vote weight is isqrt(shares * QUADRATIC_WEIGHT_SCALE), keeping three more digits
+const QUADRATIC_WEIGHT_SCALE: u64 = 1_000;
+
+/// After this many consecutive quorum failures, the next proposal's quorum steps down
+const QUORUM_STEP_DOWN_FAILURES: u16 = 3;
+/// Quorum reduction per QUORUM_STEP_DOWN_FAILURES consecutive failures (5%)
+const QUORUM_STEP_DOWN_BPS: u16 = 500;
+/// Largest total step-down from the pool's configured quorum (15%)
+const MAX_QUORUM_STEP_DOWN_BPS: u16 = 1_500;
+
 /// Strike reset threshold: 3 successful participations
 const STRIKE_RESET_THRESHOLD: u16 = 3;
 
 /// Minimum abort voters required
 const MIN_ABORT_VOTERS: u8 = 2;
 
+/// Longest free-text memo a donor can attach to `donate_sol`
+const DONATION_MEMO_MAX: usize = 80;
+/// Longest campaign tag a donor can attach to `donate_sol`
+const CAMPAIGN_TAG_MAX: usize = 32;
+
 // ============= Macros =============
 
 /// Generates PDA seeds for pool-signed operations.
@@ -124,6 +400,181 @@ macro_rules! unlock_pool {
     };
 }
 
+// ============= PDA derivation =============
+//
+// Canonical seeds for every program-owned account. Off-chain users should depend on this crate
+// with the `no-entrypoint` feature and call these instead of hardcoding seed strings.
+
+/// Pool PDA: ['pool', token_mint].
+pub fn pool_address(token_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"pool", token_mint.as_ref()], &ID)
+}
+
+/// Donor PDA: ['donor', pool, wallet].
+pub fn donor_address(pool: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"donor", pool.as_ref(), wallet.as_ref()], &ID)
+}
+
+/// Proposal PDA: ['proposal', pool, proposal_index_le]. `proposal_index` is `pool.proposal_count` at creation.
+pub fn proposal_address(pool: &Pubkey, proposal_index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"proposal", pool.as_ref(), &proposal_index.to_le_bytes()], &ID)
+}
+
+/// Recovery PDA: ['recovery', pool, recovery_index_le].
+pub fn recovery_address(pool: &Pubkey, recovery_index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"recovery", pool.as_ref(), &recovery_index.to_le_bytes()], &ID)
+}
+
+/// VoteRecord PDA: ['vote', proposal_or_recovery, voter]. For delegated votes `voter` is the VoteEscrow.
+pub fn vote_record_address(proposal: &Pubkey, voter: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vote", proposal.as_ref(), voter.as_ref()], &ID)
+}
+
+/// DestinationRecord PDA: ['destination', pool, destination].
+pub fn destination_record_address(pool: &Pubkey, destination: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"destination", pool.as_ref(), destination.as_ref()], &ID)
+}
+
+/// ActivityLog PDA: ['activity', pool].
+pub fn activity_log_address(pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"activity", pool.as_ref()], &ID)
+}
+
+/// PoolRegistry PDA: ['registry'].
+pub fn registry_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"registry"], &ID)
+}
+
+/// PoolRegistryPage PDA: ['registry_page', page_index_le].
+pub fn registry_page_address(page_index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"registry_page", &page_index.to_le_bytes()], &ID)
+}
+
+/// DonorIndexPage PDA: ['donor_index', pool, page_index_le].
+pub fn donor_index_page_address(pool: &Pubkey, page_index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"donor_index", pool.as_ref(), &page_index.to_le_bytes()], &ID)
+}
+
+/// ProposalIndexPage PDA: ['proposal_index', pool, page_index_le].
+pub fn proposal_index_page_address(pool: &Pubkey, page_index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"proposal_index", pool.as_ref(), &page_index.to_le_bytes()], &ID)
+}
+
+/// ProposalArchive PDA: ['archive', pool, page_index_le].
+pub fn proposal_archive_address(pool: &Pubkey, page_index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"archive", pool.as_ref(), &page_index.to_le_bytes()], &ID)
+}
+
+/// ProposalTemplate PDA: ['template', pool, template_id_le].
+pub fn template_address(pool: &Pubkey, template_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"template", pool.as_ref(), &template_id.to_le_bytes()], &ID)
+}
+
+/// SplPosition PDA: ['spl_position', spl_donation_mint, wallet].
+pub fn spl_position_address(spl_donation_mint: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"spl_position", spl_donation_mint.as_ref(), wallet.as_ref()], &ID)
+}
+
+/// SplDonationMint PDA: ['spl_mint', pool, mint].
+pub fn spl_donation_mint_address(pool: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"spl_mint", pool.as_ref(), mint.as_ref()], &ID)
+}
+
+/// PoolMetadata PDA: ['metadata', pool].
+pub fn pool_metadata_address(pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"metadata", pool.as_ref()], &ID)
+}
+
+/// ProposerBond PDA: ['bond', proposal].
+pub fn proposer_bond_address(proposal: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"bond", proposal.as_ref()], &ID)
+}
+
+/// AllowlistEntry PDA: ['allowlist', pool, wallet].
+pub fn allowlist_entry_address(pool: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"allowlist", pool.as_ref(), wallet.as_ref()], &ID)
+}
+
+/// PayoutClaim PDA: ['payout_claim', pool, destination].
+pub fn payout_claim_address(pool: &Pubkey, destination: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"payout_claim", pool.as_ref(), destination.as_ref()], &ID)
+}
+
+/// VoteCommitment PDA: ['vote_commit', proposal, wallet].
+pub fn vote_commitment_address(proposal: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vote_commit", proposal.as_ref(), wallet.as_ref()], &ID)
+}
+
+/// MatchingVault PDA: ['matching', pool].
+pub fn matching_vault_address(pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"matching", pool.as_ref()], &ID)
+}
+
+/// Share mint PDA: ['share_mint', pool].
+pub fn share_mint_address(pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"share_mint", pool.as_ref()], &ID)
+}
+
+/// Sponsorship PDA: ['sponsor', proposal, wallet].
+pub fn sponsorship_address(proposal: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"sponsor", proposal.as_ref(), wallet.as_ref()], &ID)
+}
+
+/// CapExcessRecord PDA: ['cap_excess', proposal, wallet].
+pub fn cap_excess_record_address(proposal: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"cap_excess", proposal.as_ref(), wallet.as_ref()], &ID)
+}
+
+/// VoteEscrow PDA: ['escrow', pool, delegate].
+pub fn vote_escrow_address(pool: &Pubkey, delegate: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"escrow", pool.as_ref(), delegate.as_ref()], &ID)
+}
+
+/// Delegation PDA: ['delegation', pool, delegator].
+pub fn delegation_address(pool: &Pubkey, delegator: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"delegation", pool.as_ref(), delegator.as_ref()], &ID)
+}
+
+/// Pool WSOL token account PDA: ['pool_wsol', pool].
+pub fn pool_wsol_address(pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"pool_wsol", pool.as_ref()], &ID)
+}
+
+/// Pool burn-token account PDA: ['pool_ctop', pool, burn_token_mint].
+pub fn pool_ctop_address(pool: &Pubkey, burn_token_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"pool_ctop", pool.as_ref(), burn_token_mint.as_ref()], &ID)
+}
+
+/// Pool LP token account PDA: ['pool_lp', pool].
+pub fn pool_lp_address(pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"pool_lp", pool.as_ref()], &ID)
+}
+
+/// Pool liquidity WSOL token account PDA: ['pool_liquidity_wsol', pool].
+pub fn pool_liquidity_wsol_address(pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"pool_liquidity_wsol", pool.as_ref()], &ID)
+}
+
+/// Pool intermediate-hop token account PDA: ['pool_hop', pool, hop_mint].
+pub fn pool_hop_address(pool: &Pubkey, hop_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"pool_hop", pool.as_ref(), hop_mint.as_ref()], &ID)
+}
+
+/// StakeWithdrawal PDA: ['stake_withdrawal', pool, index_le]. `index` is `pool.stake_withdrawal_count` at creation.
+pub fn stake_withdrawal_address(pool: &Pubkey, index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"stake_withdrawal", pool.as_ref(), &index.to_le_bytes()], &ID)
+}
+
+/// WithdrawalRequest PDA: ['withdrawal_request', pool, index_le]. `index` is `pool.withdrawal_queue_tail` at creation.
+pub fn withdrawal_request_address(pool: &Pubkey, index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"withdrawal_request", pool.as_ref(), &index.to_le_bytes()], &ID)
+}
+
+/// Pool-owned stake account of a StakeWithdrawal: ['withdrawal_stake', pool, index_le].
+pub fn withdrawal_stake_address(pool: &Pubkey, index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"withdrawal_stake", pool.as_ref(), &index.to_le_bytes()], &ID)
+}
+
 // ============= Program =============
 
 #[program]
@@ -132,8 +583,15 @@ pub mod cto_pools {
 
     /// Create one pool per token mint.
     ///
+    /// `token_mint` may be a classic SPL Token or a Token-2022 mint.
     /// `stake_pool_program`, `stake_pool`, and `lst_mint` configure the liquid staking backend.
-    /// For devnet testing, use the devnet constants in this file.
+    /// For devnet testing, build with the `devnet` feature.
+    /// `quorum_bps` (MIN_QUORUM_BPS..=MAX_QUORUM_BPS) and `max_voter_bps`
+    /// (MIN_VOTER_CAP_BPS..=MAX_VOTER_CAP_BPS) set the pool's quorum and per-wallet voting cap;
+    /// 3000 and 2000 match the former fixed values.
+    /// `custody` picks staked (LST) or native SOL custody; native pools keep donations as
+    /// lamports in the pool PDA and never call the stake pool.
+    #[allow(clippy::too_many_arguments)]
     pub fn create_pool(
         ctx: Context<CreatePool>,
         dev_fee_wallet: Pubkey,
@@ -141,26 +599,47 @@ pub mod cto_pools {
         stake_pool_program: Pubkey,
         stake_pool: Pubkey,
         lst_mint: Pubkey,
+        quorum_bps: u16,
+        max_voter_bps: u16,
+        custody: PoolCustody,
     ) -> Result<()> {
         validate_stake_pool_config(stake_pool_program, stake_pool, lst_mint)?;
+        require!(
+            (MIN_QUORUM_BPS..=MAX_QUORUM_BPS).contains(&quorum_bps)
+                && (MIN_VOTER_CAP_BPS..=MAX_VOTER_CAP_BPS).contains(&max_voter_bps),
+            CtoError::InvalidGovernanceParams
+        );
 
         let pool = &mut ctx.accounts.pool;
 
         // Identity / ownership
+        pool.version = POOL_VERSION;
         pool.token_mint = ctx.accounts.token_mint.key();
         pool.authority = ctx.accounts.creator.key();
+        pool.mint_authority_set = ctx.accounts.token_mint.mint_authority.is_some();
+        pool.freeze_authority_set = ctx.accounts.token_mint.freeze_authority.is_some();
         pool.creator = ctx.accounts.creator.key();
+        pool.pending_authority = Pubkey::default();
+        pool.authority_expires_at_ts = 0;
+        pool.approved_admin_call = [0u8; 32];
 
         // Accounting
         pool.total_shares = 0;
+        pool.share_scale = SHARE_SCALE;
         pool.total_pool_tokens = 0; // will be set from on-chain balance after first deposit
         pool.reserved_pool_tokens = 0;
+        pool.rounding_dust_pool_tokens = 0;
+        pool.last_dust_fold_ts = 0;
         pool.total_spent_lamports = 0;
 
         // Governance/config
         pool.protocol_fee_bps = PROTOCOL_FEE_BPS;
-        pool.quorum_bps = QUORUM_BPS;
+        pool.quorum_bps = quorum_bps;
+        pool.max_voter_bps = max_voter_bps;
         pool.min_proposer_deposit_lamports = MIN_PROPOSER_DEPOSIT_LAMPORTS;
+        pool.proposal_delay_slots = MIN_PROPOSAL_DELAY_SLOTS;
+        pool.max_proposal_bps = MAX_PROPOSAL_BPS;
+        pool.min_proposal_lamports = MIN_PROPOSAL_LAMPORTS;
 
         // Fee outputs
         pool.dev_fee_wallet = dev_fee_wallet;
@@ -168,43 +647,171 @@ pub mod cto_pools {
 
         // Proposal tracking
         pool.active_proposal = None;
+        pool.active_poll = None;
+        pool.proposal_snapshot_slot = 0;
+        pool.poll_snapshot_slot = 0;
         pool.proposal_count = 0;
+        pool.archived_proposal_count = 0;
+        pool.template_count = 0;
+
+        // Donor enumeration
+        pool.indexed_donor_count = 0;
+        pool.donor_count = 0;
 
         // Abort governance controls
         pool.proposal_cooldown_until_ts = 0;
+        pool.proposal_gap_seconds = DEFAULT_PROPOSAL_GAP_SECONDS;
+        pool.last_proposal_finalized_ts = 0;
+        pool.donor_proposal_cooldown_slots = 0;
+        pool.consecutive_quorum_failures = 0;
+        pool.required_sponsors = 0;
+        pool.sponsor_min_share_bps = 0;
+        pool.dead_man_epochs = 0;
+        pool.paused = false;
+        pool.last_heartbeat_epoch = Clock::get()?.epoch;
+        pool.last_engagement_ts = Clock::get()?.unix_timestamp;
+        pool.sunset_started_ts = 0;
+        pool.min_voting_seconds = DEFAULT_MIN_VOTING_SECONDS;
+        pool.max_voting_seconds = DEFAULT_MAX_VOTING_SECONDS;
+        pool.donor_slippage_bps = DEFAULT_DONOR_SLIPPAGE_BPS;
+        pool.token_donation_policy = TokenDonationPolicy::NoShares;
+        pool.sol_usd_price_feed = Pubkey::default();
+        pool.ctop_usd_price_feed = Pubkey::default();
+        pool.burn_price_band_bps = 0;
+        pool.lst_rate_band_bps = 0;
+        pool.campaign_goal_lamports = 0;
+        pool.campaign_deadline_ts = 0;
+        pool.campaign_raised_lamports = 0;
+        pool.campaign_goal_reached = false;
+        pool.referral_bps = 0;
+        pool.referral_cap_shares = 0;
+        pool.referral_shares_minted = 0;
+        pool.min_donation_lamports = 0;
+        pool.max_donor_share_bps = 0;
+        pool.allowlist_enabled = false;
+        pool.proposer_bond_lamports = 0;
+        pool.bond_spam_threshold_bps = 0;
+        pool.commit_reveal_seconds = 0;
+        pool.snipe_extension_seconds = 0;
+        pool.max_snipe_extensions = 0;
+        pool.vote_ramp_slots = 0;
+        pool.share_mint = Pubkey::default();
+        pool.tokenized_shares = 0;
+        pool.queued_shares = 0;
+        pool.withdrawal_queue_head = 0;
+        pool.withdrawal_queue_tail = 0;
+        pool.spl_donated_lamports = 0;
+        pool.spl_position_shares = 0;
+        pool.treasury_token_amount = 0;
         pool.base_penalty_lamports = 100_000_000; // 0.1 SOL base penalty
 
         // Recovery tracking
         pool.active_recovery = None;
         pool.recovery_count = 0;
+        pool.stake_withdrawal_count = 0;
+        pool.open_stake_withdrawals = 0;
 
         // LST config
         pool.stake_pool_program = stake_pool_program;
         pool.stake_pool = stake_pool;
         pool.lst_mint = lst_mint;
+        pool.custody = custody;
 
         // PumpSwap buy&burn config (post Pump.fun graduation)
-        pool.pumpswap_enabled = false;
+        pool.burn_venue = BurnVenue::None;
         pool.pumpswap_pool_id = Pubkey::default();
         pool.pumpswap_base_vault = Pubkey::default();
         pool.pumpswap_quote_vault = Pubkey::default();
         pool.pumpswap_global_config = Pubkey::default();
         pool.pumpswap_fee_recipient = Pubkey::default();
         pool.pumpswap_version = PUMPSWAP_EXPECTED_VERSION; // H-05 FIX: Track PumpSwap version
-
-        // Legacy Raydium buy&burn config (optional)
-        pool.raydium_enabled = false;
+        pool.pumpswap_hop_enabled = false;
+        pool.pumpswap_hop_mint = Pubkey::default();
+        pool.pumpswap_hop_pool_id = Pubkey::default();
+        pool.pumpswap_hop_base_vault = Pubkey::default();
+        pool.pumpswap_hop_quote_vault = Pubkey::default();
+
+        // Orca Whirlpool / Meteora DLMM buy&burn config (off until configured)
+        pool.whirlpool_pool_id = Pubkey::default();
+        pool.whirlpool_token_vault_a = Pubkey::default();
+        pool.whirlpool_token_vault_b = Pubkey::default();
+        pool.meteora_lb_pair = Pubkey::default();
+        pool.meteora_reserve_x = Pubkey::default();
+        pool.meteora_reserve_y = Pubkey::default();
+
+        // Buyback destination
+        pool.buyback_mode = BuybackMode::Burn;
+        pool.held_ctop_amount = 0;
+
+        // Dutch-auction burn (off until configured)
+        pool.burn_auction_enabled = false;
+        pool.burn_auction_lamports = 0;
+        pool.burn_auction_start_ts = 0;
+        pool.burn_auction_start_ctop_per_sol = 0;
+        pool.burn_auction_floor_ctop_per_sol = 0;
+        pool.burn_auction_duration_seconds = 0;
+
+        // Scheduled burns (off: burn every execution)
+        pool.pending_burn_lamports = 0;
+        pool.burn_threshold_lamports = 0;
+        pool.burn_interval_seconds = 0;
+        pool.last_burn_ts = 0;
+
+        // Fee sink (default: buy & burn)
+        pool.fee_sink = FeeSink::Burn;
+        pool.fee_sink_destination = Pubkey::default();
+
+        // Ties fail unless reconfigured
+        pool.tie_break_rule = TieBreakRule::Fail;
+        pool.vote_weighting = VoteWeighting::Linear;
+        pool.merkle_voting = false;
+        pool.cap_adjusted_quorum = false;
+        pool.timelock_seconds = 0;
+        pool.quorum_tiers = [QuorumTier::default(); QUORUM_TIER_COUNT];
+        pool.small_payout_threshold_lamports = 0;
+        pool.fast_track_quorum_bps = 0;
+        pool.fast_track_voting_seconds = 0;
+
+        // Raydium CPMM buy&burn config (off until configured)
         pool.raydium_pool_id = Pubkey::default();
+        pool.raydium_token_0_vault = Pubkey::default();
+        pool.raydium_token_1_vault = Pubkey::default();
+        pool.pending_liquidity_lamports = 0;
+        pool.pending_liquidity_tokens = 0;
+        pool.lp_token_amount = 0;
+        pool.pending_lp_withdrawal = 0;
+        pool.pending_claim_lamports = 0;
         
         // H-04 FIX: Initialize reentrancy lock
         pool.locked = false;
 
+        // Activity feed ring buffer
+        let activity_log = &mut ctx.accounts.activity_log;
+        activity_log.pool = pool.key();
+        activity_log.head = 0;
+        activity_log.total_recorded = 0;
+
+        // Global discovery registry: append (mint, pool) to the current page.
+        let registry = &mut ctx.accounts.registry;
+        let page = &mut ctx.accounts.registry_page;
+        let page_index = registry.pool_count / PoolRegistryPage::CAPACITY as u64;
+        if page.entries.is_empty() {
+            page.page_index = page_index;
+        }
+        page.entries.push(PoolRegistryEntry {
+            token_mint: pool.token_mint,
+            pool: pool.key(),
+        });
+        registry.pool_count = registry.pool_count.checked_add(1).ok_or(CtoError::MathOverflow)?;
+
         // L-05 FIX: Emit pool creation event
         let clock = Clock::get()?;
-        emit!(PoolCreatedEvent {
+        emit_cpi!(PoolCreatedEvent {
             pool: pool.key(),
             token_mint: ctx.accounts.token_mint.key(),
             authority: ctx.accounts.creator.key(),
+            mint_authority_set: pool.mint_authority_set,
+            freeze_authority_set: pool.freeze_authority_set,
             timestamp: clock.unix_timestamp,
         });
 
@@ -213,6 +820,8 @@ pub mod cto_pools {
 
     /// Configure PumpSwap pool for buy & burn (post Pump.fun graduation).
     ///
+    /// `enabled` makes PumpSwap the pool's `burn_venue`; `false` clears the venue if it was PumpSwap.
+    ///
     /// Security model:
     /// - The pool stores **all critical PumpSwap addresses** in the Pool account (pool id + vaults + config + fee recipient).
     /// - Any executor may call `execute_proposal`, but they cannot redirect the swap output:
@@ -228,10 +837,7 @@ pub mod cto_pools {
         fee_recipient: Pubkey,
         enabled: bool,
     ) -> Result<()> {
-        require!(
-            ctx.accounts.authority.key() == ctx.accounts.pool.authority,
-            CtoError::UnauthorizedAuthority
-        );
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
 
         // Conservative safety checks to avoid partially-configured pools.
         require!(pumpswap_pool_id != Pubkey::default(), CtoError::InvalidPumpSwapConfig);
@@ -246,2358 +852,16304 @@ pub mod cto_pools {
         pool.pumpswap_quote_vault = quote_vault;
         pool.pumpswap_global_config = global_config;
         pool.pumpswap_fee_recipient = fee_recipient;
-        pool.pumpswap_enabled = enabled;
+        select_burn_venue(pool, BurnVenue::PumpSwap, enabled);
 
         Ok(())
     }
 
-    /// Configure Raydium pool for buy & burn (legacy / optional).
-    pub fn configure_raydium_pool(
-        ctx: Context<ConfigureRaydiumPool>,
-        raydium_pool_id: Pubkey,
+    /// Configure an optional second PumpSwap hop for buy & burn.
+    ///
+    /// When enabled, the PumpSwap pool set via `configure_pumpswap_pool` is treated as the
+    /// SOL -> `hop_mint` leg (e.g. SOL/USDC), and `hop_pool_id` as the `hop_mint` -> CTOP leg.
+    /// This lets thin direct SOL/CTOP liquidity be bypassed. The intermediate tokens land in
+    /// a PDA-owned account (`pool_hop_account`, one per hop mint) so executors cannot redirect
+    /// them; switching to another hop mint leaves the previous account and its balance intact.
+    pub fn configure_pumpswap_hop(
+        ctx: Context<ConfigurePumpSwapHop>,
+        hop_pool_id: Pubkey,
+        hop_base_vault: Pubkey,
+        hop_quote_vault: Pubkey,
         enabled: bool,
     ) -> Result<()> {
-        require!(
-            ctx.accounts.authority.key() == ctx.accounts.pool.authority,
-            CtoError::UnauthorizedAuthority
-        );
-        ctx.accounts.pool.raydium_pool_id = raydium_pool_id;
-        ctx.accounts.pool.raydium_enabled = enabled;
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+
+        if enabled {
+            require!(hop_pool_id != Pubkey::default(), CtoError::InvalidPumpSwapHopConfig);
+            require!(hop_base_vault != Pubkey::default(), CtoError::InvalidPumpSwapHopConfig);
+            require!(hop_quote_vault != Pubkey::default(), CtoError::InvalidPumpSwapHopConfig);
+            require!(
+                ctx.accounts.hop_mint.key() != ctx.accounts.pool.burn_token_mint
+                    && ctx.accounts.hop_mint.key() != WSOL_MINT,
+                CtoError::InvalidPumpSwapHopConfig
+            );
+        }
+
+        let pool = &mut ctx.accounts.pool;
+        pool.pumpswap_hop_mint = ctx.accounts.hop_mint.key();
+        pool.pumpswap_hop_pool_id = hop_pool_id;
+        pool.pumpswap_hop_base_vault = hop_base_vault;
+        pool.pumpswap_hop_quote_vault = hop_quote_vault;
+        pool.pumpswap_hop_enabled = enabled;
+
         Ok(())
     }
 
-    /// Donate native SOL to the pool.
+    /// Configure an Orca Whirlpool for buy & burn, for tokens whose liquidity migrated to Orca.
     ///
-    /// Flow:
-    /// - CPI into the configured stake-pool program `deposit_sol_with_slippage`
-    /// - pool receives LST tokens (e.g. jitoSOL) into its token account
-    /// - shares minted to donor based on LST received
-    pub fn donate_sol(ctx: Context<DonateSol>, lamports_in: u64, minimum_pool_tokens_out: u64) -> Result<()> {
-        require!(lamports_in > 0, CtoError::ZeroAmount);
+    /// `enabled` makes the Whirlpool the pool's `burn_venue`. As with PumpSwap, the
+    /// whirlpool and its vaults are pinned here; at crank time the vaults must match the
+    /// whirlpool's own state, hold WSOL and the burn target, and be owned by the whirlpool.
+    /// Tick arrays and the oracle are supplied by the cranker and checked by the Whirlpool program.
+    pub fn configure_whirlpool(
+        ctx: Context<ConfigureWhirlpool>,
+        whirlpool: Pubkey,
+        token_vault_a: Pubkey,
+        token_vault_b: Pubkey,
+        enabled: bool,
+    ) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
 
-        // H-04 FIX: Reentrancy guard - lock before CPI
-        lock_pool!(ctx.accounts.pool);
+        require!(whirlpool != Pubkey::default(), CtoError::InvalidWhirlpoolConfig);
+        require!(token_vault_a != Pubkey::default(), CtoError::InvalidWhirlpoolConfig);
+        require!(token_vault_b != Pubkey::default(), CtoError::InvalidWhirlpoolConfig);
+        require!(token_vault_a != token_vault_b, CtoError::InvalidWhirlpoolConfig);
 
-        // Defensive accounting: read pre-CPI balance from the token account itself.
-        ctx.accounts.pool_lst_account.reload()?;
-        let pre_balance = ctx.accounts.pool_lst_account.amount;
+        let pool = &mut ctx.accounts.pool;
+        pool.whirlpool_pool_id = whirlpool;
+        pool.whirlpool_token_vault_a = token_vault_a;
+        pool.whirlpool_token_vault_b = token_vault_b;
+        select_burn_venue(pool, BurnVenue::Whirlpool, enabled);
 
-        stake_pool_deposit_sol(&ctx, lamports_in, minimum_pool_tokens_out)?;
+        Ok(())
+    }
+
+    /// Configure a Meteora DLMM pair for buy & burn.
+    ///
+    /// `enabled` makes the pair the pool's `burn_venue`. The pair and its reserves are pinned
+    /// here; at crank time the reserves must match the pair's own state, hold WSOL and the burn
+    /// target, and be owned by the pair. Bin arrays, the bitmap extension and the oracle are
+    /// supplied by the cranker and checked by the DLMM program.
+    pub fn configure_meteora_dlmm(
+        ctx: Context<ConfigureMeteoraDlmm>,
+        lb_pair: Pubkey,
+        reserve_x: Pubkey,
+        reserve_y: Pubkey,
+        enabled: bool,
+    ) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+
+        require!(lb_pair != Pubkey::default(), CtoError::InvalidMeteoraConfig);
+        require!(reserve_x != Pubkey::default(), CtoError::InvalidMeteoraConfig);
+        require!(reserve_y != Pubkey::default(), CtoError::InvalidMeteoraConfig);
+        require!(reserve_x != reserve_y, CtoError::InvalidMeteoraConfig);
 
-        // Observe actual received LST and update accounting.
         let pool = &mut ctx.accounts.pool;
-        // Keep state aligned with observed pre balance (handles external top-ups as shared donations).
-        pool.total_pool_tokens = pre_balance;
-        ctx.accounts.pool_lst_account.reload()?;
-        let new_balance = ctx.accounts.pool_lst_account.amount;
+        pool.meteora_lb_pair = lb_pair;
+        pool.meteora_reserve_x = reserve_x;
+        pool.meteora_reserve_y = reserve_y;
+        select_burn_venue(pool, BurnVenue::MeteoraDlmm, enabled);
 
-        let prev_total = pre_balance;
-        let received = new_balance.checked_sub(prev_total).ok_or(CtoError::MathOverflow)?;
-        require!(received > 0, CtoError::StakePoolReturnedZero);
+        Ok(())
+    }
 
-        // Shares: 1st donor mints 1:1 with LST; else proportional
-        let shares_minted = if pool.total_shares == 0 {
-            received
+    /// Configure what happens to CTOP bought with the burn half of the protocol fee.
+    ///
+    /// `BuybackMode::Hold` keeps the CTOP in the pool's PDA (`pool_ctop_account`) as a treasury
+    /// asset instead of sending it to the incinerator. Held CTOP is tracked in `held_ctop_amount`
+    /// and is never burned by later buybacks, even if the mode is switched back to `Burn`.
+    pub fn configure_buyback_mode(ctx: Context<ConfigureBuybackMode>, mode: BuybackMode) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+        ctx.accounts.pool.buyback_mode = mode;
+        Ok(())
+    }
+
+    /// Configure the Dutch-auction burn.
+    ///
+    /// When enabled, the burn half of the protocol fee is not swapped on an AMM. It accumulates
+    /// in the pool PDA as an auction lot that anyone can buy with CTOP via `fill_burn_auction`.
+    /// The price (CTOP base units per SOL) decays linearly from `start_ctop_per_sol` to
+    /// `floor_ctop_per_sol` over `duration_seconds`, and the CTOP paid is sent to the incinerator.
+    /// Disabling stops new funding; an existing lot stays fillable.
+    pub fn configure_burn_auction(
+        ctx: Context<ConfigureBurnAuction>,
+        enabled: bool,
+        start_ctop_per_sol: u64,
+        floor_ctop_per_sol: u64,
+        duration_seconds: i64,
+    ) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+        require!(
+            floor_ctop_per_sol > 0 && start_ctop_per_sol >= floor_ctop_per_sol && duration_seconds > 0,
+            CtoError::InvalidBurnAuctionConfig
+        );
+
+        let pool = &mut ctx.accounts.pool;
+        pool.burn_auction_enabled = enabled;
+        pool.burn_auction_start_ctop_per_sol = start_ctop_per_sol;
+        pool.burn_auction_floor_ctop_per_sol = floor_ctop_per_sol;
+        pool.burn_auction_duration_seconds = duration_seconds;
+        Ok(())
+    }
+
+    /// Swap pending burn lamports for the burn target on the pool's `burn_venue` (PumpSwap, an
+    /// Orca Whirlpool, a Meteora DLMM pair or a Raydium CPMM pool) and burn (or hold) it.
+    ///
+    /// Permissionless crank, decoupled from `execute_proposal` so execution needs no swap
+    /// accounts and the swap can wait for favourable liquidity. Swaps up to `max_lamports`
+    /// (0 = everything pending) once the burn schedule says it is due. A crank that has to create
+    /// the pool's WSOL/CTOP/incinerator accounts is reimbursed the rent it paid out of the pending
+    /// amount. Any failure reverts the crank and leaves the lamports pending; a failed swap logs
+    /// its `SwapFailureCode` first.
+    pub fn process_buy_and_burn(mut ctx: Context<ProcessBuyAndBurn>, max_lamports: u64) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(!ctx.accounts.pool.paused, CtoError::PoolPaused);
+        lock_pool!(ctx.accounts.pool);
+
+        let pool = &ctx.accounts.pool;
+        require!(burn_due(pool, clock.unix_timestamp), CtoError::BurnNotDue);
+        require!(burn_venue_configured(pool), CtoError::InvalidPumpSwapConfig);
+        let venue = pool.burn_venue;
+        if venue == BurnVenue::PumpSwap {
+            require!(
+                ctx.accounts.pumpswap_pool.key() == pool.pumpswap_pool_id
+                    && ctx.accounts.pumpswap_pool_base_vault.key() == pool.pumpswap_base_vault
+                    && ctx.accounts.pumpswap_pool_quote_vault.key() == pool.pumpswap_quote_vault
+                    && ctx.accounts.pumpswap_global_config.key() == pool.pumpswap_global_config
+                    && ctx.accounts.pumpswap_protocol_fee_recipient.key() == pool.pumpswap_fee_recipient,
+                CtoError::InvalidPumpSwapConfig
+            );
+        }
+
+        let pool_key = pool.key();
+        let pool_bump = ctx.bumps.pool;
+        let mut amount_sol = if max_lamports == 0 {
+            pool.pending_burn_lamports
         } else {
-            let r = (received as u128)
-                .checked_mul(pool.total_shares as u128)
-                .ok_or(CtoError::MathOverflow)?
-                .checked_div(prev_total as u128)
-                .ok_or(CtoError::MathOverflow)?;
-            u64::try_from(r).map_err(|_| CtoError::MathOverflow)?
+            pool.pending_burn_lamports.min(max_lamports)
         };
-        require!(shares_minted > 0, CtoError::StakePoolReturnedZero);
+        {
+            let pool = &mut ctx.accounts.pool;
+            pool.pending_burn_lamports -= amount_sol;
+            pool.last_burn_ts = clock.unix_timestamp;
+        }
 
-        // Update pool
-        pool.total_pool_tokens = new_balance;
-        pool.total_shares = pool
-            .total_shares
-            .checked_add(shares_minted)
-            .ok_or(CtoError::MathOverflow)?;
+        let rent_reimbursement = create_buy_and_burn_accounts(&ctx)?.min(amount_sol);
+        if rent_reimbursement > 0 {
+            transfer_lamports_signed(
+                &ctx.accounts.pool.to_account_info(),
+                &ctx.accounts.cranker.to_account_info(),
+                pool_seeds!(&ctx.accounts.pool, pool_bump),
+                rent_reimbursement,
+            )?;
+            amount_sol -= rent_reimbursement;
+            emit_cpi!(ExecutorRentReimbursedEvent {
+                pool: pool_key,
+                executor: ctx.accounts.cranker.key(),
+                lamports: rent_reimbursement,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+        require!(amount_sol > 0, CtoError::ZeroAmount);
 
-        // Update donor
-        let donor = &mut ctx.accounts.donor;
-        let clock = Clock::get()?;
-        donor.pool = pool.key();
-        donor.wallet = ctx.accounts.donor_wallet.key();
-        donor.shares = donor
-            .shares
-            .checked_add(shares_minted)
-            .ok_or(CtoError::MathOverflow)?;
-        donor.total_deposited_lamports = donor
-            .total_deposited_lamports
-            .checked_add(lamports_in)
-            .ok_or(CtoError::MathOverflow)?;
-        donor.last_shares_change_slot = clock.slot;
+        // Oracle prices are read (and checked for staleness) before any lamports move.
+        let oracle_prices = burn_oracle_prices(&ctx, clock.unix_timestamp)?;
+
+        let swapped = match venue {
+            BurnVenue::PumpSwap => attempt_pumpswap_swap_and_burn(&mut ctx, amount_sol, pool_bump),
+            BurnVenue::Whirlpool => attempt_whirlpool_swap_and_burn(&mut ctx, amount_sol, pool_bump),
+            BurnVenue::MeteoraDlmm => attempt_meteora_dlmm_swap_and_burn(&mut ctx, amount_sol, pool_bump),
+            BurnVenue::RaydiumCpmm => attempt_raydium_cpmm_swap_and_burn(&mut ctx, amount_sol, pool_bump),
+            BurnVenue::None => return err!(CtoError::InvalidPumpSwapConfig),
+        };
+        // The revert drops any event, so the classification goes to the transaction log.
+        let ctop_bought = swapped.inspect_err(|e| msg!("Swap failure: {:?}", SwapFailureCode::from_error(e)))?;
+
+        if let Some((ctop_usd, sol_usd)) = oracle_prices {
+            require_burn_price_in_band(
+                &ctx.accounts.pool,
+                amount_sol,
+                ctop_bought,
+                ctx.accounts.ctop_mint.decimals,
+                &ctop_usd,
+                &sol_usd,
+            )?;
+        }
 
-        // H-04 FIX: Unlock after successful operation
         unlock_pool!(ctx.accounts.pool);
 
-        // L-05 FIX: Emit donation event
-        emit!(DonationEvent {
-            pool: ctx.accounts.pool.key(),
-            donor: ctx.accounts.donor_wallet.key(),
-            lamports_in,
-            shares_minted,
-            timestamp: clock.unix_timestamp,
-        });
+        if ctx.accounts.pool.buyback_mode == BuybackMode::Hold {
+            emit_cpi!(BuybackHeldEvent {
+                pool: pool_key,
+                amount_sol,
+                amount_ctop: ctop_bought,
+                held_ctop_total: ctx.accounts.pool.held_ctop_amount,
+                timestamp: clock.unix_timestamp,
+            });
+        } else {
+            emit_cpi!(TokenBurnEvent {
+                pool: pool_key,
+                amount_sol,
+                amount_ctop: ctop_bought,
+                timestamp: clock.unix_timestamp,
+            });
+        }
 
         Ok(())
     }
 
-    /// Withdraw X SOL worth of stake from the pool.
+    /// Buy (part of) the burn-auction lot at the current decayed price.
     ///
-    /// Notes:
-    /// - Uses a stake-pool CPI withdraw (burn LST, receive SOL into pool PDA).
-    /// - Then immediately transfers the SOL out to the donor.
-    pub fn withdraw_sol(ctx: Context<WithdrawSol>, lamports_out_desired: u64, minimum_lamports_out: u64) -> Result<()> {
-        require!(lamports_out_desired > 0, CtoError::ZeroAmount);
+    /// The filler's CTOP goes straight to the incinerator ATA and the filler receives `lamports`
+    /// from the pool PDA. `max_ctop_in` bounds the price the filler is willing to pay.
+    pub fn fill_burn_auction(ctx: Context<FillBurnAuction>, lamports: u64, max_ctop_in: u64) -> Result<()> {
+        require!(lamports > 0, CtoError::ZeroAmount);
+        require!(
+            lamports <= ctx.accounts.pool.burn_auction_lamports,
+            CtoError::BurnAuctionLotExceeded
+        );
 
-        // H-04 FIX: Reentrancy guard - lock before CPI
         lock_pool!(ctx.accounts.pool);
 
-        // ============ PHASE 1: Immutable reads and calculations ============
-        let donor_shares = ctx.accounts.donor.shares;
-        let total_shares = ctx.accounts.pool.total_shares;
-        let reserved_pool_tokens = ctx.accounts.pool.reserved_pool_tokens;
+        let now = Clock::get()?.unix_timestamp;
+        let price = burn_auction_price(&ctx.accounts.pool, now)?;
+        let ctop_in = mul_div_ceil(lamports, price, LAMPORTS_PER_SOL)?;
+        require!(ctop_in > 0, CtoError::ZeroAmount);
+        require!(ctop_in <= max_ctop_in, CtoError::SlippageExceeded);
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.filler_ctop_account.to_account_info(),
+                    to: ctx.accounts.incinerator_ctop_account.to_account_info(),
+                    authority: ctx.accounts.filler.to_account_info(),
+                },
+            ),
+            ctop_in,
+        )?;
+
         let pool_bump = ctx.bumps.pool;
         let pool_token_mint = ctx.accounts.pool.token_mint;
+        transfer_lamports_signed(
+            &ctx.accounts.pool.to_account_info(),
+            &ctx.accounts.filler.to_account_info(),
+            &[&[b"pool", pool_token_mint.as_ref(), &[pool_bump]]],
+            lamports,
+        )?;
 
-        require!(donor_shares > 0, CtoError::NoShares);
-        require!(total_shares > 0, CtoError::MathOverflow);
-
-        ctx.accounts.pool_lst_account.reload()?;
-        let total_pool_tokens_observed = ctx.accounts.pool_lst_account.amount;
-
-        let free_pool_tokens = total_pool_tokens_observed
-            .checked_sub(reserved_pool_tokens)
+        let pool = &mut ctx.accounts.pool;
+        pool.burn_auction_lamports = pool
+            .burn_auction_lamports
+            .checked_sub(lamports)
             .ok_or(CtoError::MathOverflow)?;
+        if pool.burn_auction_lamports == 0 {
+            pool.burn_auction_start_ts = 0;
+        }
 
-        let donor_free_pool_tokens = ((donor_shares as u128)
-            .checked_mul(free_pool_tokens as u128)
-            .ok_or(CtoError::MathOverflow)?)
-            .checked_div(total_shares as u128)
-            .ok_or(CtoError::MathOverflow)?;
-        let donor_free_pool_tokens = u64::try_from(donor_free_pool_tokens).map_err(|_| CtoError::MathOverflow)?;
+        unlock_pool!(ctx.accounts.pool);
 
-        let stake_pool_state = read_stake_pool(&ctx.accounts.stake_pool)?;
-        let pool_tokens_to_burn = pool_tokens_for_lamports_ceil(&stake_pool_state, lamports_out_desired)?;
-        require!(pool_tokens_to_burn > 0, CtoError::ZeroAmount);
-        require!(pool_tokens_to_burn <= donor_free_pool_tokens, CtoError::InsufficientWithdrawable);
+        emit_cpi!(BurnAuctionFilledEvent {
+            pool: ctx.accounts.pool.key(),
+            filler: ctx.accounts.filler.key(),
+            amount_sol: lamports,
+            amount_ctop: ctop_in,
+            price_ctop_per_sol: price,
+            remaining_lamports: ctx.accounts.pool.burn_auction_lamports,
+            timestamp: now,
+        });
 
-        let shares_to_burn = ((pool_tokens_to_burn as u128)
-            .checked_mul(total_shares as u128)
-            .ok_or(CtoError::MathOverflow)?)
-            .checked_div(total_pool_tokens_observed as u128)
-            .ok_or(CtoError::MathOverflow)?;
-        let shares_to_burn = u64::try_from(shares_to_burn).map_err(|_| CtoError::MathOverflow)?;
-        require!(shares_to_burn > 0 && shares_to_burn <= donor_shares, CtoError::MathOverflow);
+        Ok(())
+    }
 
-        // ============ PHASE 2: CPIs ============
-        let pre_pool_lamports = ctx.accounts.pool.to_account_info().lamports();
-        stake_pool_withdraw_sol(&ctx, pool_tokens_to_burn, minimum_lamports_out)?;
-        let post_pool_lamports = ctx.accounts.pool.to_account_info().lamports();
-        let received = post_pool_lamports
-            .checked_sub(pre_pool_lamports)
-            .ok_or(CtoError::MathOverflow)?;
-        require!(received >= minimum_lamports_out, CtoError::SlippageExceeded);
+    /// Configure scheduled (batched) buy & burn.
+    ///
+    /// The burn half of each fee accumulates in `pending_burn_lamports`. With a non-zero
+    /// `threshold_lamports` or `interval_seconds`, `process_buy_and_burn` only runs once the pending
+    /// amount reaches the threshold or the interval since the last burn has elapsed. Both zero lets
+    /// it run whenever anything is pending.
+    pub fn configure_burn_schedule(
+        ctx: Context<ConfigureBurnSchedule>,
+        threshold_lamports: u64,
+        interval_seconds: i64,
+    ) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+        require!(interval_seconds >= 0, CtoError::InvalidBurnSchedule);
 
-        transfer_lamports_signed(
-            &ctx.accounts.pool.to_account_info(),
-            &ctx.accounts.donor_wallet.to_account_info(),
-            &[&[b"pool", pool_token_mint.as_ref(), &[pool_bump]]],
-            received,
-        )?;
+        let pool = &mut ctx.accounts.pool;
+        pool.burn_threshold_lamports = threshold_lamports;
+        pool.burn_interval_seconds = interval_seconds;
+        Ok(())
+    }
 
-        ctx.accounts.pool_lst_account.reload()?;
-        let final_pool_tokens = ctx.accounts.pool_lst_account.amount;
-        let clock = Clock::get()?;
-        // ============ PHASE 3: state updates ============
-        {
-            let pool = &mut ctx.accounts.pool;
-            pool.total_pool_tokens = final_pool_tokens;
-            pool.total_shares = pool
-                .total_shares
-                .checked_sub(shares_to_burn)
-                .ok_or(CtoError::MathOverflow)?;
-        }
-        {
-            let donor = &mut ctx.accounts.donor;
-            donor.shares = donor
-                .shares
-                .checked_sub(shares_to_burn)
-                .ok_or(CtoError::MathOverflow)?;
-            donor.last_shares_change_slot = clock.slot;
-        }
+    /// Change the token the burn leg buys (the pool's own token, CTOP, or any other SPL mint).
+    ///
+    /// Venue config is per target: PumpSwap (including the hop), Raydium, and Dutch-auction settings
+    /// are cleared and must be reconfigured for the new mint. Blocked while CTOP is held or an
+    /// auction lot is open, since both are denominated in the current target.
+    pub fn configure_burn_target(ctx: Context<ConfigureBurnTarget>) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+        require!(
+            ctx.accounts.burn_token_mint.key() != WSOL_MINT,
+            CtoError::InvalidBurnTarget
+        );
 
-        // H-04 FIX: Unlock after successful operation
-        unlock_pool!(ctx.accounts.pool);
+        let pool = &mut ctx.accounts.pool;
+        require!(
+            pool.held_ctop_amount == 0 && pool.burn_auction_lamports == 0,
+            CtoError::BurnTargetInUse
+        );
 
-        // L-05 FIX: Emit withdrawal event
-        emit!(WithdrawalEvent {
-            pool: ctx.accounts.pool.key(),
-            donor: ctx.accounts.donor_wallet.key(),
-            lamports_out: received,
-            shares_burned: shares_to_burn,
-            timestamp: clock.slot as i64,
+        let previous_mint = pool.burn_token_mint;
+        pool.burn_token_mint = ctx.accounts.burn_token_mint.key();
+
+        pool.burn_venue = BurnVenue::None;
+        pool.pumpswap_pool_id = Pubkey::default();
+        pool.pumpswap_base_vault = Pubkey::default();
+        pool.pumpswap_quote_vault = Pubkey::default();
+        pool.pumpswap_hop_enabled = false;
+        pool.pumpswap_hop_mint = Pubkey::default();
+        pool.pumpswap_hop_pool_id = Pubkey::default();
+        pool.pumpswap_hop_base_vault = Pubkey::default();
+        pool.pumpswap_hop_quote_vault = Pubkey::default();
+        pool.whirlpool_pool_id = Pubkey::default();
+        pool.whirlpool_token_vault_a = Pubkey::default();
+        pool.whirlpool_token_vault_b = Pubkey::default();
+        pool.meteora_lb_pair = Pubkey::default();
+        pool.meteora_reserve_x = Pubkey::default();
+        pool.meteora_reserve_y = Pubkey::default();
+        pool.raydium_pool_id = Pubkey::default();
+        pool.raydium_token_0_vault = Pubkey::default();
+        pool.raydium_token_1_vault = Pubkey::default();
+        pool.burn_auction_enabled = false;
+
+        emit_cpi!(BurnTargetChangedEvent {
+            pool: pool.key(),
+            previous_mint,
+            new_mint: pool.burn_token_mint,
+            timestamp: Clock::get()?.unix_timestamp,
         });
 
         Ok(())
     }
 
-    /// Create a payout proposal.
-    pub fn create_proposal(
-        ctx: Context<CreateProposal>,
-        requested_lamports: u64,
-        destination_wallet: Pubkey,
-        title: String,
-        description: String,
-    ) -> Result<()> {
-        require!(requested_lamports > 0, CtoError::ZeroAmount);
-        require!(title.as_bytes().len() <= Proposal::TITLE_MAX, CtoError::TitleTooLong);
-        require!(description.as_bytes().len() <= Proposal::DESC_MAX, CtoError::DescriptionTooLong);
+    /// Point the non-dev half of the protocol fee at a sink.
+    ///
+    /// `FeeSink::Burn` uses the buy & burn venues (destination must be unset). Every other sink
+    /// sends the SOL to `destination`, which must be a real wallet distinct from the pool and dev wallet.
+    /// Governance makes the same change through a ConfigChange's `fee_sink`.
+    pub fn configure_fee_sink(ctx: Context<ConfigureFeeSink>, sink: FeeSink, destination: Pubkey) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
 
-        let clock = Clock::get()?;
+        let pool = &mut ctx.accounts.pool;
+        validate_fee_sink(pool, sink, destination)?;
 
-        // ============ PHASE 1: Immutable reads and penalty fee CPI ============
-        // Read values needed for penalty fee calculation before any mutable borrows.
-        let base_penalty = ctx.accounts.pool.base_penalty_lamports;
-        let propose_strikes = ctx.accounts.donor.propose_strike_count;
-        let pool_key = ctx.accounts.pool.key();
+        pool.fee_sink = sink;
+        pool.fee_sink_destination = destination;
 
-        // Escalating proposer penalty (uncapped). Collected into pool PDA.
-        let proposer_fee = penalty_fee(base_penalty, propose_strikes);
-        if proposer_fee > 0 {
-            invoke(
-                &system_instruction::transfer(&ctx.accounts.proposer_wallet.key(), &pool_key, proposer_fee),
-                &[ctx.accounts.proposer_wallet.to_account_info(), ctx.accounts.pool.to_account_info(), ctx.accounts.system_program.to_account_info()],
-            ).map_err(|_| CtoError::LamportTransferFailed)?;
-        }
+        emit_cpi!(FeeSinkConfiguredEvent {
+            pool: pool.key(),
+            sink,
+            destination,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
 
-        // ============ PHASE 2: Mutable state and validation ============
-        let pool = &mut ctx.accounts.pool;
-        let donor = &ctx.accounts.donor;
-        let proposal = &mut ctx.accounts.proposal;
+        Ok(())
+    }
 
-        require!(pool.active_proposal.is_none(), CtoError::ActiveProposalExists);
-        // Global cooldown after an Abort to allow withdrawals.
-        require!(clock.unix_timestamp >= pool.proposal_cooldown_until_ts, CtoError::ProposalCooldownActive);
-        require!(donor.shares > 0, CtoError::NoShares);
+    /// Configure how a proposal that meets quorum with `yes_weight == no_weight > 0` is resolved.
+    pub fn configure_tie_break(ctx: Context<ConfigureTieBreak>, rule: TieBreakRule) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+        ctx.accounts.pool.tie_break_rule = rule;
+        Ok(())
+    }
 
-        // Block single-donor governance
-        require!(pool.total_shares != donor.shares, CtoError::SingleDonorCannotPropose);
+    /// Configure how snapshot shares turn into vote weight on new proposals.
+    ///
+    /// Quadratic weighting tallies the square root of each ballot's (capped) shares, so a whale
+    /// needs 4x the shares for 2x the say. Quorum still counts the shares behind the ballots.
+    pub fn configure_vote_weighting(ctx: Context<ConfigureVoteWeighting>, weighting: VoteWeighting) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+        ctx.accounts.pool.vote_weighting = weighting;
+        Ok(())
+    }
 
-        // Holding delay for non-creator
-        if pool.creator != ctx.accounts.proposer_wallet.key() {
-            let slots_since = clock
-                .slot
-                .checked_sub(donor.last_shares_change_slot)
-                .ok_or(CtoError::MathOverflow)?;
-            require!(slots_since >= MIN_PROPOSAL_DELAY_SLOTS, CtoError::SharesTooRecent);
+    /// Configure the execution timelock (0 disables it, at most MAX_TIMELOCK_SECONDS).
+    ///
+    /// With a timelock, a passing proposal must go through `queue_proposal` and can only be
+    /// executed `timelock_seconds` later, giving dissenting donors time to withdraw.
+    pub fn configure_timelock(ctx: Context<ConfigureTimelock>, timelock_seconds: i64) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+        require!(
+            (0..=MAX_TIMELOCK_SECONDS).contains(&timelock_seconds),
+            CtoError::InvalidTimelock
+        );
+        ctx.accounts.pool.timelock_seconds = timelock_seconds;
+        Ok(())
+    }
+
+    /// Configure amount-based quorum tiers for payout proposals created from now on.
+    ///
+    /// A request locking at most `max_tvl_bps` of pool LST needs that tier's `quorum_bps`; the first
+    /// matching tier wins and larger requests fall back to the pool's quorum. All-zero tiers are
+    /// unused; passing only those disables tiering.
+    pub fn configure_quorum_tiers(
+        ctx: Context<ConfigureQuorumTiers>,
+        tiers: [QuorumTier; QUORUM_TIER_COUNT],
+    ) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+        validate_quorum_tiers(&tiers)?;
+        ctx.accounts.pool.quorum_tiers = tiers;
+        Ok(())
+    }
+
+    /// Configure the fast track for small payouts (`threshold_lamports == 0` disables it).
+    ///
+    /// Payout proposals requesting at most `threshold_lamports` need only `quorum_bps`
+    /// (MIN_FAST_TRACK_QUORUM_BPS up to the pool's quorum) and may pick a voting window as short
+    /// as `voting_seconds` (MIN_EXECUTE_DELAY_SECONDS up to the pool's minimum window).
+    pub fn configure_fast_track(
+        ctx: Context<ConfigureFastTrack>,
+        threshold_lamports: u64,
+        quorum_bps: u16,
+        voting_seconds: i64,
+    ) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+        let pool = &mut ctx.accounts.pool;
+        if threshold_lamports > 0 {
+            require!(
+                (MIN_FAST_TRACK_QUORUM_BPS..=pool.quorum_bps).contains(&quorum_bps)
+                    && (MIN_EXECUTE_DELAY_SECONDS..=pool.min_voting_seconds).contains(&voting_seconds),
+                CtoError::InvalidFastTrackConfig
+            );
         }
 
-        ctx.accounts.pool_lst_account.reload()?;
-        pool.total_pool_tokens = ctx.accounts.pool_lst_account.amount;
-        let stake_pool_state = read_stake_pool(&ctx.accounts.stake_pool)?;
+        pool.small_payout_threshold_lamports = threshold_lamports;
+        pool.fast_track_quorum_bps = quorum_bps;
+        pool.fast_track_voting_seconds = voting_seconds;
+        Ok(())
+    }
 
-        // Enforce proposer value >= 1 SOL at current withdraw rate.
-        let proposer_pool_tokens = ((donor.shares as u128)
-            .checked_mul(pool.total_pool_tokens as u128)
-            .ok_or(CtoError::MathOverflow)?)
-            .checked_div(pool.total_shares as u128)
-            .ok_or(CtoError::MathOverflow)?;
-        let proposer_pool_tokens = u64::try_from(proposer_pool_tokens).map_err(|_| CtoError::MathOverflow)?;
-        let proposer_value_lamports = stake_pool_state
-            .calc_lamports_withdraw_amount(proposer_pool_tokens)
-            .ok_or(CtoError::MathOverflow)?;
+    /// Configure Merkle-snapshot voting for proposals created from now on.
+    ///
+    /// When enabled, each new proposal needs a (wallet -> weight) root from `post_weight_root`
+    /// before Yes/No/Abstain votes are accepted, and those votes go through `vote_merkle`.
+    pub fn configure_merkle_voting(ctx: Context<ConfigureMerkleVoting>, enabled: bool) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+        ctx.accounts.pool.merkle_voting = enabled;
+        Ok(())
+    }
+
+    /// Configure co-sponsorship for payout proposals created from now on.
+    ///
+    /// `required_sponsors == 0` disables it. Sponsors must be distinct donors other than the
+    /// proposer, each holding at least `min_share_bps` of total shares at the proposal snapshot.
+    pub fn configure_sponsorship(
+        ctx: Context<ConfigureSponsorship>,
+        required_sponsors: u8,
+        min_share_bps: u16,
+    ) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
         require!(
-            proposer_value_lamports >= pool.min_proposer_deposit_lamports,
-            CtoError::ProposerTooSmall
+            required_sponsors <= MAX_REQUIRED_SPONSORS && min_share_bps as u64 <= BPS_DENOM,
+            CtoError::InvalidSponsorshipConfig
         );
 
-        // Compute pool tokens to lock = ceil(requested * (1 + buffer))
-        let buffered = requested_lamports
-            .checked_mul(BPS_DENOM + PROPOSAL_BUFFER_BPS)
-            .ok_or(CtoError::MathOverflow)?
-            .checked_div(BPS_DENOM)
-            .ok_or(CtoError::MathOverflow)?;
-        let locked_pool_tokens = pool_tokens_for_lamports_ceil(&stake_pool_state, buffered)?;
+        let pool = &mut ctx.accounts.pool;
+        pool.required_sponsors = required_sponsors;
+        pool.sponsor_min_share_bps = min_share_bps;
+        Ok(())
+    }
 
-        let free_pool_tokens = pool
-            .total_pool_tokens
-            .checked_sub(pool.reserved_pool_tokens)
-            .ok_or(CtoError::MathOverflow)?;
-        require!(locked_pool_tokens <= free_pool_tokens, CtoError::InsufficientFreeLiquidity);
+    /// Configure cap-adjusted quorum for proposals created from now on.
+    ///
+    /// When enabled, snapshot shares a wallet holds above the per-voter cap can be excluded from
+    /// the quorum denominator with `exclude_capped_excess`, since that weight can never vote.
+    pub fn configure_cap_adjusted_quorum(ctx: Context<ConfigureCapAdjustedQuorum>, enabled: bool) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+        ctx.accounts.pool.cap_adjusted_quorum = enabled;
+        Ok(())
+    }
 
-        pool.reserved_pool_tokens = pool
-            .reserved_pool_tokens
-            .checked_add(locked_pool_tokens)
-            .ok_or(CtoError::MathOverflow)?;
+    /// Exclude a donor's shares above the per-voter cap from a proposal's quorum denominator.
+    /// Permissionless; once per (proposal, donor). The excess is the donor's snapshot vote weight
+    /// (checkpointed and ramped, as `vote` would count it) above the cap, in the share units
+    /// participation is counted in.
+    pub fn exclude_capped_excess(ctx: Context<ExcludeCappedExcess>) -> Result<()> {
+        let donor = &ctx.accounts.donor;
+        let proposal = &mut ctx.accounts.proposal;
+        require!(proposal.cap_adjusted_quorum, CtoError::CapAdjustedQuorumDisabled);
+        require!(proposal.status == ProposalStatus::Active, CtoError::ProposalNotActive);
+        require!(donor.share_scale == ctx.accounts.pool.share_scale, CtoError::ShareScaleMigrationRequired);
 
-        proposal.pool = pool.key();
-        proposal.proposer_wallet = ctx.accounts.proposer_wallet.key();
-        proposal.kind = ProposalKind::Payout;
-        proposal.requested_lamports = requested_lamports;
-        proposal.destination_wallet = destination_wallet;
-        proposal.title = title;
-        proposal.description = description;
+        // Same weight the donor would vote with; delegated shares are voted (and capped) by the delegate.
+        let own_weight = snapshot_vote_weight(donor, proposal)?;
+        let excess = own_weight.saturating_sub(voter_weight_cap(proposal.max_voter_bps, proposal.total_snapshot_shares)?);
+        require!(excess > 0, CtoError::NotAboveVoterCap);
 
-        proposal.created_at_ts = clock.unix_timestamp;
-        proposal.deadline_ts = clock
-            .unix_timestamp
-            .checked_add(24 * 60 * 60)
+        let record = &mut ctx.accounts.cap_excess_record;
+        record.proposal = proposal.key();
+        record.wallet = donor.wallet;
+        record.excess = excess;
+
+        proposal.excluded_cap_weight = proposal
+            .excluded_cap_weight
+            .checked_add(excess)
             .ok_or(CtoError::MathOverflow)?;
-        proposal.snapshot_slot = clock.slot;
-        proposal.total_snapshot_shares = pool.total_shares;
 
-        proposal.locked_pool_tokens = locked_pool_tokens;
+        emit_cpi!(CapExcessExcludedEvent {
+            pool: ctx.accounts.pool.key(),
+            proposal: proposal.key(),
+            wallet: donor.wallet,
+            excess,
+            quorum_supply: quorum_supply(proposal),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
 
-        proposal.yes_weight = 0;
-        proposal.no_weight = 0;
-        proposal.abstain_weight = 0;
-        proposal.participation_weight = 0;
-        proposal.status = ProposalStatus::Active;
-        proposal.abort_voter_1 = Pubkey::default();
-        proposal.abort_voter_2 = Pubkey::default();
-        proposal.abort_count = 0;
+        Ok(())
+    }
 
-        pool.active_proposal = Some(proposal.key());
-        pool.proposal_count = pool.proposal_count.checked_add(1).ok_or(CtoError::MathOverflow)?;
+    /// Post the vote-weight Merkle root for a Merkle-mode proposal. Once per proposal.
+    ///
+    /// Leaves are `sha256(0x00 || wallet || weight_le)` computed from the share ledger at the
+    /// proposal's snapshot slot; delegated weight is credited to the delegate's wallet.
+    /// `total_weight` is the sum of all leaf weights and can't exceed the proposal's snapshot
+    /// total; `vote_merkle` rejects ballots once proven weight would pass it.
+    pub fn post_weight_root(ctx: Context<PostWeightRoot>, root: [u8; 32], total_weight: u64) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+        let proposal = &mut ctx.accounts.proposal;
+        require!(proposal.status == ProposalStatus::Active, CtoError::ProposalNotActive);
+        require!(proposal.merkle_weights, CtoError::NotMerkleProposal);
+        require!(proposal.weight_root == [0u8; 32], CtoError::WeightRootAlreadyPosted);
+        require!(root != [0u8; 32], CtoError::InvalidWeightRoot);
+        require!(
+            total_weight > 0 && total_weight <= proposal.total_snapshot_shares,
+            CtoError::InvalidWeightRootTotal
+        );
 
-        // L-05 FIX: Emit proposal created event
-        emit!(ProposalCreatedEvent {
-            pool: pool.key(),
+        proposal.weight_root = root;
+        proposal.weight_root_total = total_weight;
+
+        emit_cpi!(WeightRootPostedEvent {
+            pool: ctx.accounts.pool.key(),
             proposal: proposal.key(),
-            proposer: ctx.accounts.proposer_wallet.key(),
-            requested_lamports,
-            timestamp: clock.unix_timestamp,
+            root,
+            total_weight,
+            snapshot_slot: proposal.snapshot_slot,
+            timestamp: Clock::get()?.unix_timestamp,
         });
 
         Ok(())
     }
 
-    /// Vote on proposal.
-    pub fn vote(ctx: Context<Vote>, choice: VoteChoice) -> Result<()> {
-        let proposal = &mut ctx.accounts.proposal;
-        let donor = &ctx.accounts.donor;
-        let vote_record = &mut ctx.accounts.vote_record;
-        let clock = Clock::get()?;
-        require!(proposal.status == ProposalStatus::Active, CtoError::ProposalNotActive);
-        require!(clock.unix_timestamp <= proposal.deadline_ts, CtoError::VotingClosed);
-        require!(donor.shares > 0, CtoError::NoShares);
+    /// Configure the authority dead-man switch.
+    ///
+    /// With `epochs > 0`, the authority must call `authority_heartbeat` at least once every `epochs`
+    /// epochs. If it lapses, every authority-gated instruction is rejected unless a passed
+    /// ConfigChange approves the call (`admin_call`). `epochs == 0` disables the switch. Counts as
+    /// a heartbeat.
+    pub fn configure_dead_man_switch(ctx: Context<ConfigureDeadManSwitch>, epochs: u64) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+        require!(epochs == 0 || epochs >= MIN_DEAD_MAN_EPOCHS, CtoError::InvalidDeadManSwitch);
+
+        let pool = &mut ctx.accounts.pool;
+        pool.dead_man_epochs = epochs;
+        pool.last_heartbeat_epoch = Clock::get()?.epoch;
+        Ok(())
+    }
+
+    /// Emergency pause for exploits or stake pool / swap venue incidents.
+    ///
+    /// While paused, donations, new payout proposals, payouts (including milestone, stream and
+    /// budget releases) and buy & burn are rejected; withdrawals, rage quits, votes and ConfigChange
+    /// proposals still work, so governance can lift a pause through `ConfigChangeParams::paused`.
+    pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+        ctx.accounts.pool.paused = paused;
+
+        emit_cpi!(PoolPausedEvent {
+            pool: ctx.accounts.pool.key(),
+            paused,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
 
-        // Must have shares before snapshot
+    /// Set when the pool authority's powers expire (a decentralization schedule).
+    ///
+    /// From `expires_at_ts` on, every authority-gated instruction needs a passed ConfigChange whose
+    /// `admin_call` approves that exact call instead of the authority's signature. Once set, the
+    /// expiry can only be brought forward.
+    pub fn configure_authority_expiry(ctx: Context<ConfigureAuthorityExpiry>, expires_at_ts: i64) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+        let now = Clock::get()?.unix_timestamp;
+        let pool = &mut ctx.accounts.pool;
         require!(
-            donor.last_shares_change_slot <= proposal.snapshot_slot,
-            CtoError::NotEligibleForThisProposal
+            expires_at_ts > now
+                && (pool.authority_expires_at_ts == 0 || expires_at_ts <= pool.authority_expires_at_ts),
+            CtoError::InvalidAuthorityExpiry
         );
+        pool.authority_expires_at_ts = expires_at_ts;
+        Ok(())
+    }
 
-        // Remove previous vote weight, if re-voting.
-        if vote_record.initialized {
-            let w = vote_record.snapshot_weight;
-            match vote_record.choice {
-                VoteChoice::Yes => proposal.yes_weight = proposal.yes_weight.checked_sub(w).ok_or(CtoError::MathOverflow)?,
-                VoteChoice::No => proposal.no_weight = proposal.no_weight.checked_sub(w).ok_or(CtoError::MathOverflow)?,
-                VoteChoice::Abstain => proposal.abstain_weight = proposal.abstain_weight.checked_sub(w).ok_or(CtoError::MathOverflow)?,
-                VoteChoice::Abort => { /* weights not affected */ },
-            }
-        }
+    /// Authority liveness signal for the dead-man switch.
+    pub fn authority_heartbeat(ctx: Context<AuthorityHeartbeat>) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
 
-        // M-01 FIX: Use u128 for intermediate calculation to prevent overflow
-        // when total_snapshot_shares is very large (e.g., near u64::MAX)
-        let snapshot_weight = if vote_record.initialized {
-            vote_record.snapshot_weight
-        } else {
-            let raw = donor.shares;
-            let cap = ((proposal.total_snapshot_shares as u128)
-                .checked_mul(MAX_VOTER_BPS as u128)
-                .ok_or(CtoError::MathOverflow)?
-                .checked_div(BPS_DENOM as u128)
-                .ok_or(CtoError::MathOverflow)?) as u64;
-            raw.min(cap)
-        };
+        let epoch = Clock::get()?.epoch;
+        ctx.accounts.pool.last_heartbeat_epoch = epoch;
 
-        match choice {
-            VoteChoice::Yes => proposal.yes_weight = proposal.yes_weight.checked_add(snapshot_weight).ok_or(CtoError::MathOverflow)?,
-            VoteChoice::No => proposal.no_weight = proposal.no_weight.checked_add(snapshot_weight).ok_or(CtoError::MathOverflow)?,
-            VoteChoice::Abstain => proposal.abstain_weight = proposal.abstain_weight.checked_add(snapshot_weight).ok_or(CtoError::MathOverflow)?,
-            VoteChoice::Abort => { /* tracked separately */ },
-        }
+        emit_cpi!(AuthorityHeartbeatEvent {
+            pool: ctx.accounts.pool.key(),
+            authority: ctx.accounts.authority.key(),
+            epoch,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
 
-        proposal.participation_weight = proposal
-            .yes_weight
-            .checked_add(proposal.no_weight)
-            .ok_or(CtoError::MathOverflow)?
-            .checked_add(proposal.abstain_weight)
-            .ok_or(CtoError::MathOverflow)?;
+    /// First step of handing the pool authority to another key (e.g. a multisig or DAO).
+    ///
+    /// Nothing changes until `new_authority` signs `accept_authority`, so a mistyped key can't
+    /// lock the pool's configuration. Proposing again replaces the pending key; the default
+    /// pubkey cancels.
+    pub fn propose_authority_transfer(ctx: Context<ProposeAuthorityTransfer>, new_authority: Pubkey) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
 
-        vote_record.proposal = proposal.key();
-        vote_record.voter = donor.wallet;
-        vote_record.snapshot_weight = snapshot_weight;
-        vote_record.choice = choice;
-        vote_record.initialized = true;
+        ctx.accounts.pool.pending_authority = new_authority;
 
-        // === Abort tracking + penalty reset counters ===
-        // If voter participates normally (Yes/No), they progress toward resetting strike counters.
-        if matches!(choice, VoteChoice::Yes | VoteChoice::No) {
-            let d = &mut ctx.accounts.donor;
-            d.non_abort_participation_count = d.non_abort_participation_count.saturating_add(1);
-            if d.non_abort_participation_count >= 3 {
-                d.abort_strike_count = 0;
-                d.non_abort_participation_count = 0;
-            }
-            // Proposer strike reset requires sitting out proposing and voting on 3 proposals.
-            // Use proposal variable to avoid borrow conflict
-            if proposal.proposer_wallet != d.wallet {
-                d.non_propose_participation_count = d.non_propose_participation_count.saturating_add(1);
-                if d.non_propose_participation_count >= 3 {
-                    d.propose_strike_count = 0;
-                    d.non_propose_participation_count = 0;
-                }
-            }
-        }
+        emit_cpi!(AuthorityTransferProposedEvent {
+            pool: ctx.accounts.pool.key(),
+            authority: ctx.accounts.authority.key(),
+            pending_authority: new_authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
 
-        if choice == VoteChoice::Abort {
-            // Eligibility for Abort vote (same as proposer: >= 1 SOL deposited, and stake held long enough)
-            require!(ctx.accounts.donor.total_deposited_lamports >= MIN_PROPOSER_DEPOSIT_LAMPORTS, CtoError::AbortNotEligible);
-            let slots_since = clock.slot.checked_sub(ctx.accounts.donor.last_shares_change_slot).ok_or(CtoError::MathOverflow)?;
-            require!(slots_since >= MIN_ABORTER_DELAY_SLOTS, CtoError::AbortNotEligible);
-            // Use proposal variable to avoid borrow conflict
-            require!(clock.unix_timestamp >= proposal.created_at_ts.checked_add(MIN_ABORT_REVIEW_SECONDS).ok_or(CtoError::MathOverflow)?, CtoError::AbortTooEarly);
-            // Charge escalating abort-vote fee into pool PDA (uncapped).
-            // This penalizes *both* abort voters, because each must sign to cast an Abort vote.
-            let fee = penalty_fee(ctx.accounts.pool.base_penalty_lamports, ctx.accounts.donor.abort_strike_count);
-            if fee > 0 {
-                invoke(
-                    &system_instruction::transfer(&ctx.accounts.voter_wallet.key(), &ctx.accounts.pool.key(), fee),
-                    &[
-                        ctx.accounts.voter_wallet.to_account_info(),
-                        ctx.accounts.pool.to_account_info(),
-                        ctx.accounts.system_program.to_account_info(),
-                    ],
-                )
-                .map_err(|_| CtoError::LamportTransferFailed)?;
-            }
+    /// Second step of an authority transfer: the pending authority signs to take over.
+    ///
+    /// Not possible once the current authority has lapsed under the dead-man switch; a
+    /// successful transfer counts as a heartbeat.
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let epoch = Clock::get()?.epoch;
+        let pool = &mut ctx.accounts.pool;
+        require!(
+            pool.pending_authority != Pubkey::default()
+                && ctx.accounts.new_authority.key() == pool.pending_authority,
+            CtoError::UnauthorizedAuthority
+        );
+        require!(!authority_lapsed(pool, epoch), CtoError::AuthorityLapsed);
+        require!(!authority_expired(pool, Clock::get()?.unix_timestamp), CtoError::AuthorityExpired);
 
-            // Increment abort strike immediately; reset requires 3 non-abort participations (Yes/No).
-            ctx.accounts.donor.abort_strike_count = ctx.accounts.donor.abort_strike_count.saturating_add(1);
-            ctx.accounts.donor.non_abort_participation_count = 0;
+        let previous_authority = pool.authority;
+        pool.authority = pool.pending_authority;
+        pool.pending_authority = Pubkey::default();
+        pool.last_heartbeat_epoch = epoch;
 
+        emit_cpi!(AuthorityTransferredEvent {
+            pool: ctx.accounts.pool.key(),
+            previous_authority,
+            new_authority: ctx.accounts.new_authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
 
-            // Record up to 2 unique abort voters using proposal variable to avoid borrow conflict.
-            let voter = ctx.accounts.donor.wallet;
-            if proposal.abort_voter_1 == Pubkey::default() {
-                proposal.abort_voter_1 = voter;
-                proposal.abort_count = 1;
-            } else if proposal.abort_voter_1 == voter {
-                // already recorded
-            } else if proposal.abort_voter_2 == Pubkey::default() {
-                proposal.abort_voter_2 = voter;
-                proposal.abort_count = 2;
-            } else if proposal.abort_voter_2 == voter {
-                // already recorded
-            } else {
-                // only 2 abort voters tracked by design
-                return err!(CtoError::AbortVoterSlotsFull);
-            }
-        }
+    /// Re-read the token mint's authorities into the pool's risk flags. Permissionless, so a
+    /// revoked mint or freeze authority can clear the warning without waiting on the pool authority.
+    pub fn refresh_mint_risk_flags(ctx: Context<RefreshMintRiskFlags>) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        pool.mint_authority_set = ctx.accounts.token_mint.mint_authority.is_some();
+        pool.freeze_authority_set = ctx.accounts.token_mint.freeze_authority.is_some();
 
-        // L-05 FIX: Emit vote cast event
-        emit!(VoteCastEvent {
-            pool: ctx.accounts.pool.key(),
-            proposal: proposal.key(),
-            voter: ctx.accounts.voter_wallet.key(),
-            choice,
-            weight: snapshot_weight,
-            timestamp: clock.unix_timestamp,
+        emit_cpi!(MintRiskFlagsEvent {
+            pool: pool.key(),
+            token_mint: pool.token_mint,
+            mint_authority_set: pool.mint_authority_set,
+            freeze_authority_set: pool.freeze_authority_set,
+            timestamp: Clock::get()?.unix_timestamp,
         });
+        Ok(())
+    }
+
+    /// Configure a Raydium CPMM pool for buy & burn.
+    ///
+    /// `enabled` makes the pool the `burn_venue`. Targets the CPMM program post-graduation
+    /// Raydium pools use (legacy AMM v4 + Serum pools are not supported). The pool state and its
+    /// vaults are pinned here; at crank time the vaults must match the pool state and hold WSOL
+    /// and the burn target. The CPMM authority, AMM config and observation account are supplied
+    /// by the cranker and checked by the CPMM program.
+    pub fn configure_raydium_pool(
+        ctx: Context<ConfigureRaydiumPool>,
+        raydium_pool_id: Pubkey,
+        token_0_vault: Pubkey,
+        token_1_vault: Pubkey,
+        enabled: bool,
+    ) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+
+        require!(raydium_pool_id != Pubkey::default(), CtoError::InvalidRaydiumConfig);
+        require!(token_0_vault != Pubkey::default(), CtoError::InvalidRaydiumConfig);
+        require!(token_1_vault != Pubkey::default(), CtoError::InvalidRaydiumConfig);
+        require!(token_0_vault != token_1_vault, CtoError::InvalidRaydiumConfig);
 
+        let pool = &mut ctx.accounts.pool;
+        // LP tokens and queued deposits belong to the current CPMM pool.
+        require!(
+            raydium_pool_id == pool.raydium_pool_id
+                || (pool.lp_token_amount == 0 && pool.pending_liquidity_lamports == 0),
+            CtoError::LiquidityPositionOpen
+        );
+        pool.raydium_pool_id = raydium_pool_id;
+        pool.raydium_token_0_vault = token_0_vault;
+        pool.raydium_token_1_vault = token_1_vault;
+        select_burn_venue(pool, BurnVenue::RaydiumCpmm, enabled);
         Ok(())
     }
 
-    /// Execute proposal.
+    /// Configure per-pool proposal sizing limits.
     ///
-    /// Atomic, community-executable flow:
-    /// - If proposal fails: unlock reserved liquidity and mark Failed.
-    /// - If proposal passes: withdraw SOL from stake-pool, pay destination, charge protocol fee.
-    /// - Fee split:
-    ///   * 50% -> dev wallet
-    ///   * 50% -> buy & burn CTOP (best-effort). If swap fails, that half is also sent to dev.
+    /// `max_proposal_bps` caps the share of pool LST a single proposal may lock.
+    /// `min_proposal_lamports` is the floor for the requested SOL value of a proposal.
+    /// `proposal_gap_seconds` is the minimum time between a proposal finalizing and the next one.
+    /// `donor_proposal_cooldown_slots` is the minimum gap between two proposals by the same donor
+    /// (0 = off), so no single donor can keep taking the proposal slot.
+    pub fn configure_proposal_limits(
+        ctx: Context<ConfigureProposalLimits>,
+        max_proposal_bps: u16,
+        min_proposal_lamports: u64,
+        proposal_gap_seconds: i64,
+        donor_proposal_cooldown_slots: u64,
+    ) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+        require!(
+            max_proposal_bps > 0 && max_proposal_bps as u64 <= BPS_DENOM,
+            CtoError::InvalidProposalLimits
+        );
+        require!(proposal_gap_seconds >= 0, CtoError::InvalidProposalLimits);
+
+        let pool = &mut ctx.accounts.pool;
+        pool.max_proposal_bps = max_proposal_bps;
+        pool.min_proposal_lamports = min_proposal_lamports;
+        pool.proposal_gap_seconds = proposal_gap_seconds;
+        pool.donor_proposal_cooldown_slots = donor_proposal_cooldown_slots;
+        Ok(())
+    }
+
+    /// Configure the bounds a proposer may choose the voting window from.
     ///
-    /// Key property: Buy & burn failure never prevents the payout leg from succeeding.
-    pub fn execute_proposal(mut ctx: Context<ExecuteProposal>, minimum_lamports_out: u64) -> Result<()> {
-        let clock = Clock::get()?;
-        // ============ PHASE 1: Immutable reads and status checks ============
-        require!(ctx.accounts.proposal.status == ProposalStatus::Active, CtoError::ProposalNotActive);
+    /// The minimum cannot undercut MIN_EXECUTE_DELAY_SECONDS and the maximum is capped at
+    /// MAX_VOTING_WINDOW_SECONDS.
+    pub fn configure_voting_window(
+        ctx: Context<ConfigureVotingWindow>,
+        min_voting_seconds: i64,
+        max_voting_seconds: i64,
+    ) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+        require!(
+            min_voting_seconds >= MIN_EXECUTE_DELAY_SECONDS
+                && max_voting_seconds >= min_voting_seconds
+                && max_voting_seconds <= MAX_VOTING_WINDOW_SECONDS,
+            CtoError::InvalidVotingPeriod
+        );
 
-        // H-04 FIX: Reentrancy guard - lock before CPI
-        lock_pool!(ctx.accounts.pool);
+        let pool = &mut ctx.accounts.pool;
+        pool.min_voting_seconds = min_voting_seconds;
+        pool.max_voting_seconds = max_voting_seconds;
+        Ok(())
+    }
 
-        let participation_weight = ctx.accounts.proposal.participation_weight;
-        let total_snapshot_shares = ctx.accounts.proposal.total_snapshot_shares;
-        let locked_pool_tokens = ctx.accounts.proposal.locked_pool_tokens;
-        let yes_weight = ctx.accounts.proposal.yes_weight;
-        let no_weight = ctx.accounts.proposal.no_weight;
-        let deadline_ts = ctx.accounts.proposal.deadline_ts;
+    /// Configure SOL donation limits (each 0 = off).
+    ///
+    /// `min_donation_lamports` rejects dust donations (and the Donor accounts they would open).
+    /// `max_donor_share_bps` rejects a donation that leaves the donor holding more than this share
+    /// of the pool; a sole donor is exempt, so the first donation can always be made.
+    pub fn configure_donation_limits(
+        ctx: Context<ConfigureDonationLimits>,
+        min_donation_lamports: u64,
+        max_donor_share_bps: u16,
+    ) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+        require!(max_donor_share_bps as u64 <= BPS_DENOM, CtoError::InvalidDonationLimits);
 
-        let quorum_bps = ctx.accounts.pool.quorum_bps;
-        let protocol_fee_bps = ctx.accounts.pool.protocol_fee_bps;
+        let pool = &mut ctx.accounts.pool;
+        pool.min_donation_lamports = min_donation_lamports;
+        pool.max_donor_share_bps = max_donor_share_bps;
+        Ok(())
+    }
 
-        let pool_token_mint = ctx.accounts.pool.token_mint;
-        let pool_bump = ctx.bumps.pool;
+    /// Configure the proposer bond `create_proposal` escrows (0 = off).
+    ///
+    /// `spam_threshold_bps`: participation (of the quorum supply) below which a proposal that
+    /// also missed quorum forfeits its bond to the pool; see `settle_proposer_bond`.
+    pub fn configure_proposer_bond(
+        ctx: Context<ConfigureProposerBond>,
+        bond_lamports: u64,
+        spam_threshold_bps: u16,
+    ) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+        require!(spam_threshold_bps as u64 <= BPS_DENOM, CtoError::InvalidProposerBondConfig);
 
-        let pumpswap_enabled = ctx.accounts.pool.pumpswap_enabled;
-        let raydium_enabled = ctx.accounts.pool.raydium_enabled;
+        let pool = &mut ctx.accounts.pool;
+        pool.proposer_bond_lamports = bond_lamports;
+        pool.bond_spam_threshold_bps = spam_threshold_bps;
+        Ok(())
+    }
 
-        let pool_key = ctx.accounts.pool.key();
-        let proposal_key = ctx.accounts.proposal.key();
+    /// Configure commit–reveal voting for new payout proposals (0 = off).
+    ///
+    /// While on, donors seal their ballot with `commit_vote` until the deadline and open it with
+    /// `reveal_vote` during the following `reveal_seconds`; tallies only move on reveal, so nobody
+    /// can follow the running count. Merkle-mode proposals keep open ballots.
+    pub fn configure_commit_reveal(ctx: Context<ConfigureCommitReveal>, reveal_seconds: i64) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+        require!(
+            reveal_seconds == 0 || (MIN_REVEAL_SECONDS..=MAX_REVEAL_SECONDS).contains(&reveal_seconds),
+            CtoError::InvalidRevealWindow
+        );
 
-        let quorum_met = participation_weight
-            .checked_mul(BPS_DENOM)
-            .ok_or(CtoError::MathOverflow)?
-            >= total_snapshot_shares
-                .checked_mul(quorum_bps as u64)
-                .ok_or(CtoError::MathOverflow)?;
+        ctx.accounts.pool.commit_reveal_seconds = reveal_seconds;
+        Ok(())
+    }
 
-        let time_over = clock.unix_timestamp >= deadline_ts;
-        require!(time_over || quorum_met, CtoError::TooEarlyToExecute);
+    /// Configure anti-sniping for new payout and pool-level proposals (both 0 = off).
+    ///
+    /// A ballot cast in the final hour of voting that flips whether Yes leads pushes the deadline
+    /// back by `extension_seconds`, at most `max_extensions` times per proposal.
+    pub fn configure_anti_sniping(
+        ctx: Context<ConfigureAntiSniping>,
+        extension_seconds: i64,
+        max_extensions: u8,
+    ) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+        let off = extension_seconds == 0 && max_extensions == 0;
+        require!(
+            off || ((MIN_SNIPE_EXTENSION_SECONDS..=MAX_SNIPE_EXTENSION_SECONDS).contains(&extension_seconds)
+                && (1..=MAX_SNIPE_EXTENSIONS).contains(&max_extensions)),
+            CtoError::InvalidAntiSnipingConfig
+        );
 
-        // Even if quorum is met early, enforce a minimum review window before any execution.
-        let min_delay_over = clock.unix_timestamp >= ctx.accounts.proposal.created_at_ts.checked_add(MIN_EXECUTE_DELAY_SECONDS).ok_or(CtoError::MathOverflow)?;
-        require!(min_delay_over, CtoError::TooEarlyToExecuteMinDelay);
+        let pool = &mut ctx.accounts.pool;
+        pool.snipe_extension_seconds = extension_seconds;
+        pool.max_snipe_extensions = max_extensions;
+        Ok(())
+    }
 
-        let majority_met = yes_weight > no_weight;
+    /// Configure time-weighted voting for new proposals (0 = off).
+    ///
+    /// Vote weight ramps linearly from nothing at a donor's last share change to full weight
+    /// `ramp_slots` later, measured at the proposal snapshot, so freshly bought shares carry
+    /// little say. Delegated weight ramps from the escrow's last change; Merkle-mode weights come
+    /// from the posted root. Quorum is still measured against unramped snapshot shares.
+    pub fn configure_time_weighted_voting(ctx: Context<ConfigureTimeWeightedVoting>, ramp_slots: u64) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+        require!(ramp_slots <= MAX_VOTE_RAMP_SLOTS, CtoError::InvalidVoteRamp);
+
+        ctx.accounts.pool.vote_ramp_slots = ramp_slots;
+        Ok(())
+    }
 
-        // ============ FAIL PATH ============
-        if !(quorum_met && majority_met) {
-            {
-                let pool = &mut ctx.accounts.pool;
-                pool.reserved_pool_tokens = pool
-                    .reserved_pool_tokens
-                    .checked_sub(locked_pool_tokens)
-                    .ok_or(CtoError::MathOverflow)?;
-                pool.active_proposal = None;
-            }
-            {
-                let proposal = &mut ctx.accounts.proposal;
-                proposal.status = ProposalStatus::Failed;
-            }
+    /// Turn allowlist mode on or off. While on, `donate_sol`, `donate_sol_for` and
+    /// `create_proposal` only accept wallets with an AllowlistEntry.
+    pub fn configure_allowlist(ctx: Context<ConfigureAllowlist>, enabled: bool) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
 
-            emit!(ProposalFailedEvent {
-                pool: pool_key,
-                proposal: proposal_key,
-                unlocked_pool_tokens: locked_pool_tokens,
-                quorum_met,
-                majority_met,
-                timestamp: clock.unix_timestamp,
-            });
+        ctx.accounts.pool.allowlist_enabled = enabled;
+        Ok(())
+    }
 
-            return Ok(());
-        }
+    /// Add `wallet` to the pool's allowlist (the authority pays the entry's rent).
+    pub fn add_to_allowlist(ctx: Context<AddToAllowlist>, wallet: Pubkey) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+        require!(wallet != Pubkey::default(), CtoError::InvalidBeneficiary);
 
-        // ============ PASS PATH ============
-        let pool_tokens_to_burn = locked_pool_tokens;
-        require!(pool_tokens_to_burn > 0, CtoError::MathOverflow);
+        let clock = Clock::get()?;
+        let entry = &mut ctx.accounts.allowlist_entry;
+        entry.pool = ctx.accounts.pool.key();
+        entry.wallet = wallet;
+        entry.added_slot = clock.slot;
 
-        // ============ PHASE 2: CPIs ============
-        // Withdraw SOL to pool PDA
-        let pre_pool_lamports = ctx.accounts.pool.to_account_info().lamports();
-        stake_pool_withdraw_sol_exec(&ctx, pool_tokens_to_burn, minimum_lamports_out)?;
-        let post_pool_lamports = ctx.accounts.pool.to_account_info().lamports();
-        let sol_received = post_pool_lamports
-            .checked_sub(pre_pool_lamports)
-            .ok_or(CtoError::MathOverflow)?;
-        require!(sol_received >= minimum_lamports_out, CtoError::SlippageExceeded);
+        emit_cpi!(AllowlistUpdatedEvent {
+            pool: ctx.accounts.pool.key(),
+            wallet,
+            allowed: true,
+            timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
 
-        // Fee is % of actual received
-        let protocol_fee = sol_received
-            .checked_mul(protocol_fee_bps as u64)
-            .ok_or(CtoError::MathOverflow)?
-            .checked_div(BPS_DENOM)
-            .ok_or(CtoError::MathOverflow)?;
-        let net_to_destination = sol_received
-            .checked_sub(protocol_fee)
-            .ok_or(CtoError::MathOverflow)?;
+    /// Remove `wallet` from the pool's allowlist, refunding the entry's rent to the authority.
+    ///
+    /// Existing shares are untouched; the wallet just can't donate or propose while allowlist
+    /// mode is on.
+    pub fn remove_from_allowlist(ctx: Context<RemoveFromAllowlist>, wallet: Pubkey) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
 
-        // Pay destination
-        transfer_lamports_signed(
-            &ctx.accounts.pool.to_account_info(),
-            &ctx.accounts.destination_wallet.to_account_info(),
-            &[&[b"pool", pool_token_mint.as_ref(), &[pool_bump]]],
-            net_to_destination,
-        )?;
+        emit_cpi!(AllowlistUpdatedEvent {
+            pool: ctx.accounts.pool.key(),
+            wallet,
+            allowed: false,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
 
-        // Fee split
-        let fee_half = protocol_fee.checked_div(2).ok_or(CtoError::MathOverflow)?;
-        let mut dev_take = protocol_fee.checked_sub(fee_half).ok_or(CtoError::MathOverflow)?;
-
-        // Buy & burn attempt with `fee_half` (best-effort).
-        if fee_half > 0 {
-            // Prefer PumpSwap for Pump.fun launches after graduation.
-            let did_try_pumpswap = pumpswap_enabled
-                && ctx.accounts.pool.pumpswap_pool_id != Pubkey::default()
-                && ctx.accounts.pumpswap_pool.key() == ctx.accounts.pool.pumpswap_pool_id
-                && ctx.accounts.pumpswap_pool_base_vault.key() == ctx.accounts.pool.pumpswap_base_vault
-                && ctx.accounts.pumpswap_pool_quote_vault.key() == ctx.accounts.pool.pumpswap_quote_vault
-                && ctx.accounts.pumpswap_global_config.key() == ctx.accounts.pool.pumpswap_global_config
-                && ctx.accounts.pumpswap_protocol_fee_recipient.key() == ctx.accounts.pool.pumpswap_fee_recipient;
-
-            if did_try_pumpswap {
-                match attempt_pumpswap_swap_and_burn(&mut ctx, fee_half, pool_bump) {
-                    Ok(ctop_burned) => {
-                        emit!(TokenBurnEvent {
-                            pool: pool_key,
-                            amount_sol: fee_half,
-                            amount_ctop: ctop_burned,
-                            timestamp: clock.unix_timestamp,
-                        });
-                    }
-                    Err(_e) => {
-                        // Best-effort means failure routes to dev.
-                        dev_take = dev_take.checked_add(fee_half).ok_or(CtoError::MathOverflow)?;
-                        emit!(SwapFailureEvent {
-                            pool: pool_key,
-                            amount_sol: fee_half,
-                            error_code: 1, // PumpSwap failure
-                            timestamp: clock.unix_timestamp,
-                        });
-                    }
-                }
-            } else {
-                // No configured venue -> send to dev (explicitly accepted design)
-                dev_take = dev_take.checked_add(fee_half).ok_or(CtoError::MathOverflow)?;
-            }
-        }
+    /// Configure the tolerance used to derive a donor's minimum out when they pass 0.
+    ///
+    /// Applies to `donate_sol`, `withdraw_sol` and `withdraw_all`: a zero `minimum_*_out` is replaced
+    /// by the stake pool's current quote (after its fees) minus `slippage_bps`.
+    pub fn configure_donor_slippage(ctx: Context<ConfigureDonorSlippage>, slippage_bps: u16) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+        require!(slippage_bps <= MAX_DONOR_SLIPPAGE_BPS, CtoError::InvalidSlippageTolerance);
 
-        // Pay dev
-        if dev_take > 0 {
-            transfer_lamports_signed(
-                &ctx.accounts.pool.to_account_info(),
-                &ctx.accounts.dev_fee_wallet.to_account_info(),
-                &[&[b"pool", pool_token_mint.as_ref(), &[pool_bump]]],
-                dev_take,
-            )?;
-        }
+        ctx.accounts.pool.donor_slippage_bps = slippage_bps;
+        Ok(())
+    }
 
-        // Reload LST account after all CPIs
-        ctx.accounts.pool_lst_account.reload()?;
-        let final_pool_tokens = ctx.accounts.pool_lst_account.amount;
+    /// Set whether holders may donate the pool's own token (see `TokenDonationPolicy`).
+    pub fn configure_token_donations(ctx: Context<ConfigureTokenDonations>, policy: TokenDonationPolicy) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
 
-        // ============ PHASE 3: Mutable state updates ============
-        {
-            let pool = &mut ctx.accounts.pool;
-            pool.total_spent_lamports = pool
-                .total_spent_lamports
-                .checked_add(net_to_destination)
-                .ok_or(CtoError::MathOverflow)?;
-            pool.reserved_pool_tokens = pool
-                .reserved_pool_tokens
-                .checked_sub(pool_tokens_to_burn)
-                .ok_or(CtoError::MathOverflow)?;
-            pool.total_pool_tokens = final_pool_tokens;
-            pool.active_proposal = None;
-        }
-        {
-            let proposal = &mut ctx.accounts.proposal;
-            proposal.status = ProposalStatus::Executed;
-        }
+        ctx.accounts.pool.token_donation_policy = policy;
+        Ok(())
+    }
+
+    /// Donate the pool's own token into the pool's treasury ATA.
+    ///
+    /// The tokens are tracked in `treasury_token_amount`, separately from the LST treasury: they
+    /// mint no shares, count toward no quorum and can't be locked by payout proposals.
+    pub fn donate_pool_token(ctx: Context<DonatePoolToken>, amount: u64) -> Result<()> {
+        require!(amount > 0, CtoError::ZeroAmount);
+        require!(ctx.accounts.pool.sunset_started_ts == 0, CtoError::PoolSunsetting);
+        require!(!ctx.accounts.pool.paused, CtoError::PoolPaused);
+        require!(
+            ctx.accounts.pool.token_donation_policy == TokenDonationPolicy::NoShares,
+            CtoError::TokenDonationsDisabled
+        );
+
+        // Reentrancy guard: Token-2022 transfer hooks run arbitrary programs.
+        lock_pool!(ctx.accounts.pool);
+
+        ctx.accounts.pool_token_treasury.reload()?;
+        let pre_balance = ctx.accounts.pool_token_treasury.amount;
+
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.donor_token_account.to_account_info(),
+                    mint: ctx.accounts.token_mint.to_account_info(),
+                    to: ctx.accounts.pool_token_treasury.to_account_info(),
+                    authority: ctx.accounts.donor_wallet.to_account_info(),
+                },
+            ),
+            amount,
+            ctx.accounts.token_mint.decimals,
+        )?;
+
+        // Observe what actually arrived (transfer-fee mints deliver less than `amount`).
+        ctx.accounts.pool_token_treasury.reload()?;
+        let received = ctx
+            .accounts
+            .pool_token_treasury
+            .amount
+            .checked_sub(pre_balance)
+            .ok_or(CtoError::MathOverflow)?;
+        require!(received > 0, CtoError::ZeroAmount);
+
+        let clock = Clock::get()?;
+        let pool = &mut ctx.accounts.pool;
+        // Resync to the observed balance so direct transfers into the ATA are counted too.
+        pool.treasury_token_amount = ctx.accounts.pool_token_treasury.amount;
+        pool.last_engagement_ts = clock.unix_timestamp;
+
+        record_activity(
+            &mut ctx.accounts.activity_log,
+            ActivityKind::Donate,
+            1,
+            ctx.accounts.donor_wallet.key(),
+            received,
+            clock.slot,
+        );
 
-        // H-04 FIX: Unlock after successful operation
         unlock_pool!(ctx.accounts.pool);
 
-        // L-05 FIX: Emit proposal executed event
-        emit!(ProposalExecutedEvent {
-            pool: pool_key,
-            proposal: proposal_key,
-            lamports_paid: net_to_destination,
-            protocol_fee,
+        emit_cpi!(PoolTokenDonationEvent {
+            pool: ctx.accounts.pool.key(),
+            donor: ctx.accounts.donor_wallet.key(),
+            amount,
+            received,
+            treasury_token_amount: ctx.accounts.pool.treasury_token_amount,
             timestamp: clock.unix_timestamp,
         });
 
         Ok(())
     }
 
-    /// Recover non-LST assets stuck in the pool.
-    pub fn recover_funds_create(
-        ctx: Context<RecoverFundsCreate>,
-        token_mint: Pubkey,
-        amount: u64,
-        destination_wallet: Pubkey,
-        title: String,
-        description: String,
-    ) -> Result<()> {
-        require!(amount > 0, CtoError::ZeroAmount);
-        require!(title.as_bytes().len() <= RecoveryProposal::TITLE_MAX, CtoError::TitleTooLong);
-        require!(description.as_bytes().len() <= RecoveryProposal::DESC_MAX, CtoError::DescriptionTooLong);
-        require!(token_mint != ctx.accounts.pool.lst_mint, CtoError::RecoveryNotAllowedForLST);
+    /// Set the Pyth SOL/USD PriceUpdateV2 account SPL donations are valued against
+    /// (default disables `donate_spl`).
+    pub fn configure_sol_usd_feed(ctx: Context<ConfigureSolUsdFeed>, price_feed: Pubkey) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
 
-        let pool_bump = ctx.bumps.pool;
-        let pool_token_mint = ctx.accounts.pool.token_mint;
-
-        if verify_inline_sender(
-            &ctx.accounts.instructions,
-            &ctx.accounts.requester.key(),
-            &ctx.accounts.pool_token_account.key(),
-            token_mint,
-            amount,
-        )? {
-            transfer_spl_from_pool_with_seeds(
-                &ctx.accounts.pool.to_account_info(),
-                &ctx.accounts.pool_token_account,
-                &ctx.accounts.destination_token_account,
-                &ctx.accounts.token_program,
-                &[&[b"pool", pool_token_mint.as_ref(), &[pool_bump]]],
-                amount,
-            )?;
-            return Ok(());
-        }
+        ctx.accounts.pool.sol_usd_price_feed = price_feed;
+        Ok(())
+    }
 
+    /// Turn the pool into an all-or-nothing campaign (`goal_lamports` 0 = off).
+    ///
+    /// Only before the first donation. Until donations (SOL and oracle-valued SPL, as credited
+    /// to donors) add up to `goal_lamports`, withdrawals and proposals stay closed. If the goal
+    /// isn't reached by `deadline_ts`, donations close for good and donors take their full
+    /// pro-rata balance back with `claim_campaign_refund`.
+    pub fn configure_campaign(ctx: Context<ConfigureCampaign>, goal_lamports: u64, deadline_ts: i64) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
         let pool = &mut ctx.accounts.pool;
-        let donor = &ctx.accounts.donor;
-        let clock = Clock::get()?;
-        
-        // H-01 FIX: Recovery proposals should also be affected by the abort cooldown.
-        // The abort cooldown is meant to be a safe time for donors to withdraw their funds if they think they are at risk.
+        require!(pool.total_shares == 0, CtoError::CampaignAfterDonations);
         require!(
-            clock.unix_timestamp >= pool.proposal_cooldown_until_ts,
-            CtoError::ProposalCooldownActive
+            goal_lamports == 0 || deadline_ts > Clock::get()?.unix_timestamp,
+            CtoError::InvalidCampaignConfig
         );
-        
-        require!(pool.active_recovery.is_none(), CtoError::ActiveRecoveryExists);
-        require!(donor.shares > 0, CtoError::NoShares);
 
-        let rec = &mut ctx.accounts.recovery;
-        rec.pool = pool.key();
-        rec.token_mint = token_mint;
-        rec.requested_amount = amount;
-        rec.destination_wallet = destination_wallet;
-        rec.title = title;
-        rec.description = description;
-        rec.created_at_ts = clock.unix_timestamp;
-        rec.deadline_ts = clock
-            .unix_timestamp
-            .checked_add(24 * 60 * 60)
-            .ok_or(CtoError::MathOverflow)?;
-        rec.snapshot_slot = clock.slot;
-        rec.total_snapshot_shares = pool.total_shares;
-        rec.yes_weight = 0;
-        rec.no_weight = 0;
-        rec.abstain_weight = 0;
-        rec.participation_weight = 0;
-        rec.status = ProposalStatus::Active;
+        pool.campaign_goal_lamports = goal_lamports;
+        pool.campaign_deadline_ts = if goal_lamports == 0 { 0 } else { deadline_ts };
+        pool.campaign_raised_lamports = 0;
+        pool.campaign_goal_reached = false;
+        Ok(())
+    }
 
-        pool.active_recovery = Some(rec.key());
-        pool.recovery_count = pool.recovery_count.checked_add(1).ok_or(CtoError::MathOverflow)?;
+    /// Configure referral rewards for `donate_sol` (bps 0 = off).
+    ///
+    /// A donation naming a referrer mints the referrer's Donor `referral_bps` of the donor's
+    /// new shares on top, until `cap_shares` bonus shares have been minted pool-wide. The bonus
+    /// is dilutive: it comes out of every donor's slice, which is why it is capped.
+    pub fn configure_referrals(ctx: Context<ConfigureReferrals>, referral_bps: u16, cap_shares: u64) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+        require!(
+            referral_bps <= MAX_REFERRAL_BPS && (referral_bps == 0 || cap_shares > 0),
+            CtoError::InvalidReferralConfig
+        );
 
+        let pool = &mut ctx.accounts.pool;
+        pool.referral_bps = referral_bps;
+        pool.referral_cap_shares = cap_shares;
         Ok(())
     }
 
-    /// L-06 FIX: Recovery proposals should have abort also, same as regular proposals.
-    /// This provides consistent emergency brake capabilities across all proposal types.
-    pub fn recover_funds_vote(ctx: Context<RecoverFundsVote>, choice: VoteChoice) -> Result<()> {
-        let proposal = &mut ctx.accounts.recovery;
-        let donor = &ctx.accounts.donor;
-        let vote_record = &mut ctx.accounts.vote_record;
-        let clock = Clock::get()?;
-        require!(proposal.status == ProposalStatus::Active, CtoError::ProposalNotActive);
-        require!(clock.unix_timestamp <= proposal.deadline_ts, CtoError::VotingClosed);
-        require!(donor.shares > 0, CtoError::NoShares);
+    /// Configure the oracle sanity checks (each band 0 = off, at most 50%).
+    ///
+    /// `burn_price_band_bps`: `process_buy_and_burn` reverts unless the CTOP it bought is worth,
+    /// at Pyth CTOP/USD and SOL/USD (`ctop_usd_price_feed` and the pool's `sol_usd_price_feed`),
+    /// at least this far below the SOL spent. Guards against swapping into manipulated reserves.
+    ///
+    /// `lst_rate_band_bps`: withdrawals, rage-quits and payouts revert while the stake pool's
+    /// exchange rate is further than this from its last-epoch rate.
+    pub fn configure_oracle_guards(
+        ctx: Context<ConfigureOracleGuards>,
+        ctop_usd_price_feed: Pubkey,
+        burn_price_band_bps: u16,
+        lst_rate_band_bps: u16,
+    ) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
         require!(
-            donor.last_shares_change_slot <= proposal.snapshot_slot,
-            CtoError::NotEligibleForThisProposal
+            burn_price_band_bps <= MAX_ORACLE_GUARD_BAND_BPS && lst_rate_band_bps <= MAX_ORACLE_GUARD_BAND_BPS,
+            CtoError::InvalidOracleGuardConfig
         );
 
-        if vote_record.initialized {
-            let w = vote_record.snapshot_weight;
-            match vote_record.choice {
-                VoteChoice::Yes => proposal.yes_weight = proposal.yes_weight.checked_sub(w).ok_or(CtoError::MathOverflow)?,
-                VoteChoice::No => proposal.no_weight = proposal.no_weight.checked_sub(w).ok_or(CtoError::MathOverflow)?,
-                VoteChoice::Abstain => proposal.abstain_weight = proposal.abstain_weight.checked_sub(w).ok_or(CtoError::MathOverflow)?,
-                VoteChoice::Abort => unreachable!(),
-            }
+        let pool = &mut ctx.accounts.pool;
+        if burn_price_band_bps > 0 {
+            require!(
+                ctop_usd_price_feed != Pubkey::default() && pool.sol_usd_price_feed != Pubkey::default(),
+                CtoError::InvalidOracleGuardConfig
+            );
         }
+        pool.ctop_usd_price_feed = ctop_usd_price_feed;
+        pool.burn_price_band_bps = burn_price_band_bps;
+        pool.lst_rate_band_bps = lst_rate_band_bps;
+        Ok(())
+    }
 
-        // M-01 FIX: Use u128 for intermediate calculation to prevent overflow
-        // when total_snapshot_shares is very large
-        let snapshot_weight = if vote_record.initialized {
-            vote_record.snapshot_weight
-        } else {
-            let raw = donor.shares;
-            let cap = ((proposal.total_snapshot_shares as u128)
-                .checked_mul(MAX_VOTER_BPS as u128)
-                .ok_or(CtoError::MathOverflow)?
-                .checked_div(BPS_DENOM as u128)
-                .ok_or(CtoError::MathOverflow)?) as u64;
-            raw.min(cap)
-        };
-
-        // L-06 FIX: Handle abort voting for recovery proposals (same as regular proposals)
-        if choice == VoteChoice::Abort {
-            // Eligibility for Abort vote (same as proposer: >= 1 SOL deposited, and stake held long enough)
-            require!(donor.total_deposited_lamports >= MIN_PROPOSER_DEPOSIT_LAMPORTS, CtoError::AbortNotEligible);
-            let slots_since = clock.slot.checked_sub(donor.last_shares_change_slot).ok_or(CtoError::MathOverflow)?;
-            require!(slots_since >= MIN_ABORTER_DELAY_SLOTS, CtoError::AbortNotEligible);
-            require!(clock.unix_timestamp >= proposal.created_at_ts.checked_add(MIN_ABORT_REVIEW_SECONDS).ok_or(CtoError::MathOverflow)?, CtoError::AbortTooEarly);
+    /// Accept (or stop accepting) an SPL mint in `donate_spl`, valued through the Pyth
+    /// mint/USD PriceUpdateV2 account `price_feed`.
+    ///
+    /// `max_total_lamports` caps the oracle value of all donations of this mint (0 = uncapped).
+    pub fn configure_spl_donation_mint(
+        ctx: Context<ConfigureSplDonationMint>,
+        price_feed: Pubkey,
+        enabled: bool,
+        max_total_lamports: u64,
+    ) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+        require!(
+            ctx.accounts.mint.key() != ctx.accounts.pool.lst_mint && ctx.accounts.mint.key() != ctx.accounts.pool.token_mint,
+            CtoError::InvalidSplDonationMint
+        );
 
-            // Record up to 2 unique abort voters.
-            let voter = donor.wallet;
-            if proposal.abort_voter_1 == Pubkey::default() {
-                proposal.abort_voter_1 = voter;
-                proposal.abort_count = 1;
-            } else if proposal.abort_voter_1 == voter {
-                // already recorded
-            } else if proposal.abort_voter_2 == Pubkey::default() {
-                proposal.abort_voter_2 = voter;
-                proposal.abort_count = 2;
-            } else if proposal.abort_voter_2 == voter {
-                // already recorded
-            } else {
-                return err!(CtoError::AbortVoterSlotsFull);
-            }
+        let accepted = &mut ctx.accounts.spl_donation_mint;
+        if accepted.pool == Pubkey::default() {
+            accepted.total_shares = 0;
         }
+        accepted.pool = ctx.accounts.pool.key();
+        accepted.mint = ctx.accounts.mint.key();
+        accepted.price_feed = price_feed;
+        accepted.enabled = enabled;
+        accepted.max_total_lamports = max_total_lamports;
+        Ok(())
+    }
 
-        match choice {
-            VoteChoice::Yes => proposal.yes_weight = proposal.yes_weight.checked_add(snapshot_weight).ok_or(CtoError::MathOverflow)?,
-            VoteChoice::No => proposal.no_weight = proposal.no_weight.checked_add(snapshot_weight).ok_or(CtoError::MathOverflow)?,
-            VoteChoice::Abstain => proposal.abstain_weight = proposal.abstain_weight.checked_add(snapshot_weight).ok_or(CtoError::MathOverflow)?,
-            VoteChoice::Abort => { /* tracked separately via abort_voter fields */ },
-        }
+    /// Donate an accepted SPL token (e.g. USDC) for shares of that mint's vault.
+    ///
+    /// The tokens are held in the pool's ATA for that mint and never join the LST balance, so
+    /// they mint no pool shares: the donor gets SplPosition shares of the vault instead, pro rata
+    /// to what arrived, redeemed by `redeem_spl_position`. The donation is valued with Pyth
+    /// (token/USD and SOL/USD, each at the unfavourable end of its confidence interval) only for
+    /// the mint's value cap and the pool's donation totals.
+    pub fn donate_spl(ctx: Context<DonateSpl>, amount: u64, minimum_shares_out: u64) -> Result<()> {
+        require!(amount > 0, CtoError::ZeroAmount);
+        require!(ctx.accounts.pool.sunset_started_ts == 0, CtoError::PoolSunsetting);
+        require!(!ctx.accounts.pool.paused, CtoError::PoolPaused);
+        require!(!campaign_failed(&ctx.accounts.pool, Clock::get()?.unix_timestamp), CtoError::CampaignFailed);
+        require!(ctx.accounts.spl_donation_mint.enabled, CtoError::SplDonationsDisabled);
+        require!(ctx.accounts.pool.sol_usd_price_feed != Pubkey::default(), CtoError::SplDonationsDisabled);
 
-        proposal.participation_weight = proposal
-            .yes_weight
-            .checked_add(proposal.no_weight)
-            .ok_or(CtoError::MathOverflow)?
-            .checked_add(proposal.abstain_weight)
-            .ok_or(CtoError::MathOverflow)?;
+        // Reentrancy guard: Token-2022 transfer hooks run arbitrary programs.
+        lock_pool!(ctx.accounts.pool);
 
-        vote_record.proposal = proposal.key();
-        vote_record.voter = donor.wallet;
-        vote_record.snapshot_weight = snapshot_weight;
-        vote_record.choice = choice;
-        vote_record.initialized = true;
+        ctx.accounts.pool_spl_account.reload()?;
+        let pre_balance = ctx.accounts.pool_spl_account.amount;
+
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.donor_token_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.pool_spl_account.to_account_info(),
+                    authority: ctx.accounts.donor_wallet.to_account_info(),
+                },
+            ),
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
 
-        Ok(())
-    }
+        // Value what actually arrived (transfer-fee mints deliver less than `amount`).
+        ctx.accounts.pool_spl_account.reload()?;
+        let received = ctx
+            .accounts
+            .pool_spl_account
+            .amount
+            .checked_sub(pre_balance)
+            .ok_or(CtoError::MathOverflow)?;
+        require!(received > 0, CtoError::ZeroAmount);
 
-    /// Finalize an Abort once 2 eligible wallets have voted Abort.
-    ///
-    /// Effects:
-    /// - Unlocks reserved liquidity for this proposal
-    /// - Marks proposal Aborted
-    /// - Clears active_proposal
-    /// - Sets pool cooldown window
-    /// - Increments proposer strike (proposer pays next time they create a proposal)
-    pub fn abort_proposal(ctx: Context<AbortProposal>) -> Result<()> {
         let clock = Clock::get()?;
+        let token_usd = read_pyth_price(&ctx.accounts.price_feed, clock.unix_timestamp)?;
+        let sol_usd = read_pyth_price(&ctx.accounts.sol_usd_price_feed, clock.unix_timestamp)?;
+        let lamports_value = oracle_value_lamports(received, ctx.accounts.mint.decimals, &token_usd, &sol_usd)?;
+
+        let accepted = &mut ctx.accounts.spl_donation_mint;
+        let total_donated_lamports = accepted
+            .total_donated_lamports
+            .checked_add(lamports_value)
+            .ok_or(CtoError::MathOverflow)?;
+        require!(
+            accepted.max_total_lamports == 0 || total_donated_lamports <= accepted.max_total_lamports,
+            CtoError::SplDonationCapExceeded
+        );
+        accepted.total_donated_lamports = total_donated_lamports;
+        accepted.total_donated_amount = accepted
+            .total_donated_amount
+            .checked_add(received)
+            .ok_or(CtoError::MathOverflow)?;
 
-        require!(ctx.accounts.proposal.status == ProposalStatus::Active, CtoError::ProposalNotActive);
-        require!(ctx.accounts.proposal.abort_count >= 2, CtoError::AbortThresholdNotMet);
-        require!(clock.unix_timestamp >= ctx.accounts.proposal.created_at_ts.checked_add(MIN_ABORT_REVIEW_SECONDS).ok_or(CtoError::MathOverflow)?, CtoError::AbortTooEarly);
-
-        // Aborter must be one of the recorded abort voters
-        let aborter = ctx.accounts.aborter.wallet;
-        require!(aborter == ctx.accounts.proposal.abort_voter_1 || aborter == ctx.accounts.proposal.abort_voter_2, CtoError::AbortNotEligible);
+        // Priced against the vault as it stood, so earlier donors keep their slice of it.
+        let shares_minted = shares_for_deposit(received, accepted.total_shares, pre_balance, ctx.accounts.pool.share_scale)?;
+        require!(shares_minted >= MIN_SHARES_MINTED, CtoError::DonationTooSmall);
+        require!(shares_minted >= minimum_shares_out, CtoError::SlippageExceeded);
+        accepted.total_shares = accepted
+            .total_shares
+            .checked_add(shares_minted)
+            .ok_or(CtoError::MathOverflow)?;
 
-        // Unlock reserved liquidity
-        let locked = ctx.accounts.proposal.locked_pool_tokens;
-        ctx.accounts.pool.reserved_pool_tokens = ctx.accounts.pool.reserved_pool_tokens.checked_sub(locked).ok_or(CtoError::MathOverflow)?;
-        ctx.accounts.pool.active_proposal = None;
+        let position = &mut ctx.accounts.spl_position;
+        position.spl_donation_mint = accepted.key();
+        position.wallet = ctx.accounts.donor_wallet.key();
+        position.shares = position.shares.checked_add(shares_minted).ok_or(CtoError::MathOverflow)?;
 
-        // Apply global cooldown
-        ctx.accounts.pool.proposal_cooldown_until_ts = clock.unix_timestamp.checked_add(COOLDOWN_AFTER_ABORT_SECONDS).ok_or(CtoError::MathOverflow)?;
-        // Penalize proposer: aborted proposals raise the next proposal-creation fee (uncapped)
-        ctx.accounts.proposer_donor.propose_strike_count = ctx.accounts.proposer_donor.propose_strike_count.saturating_add(1);
-        ctx.accounts.proposer_donor.non_propose_participation_count = 0;
+        let pool = &mut ctx.accounts.pool;
+        pool.spl_position_shares = pool
+            .spl_position_shares
+            .checked_add(shares_minted)
+            .ok_or(CtoError::MathOverflow)?;
+        pool.spl_donated_lamports = pool
+            .spl_donated_lamports
+            .checked_add(lamports_value)
+            .ok_or(CtoError::MathOverflow)?;
+        pool.last_engagement_ts = clock.unix_timestamp;
+
+        record_activity(
+            &mut ctx.accounts.activity_log,
+            ActivityKind::Donate,
+            2,
+            ctx.accounts.donor_wallet.key(),
+            lamports_value,
+            clock.slot,
+        );
 
-        // Mark proposal aborted
-        ctx.accounts.proposal.status = ProposalStatus::Aborted;
+        unlock_pool!(ctx.accounts.pool);
 
-        // L-05 FIX: Emit proposal aborted event
-        emit!(ProposalAbortedEvent {
+        emit_cpi!(SplDonationEvent {
             pool: ctx.accounts.pool.key(),
-            proposal: ctx.accounts.proposal.key(),
-            abort_voters: [ctx.accounts.proposal.abort_voter_1, ctx.accounts.proposal.abort_voter_2],
+            donor: ctx.accounts.donor_wallet.key(),
+            mint: ctx.accounts.mint.key(),
+            amount,
+            received,
+            lamports_value,
+            shares_minted,
             timestamp: clock.unix_timestamp,
         });
 
         Ok(())
     }
 
-    pub fn recover_funds_execute(ctx: Context<RecoverFundsExecute>) -> Result<()> {
-        let clock = Clock::get()?;
-        let pool_bump = ctx.bumps.pool;
-        let pool_token_mint = ctx.accounts.pool.token_mint;
-        let pool_quorum_bps = ctx.accounts.pool.quorum_bps;
-        let requested_amount = ctx.accounts.recovery.requested_amount;
-
-        let rec = &mut ctx.accounts.recovery;
-
-        require!(rec.status == ProposalStatus::Active, CtoError::ProposalNotActive);
+    /// Redeem `shares` of an SplPosition for their pro-rata slice of the mint's vault.
+    ///
+    /// Allowed whether or not the mint is still accepted, and while the pool is paused or in
+    /// sunset. `minimum_amount_out` bounds what governance payouts from the vault may have left.
+    pub fn redeem_spl_position(ctx: Context<RedeemSplPosition>, shares: u64, minimum_amount_out: u64) -> Result<()> {
+        require!(shares > 0, CtoError::ZeroAmount);
+        require!(shares <= ctx.accounts.spl_position.shares, CtoError::InsufficientShares);
 
-        let quorum_met = rec
-            .participation_weight
-            .checked_mul(BPS_DENOM)
-            .ok_or(CtoError::MathOverflow)?
-            >= rec
-                .total_snapshot_shares
-                .checked_mul(pool_quorum_bps as u64)
-                .ok_or(CtoError::MathOverflow)?;
+        // Reentrancy guard: Token-2022 transfer hooks run arbitrary programs.
+        lock_pool!(ctx.accounts.pool);
 
-        let time_over = clock.unix_timestamp >= rec.deadline_ts;
-        require!(time_over || quorum_met, CtoError::TooEarlyToExecute);
+        ctx.accounts.pool_spl_account.reload()?;
+        let total_shares = ctx.accounts.spl_donation_mint.total_shares;
+        let amount = mul_div_floor(shares, ctx.accounts.pool_spl_account.amount, total_shares)?;
+        require!(amount > 0, CtoError::ZeroAmount);
+        require!(amount >= minimum_amount_out, CtoError::SlippageExceeded);
 
-        let majority_met = rec.yes_weight > rec.no_weight;
+        let pool_bump = ctx.bumps.pool;
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.pool_spl_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.wallet_token_account.to_account_info(),
+                    authority: ctx.accounts.pool.to_account_info(),
+                },
+                pool_seeds!(&ctx.accounts.pool, pool_bump),
+            ),
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
 
-        if !(quorum_met && majority_met) {
-            rec.status = ProposalStatus::Failed;
-            ctx.accounts.pool.active_recovery = None;
-            return Ok(());
-        }
+        let accepted = &mut ctx.accounts.spl_donation_mint;
+        accepted.total_shares = accepted.total_shares.checked_sub(shares).ok_or(CtoError::MathOverflow)?;
+        let position = &mut ctx.accounts.spl_position;
+        position.shares = position.shares.checked_sub(shares).ok_or(CtoError::MathOverflow)?;
+        let pool = &mut ctx.accounts.pool;
+        pool.spl_position_shares = pool
+            .spl_position_shares
+            .checked_sub(shares)
+            .ok_or(CtoError::MathOverflow)?;
 
-        transfer_spl_from_pool_with_seeds(
-            &ctx.accounts.pool.to_account_info(),
-            &ctx.accounts.pool_token_account,
-            &ctx.accounts.destination_token_account,
-            &ctx.accounts.token_program,
-            &[&[b"pool", pool_token_mint.as_ref(), &[pool_bump]]],
-            requested_amount,
-        )?;
+        unlock_pool!(ctx.accounts.pool);
 
-        rec.status = ProposalStatus::Executed;
-        ctx.accounts.pool.active_recovery = None;
+        emit_cpi!(SplPositionRedeemedEvent {
+            pool: ctx.accounts.pool.key(),
+            wallet: ctx.accounts.wallet.key(),
+            mint: ctx.accounts.mint.key(),
+            shares_burned: shares,
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
 
         Ok(())
     }
-}
 
-// ============= Helper Functions =============
+    /// Donate native SOL to the pool.
+    ///
+    /// Flow:
+    /// - CPI into the configured stake-pool program `deposit_sol_with_slippage`
+    /// - pool receives LST tokens (e.g. jitoSOL) into its token account
+    /// - shares minted to donor based on LST received
+    ///
+    /// `memo` and `campaign_tag` are optional (empty = none); when either is set a
+    /// DonationMemoEvent is emitted so pools can attribute the donation to a drive.
+    ///
+    /// `referrer` names an existing donor (passed as `referrer_donor`) who is minted the pool's
+    /// referral bonus, if the pool has referrals on (see `configure_referrals`).
+    pub fn donate_sol(
+        ctx: Context<DonateSol>,
+        lamports_in: u64,
+        minimum_pool_tokens_out: u64,
+        memo: String,
+        campaign_tag: String,
+        referrer: Option<Pubkey>,
+    ) -> Result<()> {
+        require!(lamports_in > 0, CtoError::ZeroAmount);
+        require!(lamports_in >= ctx.accounts.pool.min_donation_lamports, CtoError::DonationBelowMinimum);
+        require_allowlisted(&ctx.accounts.pool, &ctx.accounts.allowlist_entry)?;
+        validate_donation_memo(&memo, &campaign_tag)?;
+        if let Some(referrer) = referrer {
+            let referrer_donor = ctx.accounts.referrer_donor.as_ref().ok_or(CtoError::InvalidReferrer)?;
+            require!(
+                referrer != ctx.accounts.donor_wallet.key()
+                    && referrer_donor.wallet == referrer
+                    && referrer_donor.pool == ctx.accounts.pool.key(),
+                CtoError::InvalidReferrer
+            );
+        }
+        require!(ctx.accounts.pool.sunset_started_ts == 0, CtoError::PoolSunsetting);
+        require!(!ctx.accounts.pool.paused, CtoError::PoolPaused);
+        require!(!campaign_failed(&ctx.accounts.pool, Clock::get()?.unix_timestamp), CtoError::CampaignFailed);
 
-/// Uncapped exponential penalty fee: base * 2^strikes
-fn penalty_fee(base: u64, strikes: u16) -> u64 {
-    if strikes == 0 {
-        return 0;
+        // H-04 FIX: Reentrancy guard - lock before CPI
+        lock_pool!(ctx.accounts.pool);
+
+        // Defensive accounting: read pre-CPI balance from the token account itself.
+        ctx.accounts.pool_lst_account.reload()?;
+        let pre_balance = observed_pool_tokens(&ctx.accounts.pool, &ctx.accounts.pool_lst_account);
+
+        // Zero means "no preference": derive a floor from the current rate instead of accepting anything.
+        let minimum_pool_tokens_out = if minimum_pool_tokens_out == 0 {
+            let stake_pool_state = read_pool_rate(&ctx.accounts.pool, &ctx.accounts.stake_pool)?;
+            default_min_pool_tokens_out(&stake_pool_state, lamports_in, ctx.accounts.pool.donor_slippage_bps)?
+        } else {
+            minimum_pool_tokens_out
+        };
+
+        if ctx.accounts.pool.custody == PoolCustody::Native {
+            let payer = ctx.accounts.donor_wallet.to_account_info();
+            let system_program = ctx.accounts.system_program.to_account_info();
+            deposit_native_lamports(&mut ctx.accounts.pool, payer, system_program, lamports_in)?;
+        } else {
+            stake_pool_deposit_sol(&ctx, lamports_in, minimum_pool_tokens_out)?;
+        }
+
+        // Observe actual received LST and update accounting.
+        ctx.accounts.pool_lst_account.reload()?;
+        let new_balance = observed_pool_tokens(&ctx.accounts.pool, &ctx.accounts.pool_lst_account);
+        let clock = Clock::get()?;
+
+        // First donation (freshly initialized Donor): append wallet to the pool's donor index.
+        if ctx.accounts.donor.wallet == Pubkey::default() {
+            register_donor(
+                &mut ctx.accounts.pool,
+                &mut ctx.accounts.donor,
+                &mut ctx.accounts.donor_index_page,
+                ctx.accounts.donor_wallet.key(),
+            )?;
+        }
+
+        let shares_minted = credit_donation(
+            &mut ctx.accounts.pool,
+            &mut ctx.accounts.donor,
+            pre_balance,
+            new_balance,
+            lamports_in,
+            clock.slot,
+        )?;
+
+        ctx.accounts.pool.last_engagement_ts = clock.unix_timestamp;
+
+        let (matched_lamports, matched_shares) = match ctx.accounts.matching_vault.as_mut() {
+            Some(matching_vault) => apply_donation_match(
+                &mut ctx.accounts.pool,
+                matching_vault,
+                &mut ctx.accounts.donor,
+                lamports_in,
+                new_balance.saturating_sub(pre_balance),
+                &clock,
+            )?,
+            None => (0, 0),
+        };
+
+        require_within_donor_share_cap(&ctx.accounts.pool, &ctx.accounts.donor)?;
+
+        let referral_shares = match (referrer, ctx.accounts.referrer_donor.as_mut()) {
+            (Some(_), Some(referrer_donor)) => {
+                credit_referral(&mut ctx.accounts.pool, referrer_donor, shares_minted, clock.slot)?
+            }
+            _ => 0,
+        };
+
+        record_activity(
+            &mut ctx.accounts.activity_log,
+            ActivityKind::Donate,
+            0,
+            ctx.accounts.donor_wallet.key(),
+            lamports_in,
+            clock.slot,
+        );
+
+        // H-04 FIX: Unlock after successful operation
+        unlock_pool!(ctx.accounts.pool);
+
+        if matched_lamports > 0 {
+            emit_cpi!(DonationMatchedEvent {
+                pool: ctx.accounts.pool.key(),
+                donor: ctx.accounts.donor_wallet.key(),
+                matched_lamports,
+                matched_shares,
+                remaining_lamports: ctx.accounts.matching_vault.as_ref().map_or(0, |v| v.available_lamports),
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
+        if referral_shares > 0 {
+            emit_cpi!(ReferralRewardEvent {
+                pool: ctx.accounts.pool.key(),
+                referrer: referrer.unwrap_or_default(),
+                donor: ctx.accounts.donor_wallet.key(),
+                referred_shares: shares_minted,
+                bonus_shares: referral_shares,
+                total_referral_shares: ctx.accounts.pool.referral_shares_minted,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
+        // L-05 FIX: Emit donation event
+        emit_cpi!(DonationEvent {
+            pool: ctx.accounts.pool.key(),
+            donor: ctx.accounts.donor_wallet.key(),
+            payer: ctx.accounts.donor_wallet.key(),
+            lamports_in,
+            pool_tokens_received: new_balance.saturating_sub(pre_balance),
+            shares_minted,
+            donor_shares: ctx.accounts.donor.shares,
+            total_shares: ctx.accounts.pool.total_shares,
+            timestamp: clock.unix_timestamp,
+        });
+
+        if !memo.is_empty() || !campaign_tag.is_empty() {
+            emit_cpi!(DonationMemoEvent {
+                pool: ctx.accounts.pool.key(),
+                donor: ctx.accounts.donor_wallet.key(),
+                lamports_in,
+                shares_minted,
+                campaign_tag,
+                memo,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
+        Ok(())
     }
-    let shift = strikes as u32;
-    base.checked_shl(shift).unwrap_or(u64::MAX)
+
+    /// Create the Donor account for `beneficiary` ahead of `donate_sol_for`.
+    ///
+    /// Anyone may pay for it. Fails if the Donor already exists.
+    pub fn init_donor(ctx: Context<InitDonor>, beneficiary: Pubkey) -> Result<()> {
+        require!(ctx.accounts.pool.sunset_started_ts == 0, CtoError::PoolSunsetting);
+        require!(beneficiary != Pubkey::default(), CtoError::InvalidBeneficiary);
+
+        register_donor(
+            &mut ctx.accounts.pool,
+            &mut ctx.accounts.donor,
+            &mut ctx.accounts.donor_index_page,
+            beneficiary,
+        )?;
+        Ok(())
+    }
+
+    /// Donate SOL from `payer` and credit the shares to an existing Donor.
+    ///
+    /// Built for CPI callers (launchpads, routers): the beneficiary's Donor must already exist
+    /// (`init_donor`), no account is created here, and the shares minted are returned as
+    /// return data (little-endian u64). The credit counts as a share change for the beneficiary,
+    /// so a payer other than the beneficiary must credit at least MIN_THIRD_PARTY_CREDIT_LAMPORTS.
+    pub fn donate_sol_for(ctx: Context<DonateSolFor>, lamports_in: u64, minimum_pool_tokens_out: u64) -> Result<u64> {
+        require!(lamports_in > 0, CtoError::ZeroAmount);
+        require!(lamports_in >= ctx.accounts.pool.min_donation_lamports, CtoError::DonationBelowMinimum);
+        require!(
+            ctx.accounts.payer.key() == ctx.accounts.donor.wallet || lamports_in >= MIN_THIRD_PARTY_CREDIT_LAMPORTS,
+            CtoError::DonationBelowMinimum
+        );
+        require_allowlisted(&ctx.accounts.pool, &ctx.accounts.allowlist_entry)?;
+        require!(ctx.accounts.pool.sunset_started_ts == 0, CtoError::PoolSunsetting);
+        require!(!ctx.accounts.pool.paused, CtoError::PoolPaused);
+        require!(!campaign_failed(&ctx.accounts.pool, Clock::get()?.unix_timestamp), CtoError::CampaignFailed);
+
+        lock_pool!(ctx.accounts.pool);
+
+        ctx.accounts.pool_lst_account.reload()?;
+        let pre_balance = observed_pool_tokens(&ctx.accounts.pool, &ctx.accounts.pool_lst_account);
+
+        let minimum_pool_tokens_out = if minimum_pool_tokens_out == 0 {
+            let stake_pool_state = read_pool_rate(&ctx.accounts.pool, &ctx.accounts.stake_pool)?;
+            default_min_pool_tokens_out(&stake_pool_state, lamports_in, ctx.accounts.pool.donor_slippage_bps)?
+        } else {
+            minimum_pool_tokens_out
+        };
+
+        if ctx.accounts.pool.custody == PoolCustody::Native {
+            let payer = ctx.accounts.payer.to_account_info();
+            let system_program = ctx.accounts.system_program.to_account_info();
+            deposit_native_lamports(&mut ctx.accounts.pool, payer, system_program, lamports_in)?;
+        } else {
+            stake_pool_deposit_sol_for(&ctx, lamports_in, minimum_pool_tokens_out)?;
+        }
+
+        ctx.accounts.pool_lst_account.reload()?;
+        let new_balance = observed_pool_tokens(&ctx.accounts.pool, &ctx.accounts.pool_lst_account);
+        let clock = Clock::get()?;
+
+        let shares_minted = credit_donation(
+            &mut ctx.accounts.pool,
+            &mut ctx.accounts.donor,
+            pre_balance,
+            new_balance,
+            lamports_in,
+            clock.slot,
+        )?;
+
+        ctx.accounts.pool.last_engagement_ts = clock.unix_timestamp;
+
+        let (matched_lamports, matched_shares) = match ctx.accounts.matching_vault.as_mut() {
+            Some(matching_vault) => apply_donation_match(
+                &mut ctx.accounts.pool,
+                matching_vault,
+                &mut ctx.accounts.donor,
+                lamports_in,
+                new_balance.saturating_sub(pre_balance),
+                &clock,
+            )?,
+            None => (0, 0),
+        };
+
+        require_within_donor_share_cap(&ctx.accounts.pool, &ctx.accounts.donor)?;
+
+        let beneficiary = ctx.accounts.donor.wallet;
+        record_activity(
+            &mut ctx.accounts.activity_log,
+            ActivityKind::Donate,
+            0,
+            beneficiary,
+            lamports_in,
+            clock.slot,
+        );
+
+        unlock_pool!(ctx.accounts.pool);
+
+        emit_cpi!(DonationEvent {
+            pool: ctx.accounts.pool.key(),
+            donor: beneficiary,
+            payer: ctx.accounts.payer.key(),
+            lamports_in,
+            pool_tokens_received: new_balance.saturating_sub(pre_balance),
+            shares_minted,
+            donor_shares: ctx.accounts.donor.shares,
+            total_shares: ctx.accounts.pool.total_shares,
+            timestamp: clock.unix_timestamp,
+        });
+
+        if matched_lamports > 0 {
+            emit_cpi!(DonationMatchedEvent {
+                pool: ctx.accounts.pool.key(),
+                donor: beneficiary,
+                matched_lamports,
+                matched_shares,
+                remaining_lamports: ctx.accounts.matching_vault.as_ref().map_or(0, |v| v.available_lamports),
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
+        Ok(shares_minted)
+    }
+
+    /// Open a matching campaign for the pool, with the caller as its sponsor.
+    ///
+    /// Between `start_ts` and `end_ts`, every `donate_sol` / `donate_sol_for` that passes the
+    /// MatchingVault pulls `match_bps` of the donated lamports from it (while funds last) and
+    /// credits the donor the matching shares. One campaign per pool at a time.
+    pub fn create_matching_campaign(
+        ctx: Context<CreateMatchingCampaign>,
+        match_bps: u16,
+        start_ts: i64,
+        end_ts: i64,
+    ) -> Result<()> {
+        require!(ctx.accounts.pool.sunset_started_ts == 0, CtoError::PoolSunsetting);
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            match_bps > 0
+                && match_bps <= MAX_MATCH_BPS
+                && start_ts < end_ts
+                && end_ts > now
+                && end_ts - start_ts <= MAX_MATCH_WINDOW_SECONDS,
+            CtoError::InvalidMatchingCampaign
+        );
+
+        let vault = &mut ctx.accounts.matching_vault;
+        vault.pool = ctx.accounts.pool.key();
+        vault.sponsor = ctx.accounts.sponsor.key();
+        vault.match_bps = match_bps;
+        vault.start_ts = start_ts;
+        vault.end_ts = end_ts;
+        vault.available_lamports = 0;
+        vault.matched_lamports = 0;
+        vault.bump = ctx.bumps.matching_vault;
+
+        emit_cpi!(MatchingCampaignCreatedEvent {
+            pool: ctx.accounts.pool.key(),
+            sponsor: ctx.accounts.sponsor.key(),
+            match_bps,
+            start_ts,
+            end_ts,
+            timestamp: now,
+        });
+        Ok(())
+    }
+
+    /// Add `lamports` to the matching campaign's budget. Sponsor only, until the window ends.
+    pub fn fund_matching_campaign(ctx: Context<FundMatchingCampaign>, lamports: u64) -> Result<()> {
+        require!(lamports > 0, CtoError::ZeroAmount);
+        let now = Clock::get()?.unix_timestamp;
+        require!(now < ctx.accounts.matching_vault.end_ts, CtoError::MatchingCampaignEnded);
+
+        invoke(
+            &system_instruction::transfer(
+                &ctx.accounts.sponsor.key(),
+                &ctx.accounts.matching_vault.key(),
+                lamports,
+            ),
+            &[
+                ctx.accounts.sponsor.to_account_info(),
+                ctx.accounts.matching_vault.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )
+        .map_err(|_| CtoError::LamportTransferFailed)?;
+
+        let vault = &mut ctx.accounts.matching_vault;
+        vault.available_lamports = vault
+            .available_lamports
+            .checked_add(lamports)
+            .ok_or(CtoError::MathOverflow)?;
+
+        emit_cpi!(MatchingCampaignFundedEvent {
+            pool: ctx.accounts.pool.key(),
+            sponsor: ctx.accounts.sponsor.key(),
+            lamports,
+            available_lamports: ctx.accounts.matching_vault.available_lamports,
+            timestamp: now,
+        });
+        Ok(())
+    }
+
+    /// Close the matching campaign, returning the unspent budget and rent to the sponsor.
+    ///
+    /// Anyone may close it once the window has ended (which frees the PDA for a new campaign);
+    /// the sponsor may also withdraw before it starts.
+    pub fn close_matching_campaign(ctx: Context<CloseMatchingCampaign>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let vault = &ctx.accounts.matching_vault;
+        require!(
+            now >= vault.end_ts || (now < vault.start_ts && ctx.accounts.closer.key() == vault.sponsor),
+            CtoError::MatchingCampaignActive
+        );
+
+        emit_cpi!(MatchingCampaignClosedEvent {
+            pool: ctx.accounts.pool.key(),
+            sponsor: vault.sponsor,
+            matched_lamports: vault.matched_lamports,
+            refunded_lamports: vault.available_lamports,
+            timestamp: now,
+        });
+        Ok(())
+    }
+
+    /// Stake lamports sent directly to the pool PDA (tips, MEV rebates, penalty fees) into the LST
+    /// position.
+    ///
+    /// Permissionless. Surplus is everything above the Pool account's rent-exempt minimum (and, in a
+    /// native pool, above the donor lamports it already holds, to which the surplus is added
+    /// instead of being staked). No shares are minted, so the extra value accrues pro-rata to
+    /// all shareholders.
+    pub fn sweep_surplus(ctx: Context<SweepSurplus>, minimum_pool_tokens_out: u64) -> Result<()> {
+        lock_pool!(ctx.accounts.pool);
+
+        let pool_info = ctx.accounts.pool.to_account_info();
+        let rent_minimum = Rent::get()?.minimum_balance(pool_info.data_len());
+        let surplus = pool_info
+            .lamports()
+            .saturating_sub(rent_minimum)
+            .saturating_sub(ctx.accounts.pool.burn_auction_lamports)
+            .saturating_sub(ctx.accounts.pool.pending_burn_lamports)
+            .saturating_sub(ctx.accounts.pool.pending_liquidity_lamports)
+            .saturating_sub(ctx.accounts.pool.pending_claim_lamports)
+            .saturating_sub(native_held_lamports(&ctx.accounts.pool));
+        require!(surplus > 0, CtoError::NoSurplus);
+
+        ctx.accounts.pool_lst_account.reload()?;
+        let pre_balance = observed_pool_tokens(&ctx.accounts.pool, &ctx.accounts.pool_lst_account);
+
+        if ctx.accounts.pool.custody == PoolCustody::Native {
+            let pool = &mut ctx.accounts.pool;
+            pool.total_pool_tokens = pool.total_pool_tokens.checked_add(surplus).ok_or(CtoError::MathOverflow)?;
+        } else {
+            stake_pool_deposit_sol_from_pool(&ctx, surplus, minimum_pool_tokens_out)?;
+        }
+
+        ctx.accounts.pool_lst_account.reload()?;
+        let new_balance = observed_pool_tokens(&ctx.accounts.pool, &ctx.accounts.pool_lst_account);
+        let received = new_balance.checked_sub(pre_balance).ok_or(CtoError::MathOverflow)?;
+        require!(received > 0, CtoError::StakePoolReturnedZero);
+
+        ctx.accounts.pool.total_pool_tokens = new_balance;
+
+        unlock_pool!(ctx.accounts.pool);
+
+        emit_cpi!(SurplusSweptEvent {
+            pool: ctx.accounts.pool.key(),
+            lamports_swept: surplus,
+            pool_tokens_received: received,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Migrate pre-scaling share balances to SHARE_SCALE.
+    ///
+    /// Permissionless and idempotent. The pool's total is scaled on the first call; each Donor is
+    /// scaled when passed in. Donors whose scale lags the pool's are rejected by share-based
+    /// instructions until migrated. Legacy accounts are launch (version 0) Pools and Donors,
+    /// which `migrate_account` must first resize to the current layout; they carry
+    /// `share_scale == 0` (1x). The pool can't be rescaled while a proposal, poll or recovery
+    /// is open (Active or Queued), since its snapshot totals and tallies are in the old scale.
+    pub fn migrate_share_scale(ctx: Context<MigrateShareScale>) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        let mut pool_factor = 1;
+        if pool.share_scale != SHARE_SCALE {
+            require!(
+                pool.active_proposal.is_none() && pool.active_poll.is_none() && pool.active_recovery.is_none(),
+                CtoError::ActiveProposalExists
+            );
+            pool_factor = SHARE_SCALE / pool.share_scale.max(1);
+            pool.total_shares = pool.total_shares.checked_mul(pool_factor).ok_or(CtoError::MathOverflow)?;
+            pool.share_scale = SHARE_SCALE;
+        }
+
+        let donor = &mut ctx.accounts.donor;
+        let mut donor_factor = 1;
+        if donor.share_scale != pool.share_scale {
+            donor_factor = pool.share_scale / donor.share_scale.max(1);
+            donor.shares = donor.shares.checked_mul(donor_factor).ok_or(CtoError::MathOverflow)?;
+            for checkpoint in donor.share_checkpoints.iter_mut() {
+                checkpoint.shares = checkpoint.shares.checked_mul(donor_factor).ok_or(CtoError::MathOverflow)?;
+            }
+            donor.share_scale = pool.share_scale;
+        }
+
+        if pool_factor != 1 || donor_factor != 1 {
+            emit_cpi!(ShareScaleMigratedEvent {
+                pool: pool.key(),
+                wallet: donor.wallet,
+                pool_factor,
+                donor_factor,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Upgrade a Pool, Donor or Proposal to the current layout in place.
+    ///
+    /// Permissionless and idempotent. Reads the version byte after the discriminator (accounts
+    /// from before versioning have none and are recognized as version 0 by their length) and
+    /// applies `upgrade_layout` one version at a time, resizing the account and topping up rent
+    /// from `payer` as needed. Accounts already at the current version are left untouched.
+    /// Upgrading a version-0 Proposal needs its (already upgraded) Pool, which supplies the
+    /// quorum it is finalized against; upgrading a version-0 Pool also creates its ActivityLog.
+    pub fn migrate_account(ctx: Context<MigrateAccount>, kind: MigratableAccount) -> Result<()> {
+        let target = ctx.accounts.target.to_account_info();
+        let (discriminator, current_version, v0_size): (&[u8], u8, usize) = match kind {
+            MigratableAccount::Pool => (Pool::DISCRIMINATOR, POOL_VERSION, PoolV0::SIZE),
+            MigratableAccount::Donor => (Donor::DISCRIMINATOR, DONOR_VERSION, DonorV0::SIZE),
+            MigratableAccount::Proposal => (Proposal::DISCRIMINATOR, PROPOSAL_VERSION, ProposalV0::SIZE),
+        };
+
+        let mut data = target.try_borrow_data()?.to_vec();
+        require!(
+            data.len() > 8 && data[..8] == *discriminator,
+            CtoError::InvalidAccountData
+        );
+        let from_version = if data.len() == 8 + v0_size { 0 } else { data[8] };
+        require!(from_version <= current_version, CtoError::UnknownAccountVersion);
+        // Launch pools predate the ActivityLog every donate, withdraw and propose context requires.
+        let creates_activity_log = kind == MigratableAccount::Pool && from_version == 0;
+        require!(
+            ctx.accounts.activity_log.is_some() == creates_activity_log,
+            CtoError::MigrationActivityLogMismatch
+        );
+        if let Some(activity_log) = ctx.accounts.activity_log.as_mut() {
+            activity_log.pool = target.key();
+            activity_log.head = 0;
+            activity_log.total_recorded = 0;
+        }
+        if from_version == current_version {
+            return Ok(());
+        }
+
+        for version in from_version..current_version {
+            data = upgrade_layout(kind, version, data, ctx.accounts.pool.as_ref())?;
+            data[8] = version + 1;
+        }
+
+        let required_rent = Rent::get()?.minimum_balance(data.len());
+        let top_up = required_rent.saturating_sub(target.lamports());
+        if top_up > 0 {
+            invoke(
+                &system_instruction::transfer(&ctx.accounts.payer.key(), &target.key(), top_up),
+                &[
+                    ctx.accounts.payer.to_account_info(),
+                    target.clone(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+            )
+            .map_err(|_| CtoError::LamportTransferFailed)?;
+        }
+        target.resize(data.len())?;
+        target.try_borrow_mut_data()?.copy_from_slice(&data);
+
+        emit_cpi!(AccountMigratedEvent {
+            account: target.key(),
+            kind,
+            from_version,
+            to_version: current_version,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Create the ActivityLog of a pool that predates it.
+    ///
+    /// Permissionless; `payer` funds the rent. Instructions that record activity require the
+    /// log, so pools created before it existed need this once before they can be used.
+    pub fn init_activity_log(ctx: Context<InitActivityLog>) -> Result<()> {
+        let activity_log = &mut ctx.accounts.activity_log;
+        activity_log.pool = ctx.accounts.pool.key();
+        activity_log.head = 0;
+        activity_log.total_recorded = 0;
+        Ok(())
+    }
+
+    /// Fold accumulated rounding dust back into withdrawable TVL.
+    ///
+    /// Permissionless, at most once per DUST_FOLD_INTERVAL_SECONDS. Rounding always favors the pool
+    /// (mints floor, burns and locks ceil, payouts floor); the retained LST is held out of
+    /// withdrawable liquidity until folded, so it accrues to all shareholders rather than the next
+    /// withdrawer.
+    pub fn fold_rounding_dust(ctx: Context<FoldRoundingDust>) -> Result<()> {
+        let clock = Clock::get()?;
+        let pool = &mut ctx.accounts.pool;
+        require!(
+            clock.unix_timestamp
+                >= pool
+                    .last_dust_fold_ts
+                    .checked_add(DUST_FOLD_INTERVAL_SECONDS)
+                    .ok_or(CtoError::MathOverflow)?,
+            CtoError::DustFoldTooEarly
+        );
+
+        let folded = pool.rounding_dust_pool_tokens;
+        pool.rounding_dust_pool_tokens = 0;
+        pool.last_dust_fold_ts = clock.unix_timestamp;
+
+        emit_cpi!(RoundingDustFoldedEvent {
+            pool: pool.key(),
+            pool_tokens_folded: folded,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw X SOL worth of stake from the pool.
+    ///
+    /// Notes:
+    /// - Uses a stake-pool CPI withdraw (burn LST, receive SOL into pool PDA).
+    /// - Then immediately transfers the SOL out to the donor.
+    pub fn withdraw_sol(mut ctx: Context<WithdrawSol>, lamports_out_desired: u64, minimum_lamports_out: u64) -> Result<()> {
+        require!(lamports_out_desired > 0, CtoError::ZeroAmount);
+
+        // H-04 FIX: Reentrancy guard - lock before CPI
+        lock_pool!(ctx.accounts.pool);
+
+        require!(
+            ctx.accounts.donor.share_scale == ctx.accounts.pool.share_scale,
+            CtoError::ShareScaleMigrationRequired
+        );
+
+        // ============ PHASE 1: Immutable reads and calculations ============
+        let donor_shares = ctx.accounts.donor.shares;
+        let total_shares = ctx.accounts.pool.total_shares;
+        let reserved_pool_tokens = ctx.accounts.pool.reserved_pool_tokens;
+
+        require!(donor_shares > 0, CtoError::NoShares);
+        require!(total_shares > 0, CtoError::MathOverflow);
+
+        ctx.accounts.pool_lst_account.reload()?;
+        let total_pool_tokens_observed = observed_pool_tokens(&ctx.accounts.pool, &ctx.accounts.pool_lst_account);
+
+        // Rounding dust is not withdrawable until folded back in.
+        let free_pool_tokens = total_pool_tokens_observed
+            .checked_sub(reserved_pool_tokens)
+            .ok_or(CtoError::MathOverflow)?
+            .saturating_sub(ctx.accounts.pool.rounding_dust_pool_tokens);
+
+        // Rounding policy: withdrawable floors, burns ceil.
+        let donor_free_pool_tokens = mul_div_floor(donor_shares, free_pool_tokens, total_shares)?;
+
+        let stake_pool_state = read_pool_rate(&ctx.accounts.pool, &ctx.accounts.stake_pool)?;
+        let pool_tokens_to_burn = pool_tokens_for_lamports_ceil(&stake_pool_state, lamports_out_desired)?;
+        require!(pool_tokens_to_burn > 0, CtoError::ZeroAmount);
+        require!(pool_tokens_to_burn <= donor_free_pool_tokens, CtoError::InsufficientWithdrawable);
+
+        let shares_to_burn = mul_div_ceil(pool_tokens_to_burn, total_shares, total_pool_tokens_observed)?;
+        require!(shares_to_burn > 0, CtoError::MathOverflow);
+        require!(shares_to_burn <= donor_shares, CtoError::InsufficientWithdrawable);
+
+        // LST value of the shares burned beyond what is paid out stays behind as dust.
+        let burned_value = mul_div_floor(shares_to_burn, total_pool_tokens_observed, total_shares)?;
+        let pool = &mut ctx.accounts.pool;
+        pool.rounding_dust_pool_tokens = pool
+            .rounding_dust_pool_tokens
+            .checked_add(burned_value.saturating_sub(pool_tokens_to_burn))
+            .ok_or(CtoError::MathOverflow)?;
+
+        complete_withdrawal(&mut ctx, pool_tokens_to_burn, shares_to_burn, minimum_lamports_out)
+    }
+
+    /// Full exit: burn all of the donor's shares for their entire pro-rata LST balance.
+    ///
+    /// Avoids the floor-division residue `withdraw_sol` leaves behind. The last donor out receives
+    /// the whole remaining LST balance. Only available while nothing is reserved, since burning all
+    /// shares would otherwise forfeit the donor's slice of locked liquidity.
+    pub fn withdraw_all(mut ctx: Context<WithdrawSol>, minimum_lamports_out: u64) -> Result<()> {
+        // H-04 FIX: Reentrancy guard - lock before CPI
+        lock_pool!(ctx.accounts.pool);
+
+        require!(
+            ctx.accounts.donor.share_scale == ctx.accounts.pool.share_scale,
+            CtoError::ShareScaleMigrationRequired
+        );
+
+        let donor_shares = ctx.accounts.donor.shares;
+        let total_shares = ctx.accounts.pool.total_shares;
+        require!(donor_shares > 0, CtoError::NoShares);
+        require!(ctx.accounts.pool.reserved_pool_tokens == 0, CtoError::FullExitBlockedByReservation);
+
+        ctx.accounts.pool_lst_account.reload()?;
+        let total_pool_tokens_observed = observed_pool_tokens(&ctx.accounts.pool, &ctx.accounts.pool_lst_account);
+
+        let pool_tokens_to_burn = if donor_shares == total_shares {
+            // Last donor out takes everything, including unfolded dust.
+            ctx.accounts.pool.rounding_dust_pool_tokens = 0;
+            total_pool_tokens_observed
+        } else {
+            let withdrawable = total_pool_tokens_observed.saturating_sub(ctx.accounts.pool.rounding_dust_pool_tokens);
+            mul_div_floor(donor_shares, withdrawable, total_shares)?
+        };
+        require!(pool_tokens_to_burn > 0, CtoError::ZeroAmount);
+
+        complete_withdrawal(&mut ctx, pool_tokens_to_burn, donor_shares, minimum_lamports_out)
+    }
+
+    /// Permissionless crank: pays out a `StakeWithdrawal` once its stake account has deactivated.
+    ///
+    /// Withdrawals and payouts fall back to the stake pool's WithdrawStake when its reserve can't
+    /// cover them, leaving the SOL in a deactivating stake account owned by the pool PDA. This
+    /// withdraws that account into the pool PDA, pays the recipient net of any protocol fee
+    /// (split as at execution), refunds the stake account's rent to whoever funded it and closes
+    /// the record. Rewards earned while deactivating stay in the pool for `sweep_surplus`.
+    pub fn claim_deactivated_stake(ctx: Context<ClaimDeactivatedStake>) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(
+            clock.epoch > ctx.accounts.stake_withdrawal.deactivation_epoch,
+            CtoError::StakeStillDeactivating
+        );
+
+        let pool_token_mint = ctx.accounts.pool.token_mint;
+        let pool_bump = ctx.bumps.pool;
+        let seeds: &[&[&[u8]]] = &[&[b"pool", pool_token_mint.as_ref(), &[pool_bump]]];
+
+        let total = ctx.accounts.withdrawal_stake.lamports();
+        withdraw_deactivated_stake(
+            &ctx.accounts.withdrawal_stake.to_account_info(),
+            &ctx.accounts.pool.to_account_info(),
+            &ctx.accounts.clock.to_account_info(),
+            &ctx.accounts.stake_history.to_account_info(),
+            &ctx.accounts.stake_program.to_account_info(),
+            seeds,
+            total,
+        )?;
+
+        let record = &ctx.accounts.stake_withdrawal;
+        let owed = record.lamports.min(total);
+        let protocol_fee = record.protocol_fee.min(owed);
+        let net_to_recipient = owed.checked_sub(protocol_fee).ok_or(CtoError::MathOverflow)?;
+        let rent_refund = total.saturating_sub(owed).min(record.rent_lamports);
+
+        transfer_lamports_signed(
+            &ctx.accounts.pool.to_account_info(),
+            &ctx.accounts.recipient.to_account_info(),
+            seeds,
+            net_to_recipient,
+        )?;
+        if rent_refund > 0 {
+            transfer_lamports_signed(
+                &ctx.accounts.pool.to_account_info(),
+                &ctx.accounts.rent_payer.to_account_info(),
+                seeds,
+                rent_refund,
+            )?;
+        }
+        split_protocol_fee(
+            &mut ctx.accounts.pool,
+            ctx.accounts.fee_sink_wallet.as_ref(),
+            &ctx.accounts.dev_fee_wallet.to_account_info(),
+            seeds,
+            protocol_fee,
+            clock.unix_timestamp,
+            &ctx.accounts.event_authority.to_account_info(),
+            ctx.bumps.event_authority,
+        )?;
+        // Records opened before the counter existed aren't in it.
+        ctx.accounts.pool.open_stake_withdrawals = ctx.accounts.pool.open_stake_withdrawals.saturating_sub(1);
+
+        emit_cpi!(StakeWithdrawalClaimedEvent {
+            pool: ctx.accounts.pool.key(),
+            stake_withdrawal: ctx.accounts.stake_withdrawal.key(),
+            recipient: ctx.accounts.recipient.key(),
+            lamports_out: net_to_recipient,
+            protocol_fee,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Pull the SOL credited to the signer's PayoutClaim by executed payouts.
+    ///
+    /// Payouts are credited rather than pushed so only a key that can sign (a wallet, or a
+    /// multisig vault through its program) ever receives treasury SOL. The claim stays open for
+    /// later credits. Payouts that fell back to a stake withdrawal are still paid by
+    /// `claim_deactivated_stake`.
+    pub fn claim_payout(ctx: Context<ClaimPayout>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let lamports = ctx.accounts.payout_claim.lamports;
+        require!(lamports > 0, CtoError::NothingToClaim);
+
+        let pool_bump = ctx.bumps.pool;
+        transfer_lamports_signed(
+            &ctx.accounts.pool.to_account_info(),
+            &ctx.accounts.destination.to_account_info(),
+            pool_seeds!(&ctx.accounts.pool, pool_bump),
+            lamports,
+        )?;
+
+        let pool = &mut ctx.accounts.pool;
+        pool.pending_claim_lamports = pool
+            .pending_claim_lamports
+            .checked_sub(lamports)
+            .ok_or(CtoError::MathOverflow)?;
+        ctx.accounts.payout_claim.lamports = 0;
+
+        emit_cpi!(PayoutClaimedEvent {
+            pool: ctx.accounts.pool.key(),
+            destination: ctx.accounts.destination.key(),
+            lamports,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Return a PayoutClaim left unclaimed past its expiry to the pool. Permissionless.
+    ///
+    /// The SOL stays in the pool PDA, where `sweep_surplus` stakes it for all donors, and no
+    /// longer counts as spent.
+    pub fn reclaim_expired_payout(ctx: Context<ReclaimExpiredPayout>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let claim = &mut ctx.accounts.payout_claim;
+        let lamports = claim.lamports;
+        require!(lamports > 0, CtoError::NothingToClaim);
+        require!(now >= claim.expires_ts, CtoError::PayoutClaimNotExpired);
+        claim.lamports = 0;
+
+        let pool = &mut ctx.accounts.pool;
+        pool.pending_claim_lamports = pool
+            .pending_claim_lamports
+            .checked_sub(lamports)
+            .ok_or(CtoError::MathOverflow)?;
+        pool.total_spent_lamports = pool.total_spent_lamports.saturating_sub(lamports);
+
+        emit_cpi!(PayoutClaimExpiredEvent {
+            pool: pool.key(),
+            destination: claim.destination,
+            lamports,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Refund after a failed campaign: burns all of the donor's shares for their entire pro-rata
+    /// LST balance, as `withdraw_all`.
+    pub fn claim_campaign_refund(ctx: Context<WithdrawSol>, minimum_lamports_out: u64) -> Result<()> {
+        require!(
+            campaign_failed(&ctx.accounts.pool, Clock::get()?.unix_timestamp),
+            CtoError::CampaignNotFailed
+        );
+        withdraw_all(ctx, minimum_lamports_out)
+    }
+
+    /// Rage-quit: a donor who voted No on the active proposal exits completely, taking their
+    /// pro-rata share of free liquidity plus the same fraction of the proposal's lock.
+    ///
+    /// The proposal's lock, and with it the most it can pay out, shrinks by that fraction; the
+    /// No vote stays counted. Allowed while the proposal is Active or Queued, so a timelock
+    /// doubles as the exit window. Shares of other reservations (escrowed payouts) are forfeited,
+    /// and delegated shares must be undelegated first.
+    pub fn rage_quit(ctx: Context<RageQuit>, minimum_lamports_out: u64) -> Result<()> {
+        lock_pool!(ctx.accounts.pool);
+
+        let pool = &ctx.accounts.pool;
+        let proposal = &ctx.accounts.proposal;
+        let donor = &ctx.accounts.donor;
+        require!(donor.share_scale == pool.share_scale, CtoError::ShareScaleMigrationRequired);
+        require!(pool.active_proposal == Some(proposal.key()), CtoError::ProposalNotActive);
+        require!(
+            matches!(proposal.status, ProposalStatus::Active | ProposalStatus::Queued),
+            CtoError::ProposalNotActive
+        );
+        let voted_no = match &ctx.accounts.vote_record {
+            Some(record) => record.initialized && record.choice == VoteChoice::No,
+            None => donor.ballot_proposal == proposal.key() && donor.ballot_choice == VoteChoice::No,
+        };
+        require!(voted_no, CtoError::RageQuitRequiresNoVote);
+        require!(donor.delegated_shares == 0, CtoError::SharesDelegated);
+
+        let donor_shares = donor.shares;
+        let total_shares = pool.total_shares;
+        require!(donor_shares > 0, CtoError::NoShares);
+
+        ctx.accounts.pool_lst_account.reload()?;
+        let free_pool_tokens = observed_pool_tokens(pool, &ctx.accounts.pool_lst_account)
+            .checked_sub(pool.reserved_pool_tokens)
+            .ok_or(CtoError::MathOverflow)?
+            .saturating_sub(pool.rounding_dust_pool_tokens);
+        let unlocked_pool_tokens = mul_div_floor(donor_shares, proposal.locked_pool_tokens, total_shares)?;
+        let pool_tokens_to_burn = mul_div_floor(donor_shares, free_pool_tokens, total_shares)?
+            .checked_add(unlocked_pool_tokens)
+            .ok_or(CtoError::MathOverflow)?;
+        require!(pool_tokens_to_burn > 0, CtoError::ZeroAmount);
+
+        let minimum_lamports_out = if minimum_lamports_out == 0 {
+            let stake_pool_state = read_pool_rate(&ctx.accounts.pool, &ctx.accounts.stake_pool)?;
+            default_min_lamports_out(&stake_pool_state, pool_tokens_to_burn, pool.donor_slippage_bps)?
+        } else {
+            minimum_lamports_out
+        };
+
+        require_lst_rate_in_band(&ctx.accounts.pool, &ctx.accounts.stake_pool)?;
+
+        let received = if ctx.accounts.pool.custody == PoolCustody::Native {
+            release_native_lamports(&mut ctx.accounts.pool, pool_tokens_to_burn)?
+        } else {
+            let pre_pool_lamports = ctx.accounts.pool.to_account_info().lamports();
+            stake_pool_withdraw_sol_rage_quit(&ctx, pool_tokens_to_burn, minimum_lamports_out)?;
+            ctx.accounts
+                .pool
+                .to_account_info()
+                .lamports()
+                .checked_sub(pre_pool_lamports)
+                .ok_or(CtoError::MathOverflow)?
+        };
+        require!(received >= minimum_lamports_out, CtoError::SlippageExceeded);
+
+        transfer_lamports_signed(
+            &ctx.accounts.pool.to_account_info(),
+            &ctx.accounts.donor_wallet.to_account_info(),
+            pool_seeds!(&ctx.accounts.pool, ctx.bumps.pool),
+            received,
+        )?;
+
+        ctx.accounts.pool_lst_account.reload()?;
+        let final_pool_tokens = observed_pool_tokens(&ctx.accounts.pool, &ctx.accounts.pool_lst_account);
+        let clock = Clock::get()?;
+        {
+            let pool = &mut ctx.accounts.pool;
+            pool.total_pool_tokens = final_pool_tokens;
+            pool.total_shares = pool
+                .total_shares
+                .checked_sub(donor_shares)
+                .ok_or(CtoError::MathOverflow)?;
+            pool.reserved_pool_tokens = pool
+                .reserved_pool_tokens
+                .checked_sub(unlocked_pool_tokens)
+                .ok_or(CtoError::MathOverflow)?;
+        }
+        {
+            let proposal = &mut ctx.accounts.proposal;
+            proposal.locked_pool_tokens = proposal
+                .locked_pool_tokens
+                .checked_sub(unlocked_pool_tokens)
+                .ok_or(CtoError::MathOverflow)?;
+        }
+        {
+            let donor = &mut ctx.accounts.donor;
+            donor.shares = 0;
+            mark_shares_changed(&ctx.accounts.pool, donor, clock.slot);
+        }
+
+        record_activity(
+            &mut ctx.accounts.activity_log,
+            ActivityKind::Withdraw,
+            0,
+            ctx.accounts.donor_wallet.key(),
+            received,
+            clock.slot,
+        );
+
+        unlock_pool!(ctx.accounts.pool);
+
+        emit_cpi!(RageQuitEvent {
+            pool: ctx.accounts.pool.key(),
+            proposal: ctx.accounts.proposal.key(),
+            donor: ctx.accounts.donor_wallet.key(),
+            lamports_out: received,
+            shares_burned: donor_shares,
+            unlocked_pool_tokens,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Create the pool's share mint (PDA ['share_mint', pool], mint authority = pool).
+    ///
+    /// Only for pools at the current SHARE_SCALE, so one share token always means one share.
+    /// Tokenization is opt-in per donor: donations still credit the Donor account and
+    /// withdrawals still debit it, because share tokens carry no vote. Holders move between
+    /// the two with `tokenize_shares` and `redeem_share_tokens`.
+    pub fn enable_share_mint(ctx: Context<EnableShareMint>) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+        require!(ctx.accounts.pool.share_scale == SHARE_SCALE, CtoError::ShareScaleMigrationRequired);
+
+        ctx.accounts.pool.share_mint = ctx.accounts.share_mint.key();
+        Ok(())
+    }
+
+    /// Create the pool's PoolMetadata (name, symbol, description, URI), usually in the same
+    /// transaction as `create_pool`. Afterwards only governance can change it
+    /// (`update_pool_metadata`).
+    pub fn create_pool_metadata(ctx: Context<CreatePoolMetadata>, fields: PoolMetadataFields) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+        validate_pool_metadata(&fields)?;
+
+        let clock = Clock::get()?;
+        let metadata = &mut ctx.accounts.metadata;
+        metadata.pool = ctx.accounts.pool.key();
+        metadata.fields = fields;
+        metadata.updated_slot = clock.slot;
+
+        emit_cpi!(PoolMetadataUpdatedEvent {
+            pool: ctx.accounts.pool.key(),
+            name: ctx.accounts.metadata.fields.name.clone(),
+            symbol: ctx.accounts.metadata.fields.symbol.clone(),
+            uri: ctx.accounts.metadata.fields.uri.clone(),
+            change_proposal: Pubkey::default(),
+            timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Apply new metadata approved by a passed ConfigChange. Permissionless.
+    ///
+    /// The change's `metadata_hash` must be the sha256 of the Borsh-encoded `fields`, and the
+    /// change must have executed after the metadata was last written.
+    pub fn update_pool_metadata(ctx: Context<UpdatePoolMetadata>, fields: PoolMetadataFields) -> Result<()> {
+        validate_pool_metadata(&fields)?;
+        let change = &ctx.accounts.change_proposal;
+        require!(change.kind == ProposalKind::ConfigChange, CtoError::WrongProposalKind);
+        require!(change.status == ProposalStatus::Executed, CtoError::ProposalNotPassed);
+        require!(
+            change.config_change.metadata_hash == Some(pool_metadata_hash(&fields)?),
+            CtoError::PoolMetadataMismatch
+        );
+        // Each approval applies once: only a change that executed after the last write counts.
+        require!(
+            change.finalized_slot > ctx.accounts.metadata.updated_slot,
+            CtoError::PoolMetadataMismatch
+        );
+
+        let clock = Clock::get()?;
+        let metadata = &mut ctx.accounts.metadata;
+        metadata.fields = fields;
+        metadata.updated_slot = clock.slot;
+
+        emit_cpi!(PoolMetadataUpdatedEvent {
+            pool: ctx.accounts.pool.key(),
+            name: ctx.accounts.metadata.fields.name.clone(),
+            symbol: ctx.accounts.metadata.fields.symbol.clone(),
+            uri: ctx.accounts.metadata.fields.uri.clone(),
+            change_proposal: ctx.accounts.change_proposal.key(),
+            timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Move `shares` out of the caller's Donor account into share tokens in their ATA.
+    ///
+    /// Share tokens are freely transferable but carry no vote and can't be withdrawn directly;
+    /// any holder turns them back into Donor shares with `redeem_share_tokens`.
+    pub fn tokenize_shares(ctx: Context<TokenizeShares>, shares: u64) -> Result<()> {
+        require!(shares > 0, CtoError::ZeroAmount);
+        let donor = &mut ctx.accounts.donor;
+        require!(donor.share_scale == ctx.accounts.pool.share_scale, CtoError::ShareScaleMigrationRequired);
+        let free_shares = donor
+            .shares
+            .checked_sub(donor.delegated_shares)
+            .ok_or(CtoError::MathOverflow)?;
+        require!(shares <= free_shares, CtoError::InsufficientWithdrawable);
+
+        let clock = Clock::get()?;
+        donor.shares -= shares;
+        mark_shares_changed(&ctx.accounts.pool, donor, clock.slot);
+        let pool = &mut ctx.accounts.pool;
+        pool.tokenized_shares = pool
+            .tokenized_shares
+            .checked_add(shares)
+            .ok_or(CtoError::MathOverflow)?;
+
+        let pool_bump = ctx.bumps.pool;
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::MintTo {
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    to: ctx.accounts.donor_share_account.to_account_info(),
+                    authority: ctx.accounts.pool.to_account_info(),
+                },
+                pool_seeds!(&ctx.accounts.pool, pool_bump),
+            ),
+            shares,
+        )?;
+
+        emit_cpi!(SharesTokenizedEvent {
+            pool: ctx.accounts.pool.key(),
+            wallet: ctx.accounts.donor_wallet.key(),
+            shares,
+            tokenized_shares: ctx.accounts.pool.tokenized_shares,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Queue a withdrawal of `shares` for when free liquidity can cover it.
+    ///
+    /// For donors whose withdrawal doesn't fit in free liquidity (LST reserved for proposals, a
+    /// dry stake pool reserve). The shares leave the caller's Donor account into a
+    /// WithdrawalRequest at the back of the pool's FIFO queue, where they carry no vote;
+    /// `process_withdrawal_queue` pays them out at the share value current when it runs.
+    /// `cancel_withdrawal_request` returns them to the Donor account until then.
+    pub fn request_withdrawal(ctx: Context<RequestWithdrawal>, shares: u64) -> Result<()> {
+        require!(shares > 0, CtoError::ZeroAmount);
+        require_campaign_settled(&ctx.accounts.pool, Clock::get()?.unix_timestamp)?;
+        let donor = &mut ctx.accounts.donor;
+        require!(donor.share_scale == ctx.accounts.pool.share_scale, CtoError::ShareScaleMigrationRequired);
+        let free_shares = donor
+            .shares
+            .checked_sub(donor.delegated_shares)
+            .ok_or(CtoError::MathOverflow)?;
+        require!(shares <= free_shares, CtoError::InsufficientWithdrawable);
+
+        let clock = Clock::get()?;
+        donor.shares -= shares;
+        mark_shares_changed(&ctx.accounts.pool, donor, clock.slot);
+
+        let pool = &mut ctx.accounts.pool;
+        pool.queued_shares = pool
+            .queued_shares
+            .checked_add(shares)
+            .ok_or(CtoError::MathOverflow)?;
+        let index = pool.withdrawal_queue_tail;
+        pool.withdrawal_queue_tail = index.checked_add(1).ok_or(CtoError::MathOverflow)?;
+
+        let request = &mut ctx.accounts.withdrawal_request;
+        request.pool = pool.key();
+        request.owner = ctx.accounts.donor_wallet.key();
+        request.index = index;
+        request.shares = shares;
+        request.created_ts = clock.unix_timestamp;
+        request.cancelled = false;
+
+        emit_cpi!(WithdrawalQueuedEvent {
+            pool: pool.key(),
+            owner: request.owner,
+            index,
+            shares,
+            queued_shares: pool.queued_shares,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Take back the shares of a queued withdrawal. The request keeps its queue slot as a
+    /// cancelled entry, which `process_withdrawal_queue` closes when it reaches the head.
+    pub fn cancel_withdrawal_request(ctx: Context<CancelWithdrawalRequest>) -> Result<()> {
+        let request = &mut ctx.accounts.withdrawal_request;
+        require!(!request.cancelled, CtoError::WithdrawalRequestCancelled);
+        let shares = request.shares;
+        request.shares = 0;
+        request.cancelled = true;
+
+        let clock = Clock::get()?;
+        let donor = &mut ctx.accounts.donor;
+        require!(donor.share_scale == ctx.accounts.pool.share_scale, CtoError::ShareScaleMigrationRequired);
+        donor.shares = donor.shares.checked_add(shares).ok_or(CtoError::MathOverflow)?;
+        mark_shares_changed(&ctx.accounts.pool, donor, clock.slot);
+
+        let pool = &mut ctx.accounts.pool;
+        pool.queued_shares = pool
+            .queued_shares
+            .checked_sub(shares)
+            .ok_or(CtoError::MathOverflow)?;
+
+        emit_cpi!(WithdrawalRequestCancelledEvent {
+            pool: pool.key(),
+            owner: request.owner,
+            index: request.index,
+            shares,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless crank: fills the WithdrawalRequest at the head of the pool's queue.
+    ///
+    /// Strictly FIFO: fails until free liquidity covers the head request's pro-rata LST, so
+    /// later (smaller) requests can't jump ahead. The LST is withdrawn for SOL (floor derived
+    /// from `donor_slippage_bps`) and paid to the request's owner; the request is closed to them
+    /// and the head advances. Cancelled requests are just closed.
+    pub fn process_withdrawal_queue(ctx: Context<ProcessWithdrawalQueue>) -> Result<()> {
+        lock_pool!(ctx.accounts.pool);
+
+        let shares = ctx.accounts.withdrawal_request.shares;
+        let mut received = 0;
+        let clock = Clock::get()?;
+
+        if !ctx.accounts.withdrawal_request.cancelled {
+            let total_shares = ctx.accounts.pool.total_shares;
+            require!(total_shares > 0, CtoError::MathOverflow);
+
+            ctx.accounts.pool_lst_account.reload()?;
+            let total_pool_tokens_observed = observed_pool_tokens(&ctx.accounts.pool, &ctx.accounts.pool_lst_account);
+            let dust = ctx.accounts.pool.rounding_dust_pool_tokens;
+            let free_pool_tokens = total_pool_tokens_observed
+                .checked_sub(ctx.accounts.pool.reserved_pool_tokens)
+                .ok_or(CtoError::MathOverflow)?
+                .saturating_sub(dust);
+
+            // Rounding policy as withdraw_sol: the payout floors, the remainder stays as dust.
+            let pool_tokens_to_burn = mul_div_floor(shares, total_pool_tokens_observed.saturating_sub(dust), total_shares)?;
+            require!(pool_tokens_to_burn > 0, CtoError::ZeroAmount);
+            require!(pool_tokens_to_burn <= free_pool_tokens, CtoError::WithdrawalQueueHeadNotFillable);
+            let burned_value = mul_div_floor(shares, total_pool_tokens_observed, total_shares)?;
+
+            let stake_pool_state = read_pool_rate(&ctx.accounts.pool, &ctx.accounts.stake_pool)?;
+            let minimum_lamports_out =
+                default_min_lamports_out(&stake_pool_state, pool_tokens_to_burn, ctx.accounts.pool.donor_slippage_bps)?;
+            require_lst_rate_in_band(&ctx.accounts.pool, &ctx.accounts.stake_pool)?;
+
+            received = if ctx.accounts.pool.custody == PoolCustody::Native {
+                release_native_lamports(&mut ctx.accounts.pool, pool_tokens_to_burn)?
+            } else {
+                let pre_pool_lamports = ctx.accounts.pool.to_account_info().lamports();
+                stake_pool_withdraw_sol_queue(&ctx, pool_tokens_to_burn, minimum_lamports_out)?;
+                ctx.accounts
+                    .pool
+                    .to_account_info()
+                    .lamports()
+                    .checked_sub(pre_pool_lamports)
+                    .ok_or(CtoError::MathOverflow)?
+            };
+            require!(received >= minimum_lamports_out, CtoError::SlippageExceeded);
+
+            transfer_lamports_signed(
+                &ctx.accounts.pool.to_account_info(),
+                &ctx.accounts.owner.to_account_info(),
+                pool_seeds!(&ctx.accounts.pool, ctx.bumps.pool),
+                received,
+            )?;
+
+            ctx.accounts.pool_lst_account.reload()?;
+            let final_pool_tokens = observed_pool_tokens(&ctx.accounts.pool, &ctx.accounts.pool_lst_account);
+            let pool = &mut ctx.accounts.pool;
+            pool.total_pool_tokens = final_pool_tokens;
+            pool.rounding_dust_pool_tokens = pool
+                .rounding_dust_pool_tokens
+                .checked_add(burned_value.saturating_sub(pool_tokens_to_burn))
+                .ok_or(CtoError::MathOverflow)?;
+            pool.total_shares = pool.total_shares.checked_sub(shares).ok_or(CtoError::MathOverflow)?;
+            pool.queued_shares = pool.queued_shares.checked_sub(shares).ok_or(CtoError::MathOverflow)?;
+
+            record_activity(
+                &mut ctx.accounts.activity_log,
+                ActivityKind::Withdraw,
+                0,
+                ctx.accounts.owner.key(),
+                received,
+                clock.slot,
+            );
+        }
+
+        let pool = &mut ctx.accounts.pool;
+        pool.withdrawal_queue_head = pool
+            .withdrawal_queue_head
+            .checked_add(1)
+            .ok_or(CtoError::MathOverflow)?;
+
+        unlock_pool!(ctx.accounts.pool);
+
+        emit_cpi!(WithdrawalQueueProcessedEvent {
+            pool: ctx.accounts.pool.key(),
+            owner: ctx.accounts.owner.key(),
+            index: ctx.accounts.withdrawal_request.index,
+            shares,
+            lamports_out: received,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Burn share tokens from the caller's ATA and credit the shares to their Donor account
+    /// (created on first use).
+    ///
+    /// Counts as a share change, so the shares can't vote on proposals already snapshotted.
+    pub fn redeem_share_tokens(ctx: Context<RedeemShareTokens>, shares: u64) -> Result<()> {
+        require!(shares > 0, CtoError::ZeroAmount);
+
+        token::burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token::Burn {
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    from: ctx.accounts.holder_share_account.to_account_info(),
+                    authority: ctx.accounts.holder_wallet.to_account_info(),
+                },
+            ),
+            shares,
+        )?;
+
+        if ctx.accounts.donor.wallet == Pubkey::default() {
+            register_donor(
+                &mut ctx.accounts.pool,
+                &mut ctx.accounts.donor,
+                &mut ctx.accounts.donor_index_page,
+                ctx.accounts.holder_wallet.key(),
+            )?;
+        }
+
+        let clock = Clock::get()?;
+        let pool = &mut ctx.accounts.pool;
+        pool.tokenized_shares = pool
+            .tokenized_shares
+            .checked_sub(shares)
+            .ok_or(CtoError::MathOverflow)?;
+        let donor = &mut ctx.accounts.donor;
+        require!(donor.share_scale == pool.share_scale, CtoError::ShareScaleMigrationRequired);
+        donor.shares = donor.shares.checked_add(shares).ok_or(CtoError::MathOverflow)?;
+        mark_shares_changed(pool, donor, clock.slot);
+
+        emit_cpi!(ShareTokensRedeemedEvent {
+            pool: pool.key(),
+            wallet: ctx.accounts.holder_wallet.key(),
+            shares,
+            tokenized_shares: pool.tokenized_shares,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Close an empty Donor account and reclaim its rent.
+    ///
+    /// Requires zero shares and no outstanding penalty strikes (closing must not reset penalties).
+    /// The wallet stays in the donor index; donating again re-creates the Donor and re-appends it.
+    pub fn close_donor(ctx: Context<CloseDonor>) -> Result<()> {
+        let donor = &ctx.accounts.donor;
+        require!(donor.shares == 0, CtoError::DonorHasShares);
+        require!(
+            donor.abort_strike_count == 0 && donor.propose_strike_count == 0,
+            CtoError::DonorHasStrikes
+        );
+
+        let pool = &mut ctx.accounts.pool;
+        pool.donor_count = pool.donor_count.checked_sub(1).ok_or(CtoError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Burn a dust share balance (below MIN_SHARES_MINTED) and close its Donor account.
+    ///
+    /// Permissionless, so dust accounts can't be farmed to inflate donor counts. The burned shares'
+    /// LST stays in the pool; the Donor's rent (worth more than the dust) goes back to its wallet.
+    pub fn consolidate_dust(ctx: Context<ConsolidateDust>) -> Result<()> {
+        let donor = &ctx.accounts.donor;
+        require!(donor.share_scale == ctx.accounts.pool.share_scale, CtoError::ShareScaleMigrationRequired);
+        require!(donor.shares < MIN_SHARES_MINTED, CtoError::NotDust);
+        require!(donor.delegated_shares == 0, CtoError::SharesDelegated);
+        require!(
+            donor.abort_strike_count == 0 && donor.propose_strike_count == 0,
+            CtoError::DonorHasStrikes
+        );
+        let dust = donor.shares;
+
+        let pool = &mut ctx.accounts.pool;
+        pool.total_shares = pool.total_shares.checked_sub(dust).ok_or(CtoError::MathOverflow)?;
+        pool.donor_count = pool.donor_count.checked_sub(1).ok_or(CtoError::MathOverflow)?;
+
+        emit_cpi!(DustConsolidatedEvent {
+            pool: pool.key(),
+            donor: ctx.accounts.donor_wallet.key(),
+            shares_burned: dust,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Start winding down an abandoned pool.
+    ///
+    /// Requires the pool authority and ABANDONED_POOL_SECONDS without donations, proposals or votes.
+    /// From then on the pool is withdrawal-only: donations and new proposals are rejected, while
+    /// `withdraw_sol`/`withdraw_all` keep unstaking each donor's LST back to SOL.
+    pub fn begin_sunset(ctx: Context<BeginSunset>) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+        let now = Clock::get()?.unix_timestamp;
+        let pool = &mut ctx.accounts.pool;
+        require!(pool.sunset_started_ts == 0, CtoError::PoolSunsetting);
+        require!(
+            pool.active_proposal.is_none() && pool.active_recovery.is_none(),
+            CtoError::ActiveProposalExists
+        );
+        require!(
+            now.saturating_sub(pool.last_engagement_ts) >= ABANDONED_POOL_SECONDS,
+            CtoError::PoolNotAbandoned
+        );
+        require_no_liquidity_position(pool)?;
+
+        pool.sunset_started_ts = now;
+
+        emit_cpi!(PoolSunsetStartedEvent {
+            pool: pool.key(),
+            last_engagement_ts: pool.last_engagement_ts,
+            total_shares: pool.total_shares,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless crank: unstakes a sunset pool's whole LST balance into the pool PDA through
+    /// the stake pool's WithdrawSol and switches the pool to native custody, so
+    /// `distribute_remaining` and withdrawals pay SOL from then on.
+    ///
+    /// Only while nothing is reserved. If the stake pool reserve can't cover the balance the crank
+    /// fails and `distribute_remaining` keeps paying LST.
+    pub fn unstake_sunset_pool(ctx: Context<UnstakeSunsetPool>, minimum_lamports_out: u64) -> Result<()> {
+        lock_pool!(ctx.accounts.pool);
+
+        let pool = &ctx.accounts.pool;
+        require!(pool.sunset_started_ts != 0, CtoError::PoolNotSunsetting);
+        require!(pool.custody == PoolCustody::Staked, CtoError::PoolAlreadyUnstaked);
+        require!(pool.reserved_pool_tokens == 0, CtoError::FullExitBlockedByReservation);
+
+        ctx.accounts.pool_lst_account.reload()?;
+        let pool_tokens_in = ctx.accounts.pool_lst_account.amount;
+        let lamports_before = ctx.accounts.pool.to_account_info().lamports();
+        if pool_tokens_in > 0 {
+            stake_pool_withdraw_sol_sunset(&ctx, pool_tokens_in, minimum_lamports_out)?;
+        }
+        let lamports_out = ctx
+            .accounts
+            .pool
+            .to_account_info()
+            .lamports()
+            .checked_sub(lamports_before)
+            .ok_or(CtoError::MathOverflow)?;
+
+        // Pool tokens are lamports from here on; dust keeps its share of the proceeds.
+        let pool = &mut ctx.accounts.pool;
+        pool.rounding_dust_pool_tokens = if pool_tokens_in == 0 {
+            0
+        } else {
+            mul_div_floor(pool.rounding_dust_pool_tokens.min(pool_tokens_in), lamports_out, pool_tokens_in)?
+        };
+        pool.total_pool_tokens = lamports_out;
+        pool.custody = PoolCustody::Native;
+
+        unlock_pool!(ctx.accounts.pool);
+
+        emit_cpi!(SunsetPoolUnstakedEvent {
+            pool: ctx.accounts.pool.key(),
+            pool_tokens_in,
+            lamports_out,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless crank: pays a donor of a sunset pool their whole pro-rata balance and closes
+    /// their Donor account (rent to the donor).
+    ///
+    /// Staked pools transfer the LST itself into the donor's LST ATA (create it first), so the
+    /// crank doesn't depend on stake pool liquidity; native pools pay SOL. The last donor takes
+    /// everything, including rounding dust. Only while nothing is reserved, like `withdraw_all`.
+    pub fn distribute_remaining(ctx: Context<DistributeRemaining>) -> Result<()> {
+        lock_pool!(ctx.accounts.pool);
+
+        let pool = &ctx.accounts.pool;
+        let donor = &ctx.accounts.donor;
+        require!(pool.sunset_started_ts != 0, CtoError::PoolNotSunsetting);
+        require!(pool.reserved_pool_tokens == 0, CtoError::FullExitBlockedByReservation);
+        require!(donor.share_scale == pool.share_scale, CtoError::ShareScaleMigrationRequired);
+        require!(donor.delegated_shares == 0, CtoError::SharesDelegated);
+        let donor_shares = donor.shares;
+        let total_shares = pool.total_shares;
+        require!(donor_shares > 0, CtoError::NoShares);
+
+        ctx.accounts.pool_lst_account.reload()?;
+        let total_pool_tokens_observed = observed_pool_tokens(&ctx.accounts.pool, &ctx.accounts.pool_lst_account);
+        let pool_tokens_out = if donor_shares == total_shares {
+            ctx.accounts.pool.rounding_dust_pool_tokens = 0;
+            total_pool_tokens_observed
+        } else {
+            let withdrawable = total_pool_tokens_observed.saturating_sub(ctx.accounts.pool.rounding_dust_pool_tokens);
+            mul_div_floor(donor_shares, withdrawable, total_shares)?
+        };
+
+        let pool_bump = ctx.bumps.pool;
+        if ctx.accounts.pool.custody == PoolCustody::Native {
+            let lamports = release_native_lamports(&mut ctx.accounts.pool, pool_tokens_out)?;
+            transfer_lamports_signed(
+                &ctx.accounts.pool.to_account_info(),
+                &ctx.accounts.donor_wallet.to_account_info(),
+                pool_seeds!(&ctx.accounts.pool, pool_bump),
+                lamports,
+            )?;
+        } else if pool_tokens_out > 0 {
+            let donor_lst_account = ctx
+                .accounts
+                .donor_lst_account
+                .as_ref()
+                .ok_or(CtoError::MissingDonorLstAccount)?;
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Transfer {
+                        from: ctx.accounts.pool_lst_account.to_account_info(),
+                        to: donor_lst_account.to_account_info(),
+                        authority: ctx.accounts.pool.to_account_info(),
+                    },
+                    pool_seeds!(&ctx.accounts.pool, pool_bump),
+                ),
+                pool_tokens_out,
+            )?;
+        }
+
+        ctx.accounts.pool_lst_account.reload()?;
+        let final_pool_tokens = observed_pool_tokens(&ctx.accounts.pool, &ctx.accounts.pool_lst_account);
+        let pool = &mut ctx.accounts.pool;
+        pool.total_pool_tokens = final_pool_tokens;
+        pool.total_shares = pool
+            .total_shares
+            .checked_sub(donor_shares)
+            .ok_or(CtoError::MathOverflow)?;
+        ctx.accounts.donor.shares = 0;
+
+        unlock_pool!(ctx.accounts.pool);
+
+        emit_cpi!(SunsetDistributionEvent {
+            pool: ctx.accounts.pool.key(),
+            donor: ctx.accounts.donor_wallet.key(),
+            shares_burned: donor_shares,
+            pool_tokens_out,
+            custody: ctx.accounts.pool.custody,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Close a sunset pool once the grace period is over and every share has been withdrawn.
+    ///
+    /// Everything the pool tracks for someone else must be settled first: queued burn, auction and
+    /// liquidity SOL, reserved LST, SPL donation positions, unclaimed payouts and open stake
+    /// withdrawals. Leftover LST (rounding dust with no share claim), held CTOP and treasury tokens
+    /// are burned so their token accounts can be closed; account rent goes to the authority.
+    pub fn close_sunset_pool(ctx: Context<CloseSunsetPool>) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+        let now = Clock::get()?.unix_timestamp;
+        let pool = &ctx.accounts.pool;
+        require!(pool.sunset_started_ts != 0, CtoError::PoolNotSunsetting);
+        require!(
+            now >= pool
+                .sunset_started_ts
+                .checked_add(SUNSET_CLOSE_GRACE_SECONDS)
+                .ok_or(CtoError::MathOverflow)?,
+            CtoError::SunsetGraceActive
+        );
+        require!(pool.total_shares == 0, CtoError::DonorHasShares);
+        require!(pool.pending_claim_lamports == 0, CtoError::PayoutClaimsOutstanding);
+        require!(
+            pool.pending_burn_lamports == 0
+                && pool.burn_auction_lamports == 0
+                && pool.reserved_pool_tokens == 0
+                && pool.spl_position_shares == 0
+                && pool.open_stake_withdrawals == 0,
+            CtoError::SunsetAssetsOutstanding
+        );
+        require_no_liquidity_position(pool)?;
+
+        let pool_bump = ctx.bumps.pool;
+        if ctx.accounts.pool.held_ctop_amount > 0 {
+            let (Some(burn_token_mint), Some(pool_ctop_account)) =
+                (ctx.accounts.burn_token_mint.as_ref(), ctx.accounts.pool_ctop_account.as_ref())
+            else {
+                return err!(CtoError::SunsetAssetsOutstanding);
+            };
+            token::burn(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Burn {
+                        mint: burn_token_mint.to_account_info(),
+                        from: pool_ctop_account.to_account_info(),
+                        authority: ctx.accounts.pool.to_account_info(),
+                    },
+                    pool_seeds!(&ctx.accounts.pool, pool_bump),
+                ),
+                pool_ctop_account.amount,
+            )?;
+            token::close_account(CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::CloseAccount {
+                    account: pool_ctop_account.to_account_info(),
+                    destination: ctx.accounts.authority.to_account_info(),
+                    authority: ctx.accounts.pool.to_account_info(),
+                },
+                pool_seeds!(&ctx.accounts.pool, pool_bump),
+            ))?;
+        }
+        if ctx.accounts.pool.treasury_token_amount > 0 {
+            let (Some(token_mint), Some(pool_token_treasury), Some(token_mint_program)) = (
+                ctx.accounts.token_mint.as_ref(),
+                ctx.accounts.pool_token_treasury.as_ref(),
+                ctx.accounts.token_mint_program.as_ref(),
+            ) else {
+                return err!(CtoError::SunsetAssetsOutstanding);
+            };
+            token_interface::burn(
+                CpiContext::new_with_signer(
+                    token_mint_program.to_account_info(),
+                    token_interface::Burn {
+                        mint: token_mint.to_account_info(),
+                        from: pool_token_treasury.to_account_info(),
+                        authority: ctx.accounts.pool.to_account_info(),
+                    },
+                    pool_seeds!(&ctx.accounts.pool, pool_bump),
+                ),
+                pool_token_treasury.amount,
+            )?;
+            token_interface::close_account(CpiContext::new_with_signer(
+                token_mint_program.to_account_info(),
+                token_interface::CloseAccount {
+                    account: pool_token_treasury.to_account_info(),
+                    destination: ctx.accounts.authority.to_account_info(),
+                    authority: ctx.accounts.pool.to_account_info(),
+                },
+                pool_seeds!(&ctx.accounts.pool, pool_bump),
+            ))?;
+        }
+
+        let leftover = ctx.accounts.pool_lst_account.amount;
+        if leftover > 0 {
+            token::burn(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Burn {
+                        mint: ctx.accounts.lst_mint.to_account_info(),
+                        from: ctx.accounts.pool_lst_account.to_account_info(),
+                        authority: ctx.accounts.pool.to_account_info(),
+                    },
+                    pool_seeds!(&ctx.accounts.pool, pool_bump),
+                ),
+                leftover,
+            )?;
+        }
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::CloseAccount {
+                account: ctx.accounts.pool_lst_account.to_account_info(),
+                destination: ctx.accounts.authority.to_account_info(),
+                authority: ctx.accounts.pool.to_account_info(),
+            },
+            pool_seeds!(&ctx.accounts.pool, pool_bump),
+        ))?;
+
+        emit_cpi!(PoolClosedEvent {
+            pool: ctx.accounts.pool.key(),
+            burned_pool_tokens: leftover,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Create a payout proposal.
+    ///
+    /// `requested_amount` is interpreted according to `denomination`:
+    /// - `Lamports`: SOL to pay out; pool tokens are locked at the current rate plus a buffer.
+    /// - `PoolTokens`: LST to burn on execution; exactly this amount is locked and later burned.
+    /// - `TvlBps`: share of the pool's LST in bps, resolved against the pool balance at execution
+    ///   time. The share of the current balance is locked and acts as the payout ceiling.
+    ///
+    /// `voting_period_seconds` sets the deadline and must lie within the pool's voting window bounds.
+    pub fn create_proposal(
+        ctx: Context<CreateProposal>,
+        requested_amount: u64,
+        denomination: RequestDenomination,
+        destination_wallet: Pubkey,
+        title: String,
+        description: String,
+        voting_period_seconds: i64,
+    ) -> Result<()> {
+        require!(requested_amount > 0, CtoError::ZeroAmount);
+        if denomination == RequestDenomination::TvlBps {
+            require!(requested_amount <= BPS_DENOM, CtoError::InvalidRequestBps);
+        }
+        // An adopted template pins destination, denomination and bounds, and fills in empty text.
+        let (title, description) = match &ctx.accounts.template {
+            Some(template) => {
+                check_template_request(template, requested_amount, denomination, destination_wallet)?;
+                (
+                    if title.is_empty() { template.title.clone() } else { title },
+                    if description.is_empty() { template.description.clone() } else { description },
+                )
+            }
+            None => (title, description),
+        };
+        validate_proposal_text(&title, &description, Proposal::TITLE_MAX, Proposal::DESC_MAX)?;
+
+        let clock = Clock::get()?;
+
+        // ============ PHASE 1: Immutable reads and penalty fee CPI ============
+        // Read values needed for penalty fee calculation before any mutable borrows.
+        let base_penalty = ctx.accounts.pool.base_penalty_lamports;
+        let propose_strikes = ctx.accounts.donor.propose_strike_count;
+        let pool_key = ctx.accounts.pool.key();
+
+        // Escalating proposer penalty (uncapped). Collected into pool PDA.
+        let proposer_fee = penalty_fee(base_penalty, propose_strikes);
+        if proposer_fee > 0 {
+            invoke(
+                &system_instruction::transfer(&ctx.accounts.proposer_wallet.key(), &pool_key, proposer_fee),
+                &[ctx.accounts.proposer_wallet.to_account_info(), ctx.accounts.pool.to_account_info(), ctx.accounts.system_program.to_account_info()],
+            ).map_err(|_| CtoError::LamportTransferFailed)?;
+        }
+
+        // Refundable bond, escrowed in the ProposerBond until settle_proposer_bond.
+        let bond_lamports = ctx.accounts.pool.proposer_bond_lamports;
+        if bond_lamports > 0 {
+            let proposal_key = ctx.accounts.proposal.key();
+            let proposer_key = ctx.accounts.proposer_wallet.key();
+            let bond = ctx.accounts.proposer_bond.as_mut().ok_or(CtoError::MissingProposerBond)?;
+            invoke(
+                &system_instruction::transfer(&proposer_key, &bond.key(), bond_lamports),
+                &[ctx.accounts.proposer_wallet.to_account_info(), bond.to_account_info(), ctx.accounts.system_program.to_account_info()],
+            ).map_err(|_| CtoError::LamportTransferFailed)?;
+            bond.pool = pool_key;
+            bond.proposal = proposal_key;
+            bond.proposer = proposer_key;
+            bond.bump = ctx.bumps.proposer_bond.unwrap_or_default();
+        }
+
+        // ============ PHASE 2: Mutable state and validation ============
+        let pool = &mut ctx.accounts.pool;
+        let donor = &ctx.accounts.donor;
+        let proposal = &mut ctx.accounts.proposal;
+
+        require_can_propose(pool, donor, ctx.accounts.proposer_wallet.key(), &clock)?;
+        require!(!pool.paused, CtoError::PoolPaused);
+        require_allowlisted(pool, &ctx.accounts.allowlist_entry)?;
+
+        // Per-destination cooldown after failed proposals.
+        require!(
+            clock.unix_timestamp >= ctx.accounts.destination_record.cooldown_until_ts,
+            CtoError::DestinationCooldownActive
+        );
+
+        ctx.accounts.pool_lst_account.reload()?;
+        pool.total_pool_tokens = observed_pool_tokens(pool, &ctx.accounts.pool_lst_account);
+        let stake_pool_state = read_pool_rate(pool, &ctx.accounts.stake_pool)?;
+        require_proposer_value(pool, donor, &stake_pool_state)?;
+
+        let (requested_lamports, locked_pool_tokens) = match denomination {
+            RequestDenomination::Lamports => {
+                // Compute pool tokens to lock = ceil(requested * (1 + buffer))
+                let buffered = requested_amount
+                    .checked_mul(BPS_DENOM + PROPOSAL_BUFFER_BPS)
+                    .ok_or(CtoError::MathOverflow)?
+                    .checked_div(BPS_DENOM)
+                    .ok_or(CtoError::MathOverflow)?;
+                (requested_amount, pool_tokens_for_lamports_ceil(&stake_pool_state, buffered)?)
+            }
+            RequestDenomination::PoolTokens => {
+                // Exact: lock what is requested, no rate-dependent buffer.
+                // requested_lamports is informational only (value at the current withdraw rate).
+                let estimated_lamports = stake_pool_state
+                    .calc_lamports_withdraw_amount(requested_amount)
+                    .ok_or(CtoError::MathOverflow)?;
+                (estimated_lamports, requested_amount)
+            }
+            RequestDenomination::TvlBps => {
+                let locked = pool_tokens_for_bps(pool.total_pool_tokens, requested_amount)?;
+                require!(locked > 0, CtoError::ZeroAmount);
+                let estimated_lamports = stake_pool_state
+                    .calc_lamports_withdraw_amount(locked)
+                    .ok_or(CtoError::MathOverflow)?;
+                (estimated_lamports, locked)
+            }
+        };
+
+        // Per-pool floor so the single active-proposal slot isn't spent on dust requests.
+        // For non-lamport denominations this is the value at the current withdraw rate.
+        require!(requested_lamports >= pool.min_proposal_lamports, CtoError::ProposalTooSmall);
+
+        // Small payouts may take the pool's fast track: a shorter minimum window and lower quorum.
+        let fast_track = pool.small_payout_threshold_lamports > 0
+            && requested_lamports <= pool.small_payout_threshold_lamports;
+        let min_voting_seconds = if fast_track { pool.fast_track_voting_seconds } else { pool.min_voting_seconds };
+        require!(
+            voting_period_seconds >= min_voting_seconds && voting_period_seconds <= pool.max_voting_seconds,
+            CtoError::InvalidVotingPeriod
+        );
+
+        // Per-pool cap on the share of the treasury a single proposal can lock.
+        let max_proposal_pool_tokens = pool_tokens_for_bps(pool.total_pool_tokens, pool.max_proposal_bps as u64)?;
+        require!(locked_pool_tokens <= max_proposal_pool_tokens, CtoError::ProposalTooLarge);
+
+        let free_pool_tokens = pool
+            .total_pool_tokens
+            .checked_sub(pool.reserved_pool_tokens)
+            .ok_or(CtoError::MathOverflow)?
+            .saturating_sub(pool.rounding_dust_pool_tokens);
+        require!(locked_pool_tokens <= free_pool_tokens, CtoError::InsufficientFreeLiquidity);
+
+        pool.reserved_pool_tokens = pool
+            .reserved_pool_tokens
+            .checked_add(locked_pool_tokens)
+            .ok_or(CtoError::MathOverflow)?;
+
+        let destination_record = &mut ctx.accounts.destination_record;
+        destination_record.pool = pool.key();
+        destination_record.destination = destination_wallet;
+
+        proposal.version = PROPOSAL_VERSION;
+        proposal.pool = pool.key();
+        proposal.proposer_wallet = ctx.accounts.proposer_wallet.key();
+        proposal.kind = ProposalKind::Payout;
+        proposal.requested_lamports = requested_lamports;
+        proposal.denomination = denomination;
+        proposal.requested_bps = if denomination == RequestDenomination::TvlBps {
+            requested_amount as u16
+        } else {
+            0
+        };
+        proposal.destination_wallet = destination_wallet;
+        proposal.destination_kind = classify_destination(&ctx.accounts.destination, None, 0)?;
+        match ctx.accounts.template.as_mut() {
+            Some(template) => {
+                proposal.category = template.category;
+                proposal.template = template.key();
+                template.use_count = template.use_count.saturating_add(1);
+            }
+            None => {
+                proposal.category = ProposalCategory::General;
+                proposal.template = Pubkey::default();
+            }
+        }
+        proposal.title = title;
+        proposal.description = description;
+
+        proposal.created_at_ts = clock.unix_timestamp;
+        // With co-sponsorship on, the deadline first bounds the sponsor window; `sponsor_proposal`
+        // moves it to the end of the voting period once enough sponsors have signed.
+        proposal.sponsors_required = pool.required_sponsors;
+        proposal.sponsor_count = 0;
+        proposal.voting_period_seconds = voting_period_seconds;
+        proposal.deadline_ts = clock
+            .unix_timestamp
+            .checked_add(if pool.required_sponsors > 0 { SPONSOR_WINDOW_SECONDS } else { voting_period_seconds })
+            .ok_or(CtoError::MathOverflow)?;
+        proposal.snapshot_slot = clock.slot;
+        proposal.total_snapshot_shares = voting_shares(pool)?;
+        proposal.fast_track = fast_track;
+        let tier_quorum_bps = tiered_quorum_bps(pool, locked_pool_tokens)?;
+        proposal.quorum_bps = if fast_track {
+            tier_quorum_bps.min(pool.fast_track_quorum_bps)
+        } else {
+            tier_quorum_bps
+        };
+        proposal.max_voter_bps = pool.max_voter_bps;
+        proposal.queued_until_ts = 0;
+        proposal.bond_lamports = bond_lamports;
+        proposal.bond_spam_threshold_bps = pool.bond_spam_threshold_bps;
+        proposal.merkle_weights = pool.merkle_voting;
+        proposal.reveal_seconds = if pool.merkle_voting { 0 } else { pool.commit_reveal_seconds };
+        proposal.snipe_extension_seconds = pool.snipe_extension_seconds;
+        proposal.snipe_extensions_left = pool.max_snipe_extensions;
+        proposal.vote_ramp_slots = pool.vote_ramp_slots;
+        proposal.vote_weighting = pool.vote_weighting;
+        proposal.payout_mint = Pubkey::default();
+        proposal.payout_token_amount = 0;
+        proposal.liquidity_amount = 0;
+        proposal.weight_root_total = 0;
+        proposal.merkle_weight_proven = 0;
+        proposal.cap_adjusted_quorum = pool.cap_adjusted_quorum;
+        proposal.excluded_cap_weight = 0;
+        proposal.weight_root = [0u8; 32];
+        proposal.config_change = ConfigChangeParams::default();
+        proposal.milestone = MilestoneSchedule::default();
+        proposal.stream = StreamSchedule::default();
+        proposal.budget = BudgetSchedule::default();
+
+        proposal.locked_pool_tokens = locked_pool_tokens;
+
+        proposal.yes_weight = 0;
+        proposal.no_weight = 0;
+        proposal.abstain_weight = 0;
+        proposal.participation_weight = 0;
+        proposal.status = ProposalStatus::Active;
+        proposal.finalized_slot = 0;
+        proposal.tie_approved = false;
+        proposal.tie_extended = false;
+        proposal.quorum_grace_used = false;
+        proposal.abort_voter_1 = Pubkey::default();
+        proposal.abort_voter_2 = Pubkey::default();
+        proposal.abort_count = 0;
+
+        // Append to the pool's proposal index (page = proposal_count / CAPACITY).
+        let index_page = &mut ctx.accounts.proposal_index_page;
+        if index_page.proposals.is_empty() {
+            index_page.pool = pool.key();
+            index_page.page_index = pool.proposal_count / ProposalIndexPage::CAPACITY as u64;
+        }
+        index_page.proposals.push(proposal.key());
+
+        pool.active_proposal = Some(proposal.key());
+        pool.proposal_snapshot_slot = proposal.snapshot_slot;
+        pool.proposal_count = pool.proposal_count.checked_add(1).ok_or(CtoError::MathOverflow)?;
+        pool.last_engagement_ts = clock.unix_timestamp;
+        record_proposal_created(&mut ctx.accounts.donor, clock.slot);
+
+        record_activity(
+            &mut ctx.accounts.activity_log,
+            ActivityKind::Propose,
+            denomination as u8,
+            ctx.accounts.proposer_wallet.key(),
+            requested_lamports,
+            clock.slot,
+        );
+
+        // L-05 FIX: Emit proposal created event
+        emit_cpi!(ProposalCreatedEvent {
+            pool: pool.key(),
+            proposal: proposal.key(),
+            proposer: ctx.accounts.proposer_wallet.key(),
+            requested_lamports,
+            denomination,
+            locked_pool_tokens,
+            quorum_bps: proposal.quorum_bps,
+            destination_kind: proposal.destination_kind,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Propose new values for pool parameters (quorum, protocol fee, minimum proposer value,
+    /// proposal share-age delay); unset fields keep their current value. `cancel_budget` names a
+    /// recurring budget that `cancel_recurring_budget` stops once this change has executed.
+    ///
+    /// Goes through the same admission checks, proposer fee, sponsorship and vote as a payout
+    /// proposal and occupies the proposal slot, but locks no liquidity. `execute_proposal`
+    /// applies all values at once if it passes. The pool itself stands in as the destination,
+    /// so failed config changes share one destination cooldown.
+    pub fn create_config_proposal(
+        ctx: Context<CreateConfigProposal>,
+        params: ConfigChangeParams,
+        title: String,
+        description: String,
+        voting_period_seconds: i64,
+    ) -> Result<()> {
+        validate_config_change(&ctx.accounts.pool, &params)?;
+        open_pool_level_proposal(ctx, ProposalKind::ConfigChange, params, 0, title, description, voting_period_seconds)
+    }
+
+    /// Propose winding the pool down by vote.
+    ///
+    /// Admitted, sponsored and voted like a ConfigChange (same accounts). When `execute_proposal`
+    /// applies it the pool enters sunset as with `begin_sunset`: donations and new proposals stop,
+    /// donors can still withdraw, and `distribute_remaining` pays every remaining donor out so
+    /// `close_sunset_pool` can close the pool.
+    pub fn create_sunset_proposal(
+        ctx: Context<CreateConfigProposal>,
+        title: String,
+        description: String,
+        voting_period_seconds: i64,
+    ) -> Result<()> {
+        open_pool_level_proposal(
+            ctx,
+            ProposalKind::Sunset,
+            ConfigChangeParams::default(),
+            0,
+            title,
+            description,
+            voting_period_seconds,
+        )
+    }
+
+    /// Propose redeeming `lp_amount` of the LP tokens the pool holds in its Raydium CPMM pool.
+    ///
+    /// Admitted, sponsored and voted like a ConfigChange (same accounts). A pass only approves the
+    /// withdrawal; `withdraw_liquidity` then returns the pool's token to the treasury and the SOL
+    /// to the pool PDA, where `sweep_surplus` stakes it for all donors.
+    pub fn create_remove_liquidity_proposal(
+        ctx: Context<CreateConfigProposal>,
+        lp_amount: u64,
+        title: String,
+        description: String,
+        voting_period_seconds: i64,
+    ) -> Result<()> {
+        let pool = &ctx.accounts.pool;
+        require!(
+            lp_amount > 0 && lp_amount <= pool.lp_token_amount.saturating_sub(pool.pending_lp_withdrawal),
+            CtoError::InvalidLiquidityRemoval
+        );
+        open_pool_level_proposal(
+            ctx,
+            ProposalKind::RemoveLiquidity,
+            ConfigChangeParams::default(),
+            lp_amount,
+            title,
+            description,
+            voting_period_seconds,
+        )
+    }
+
+    /// Open a non-binding poll, the pool's signal vote (temperature checks such as "should we
+    /// rebrand?").
+    ///
+    /// Uses the same snapshot, vote records and tally as a payout proposal but locks no liquidity,
+    /// moves no funds and doesn't occupy the payout proposal slot. One poll may be open at a time.
+    pub fn create_poll(
+        ctx: Context<CreatePoll>,
+        title: String,
+        description: String,
+        voting_period_seconds: i64,
+    ) -> Result<()> {
+        validate_proposal_text(&title, &description, Proposal::TITLE_MAX, Proposal::DESC_MAX)?;
+
+        let accounts = &mut *ctx.accounts;
+        open_poll(
+            &mut accounts.pool,
+            &accounts.donor,
+            &mut accounts.proposal,
+            &mut accounts.proposal_index_page,
+            accounts.proposer_wallet.key(),
+            ProposalKind::Poll,
+            voting_period_seconds,
+        )?;
+        accounts.proposal.title = title;
+        accounts.proposal.description = description;
+
+        emit_cpi!(PollCreatedEvent {
+            pool: ctx.accounts.pool.key(),
+            proposal: ctx.accounts.proposal.key(),
+            proposer: ctx.accounts.proposer_wallet.key(),
+            deadline_ts: ctx.accounts.proposal.deadline_ts,
+            quorum_bps: ctx.accounts.proposal.quorum_bps,
+            timestamp: ctx.accounts.proposal.created_at_ts,
+        });
+
+        Ok(())
+    }
+
+    /// Propose a reusable payout template; it becomes usable once its adoption vote passes.
+    ///
+    /// Opens an `AdoptTemplate` proposal in the poll slot (finalized by `finalize_poll`); the
+    /// template stays Pending until then.
+    pub fn propose_template(
+        ctx: Context<ProposeTemplate>,
+        params: TemplateParams,
+        voting_period_seconds: i64,
+    ) -> Result<()> {
+        require!(
+            params.min_amount > 0 && params.min_amount <= params.max_amount,
+            CtoError::InvalidTemplateBounds
+        );
+        if params.denomination == RequestDenomination::TvlBps {
+            require!(params.max_amount <= BPS_DENOM, CtoError::InvalidRequestBps);
+        }
+        validate_proposal_text(&params.title, &params.description, Proposal::TITLE_MAX, Proposal::DESC_MAX)?;
+
+        let accounts = &mut *ctx.accounts;
+        let template_id = accounts.pool.template_count;
+        open_poll(
+            &mut accounts.pool,
+            &accounts.donor,
+            &mut accounts.proposal,
+            &mut accounts.proposal_index_page,
+            accounts.proposer_wallet.key(),
+            ProposalKind::AdoptTemplate,
+            voting_period_seconds,
+        )?;
+        accounts.pool.template_count = template_id.checked_add(1).ok_or(CtoError::MathOverflow)?;
+
+        let template = &mut accounts.template;
+        template.pool = accounts.pool.key();
+        template.template_id = template_id;
+        template.proposer = accounts.proposer_wallet.key();
+        template.category = params.category;
+        template.destination_wallet = params.destination_wallet;
+        template.denomination = params.denomination;
+        template.min_amount = params.min_amount;
+        template.max_amount = params.max_amount;
+        template.title = params.title.clone();
+        template.description = params.description.clone();
+        template.status = TemplateStatus::Pending;
+        template.adoption_proposal = accounts.proposal.key();
+        template.use_count = 0;
+
+        let proposal = &mut accounts.proposal;
+        proposal.category = params.category;
+        proposal.template = template.key();
+        proposal.title = params.title;
+        proposal.description = params.description;
+
+        emit_cpi!(TemplateProposedEvent {
+            pool: ctx.accounts.pool.key(),
+            template: ctx.accounts.template.key(),
+            template_id,
+            proposal: ctx.accounts.proposal.key(),
+            proposer: ctx.accounts.proposer_wallet.key(),
+            timestamp: ctx.accounts.proposal.created_at_ts,
+        });
+
+        Ok(())
+    }
+
+    /// Close a poll after its deadline. Permissionless.
+    ///
+    /// The poll is marked Executed if it reached quorum with a Yes majority, Failed otherwise.
+    /// Ties fail and there is no quorum grace; poll outcomes don't affect quorum step-down.
+    /// For `AdoptTemplate` the template must be passed and becomes Active or Rejected.
+    pub fn finalize_poll(ctx: Context<FinalizePoll>) -> Result<()> {
+        let clock = Clock::get()?;
+        let pool = &mut ctx.accounts.pool;
+        let proposal = &mut ctx.accounts.proposal;
+
+        require!(
+            matches!(proposal.kind, ProposalKind::Poll | ProposalKind::AdoptTemplate),
+            CtoError::WrongProposalKind
+        );
+        require!(proposal.status == ProposalStatus::Active, CtoError::ProposalNotActive);
+        require!(clock.unix_timestamp >= proposal.deadline_ts, CtoError::TooEarlyToExecute);
+
+        let quorum_met = proposal
+            .participation_weight
+            .checked_mul(BPS_DENOM)
+            .ok_or(CtoError::MathOverflow)?
+            >= quorum_supply(proposal)
+                .checked_mul(proposal.quorum_bps as u64)
+                .ok_or(CtoError::MathOverflow)?;
+        let passed = quorum_met && proposal.yes_weight > proposal.no_weight;
+
+        proposal.status = if passed { ProposalStatus::Executed } else { ProposalStatus::Failed };
+        proposal.finalized_slot = clock.slot;
+        if pool.active_poll == Some(proposal.key()) {
+            pool.active_poll = None;
+        }
+
+        if proposal.kind == ProposalKind::AdoptTemplate {
+            let template = ctx.accounts.template.as_mut().ok_or(CtoError::TemplateMismatch)?;
+            require_keys_eq!(template.key(), proposal.template, CtoError::TemplateMismatch);
+            template.status = if passed { TemplateStatus::Active } else { TemplateStatus::Rejected };
+
+            emit_cpi!(TemplateResolvedEvent {
+                pool: pool.key(),
+                template: template.key(),
+                status: template.status,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
+        emit_cpi!(PollFinalizedEvent {
+            pool: pool.key(),
+            proposal: proposal.key(),
+            yes_weight: proposal.yes_weight,
+            no_weight: proposal.no_weight,
+            abstain_weight: proposal.abstain_weight,
+            quorum_met,
+            passed,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Co-sponsor a payout proposal that is waiting for sponsors.
+    ///
+    /// Records a Sponsorship PDA per (proposal, sponsor), so each donor counts once. When the
+    /// last required sponsor signs, voting opens for the proposal's full voting period.
+    pub fn sponsor_proposal(ctx: Context<SponsorProposal>) -> Result<()> {
+        let clock = Clock::get()?;
+        let pool = &ctx.accounts.pool;
+        let donor = &ctx.accounts.donor;
+        let proposal = &mut ctx.accounts.proposal;
+
+        require!(proposal.kind.uses_proposal_slot(), CtoError::WrongProposalKind);
+        require!(proposal.status == ProposalStatus::Active, CtoError::ProposalNotActive);
+        require!(proposal.sponsor_count < proposal.sponsors_required, CtoError::SponsorshipComplete);
+        require!(clock.unix_timestamp <= proposal.deadline_ts, CtoError::SponsorWindowClosed);
+        require!(
+            ctx.accounts.sponsor_wallet.key() != proposal.proposer_wallet,
+            CtoError::ProposerCannotSponsor
+        );
+
+        require!(donor.share_scale == pool.share_scale, CtoError::ShareScaleMigrationRequired);
+        require!(
+            donor.last_shares_change_slot <= proposal.snapshot_slot,
+            CtoError::NotEligibleForThisProposal
+        );
+        let own_shares = donor.shares.saturating_sub(donor.delegated_shares);
+        require!(own_shares > 0, CtoError::NoShares);
+        require!(
+            (own_shares as u128) * (BPS_DENOM as u128)
+                >= (proposal.total_snapshot_shares as u128) * (pool.sponsor_min_share_bps as u128),
+            CtoError::SponsorTooSmall
+        );
+
+        let sponsorship = &mut ctx.accounts.sponsorship;
+        sponsorship.proposal = proposal.key();
+        sponsorship.sponsor = ctx.accounts.sponsor_wallet.key();
+        sponsorship.shares = own_shares;
+        sponsorship.slot = clock.slot;
+
+        proposal.sponsor_count = proposal.sponsor_count.saturating_add(1);
+        let voting_open = proposal.sponsor_count >= proposal.sponsors_required;
+        if voting_open {
+            proposal.deadline_ts = clock
+                .unix_timestamp
+                .checked_add(proposal.voting_period_seconds)
+                .ok_or(CtoError::MathOverflow)?;
+        }
+
+        emit_cpi!(ProposalSponsoredEvent {
+            pool: pool.key(),
+            proposal: proposal.key(),
+            sponsor: ctx.accounts.sponsor_wallet.key(),
+            sponsor_count: proposal.sponsor_count,
+            voting_open,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Re-classify an active proposal's destination. Permissionless.
+    ///
+    /// Pass the Squads v4 multisig and vault index to prove the destination is that multisig's
+    /// vault; without it the destination is classified from its owner and data as at creation.
+    pub fn classify_proposal_destination(ctx: Context<ClassifyProposalDestination>, vault_index: u8) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        require!(
+            proposal.kind.pays_destination(),
+            CtoError::WrongProposalKind
+        );
+        require!(proposal.status == ProposalStatus::Active, CtoError::ProposalNotActive);
+
+        let squads = ctx.accounts.squads_multisig.as_ref().map(|m| m.to_account_info());
+        let kind = classify_destination(&ctx.accounts.destination, squads.as_ref(), vault_index)?;
+        proposal.destination_kind = kind;
+
+        emit_cpi!(DestinationClassifiedEvent {
+            pool: ctx.accounts.pool.key(),
+            proposal: proposal.key(),
+            destination: proposal.destination_wallet,
+            kind,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Vote on proposal.
+    pub fn vote(ctx: Context<Vote>, choice: VoteChoice) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        let donor = &ctx.accounts.donor;
+        let vote_record = &mut ctx.accounts.vote_record;
+        let clock = Clock::get()?;
+        require!(proposal.status == ProposalStatus::Active, CtoError::ProposalNotActive);
+        require!(clock.unix_timestamp <= proposal.deadline_ts, CtoError::VotingClosed);
+        require!(proposal.sponsor_count >= proposal.sponsors_required, CtoError::AwaitingSponsors);
+        require!(donor.share_scale == ctx.accounts.pool.share_scale, CtoError::ShareScaleMigrationRequired);
+        // Shares held at the snapshot (from the donor's checkpoints), never more than held now,
+        // ramped by holding time in time-weighted pools.
+        let own_shares = snapshot_vote_weight(donor, proposal)?;
+        require!(own_shares > 0, CtoError::NoShares);
+        // A ballot cast through `vote_compact` can't be doubled with a VoteRecord.
+        require!(donor.ballot_proposal != proposal.key(), CtoError::AlreadyVotedCompact);
+        // Merkle-mode weight comes from the posted root; only the per-donor Abort brake stays here.
+        require!(
+            !proposal.merkle_weights || choice == VoteChoice::Abort,
+            CtoError::MerkleProofRequired
+        );
+        // Commit–reveal ballots are tallied by `reveal_vote`; the Abort brake stays open here.
+        require!(
+            proposal.reveal_seconds == 0 || choice == VoteChoice::Abort,
+            CtoError::CommitRevealVoting
+        );
+        // Polls lock nothing, so there is nothing for the emergency brake to release.
+        require!(
+            !(choice == VoteChoice::Abort && !proposal.kind.uses_proposal_slot()),
+            CtoError::PollAbortNotAllowed
+        );
+
+        let yes_led = proposal.yes_weight > proposal.no_weight;
+        let snapshot_weight = tally_vote(proposal, vote_record, own_shares, choice)?;
+        let extended = extend_if_sniped(proposal, yes_led, clock.unix_timestamp)?;
+        vote_record.voter = donor.wallet;
+        vote_record.payer = donor.wallet;
+
+        // === Abort tracking + penalty reset counters ===
+        // If voter participates normally (Yes/No), they progress toward resetting strike counters.
+        if matches!(choice, VoteChoice::Yes | VoteChoice::No) {
+            // Use proposal variable to avoid borrow conflict
+            record_vote_participation(&mut ctx.accounts.donor, proposal.proposer_wallet);
+        }
+
+        if choice == VoteChoice::Abort {
+            // Eligibility for Abort vote (same as proposer: >= 1 SOL deposited, and stake held long enough)
+            require!(ctx.accounts.donor.total_deposited_lamports >= MIN_PROPOSER_DEPOSIT_LAMPORTS, CtoError::AbortNotEligible);
+            let slots_since = clock.slot.checked_sub(ctx.accounts.donor.last_shares_change_slot).ok_or(CtoError::MathOverflow)?;
+            require!(slots_since >= MIN_ABORTER_DELAY_SLOTS, CtoError::AbortNotEligible);
+            // Use proposal variable to avoid borrow conflict
+            require!(clock.unix_timestamp >= proposal.created_at_ts.checked_add(MIN_ABORT_REVIEW_SECONDS).ok_or(CtoError::MathOverflow)?, CtoError::AbortTooEarly);
+            // Charge escalating abort-vote fee into pool PDA (uncapped).
+            // This penalizes *both* abort voters, because each must sign to cast an Abort vote.
+            let fee = penalty_fee(ctx.accounts.pool.base_penalty_lamports, ctx.accounts.donor.abort_strike_count);
+            if fee > 0 {
+                invoke(
+                    &system_instruction::transfer(&ctx.accounts.voter_wallet.key(), &ctx.accounts.pool.key(), fee),
+                    &[
+                        ctx.accounts.voter_wallet.to_account_info(),
+                        ctx.accounts.pool.to_account_info(),
+                        ctx.accounts.system_program.to_account_info(),
+                    ],
+                )
+                .map_err(|_| CtoError::LamportTransferFailed)?;
+            }
+
+            // Increment abort strike immediately; reset requires 3 non-abort participations (Yes/No).
+            ctx.accounts.donor.abort_strike_count = ctx.accounts.donor.abort_strike_count.saturating_add(1);
+            ctx.accounts.donor.non_abort_participation_count = 0;
+
+
+            // Record up to 2 unique abort voters using proposal variable to avoid borrow conflict.
+            let voter = ctx.accounts.donor.wallet;
+            if proposal.abort_voter_1 == Pubkey::default() {
+                proposal.abort_voter_1 = voter;
+                proposal.abort_count = 1;
+            } else if proposal.abort_voter_1 == voter {
+                // already recorded
+            } else if proposal.abort_voter_2 == Pubkey::default() {
+                proposal.abort_voter_2 = voter;
+                proposal.abort_count = 2;
+            } else if proposal.abort_voter_2 == voter {
+                // already recorded
+            } else {
+                // only 2 abort voters tracked by design
+                return err!(CtoError::AbortVoterSlotsFull);
+            }
+        }
+
+        record_activity(
+            &mut ctx.accounts.activity_log,
+            ActivityKind::Vote,
+            choice as u8,
+            ctx.accounts.voter_wallet.key(),
+            snapshot_weight,
+            clock.slot,
+        );
+
+        ctx.accounts.pool.last_engagement_ts = clock.unix_timestamp;
+
+        // L-05 FIX: Emit vote cast event
+        emit_cpi!(VoteCastEvent {
+            pool: ctx.accounts.pool.key(),
+            proposal: proposal.key(),
+            voter: ctx.accounts.voter_wallet.key(),
+            choice,
+            weight: snapshot_weight,
+            timestamp: clock.unix_timestamp,
+        });
+
+        if extended {
+            emit_cpi!(VotingExtendedEvent {
+                pool: ctx.accounts.pool.key(),
+                proposal: proposal.key(),
+                new_deadline_ts: proposal.deadline_ts,
+                extensions_left: proposal.snipe_extensions_left,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Rent-free vote: the ballot is kept in the voter's existing Donor account instead of a new
+    /// VoteRecord, so casting it costs only the transaction fee.
+    ///
+    /// Stands in for compressed (Light Protocol) vote records, which need an external prover and
+    /// indexer; since a pool has one active proposal at a time, one ballot slot per donor suffices.
+    /// Re-voting is allowed. Abort votes still go through `vote`, which carries the penalty flow.
+    pub fn vote_compact(ctx: Context<VoteCompact>, choice: VoteChoice) -> Result<()> {
+        let clock = Clock::get()?;
+        let proposal = &mut ctx.accounts.proposal;
+        require!(proposal.status == ProposalStatus::Active, CtoError::ProposalNotActive);
+        require!(ctx.accounts.pool.active_proposal == Some(proposal.key()), CtoError::ProposalNotActive);
+        require!(clock.unix_timestamp <= proposal.deadline_ts, CtoError::VotingClosed);
+        require!(proposal.sponsor_count >= proposal.sponsors_required, CtoError::AwaitingSponsors);
+        require!(choice != VoteChoice::Abort, CtoError::CompactAbortNotAllowed);
+        require!(!proposal.merkle_weights, CtoError::MerkleProofRequired);
+        require!(proposal.reveal_seconds == 0, CtoError::CommitRevealVoting);
+        // The voter must not also hold a VoteRecord for this proposal.
+        require!(ctx.accounts.vote_record.data_is_empty(), CtoError::AlreadyVotedWithRecord);
+
+        let donor = &mut ctx.accounts.donor;
+        require!(donor.share_scale == ctx.accounts.pool.share_scale, CtoError::ShareScaleMigrationRequired);
+        let own_shares = snapshot_vote_weight(donor, proposal)?;
+        require!(own_shares > 0, CtoError::NoShares);
+
+        let prior = (donor.ballot_proposal == proposal.key()).then_some((donor.ballot_choice, donor.ballot_weight));
+        let yes_led = proposal.yes_weight > proposal.no_weight;
+        let snapshot_weight = tally_ballot(proposal, prior, own_shares, choice)?;
+        let extended = extend_if_sniped(proposal, yes_led, clock.unix_timestamp)?;
+        donor.ballot_proposal = proposal.key();
+        donor.ballot_choice = choice;
+        donor.ballot_weight = snapshot_weight;
+
+        if matches!(choice, VoteChoice::Yes | VoteChoice::No) {
+            record_vote_participation(donor, proposal.proposer_wallet);
+        }
+
+        record_activity(
+            &mut ctx.accounts.activity_log,
+            ActivityKind::Vote,
+            choice as u8,
+            ctx.accounts.voter_wallet.key(),
+            snapshot_weight,
+            clock.slot,
+        );
+
+        ctx.accounts.pool.last_engagement_ts = clock.unix_timestamp;
+
+        emit_cpi!(VoteCastEvent {
+            pool: ctx.accounts.pool.key(),
+            proposal: proposal.key(),
+            voter: ctx.accounts.voter_wallet.key(),
+            choice,
+            weight: snapshot_weight,
+            timestamp: clock.unix_timestamp,
+        });
+
+        if extended {
+            emit_cpi!(VotingExtendedEvent {
+                pool: ctx.accounts.pool.key(),
+                proposal: proposal.key(),
+                new_deadline_ts: proposal.deadline_ts,
+                extensions_left: proposal.snipe_extensions_left,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Delegate `shares` of the caller's voting weight to `delegate` through their vote escrow.
+    ///
+    /// Delegated shares are excluded from the delegator's own vote and cannot be withdrawn until
+    /// undelegated. Both the delegator's and the escrow's change slot are bumped, so neither can
+    /// vote the moved weight on a proposal snapshotted before the change.
+    pub fn delegate_votes(ctx: Context<DelegateVotes>, shares: u64) -> Result<()> {
+        require!(shares > 0, CtoError::ZeroAmount);
+        require!(
+            ctx.accounts.delegate.key() != ctx.accounts.delegator_wallet.key(),
+            CtoError::InvalidDelegate
+        );
+        let pool = &ctx.accounts.pool;
+        let donor = &mut ctx.accounts.donor;
+        require!(
+            pool.share_scale == SHARE_SCALE && donor.share_scale == pool.share_scale,
+            CtoError::ShareScaleMigrationRequired
+        );
+        let free_shares = donor.shares.saturating_sub(donor.delegated_shares);
+        require!(shares <= free_shares, CtoError::InsufficientShares);
+
+        let slot = Clock::get()?.slot;
+        donor.delegated_shares = donor.delegated_shares.checked_add(shares).ok_or(CtoError::MathOverflow)?;
+        mark_shares_changed(&ctx.accounts.pool, donor, slot);
+
+        let escrow = &mut ctx.accounts.vote_escrow;
+        if escrow.pool == Pubkey::default() {
+            escrow.pool = pool.key();
+            escrow.delegate = ctx.accounts.delegate.key();
+        }
+        escrow.delegated_shares = escrow.delegated_shares.checked_add(shares).ok_or(CtoError::MathOverflow)?;
+        escrow.delegator_count = escrow.delegator_count.checked_add(1).ok_or(CtoError::MathOverflow)?;
+        mark_escrow_changed(pool, escrow, slot);
+
+        let delegation = &mut ctx.accounts.delegation;
+        delegation.pool = pool.key();
+        delegation.delegator = ctx.accounts.delegator_wallet.key();
+        delegation.delegate = ctx.accounts.delegate.key();
+        delegation.shares = shares;
+        delegation.unlock_ts = 0;
+
+        emit_cpi!(DelegationChangedEvent {
+            pool: pool.key(),
+            delegator: delegation.delegator,
+            delegate: delegation.delegate,
+            shares,
+            delegated: true,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Start the undelegation timelock. Weight stays with the delegate until finalized.
+    ///
+    /// The timelock outlasts the longest voting a proposal opened now could see (see
+    /// `undelegate_timelock_seconds`).
+    pub fn request_undelegate(ctx: Context<RequestUndelegate>) -> Result<()> {
+        let timelock = undelegate_timelock_seconds(&ctx.accounts.pool)?;
+        let delegation = &mut ctx.accounts.delegation;
+        require!(delegation.unlock_ts == 0, CtoError::UndelegationPending);
+        delegation.unlock_ts = Clock::get()?
+            .unix_timestamp
+            .checked_add(timelock)
+            .ok_or(CtoError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Return delegated shares to the delegator once the timelock has passed; closes the delegation.
+    pub fn finalize_undelegate(ctx: Context<FinalizeUndelegate>) -> Result<()> {
+        let clock = Clock::get()?;
+        let delegation = &ctx.accounts.delegation;
+        require!(
+            delegation.unlock_ts != 0 && clock.unix_timestamp >= delegation.unlock_ts,
+            CtoError::UndelegationLocked
+        );
+        let shares = delegation.shares;
+
+        let escrow = &mut ctx.accounts.vote_escrow;
+        escrow.delegated_shares = escrow.delegated_shares.checked_sub(shares).ok_or(CtoError::MathOverflow)?;
+        escrow.delegator_count = escrow.delegator_count.saturating_sub(1);
+        mark_escrow_changed(&ctx.accounts.pool, escrow, clock.slot);
+
+        let donor = &mut ctx.accounts.donor;
+        donor.delegated_shares = donor.delegated_shares.checked_sub(shares).ok_or(CtoError::MathOverflow)?;
+        mark_shares_changed(&ctx.accounts.pool, donor, clock.slot);
+
+        emit_cpi!(DelegationChangedEvent {
+            pool: ctx.accounts.pool.key(),
+            delegator: ctx.accounts.delegator_wallet.key(),
+            delegate: delegation.delegate,
+            shares,
+            delegated: false,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Vote on a Merkle-mode proposal, proving `weight` against the posted root.
+    ///
+    /// No Donor account is read: eligibility and weight are whatever the root committed to at the
+    /// snapshot slot. Abort still goes through `vote`, which checks per-donor eligibility.
+    pub fn vote_merkle(ctx: Context<VoteMerkle>, choice: VoteChoice, weight: u64, proof: Vec<[u8; 32]>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        let vote_record = &mut ctx.accounts.vote_record;
+        let clock = Clock::get()?;
+        require!(proposal.status == ProposalStatus::Active, CtoError::ProposalNotActive);
+        require!(clock.unix_timestamp <= proposal.deadline_ts, CtoError::VotingClosed);
+        require!(proposal.sponsor_count >= proposal.sponsors_required, CtoError::AwaitingSponsors);
+        require!(proposal.merkle_weights, CtoError::NotMerkleProposal);
+        require!(proposal.weight_root != [0u8; 32], CtoError::WeightRootNotPosted);
+        require!(choice != VoteChoice::Abort, CtoError::MerkleAbortNotAllowed);
+        require!(weight > 0, CtoError::NoShares);
+        require!(proof.len() <= MAX_MERKLE_PROOF_LEN, CtoError::InvalidMerkleProof);
+
+        let leaf = weight_leaf(&ctx.accounts.voter_wallet.key(), weight);
+        require!(verify_merkle_proof(&proof, proposal.weight_root, leaf), CtoError::InvalidMerkleProof);
+        // a re-vote keeps its first weight, so only first ballots count toward the root's total
+        if !vote_record.initialized {
+            proposal.merkle_weight_proven =
+                proposal.merkle_weight_proven.checked_add(weight).ok_or(CtoError::MathOverflow)?;
+            require!(
+                proposal.merkle_weight_proven <= proposal.weight_root_total,
+                CtoError::MerkleWeightExceedsTotal
+            );
+        }
+
+        let yes_led = proposal.yes_weight > proposal.no_weight;
+        let snapshot_weight = tally_vote(proposal, vote_record, weight, choice)?;
+        let extended = extend_if_sniped(proposal, yes_led, clock.unix_timestamp)?;
+        vote_record.voter = ctx.accounts.voter_wallet.key();
+        vote_record.payer = ctx.accounts.voter_wallet.key();
+
+        record_activity(
+            &mut ctx.accounts.activity_log,
+            ActivityKind::Vote,
+            choice as u8,
+            ctx.accounts.voter_wallet.key(),
+            snapshot_weight,
+            clock.slot,
+        );
+
+        ctx.accounts.pool.last_engagement_ts = clock.unix_timestamp;
+
+        emit_cpi!(VoteCastEvent {
+            pool: ctx.accounts.pool.key(),
+            proposal: proposal.key(),
+            voter: ctx.accounts.voter_wallet.key(),
+            choice,
+            weight: snapshot_weight,
+            timestamp: clock.unix_timestamp,
+        });
+
+        if extended {
+            emit_cpi!(VotingExtendedEvent {
+                pool: ctx.accounts.pool.key(),
+                proposal: proposal.key(),
+                new_deadline_ts: proposal.deadline_ts,
+                extensions_left: proposal.snipe_extensions_left,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Vote with the weight delegated to the caller's escrow.
+    ///
+    /// The escrow votes the delegated shares it held at the proposal snapshot (capped at what it
+    /// holds now), ramped from the change that left them there. Abort is not available to
+    /// delegated weight because abort eligibility and penalties are per donor.
+    pub fn vote_delegated(ctx: Context<VoteDelegated>, choice: VoteChoice) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        let escrow = &ctx.accounts.vote_escrow;
+        let vote_record = &mut ctx.accounts.vote_record;
+        let clock = Clock::get()?;
+        require!(proposal.status == ProposalStatus::Active, CtoError::ProposalNotActive);
+        require!(clock.unix_timestamp <= proposal.deadline_ts, CtoError::VotingClosed);
+        require!(proposal.sponsor_count >= proposal.sponsors_required, CtoError::AwaitingSponsors);
+        require!(choice != VoteChoice::Abort, CtoError::DelegatedAbortNotAllowed);
+        require!(!proposal.merkle_weights, CtoError::MerkleProofRequired);
+        require!(proposal.reveal_seconds == 0, CtoError::CommitRevealVoting);
+        require!(escrow.delegated_shares > 0, CtoError::NoShares);
+        let held = checkpoint_at(
+            &escrow.share_checkpoints,
+            escrow.last_change_slot,
+            escrow.delegated_shares,
+            proposal.snapshot_slot,
+        )?;
+
+        let yes_led = proposal.yes_weight > proposal.no_weight;
+        let delegated_weight = ramped_vote_weight(
+            held.shares,
+            proposal.snapshot_slot.saturating_sub(held.slot),
+            proposal.vote_ramp_slots,
+        )?;
+        require!(delegated_weight > 0, CtoError::NoShares);
+        let snapshot_weight = tally_vote(proposal, vote_record, delegated_weight, choice)?;
+        let extended = extend_if_sniped(proposal, yes_led, clock.unix_timestamp)?;
+        vote_record.voter = escrow.key();
+        vote_record.payer = ctx.accounts.delegate_wallet.key();
+
+        record_activity(
+            &mut ctx.accounts.activity_log,
+            ActivityKind::Vote,
+            choice as u8,
+            ctx.accounts.delegate_wallet.key(),
+            snapshot_weight,
+            clock.slot,
+        );
+
+        ctx.accounts.pool.last_engagement_ts = clock.unix_timestamp;
+
+        emit_cpi!(VoteCastEvent {
+            pool: ctx.accounts.pool.key(),
+            proposal: proposal.key(),
+            voter: ctx.accounts.delegate_wallet.key(),
+            choice,
+            weight: snapshot_weight,
+            timestamp: clock.unix_timestamp,
+        });
+
+        if extended {
+            emit_cpi!(VotingExtendedEvent {
+                pool: ctx.accounts.pool.key(),
+                proposal: proposal.key(),
+                new_deadline_ts: proposal.deadline_ts,
+                extensions_left: proposal.snipe_extensions_left,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Seal a ballot on a commit–reveal proposal while voting is open.
+    ///
+    /// `commitment` is sha256(proposal ‖ voter ‖ choice as u8 ‖ salt) for a secret 32-byte salt;
+    /// committing again replaces it. Nothing is tallied until `reveal_vote`.
+    pub fn commit_vote(ctx: Context<CommitVote>, commitment: [u8; 32]) -> Result<()> {
+        let clock = Clock::get()?;
+        let proposal = &ctx.accounts.proposal;
+        let donor = &ctx.accounts.donor;
+        require!(proposal.status == ProposalStatus::Active, CtoError::ProposalNotActive);
+        require!(proposal.reveal_seconds > 0, CtoError::NotCommitRevealProposal);
+        require!(clock.unix_timestamp <= proposal.deadline_ts, CtoError::VotingClosed);
+        require!(proposal.sponsor_count >= proposal.sponsors_required, CtoError::AwaitingSponsors);
+        require!(donor.share_scale == ctx.accounts.pool.share_scale, CtoError::ShareScaleMigrationRequired);
+        require!(snapshot_vote_weight(donor, proposal)? > 0, CtoError::NoShares);
+
+        let vote_commitment = &mut ctx.accounts.vote_commitment;
+        vote_commitment.proposal = proposal.key();
+        vote_commitment.voter = ctx.accounts.voter_wallet.key();
+        vote_commitment.commitment = commitment;
+        vote_commitment.committed_ts = clock.unix_timestamp;
+
+        ctx.accounts.pool.last_engagement_ts = clock.unix_timestamp;
+
+        emit_cpi!(VoteCommittedEvent {
+            pool: ctx.accounts.pool.key(),
+            proposal: ctx.accounts.proposal.key(),
+            voter: ctx.accounts.voter_wallet.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Open a committed ballot during the reveal window that follows a commit–reveal proposal's
+    /// deadline, tallying it like `vote`, and close the commitment.
+    ///
+    /// Ballots not revealed before `deadline_ts + reveal_seconds` don't count.
+    pub fn reveal_vote(ctx: Context<RevealVote>, choice: VoteChoice, salt: [u8; 32]) -> Result<()> {
+        let clock = Clock::get()?;
+        let proposal = &mut ctx.accounts.proposal;
+        let vote_record = &mut ctx.accounts.vote_record;
+        require!(proposal.status == ProposalStatus::Active, CtoError::ProposalNotActive);
+        require!(proposal.reveal_seconds > 0, CtoError::NotCommitRevealProposal);
+        require!(
+            clock.unix_timestamp > proposal.deadline_ts && clock.unix_timestamp < voting_ends_at(proposal)?,
+            CtoError::RevealWindowNotOpen
+        );
+        // Abort is cast in the open through `vote`.
+        require!(choice != VoteChoice::Abort, CtoError::CommitRevealVoting);
+
+        let voter = ctx.accounts.voter_wallet.key();
+        require!(
+            vote_commitment_hash(&proposal.key(), &voter, choice, &salt) == ctx.accounts.vote_commitment.commitment,
+            CtoError::CommitmentMismatch
+        );
+
+        let donor = &mut ctx.accounts.donor;
+        require!(donor.share_scale == ctx.accounts.pool.share_scale, CtoError::ShareScaleMigrationRequired);
+        let own_shares = snapshot_vote_weight(donor, proposal)?;
+        require!(own_shares > 0, CtoError::NoShares);
+
+        let snapshot_weight = tally_vote(proposal, vote_record, own_shares, choice)?;
+        vote_record.voter = voter;
+        vote_record.payer = voter;
+
+        if matches!(choice, VoteChoice::Yes | VoteChoice::No) {
+            record_vote_participation(donor, proposal.proposer_wallet);
+        }
+
+        record_activity(
+            &mut ctx.accounts.activity_log,
+            ActivityKind::Vote,
+            choice as u8,
+            voter,
+            snapshot_weight,
+            clock.slot,
+        );
+
+        ctx.accounts.pool.last_engagement_ts = clock.unix_timestamp;
+
+        emit_cpi!(VoteCastEvent {
+            pool: ctx.accounts.pool.key(),
+            proposal: proposal.key(),
+            voter,
+            choice,
+            weight: snapshot_weight,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Authority approval that lets a tied proposal pass under `TieBreakRule::PassWithAuthority`.
+    /// Has no effect on proposals that don't end in a tie.
+    pub fn approve_tie(ctx: Context<ApproveTie>) -> Result<()> {
+        require_live_authority(&mut ctx.accounts.pool, ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+        require!(
+            ctx.accounts.pool.tie_break_rule == TieBreakRule::PassWithAuthority,
+            CtoError::TieApprovalNotEnabled
+        );
+        let proposal = &mut ctx.accounts.proposal;
+        require!(proposal.status == ProposalStatus::Active, CtoError::ProposalNotActive);
+        proposal.tie_approved = true;
+        Ok(())
+    }
+
+    /// Execute proposal.
+    ///
+    /// Atomic, community-executable flow:
+    /// - If proposal fails: unlock reserved liquidity and mark Failed.
+    /// - If proposal passes: withdraw SOL from stake-pool, credit the destination's PayoutClaim
+    ///   (pulled by `claim_payout`), charge protocol fee.
+    /// - Fee split:
+    ///   * 50% -> dev wallet
+    ///   * 50% -> queued in `pending_burn_lamports` for `process_buy_and_burn`. With no
+    ///     configured venue, that half is also sent to dev.
+    ///
+    /// Key property: execution never touches a swap venue, so buy & burn can't block the payout.
+    pub fn execute_proposal(mut ctx: Context<ExecuteProposal>, minimum_lamports_out: u64) -> Result<()> {
+        let clock = Clock::get()?;
+        // ============ PHASE 1: Immutable reads and status checks ============
+        // A Queued proposal already passed; it only has to wait out the pool's timelock.
+        let queued = ctx.accounts.proposal.status == ProposalStatus::Queued;
+        require!(
+            queued || ctx.accounts.proposal.status == ProposalStatus::Active,
+            CtoError::ProposalNotActive
+        );
+        require!(ctx.accounts.proposal.kind.uses_proposal_slot(), CtoError::WrongProposalKind);
+        if queued {
+            require!(
+                clock.unix_timestamp >= ctx.accounts.proposal.queued_until_ts,
+                CtoError::TimelockActive
+            );
+        }
+
+        // H-04 FIX: Reentrancy guard - lock before CPI
+        lock_pool!(ctx.accounts.pool);
+
+        let participation_weight = ctx.accounts.proposal.participation_weight;
+        let total_snapshot_shares = ctx.accounts.proposal.total_snapshot_shares;
+        let quorum_denominator = quorum_supply(&ctx.accounts.proposal);
+        let locked_pool_tokens = ctx.accounts.proposal.locked_pool_tokens;
+        let denomination = ctx.accounts.proposal.denomination;
+        let requested_bps = ctx.accounts.proposal.requested_bps;
+        let yes_weight = ctx.accounts.proposal.yes_weight;
+        let no_weight = ctx.accounts.proposal.no_weight;
+        // Under commit–reveal, voting only closes with the reveal window.
+        let voting_ends_ts = voting_ends_at(&ctx.accounts.proposal)?;
+        let sealed = !queued && ctx.accounts.proposal.reveal_seconds > 0;
+
+        // Fixed at creation; may sit below the pool's quorum after repeated quorum failures.
+        let quorum_bps = ctx.accounts.proposal.quorum_bps;
+
+        let pool_key = ctx.accounts.pool.key();
+        let proposal_key = ctx.accounts.proposal.key();
+
+        let quorum_met = queued || quorum_reached(&ctx.accounts.proposal)?;
+
+        let time_over = clock.unix_timestamp >= voting_ends_ts;
+        // Commit–reveal tallies are incomplete until every ballot could be revealed.
+        require!(time_over || (quorum_met && !sealed), CtoError::TooEarlyToExecute);
+
+        // Even if quorum is met early, enforce a minimum review window before any execution.
+        let min_delay_over = clock.unix_timestamp >= ctx.accounts.proposal.created_at_ts.checked_add(MIN_EXECUTE_DELAY_SECONDS).ok_or(CtoError::MathOverflow)?;
+        require!(min_delay_over, CtoError::TooEarlyToExecuteMinDelay);
+
+        // Ties are only final once voting has closed. A quorum of Abstain alone (0 = 0) is not a
+        // tie: it simply has no Yes majority.
+        let tied = !queued && quorum_met && yes_weight > 0 && yes_weight == no_weight;
+        require!(!tied || time_over, CtoError::TooEarlyToExecute);
+
+        let mut majority_met = queued || yes_weight > no_weight;
+        if tied {
+            let rule = ctx.accounts.pool.tie_break_rule;
+            let extend = rule == TieBreakRule::ExtendVoting && !ctx.accounts.proposal.tie_extended;
+            majority_met = rule == TieBreakRule::PassWithAuthority && ctx.accounts.proposal.tie_approved;
+
+            emit_cpi!(ProposalTiedEvent {
+                pool: pool_key,
+                proposal: proposal_key,
+                weight: yes_weight,
+                rule,
+                passed: majority_met,
+                extended: extend,
+                timestamp: clock.unix_timestamp,
+            });
+
+            if extend {
+                let proposal = &mut ctx.accounts.proposal;
+                proposal.tie_extended = true;
+                proposal.deadline_ts = clock
+                    .unix_timestamp
+                    .checked_add(TIE_EXTENSION_SECONDS)
+                    .ok_or(CtoError::MathOverflow)?;
+                unlock_pool!(ctx.accounts.pool);
+                return Ok(());
+            }
+        }
+
+        // Near-miss on quorum with a Yes majority: extend once instead of failing outright.
+        if !quorum_met && majority_met && !ctx.accounts.proposal.quorum_grace_used {
+            let grace_quorum_bps = quorum_bps.saturating_sub(QUORUM_GRACE_BPS);
+            let within_grace = participation_weight
+                .checked_mul(BPS_DENOM)
+                .ok_or(CtoError::MathOverflow)?
+                >= quorum_denominator
+                    .checked_mul(grace_quorum_bps as u64)
+                    .ok_or(CtoError::MathOverflow)?;
+            if within_grace {
+                let proposal = &mut ctx.accounts.proposal;
+                proposal.quorum_grace_used = true;
+                proposal.deadline_ts = clock
+                    .unix_timestamp
+                    .checked_add(QUORUM_GRACE_EXTENSION_SECONDS)
+                    .ok_or(CtoError::MathOverflow)?;
+                unlock_pool!(ctx.accounts.pool);
+
+                emit_cpi!(QuorumGraceExtendedEvent {
+                    pool: pool_key,
+                    proposal: proposal_key,
+                    participation_weight,
+                    total_snapshot_shares,
+                    new_deadline_ts: proposal.deadline_ts,
+                    timestamp: clock.unix_timestamp,
+                });
+                return Ok(());
+            }
+        }
+
+        // ============ FAIL PATH ============
+        if !(quorum_met && majority_met) {
+            let accounts = &mut *ctx.accounts;
+            fail_proposal(
+                &mut accounts.pool,
+                &mut accounts.proposal,
+                &mut accounts.destination_record,
+                quorum_met,
+                &clock,
+            )?;
+            record_activity(
+                &mut ctx.accounts.activity_log,
+                ActivityKind::Execute,
+                ProposalStatus::Failed as u8,
+                ctx.accounts.executor.key(),
+                0,
+                clock.slot,
+            );
+            unlock_pool!(ctx.accounts.pool);
+
+            emit_cpi!(ProposalFailedEvent {
+                pool: pool_key,
+                proposal: proposal_key,
+                unlocked_pool_tokens: locked_pool_tokens,
+                quorum_met,
+                majority_met,
+                timestamp: clock.unix_timestamp,
+            });
+
+            return Ok(());
+        }
+
+        // With a timelock, passing only queues the proposal (see `queue_proposal`).
+        require!(
+            queued || ctx.accounts.pool.timelock_seconds == 0,
+            CtoError::ProposalMustBeQueued
+        );
+
+        // ============ CONFIG CHANGE ============
+        if ctx.accounts.proposal.kind == ProposalKind::ConfigChange {
+            let params = ctx.accounts.proposal.config_change.clone();
+            {
+                let pool = &mut ctx.accounts.pool;
+                apply_config_change(pool, &params)?;
+                pool.active_proposal = None;
+                pool.last_proposal_finalized_ts = clock.unix_timestamp;
+                pool.consecutive_quorum_failures = 0;
+            }
+            {
+                let proposal = &mut ctx.accounts.proposal;
+                proposal.status = ProposalStatus::Executed;
+                proposal.finalized_slot = clock.slot;
+            }
+            record_activity(
+                &mut ctx.accounts.activity_log,
+                ActivityKind::Execute,
+                ProposalStatus::Executed as u8,
+                ctx.accounts.executor.key(),
+                0,
+                clock.slot,
+            );
+            unlock_pool!(ctx.accounts.pool);
+
+            emit_cpi!(ConfigChangeExecutedEvent {
+                pool: pool_key,
+                proposal: proposal_key,
+                quorum_bps: ctx.accounts.pool.quorum_bps,
+                protocol_fee_bps: ctx.accounts.pool.protocol_fee_bps,
+                min_proposer_deposit_lamports: ctx.accounts.pool.min_proposer_deposit_lamports,
+                proposal_delay_slots: ctx.accounts.pool.proposal_delay_slots,
+                fee_sink: ctx.accounts.pool.fee_sink,
+                fee_sink_destination: ctx.accounts.pool.fee_sink_destination,
+                timestamp: clock.unix_timestamp,
+            });
+            return Ok(());
+        }
+
+        // ============ REMOVE LIQUIDITY ============
+        // Only approves the withdrawal (capped at the LP the pool still holds); the CPI is left to
+        // `withdraw_liquidity`, so execution never touches the AMM.
+        if ctx.accounts.proposal.kind == ProposalKind::RemoveLiquidity {
+            let lp_amount = ctx.accounts.proposal.liquidity_amount;
+            {
+                let pool = &mut ctx.accounts.pool;
+                pool.pending_lp_withdrawal = pool
+                    .pending_lp_withdrawal
+                    .checked_add(lp_amount)
+                    .ok_or(CtoError::MathOverflow)?
+                    .min(pool.lp_token_amount);
+                pool.active_proposal = None;
+                pool.last_proposal_finalized_ts = clock.unix_timestamp;
+                pool.consecutive_quorum_failures = 0;
+            }
+            {
+                let proposal = &mut ctx.accounts.proposal;
+                proposal.status = ProposalStatus::Executed;
+                proposal.finalized_slot = clock.slot;
+            }
+            record_activity(
+                &mut ctx.accounts.activity_log,
+                ActivityKind::Execute,
+                ProposalStatus::Executed as u8,
+                ctx.accounts.executor.key(),
+                lp_amount,
+                clock.slot,
+            );
+            unlock_pool!(ctx.accounts.pool);
+
+            emit_cpi!(LiquidityRemovalApprovedEvent {
+                pool: pool_key,
+                proposal: proposal_key,
+                lp_amount,
+                pending_lp_withdrawal: ctx.accounts.pool.pending_lp_withdrawal,
+                timestamp: clock.unix_timestamp,
+            });
+            return Ok(());
+        }
+
+        // ============ SUNSET ============
+        if ctx.accounts.proposal.kind == ProposalKind::Sunset {
+            require_no_liquidity_position(&ctx.accounts.pool)?;
+            {
+                let pool = &mut ctx.accounts.pool;
+                pool.sunset_started_ts = clock.unix_timestamp;
+                pool.active_proposal = None;
+                pool.last_proposal_finalized_ts = clock.unix_timestamp;
+                pool.consecutive_quorum_failures = 0;
+            }
+            {
+                let proposal = &mut ctx.accounts.proposal;
+                proposal.status = ProposalStatus::Executed;
+                proposal.finalized_slot = clock.slot;
+            }
+            record_activity(
+                &mut ctx.accounts.activity_log,
+                ActivityKind::Execute,
+                ProposalStatus::Executed as u8,
+                ctx.accounts.executor.key(),
+                0,
+                clock.slot,
+            );
+            unlock_pool!(ctx.accounts.pool);
+
+            emit_cpi!(PoolSunsetStartedEvent {
+                pool: pool_key,
+                last_engagement_ts: ctx.accounts.pool.last_engagement_ts,
+                total_shares: ctx.accounts.pool.total_shares,
+                timestamp: clock.unix_timestamp,
+            });
+            return Ok(());
+        }
+
+        // ============ PASS PATH ============
+        require!(!ctx.accounts.pool.paused, CtoError::PoolPaused);
+
+        // ============ TOKEN PAYOUT ============
+        // Paid from the pool's ATA for the mint. No LST moves, so no protocol fee is charged.
+        if ctx.accounts.proposal.kind == ProposalKind::TokenPayout {
+            let amount = ctx.accounts.proposal.payout_token_amount;
+            let mint = ctx.accounts.proposal.payout_mint;
+            let pool_token_mint = ctx.accounts.pool.token_mint;
+            let pool_bump = ctx.bumps.pool;
+            let (Some(pool_payout_account), Some(destination_payout_account), Some(payout_spl_donation_mint)) = (
+                ctx.accounts.pool_payout_account.as_mut(),
+                ctx.accounts.destination_payout_account.as_ref(),
+                ctx.accounts.payout_spl_donation_mint.as_ref(),
+            ) else {
+                return err!(CtoError::MissingTokenPayoutAccounts);
+            };
+            // A donate_spl mint may have been registered after the payout was configured.
+            require!(payout_spl_donation_mint.data_is_empty(), CtoError::SplVaultTokensReserved);
+            transfer_spl_from_pool_with_seeds(
+                &ctx.accounts.pool.to_account_info(),
+                pool_payout_account,
+                destination_payout_account,
+                &ctx.accounts.token_program,
+                &[&[b"pool", pool_token_mint.as_ref(), &[pool_bump]]],
+                amount,
+            )?;
+            pool_payout_account.reload()?;
+            let remaining = pool_payout_account.amount;
+
+            {
+                let pool = &mut ctx.accounts.pool;
+                // Paying out the pool's own token draws down its donated treasury.
+                if mint == pool_token_mint {
+                    pool.treasury_token_amount = remaining;
+                }
+                pool.active_proposal = None;
+                pool.last_proposal_finalized_ts = clock.unix_timestamp;
+                pool.consecutive_quorum_failures = 0;
+            }
+            {
+                let proposal = &mut ctx.accounts.proposal;
+                proposal.status = ProposalStatus::Executed;
+                proposal.finalized_slot = clock.slot;
+            }
+            record_activity(
+                &mut ctx.accounts.activity_log,
+                ActivityKind::Execute,
+                ProposalStatus::Executed as u8,
+                ctx.accounts.executor.key(),
+                amount,
+                clock.slot,
+            );
+            unlock_pool!(ctx.accounts.pool);
+
+            emit_cpi!(TokenPayoutExecutedEvent {
+                pool: pool_key,
+                proposal: proposal_key,
+                mint,
+                destination: ctx.accounts.proposal.destination_wallet,
+                amount,
+                remaining,
+                timestamp: clock.unix_timestamp,
+            });
+            return Ok(());
+        }
+        let pool_tokens_to_burn = match denomination {
+            RequestDenomination::TvlBps => {
+                // Resolve the percentage against the current balance, capped by what was locked.
+                ctx.accounts.pool_lst_account.reload()?;
+                pool_tokens_for_bps(observed_pool_tokens(&ctx.accounts.pool, &ctx.accounts.pool_lst_account), requested_bps as u64)?
+                    .min(locked_pool_tokens)
+            }
+            RequestDenomination::Lamports => {
+                // The lock carries a rate buffer; burn only what the request costs at today's rate.
+                let stake_pool_state = read_pool_rate(&ctx.accounts.pool, &ctx.accounts.stake_pool)?;
+                pool_tokens_for_net_lamports_ceil(&stake_pool_state, ctx.accounts.proposal.requested_lamports)?
+                    .min(locked_pool_tokens)
+            }
+            RequestDenomination::PoolTokens => locked_pool_tokens,
+        };
+        require!(pool_tokens_to_burn > 0, CtoError::MathOverflow);
+        let unlocked_pool_tokens = locked_pool_tokens
+            .checked_sub(pool_tokens_to_burn)
+            .ok_or(CtoError::MathOverflow)?;
+
+        // ============ MILESTONE / STREAM ESCROW ============
+        // Nothing is paid yet: the resolved amount stays reserved and `release_milestone` or
+        // `claim_stream` pays it out over time. The rest of the lock and the proposal slot are
+        // freed now.
+        let kind = ctx.accounts.proposal.kind;
+        if matches!(kind, ProposalKind::MilestonePayout | ProposalKind::StreamPayout) {
+            let accounts = &mut *ctx.accounts;
+            let pool = &mut accounts.pool;
+            pool.reserved_pool_tokens = pool
+                .reserved_pool_tokens
+                .checked_sub(unlocked_pool_tokens)
+                .ok_or(CtoError::MathOverflow)?;
+            pool.active_proposal = None;
+            pool.last_proposal_finalized_ts = clock.unix_timestamp;
+            pool.consecutive_quorum_failures = 0;
+            let snapshot_shares = voting_shares(pool)?;
+
+            let proposal = &mut accounts.proposal;
+            proposal.status = ProposalStatus::Escrowed;
+            proposal.finalized_slot = clock.slot;
+            if kind == ProposalKind::MilestonePayout {
+                proposal.milestone.escrowed_pool_tokens = pool_tokens_to_burn;
+                start_milestone_tranche(&mut proposal.milestone, clock.slot, clock.unix_timestamp, snapshot_shares);
+            } else {
+                proposal.stream.total_pool_tokens = pool_tokens_to_burn;
+            }
+            record_activity(
+                &mut accounts.activity_log,
+                ActivityKind::Execute,
+                ProposalStatus::Escrowed as u8,
+                accounts.executor.key(),
+                0,
+                clock.slot,
+            );
+            unlock_pool!(ctx.accounts.pool);
+
+            let proposal = &ctx.accounts.proposal;
+            if kind == ProposalKind::MilestonePayout {
+                emit_cpi!(MilestoneEscrowedEvent {
+                    pool: pool_key,
+                    proposal: proposal_key,
+                    escrowed_pool_tokens: pool_tokens_to_burn,
+                    unlocked_pool_tokens,
+                    tranches: proposal.milestone.tranches,
+                    interval_seconds: proposal.milestone.interval_seconds,
+                    timestamp: clock.unix_timestamp,
+                });
+            } else {
+                emit_cpi!(StreamEscrowedEvent {
+                    pool: pool_key,
+                    proposal: proposal_key,
+                    escrowed_pool_tokens: pool_tokens_to_burn,
+                    unlocked_pool_tokens,
+                    start_ts: proposal.stream.start_ts,
+                    end_ts: proposal.stream.end_ts,
+                    timestamp: clock.unix_timestamp,
+                });
+            }
+            return Ok(());
+        }
+
+        // ============ PHASE 2: CPIs ============
+        let (net_to_destination, protocol_fee) =
+            pay_out_pool_tokens(&mut ctx, pool_tokens_to_burn, minimum_lamports_out, clock.unix_timestamp)?;
+
+        // Reload LST account after all CPIs
+        ctx.accounts.pool_lst_account.reload()?;
+        let final_pool_tokens = observed_pool_tokens(&ctx.accounts.pool, &ctx.accounts.pool_lst_account);
+
+        // ============ PHASE 3: Mutable state updates ============
+        {
+            let pool = &mut ctx.accounts.pool;
+            pool.total_spent_lamports = pool
+                .total_spent_lamports
+                .checked_add(net_to_destination)
+                .ok_or(CtoError::MathOverflow)?;
+            // Release the full lock; any unburned remainder returns to free liquidity.
+            pool.reserved_pool_tokens = pool
+                .reserved_pool_tokens
+                .checked_sub(locked_pool_tokens)
+                .ok_or(CtoError::MathOverflow)?;
+            pool.total_pool_tokens = final_pool_tokens;
+            pool.active_proposal = None;
+            pool.last_proposal_finalized_ts = clock.unix_timestamp;
+            pool.consecutive_quorum_failures = 0;
+        }
+        {
+            let proposal = &mut ctx.accounts.proposal;
+            // A recurring budget has just paid its first period; `disburse_budget` pays the rest.
+            if kind == ProposalKind::RecurringBudget {
+                proposal.status = ProposalStatus::Recurring;
+                proposal.budget.periods_paid = 1;
+                proposal.budget.next_due_ts = clock
+                    .unix_timestamp
+                    .checked_add(proposal.budget.period_seconds)
+                    .ok_or(CtoError::MathOverflow)?;
+            } else {
+                proposal.status = ProposalStatus::Executed;
+            }
+            proposal.finalized_slot = clock.slot;
+        }
+        record_activity(
+            &mut ctx.accounts.activity_log,
+            ActivityKind::Execute,
+            ctx.accounts.proposal.status.clone() as u8,
+            ctx.accounts.executor.key(),
+            net_to_destination,
+            clock.slot,
+        );
+
+        // H-04 FIX: Unlock after successful operation
+        unlock_pool!(ctx.accounts.pool);
+
+        // L-05 FIX: Emit proposal executed event
+        emit_cpi!(ProposalExecutedEvent {
+            pool: pool_key,
+            proposal: proposal_key,
+            kind,
+            destination: ctx.accounts.proposal.destination_wallet,
+            yes_weight,
+            no_weight,
+            pool_tokens_burned: pool_tokens_to_burn,
+            lamports_paid: net_to_destination,
+            protocol_fee,
+            unlocked_pool_tokens,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Recover non-LST assets stuck in the pool.
+    pub fn recover_funds_create(
+        ctx: Context<RecoverFundsCreate>,
+        token_mint: Pubkey,
+        amount: u64,
+        destination_wallet: Pubkey,
+        title: String,
+        description: String,
+    ) -> Result<()> {
+        require!(amount > 0, CtoError::ZeroAmount);
+        validate_proposal_text(&title, &description, RecoveryProposal::TITLE_MAX, RecoveryProposal::DESC_MAX)?;
+        require_recoverable(&ctx.accounts.pool, &ctx.accounts.pool_token_account, token_mint)?;
+
+        let pool_bump = ctx.bumps.pool;
+        let pool_token_mint = ctx.accounts.pool.token_mint;
+
+        if verify_inline_sender(
+            &ctx.accounts.instructions,
+            &ctx.accounts.requester.key(),
+            &ctx.accounts.pool_token_account.key(),
+            token_mint,
+            amount,
+        )? {
+            transfer_spl_from_pool_with_seeds(
+                &ctx.accounts.pool.to_account_info(),
+                &ctx.accounts.pool_token_account,
+                &ctx.accounts.destination_token_account,
+                &ctx.accounts.token_program,
+                &[&[b"pool", pool_token_mint.as_ref(), &[pool_bump]]],
+                amount,
+            )?;
+            emit_cpi!(RecoveryExecutedEvent {
+                pool: ctx.accounts.pool.key(),
+                recovery: ctx.accounts.recovery.key(),
+                token_mint,
+                amount,
+                destination: ctx.accounts.destination_token_account.key(),
+                status: ProposalStatus::Executed,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+            return Ok(());
+        }
+
+        let pool = &mut ctx.accounts.pool;
+        let donor = &ctx.accounts.donor;
+        let clock = Clock::get()?;
+        
+        // H-01 FIX: Recovery proposals should also be affected by the abort cooldown.
+        // The abort cooldown is meant to be a safe time for donors to withdraw their funds if they think they are at risk.
+        require!(
+            clock.unix_timestamp >= pool.proposal_cooldown_until_ts,
+            CtoError::ProposalCooldownActive
+        );
+        
+        require!(pool.active_recovery.is_none(), CtoError::ActiveRecoveryExists);
+        require!(donor.shares > 0, CtoError::NoShares);
+        require!(donor.share_scale == pool.share_scale, CtoError::ShareScaleMigrationRequired);
+
+        let rec = &mut ctx.accounts.recovery;
+        rec.pool = pool.key();
+        rec.token_mint = token_mint;
+        rec.requested_amount = amount;
+        rec.destination_wallet = destination_wallet;
+        rec.title = title;
+        rec.description = description;
+        rec.created_at_ts = clock.unix_timestamp;
+        rec.deadline_ts = clock
+            .unix_timestamp
+            .checked_add(24 * 60 * 60)
+            .ok_or(CtoError::MathOverflow)?;
+        rec.snapshot_slot = clock.slot;
+        rec.total_snapshot_shares = voting_shares(pool)?;
+        rec.yes_weight = 0;
+        rec.no_weight = 0;
+        rec.abstain_weight = 0;
+        rec.participation_weight = 0;
+        rec.status = ProposalStatus::Active;
+        rec.rent_payer = ctx.accounts.requester.key();
+
+        pool.active_recovery = Some(rec.key());
+        pool.recovery_count = pool.recovery_count.checked_add(1).ok_or(CtoError::MathOverflow)?;
+
+        emit_cpi!(RecoveryCreatedEvent {
+            pool: pool.key(),
+            recovery: rec.key(),
+            requester: ctx.accounts.requester.key(),
+            token_mint,
+            amount,
+            destination_wallet,
+            total_snapshot_shares: rec.total_snapshot_shares,
+            deadline_ts: rec.deadline_ts,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// L-06 FIX: Recovery proposals should have abort also, same as regular proposals.
+    /// This provides consistent emergency brake capabilities across all proposal types.
+    pub fn recover_funds_vote(ctx: Context<RecoverFundsVote>, choice: VoteChoice) -> Result<()> {
+        let proposal = &mut ctx.accounts.recovery;
+        let donor = &ctx.accounts.donor;
+        let vote_record = &mut ctx.accounts.vote_record;
+        let clock = Clock::get()?;
+        require!(proposal.status == ProposalStatus::Active, CtoError::ProposalNotActive);
+        require!(clock.unix_timestamp <= proposal.deadline_ts, CtoError::VotingClosed);
+        require!(donor.shares > 0, CtoError::NoShares);
+        require!(donor.share_scale == ctx.accounts.pool.share_scale, CtoError::ShareScaleMigrationRequired);
+        require!(
+            donor.last_shares_change_slot <= proposal.snapshot_slot,
+            CtoError::NotEligibleForThisProposal
+        );
+
+        if vote_record.initialized {
+            let w = vote_record.snapshot_weight;
+            match vote_record.choice {
+                VoteChoice::Yes => proposal.yes_weight = proposal.yes_weight.checked_sub(w).ok_or(CtoError::MathOverflow)?,
+                VoteChoice::No => proposal.no_weight = proposal.no_weight.checked_sub(w).ok_or(CtoError::MathOverflow)?,
+                VoteChoice::Abstain => proposal.abstain_weight = proposal.abstain_weight.checked_sub(w).ok_or(CtoError::MathOverflow)?,
+                VoteChoice::Abort => unreachable!(),
+            }
+        }
+
+        // M-01 FIX: Use u128 for intermediate calculation to prevent overflow
+        // when total_snapshot_shares is very large
+        let snapshot_weight = if vote_record.initialized {
+            vote_record.snapshot_weight
+        } else {
+            let raw = donor.shares;
+            raw.min(voter_weight_cap(ctx.accounts.pool.max_voter_bps, proposal.total_snapshot_shares)?)
+        };
+
+        // L-06 FIX: Handle abort voting for recovery proposals (same as regular proposals)
+        if choice == VoteChoice::Abort {
+            // Eligibility for Abort vote (same as proposer: >= 1 SOL deposited, and stake held long enough)
+            require!(donor.total_deposited_lamports >= MIN_PROPOSER_DEPOSIT_LAMPORTS, CtoError::AbortNotEligible);
+            let slots_since = clock.slot.checked_sub(donor.last_shares_change_slot).ok_or(CtoError::MathOverflow)?;
+            require!(slots_since >= MIN_ABORTER_DELAY_SLOTS, CtoError::AbortNotEligible);
+            require!(clock.unix_timestamp >= proposal.created_at_ts.checked_add(MIN_ABORT_REVIEW_SECONDS).ok_or(CtoError::MathOverflow)?, CtoError::AbortTooEarly);
+
+            // Record up to 2 unique abort voters.
+            let voter = donor.wallet;
+            if proposal.abort_voter_1 == Pubkey::default() {
+                proposal.abort_voter_1 = voter;
+                proposal.abort_count = 1;
+            } else if proposal.abort_voter_1 == voter {
+                // already recorded
+            } else if proposal.abort_voter_2 == Pubkey::default() {
+                proposal.abort_voter_2 = voter;
+                proposal.abort_count = 2;
+            } else if proposal.abort_voter_2 == voter {
+                // already recorded
+            } else {
+                return err!(CtoError::AbortVoterSlotsFull);
+            }
+        }
+
+        match choice {
+            VoteChoice::Yes => proposal.yes_weight = proposal.yes_weight.checked_add(snapshot_weight).ok_or(CtoError::MathOverflow)?,
+            VoteChoice::No => proposal.no_weight = proposal.no_weight.checked_add(snapshot_weight).ok_or(CtoError::MathOverflow)?,
+            VoteChoice::Abstain => proposal.abstain_weight = proposal.abstain_weight.checked_add(snapshot_weight).ok_or(CtoError::MathOverflow)?,
+            VoteChoice::Abort => { /* tracked separately via abort_voter fields */ },
+        }
+
+        proposal.participation_weight = proposal
+            .yes_weight
+            .checked_add(proposal.no_weight)
+            .ok_or(CtoError::MathOverflow)?
+            .checked_add(proposal.abstain_weight)
+            .ok_or(CtoError::MathOverflow)?;
+
+        vote_record.proposal = proposal.key();
+        vote_record.voter = donor.wallet;
+        vote_record.payer = donor.wallet;
+        vote_record.snapshot_weight = snapshot_weight;
+        vote_record.choice = choice;
+        vote_record.initialized = true;
+
+        emit_cpi!(RecoveryVoteCastEvent {
+            pool: ctx.accounts.pool.key(),
+            recovery: proposal.key(),
+            voter: donor.wallet,
+            choice,
+            weight: snapshot_weight,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Queue a passed proposal in a timelocked pool. Permissionless.
+    ///
+    /// Closes voting and starts the pool's `timelock_seconds` countdown, after which
+    /// `execute_proposal` pays it out. Ties under ExtendVoting go through `execute_proposal` first.
+    pub fn queue_proposal(ctx: Context<QueueProposal>) -> Result<()> {
+        let clock = Clock::get()?;
+        let pool = &ctx.accounts.pool;
+        let proposal = &mut ctx.accounts.proposal;
+        require!(proposal.status == ProposalStatus::Active, CtoError::ProposalNotActive);
+        require!(proposal.kind.uses_proposal_slot(), CtoError::WrongProposalKind);
+        require!(pool.timelock_seconds > 0, CtoError::TimelockDisabled);
+        require!(
+            proposal_has_passed(pool, proposal, clock.unix_timestamp)?,
+            CtoError::ProposalNotPassed
+        );
+
+        proposal.status = ProposalStatus::Queued;
+        proposal.queued_until_ts = clock
+            .unix_timestamp
+            .checked_add(pool.timelock_seconds)
+            .ok_or(CtoError::MathOverflow)?;
+
+        emit_cpi!(ProposalQueuedEvent {
+            pool: pool.key(),
+            proposal: proposal.key(),
+            yes_weight: proposal.yes_weight,
+            no_weight: proposal.no_weight,
+            queued_until_ts: proposal.queued_until_ts,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Fail an expired proposal nobody executed, releasing its lock and the proposal slot.
+    ///
+    /// Needs none of the stake-pool or swap accounts. A proposal with no votes or a No majority
+    /// can be failed as soon as its deadline passes; one that might still pass (Yes >= No, which
+    /// covers ties and quorum grace) is left to `execute_proposal` for EXECUTION_WINDOW_SECONDS,
+    /// as is a Queued proposal once its timelock is over.
+    pub fn finalize_expired_proposal(ctx: Context<FinalizeExpiredProposal>) -> Result<()> {
+        let clock = Clock::get()?;
+        let accounts = &mut *ctx.accounts;
+        let proposal = &accounts.proposal;
+
+        require!(
+            matches!(proposal.status, ProposalStatus::Active | ProposalStatus::Queued),
+            CtoError::ProposalNotActive
+        );
+        require!(proposal.kind.uses_proposal_slot(), CtoError::WrongProposalKind);
+        require!(!accounts.pool.locked, CtoError::ReentrancyDetected);
+
+        let may_pass = proposal.participation_weight > 0 && proposal.yes_weight >= proposal.no_weight;
+        let expires_at = if proposal.status == ProposalStatus::Queued {
+            proposal
+                .queued_until_ts
+                .checked_add(EXECUTION_WINDOW_SECONDS)
+                .ok_or(CtoError::MathOverflow)?
+        } else if may_pass {
+            voting_ends_at(proposal)?
+                .checked_add(EXECUTION_WINDOW_SECONDS)
+                .ok_or(CtoError::MathOverflow)?
+        } else {
+            voting_ends_at(proposal)?
+        };
+        require!(clock.unix_timestamp >= expires_at, CtoError::ProposalNotExpired);
+
+        let quorum_met = quorum_reached(proposal)?;
+        let majority_met = proposal.yes_weight > proposal.no_weight;
+        let locked_pool_tokens = proposal.locked_pool_tokens;
+
+        fail_proposal(
+            &mut accounts.pool,
+            &mut accounts.proposal,
+            &mut accounts.destination_record,
+            quorum_met,
+            &clock,
+        )?;
+        record_activity(
+            &mut accounts.activity_log,
+            ActivityKind::Execute,
+            ProposalStatus::Failed as u8,
+            accounts.finalizer.key(),
+            0,
+            clock.slot,
+        );
+
+        emit_cpi!(ProposalFailedEvent {
+            pool: ctx.accounts.pool.key(),
+            proposal: ctx.accounts.proposal.key(),
+            unlocked_pool_tokens: locked_pool_tokens,
+            quorum_met,
+            majority_met,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Turn a fresh payout proposal into a MilestonePayout paid out in `tranches` parts.
+    ///
+    /// Proposer only, and only before any sponsor or ballot, so nobody backs terms that change
+    /// under them. With `interval_seconds > 0` each tranche unlocks that long after the previous
+    /// one (the first after the proposal passes); with 0, each needs `confirm_milestone` weight
+    /// reaching the proposal's quorum.
+    pub fn configure_milestones(ctx: Context<ConfigureMilestones>, tranches: u8, interval_seconds: i64) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        require_payout_terms_open(proposal)?;
+        require!(
+            (2..=MAX_MILESTONE_TRANCHES).contains(&tranches) && interval_seconds >= 0,
+            CtoError::InvalidMilestoneSchedule
+        );
+
+        proposal.kind = ProposalKind::MilestonePayout;
+        proposal.milestone = MilestoneSchedule {
+            tranches,
+            interval_seconds,
+            ..MilestoneSchedule::default()
+        };
+
+        emit_cpi!(MilestonesConfiguredEvent {
+            pool: ctx.accounts.pool.key(),
+            proposal: proposal.key(),
+            tranches,
+            interval_seconds,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Confirm the current tranche of an escrowed confirm-vote milestone payout.
+    ///
+    /// Weighs the donor's own shares; only donors whose shares predate the tranche's snapshot
+    /// count, once per tranche.
+    pub fn confirm_milestone(ctx: Context<ConfirmMilestone>) -> Result<()> {
+        let clock = Clock::get()?;
+        let proposal = &mut ctx.accounts.proposal;
+        let donor = &ctx.accounts.donor;
+        require!(proposal.status == ProposalStatus::Escrowed, CtoError::MilestoneNotEscrowed);
+        require!(proposal.kind == ProposalKind::MilestonePayout, CtoError::WrongProposalKind);
+        require!(proposal.milestone.interval_seconds == 0, CtoError::InvalidMilestoneSchedule);
+        require!(donor.share_scale == ctx.accounts.pool.share_scale, CtoError::ShareScaleMigrationRequired);
+        require!(
+            donor.last_shares_change_slot <= proposal.milestone.snapshot_slot,
+            CtoError::NotEligibleForThisProposal
+        );
+        let weight = donor.shares.saturating_sub(donor.delegated_shares);
+        require!(weight > 0, CtoError::NoShares);
+
+        proposal.milestone.confirm_weight = proposal
+            .milestone
+            .confirm_weight
+            .checked_add(weight)
+            .ok_or(CtoError::MathOverflow)?;
+
+        let confirmation = &mut ctx.accounts.confirmation;
+        confirmation.proposal = proposal.key();
+        confirmation.voter = donor.wallet;
+        confirmation.tranche = proposal.milestone.released;
+        confirmation.weight = weight;
+
+        ctx.accounts.pool.last_engagement_ts = clock.unix_timestamp;
+
+        emit_cpi!(MilestoneConfirmedEvent {
+            pool: ctx.accounts.pool.key(),
+            proposal: proposal.key(),
+            voter: donor.wallet,
+            tranche: proposal.milestone.released,
+            weight,
+            confirm_weight: proposal.milestone.confirm_weight,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Pay out the next tranche of an escrowed milestone payout. Permissionless.
+    ///
+    /// Takes the same accounts as `execute_proposal`. Each tranche is an equal share of what is
+    /// still escrowed (the last takes the remainder) and pays the protocol fee like any payout.
+    pub fn release_milestone(mut ctx: Context<ExecuteProposal>, minimum_lamports_out: u64) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(ctx.accounts.proposal.status == ProposalStatus::Escrowed, CtoError::MilestoneNotEscrowed);
+        require!(
+            ctx.accounts.proposal.kind == ProposalKind::MilestonePayout,
+            CtoError::WrongProposalKind
+        );
+        require!(!ctx.accounts.pool.paused, CtoError::PoolPaused);
+        lock_pool!(ctx.accounts.pool);
+        require!(
+            milestone_tranche_due(&ctx.accounts.proposal, clock.unix_timestamp)?,
+            CtoError::MilestoneNotDue
+        );
+
+        let milestone = &ctx.accounts.proposal.milestone;
+        let tranches_left = milestone
+            .tranches
+            .checked_sub(milestone.released)
+            .filter(|left| *left > 0)
+            .ok_or(CtoError::MathOverflow)?;
+        let tranche_pool_tokens = milestone.escrowed_pool_tokens / tranches_left as u64;
+        require!(tranche_pool_tokens > 0, CtoError::ZeroAmount);
+
+        let (net_to_destination, protocol_fee) =
+            pay_out_pool_tokens(&mut ctx, tranche_pool_tokens, minimum_lamports_out, clock.unix_timestamp)?;
+
+        ctx.accounts.pool_lst_account.reload()?;
+        let final_pool_tokens = observed_pool_tokens(&ctx.accounts.pool, &ctx.accounts.pool_lst_account);
+
+        let accounts = &mut *ctx.accounts;
+        let pool = &mut accounts.pool;
+        pool.total_spent_lamports = pool
+            .total_spent_lamports
+            .checked_add(net_to_destination)
+            .ok_or(CtoError::MathOverflow)?;
+        pool.reserved_pool_tokens = pool
+            .reserved_pool_tokens
+            .checked_sub(tranche_pool_tokens)
+            .ok_or(CtoError::MathOverflow)?;
+        pool.total_pool_tokens = final_pool_tokens;
+        let snapshot_shares = voting_shares(pool)?;
+
+        let proposal = &mut accounts.proposal;
+        let milestone = &mut proposal.milestone;
+        milestone.escrowed_pool_tokens = milestone
+            .escrowed_pool_tokens
+            .checked_sub(tranche_pool_tokens)
+            .ok_or(CtoError::MathOverflow)?;
+        milestone.released = milestone.released.checked_add(1).ok_or(CtoError::MathOverflow)?;
+        start_milestone_tranche(milestone, clock.slot, clock.unix_timestamp, snapshot_shares);
+        let tranche = milestone.released;
+        let remaining_pool_tokens = milestone.escrowed_pool_tokens;
+        if milestone.released == milestone.tranches {
+            proposal.status = ProposalStatus::Executed;
+        }
+        record_activity(
+            &mut accounts.activity_log,
+            ActivityKind::Execute,
+            proposal.status.clone() as u8,
+            accounts.executor.key(),
+            net_to_destination,
+            clock.slot,
+        );
+
+        unlock_pool!(ctx.accounts.pool);
+
+        emit_cpi!(MilestoneReleasedEvent {
+            pool: ctx.accounts.pool.key(),
+            proposal: ctx.accounts.proposal.key(),
+            tranche,
+            lamports_paid: net_to_destination,
+            protocol_fee,
+            remaining_pool_tokens,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Turn a fresh payout proposal into a StreamPayout vesting linearly from `start_ts` to `end_ts`.
+    ///
+    /// Proposer only, and only before any sponsor or ballot. The resolved amount is escrowed when
+    /// the proposal passes; whatever has vested by then is claimable straight away.
+    pub fn configure_stream(ctx: Context<ConfigureStream>, start_ts: i64, end_ts: i64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let proposal = &mut ctx.accounts.proposal;
+        require_payout_terms_open(proposal)?;
+        require!(
+            start_ts >= now
+                && end_ts > start_ts
+                && end_ts.checked_sub(start_ts).ok_or(CtoError::MathOverflow)? <= MAX_STREAM_SECONDS,
+            CtoError::InvalidStreamSchedule
+        );
+
+        proposal.kind = ProposalKind::StreamPayout;
+        proposal.stream = StreamSchedule {
+            start_ts,
+            end_ts,
+            ..StreamSchedule::default()
+        };
+
+        emit_cpi!(StreamConfiguredEvent {
+            pool: ctx.accounts.pool.key(),
+            proposal: proposal.key(),
+            start_ts,
+            end_ts,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Turn a fresh payout proposal into a TokenPayout of `amount` of an SPL token the pool holds
+    /// (e.g. the pool's own token).
+    ///
+    /// Proposer only, and only before any sponsor or ballot. The destination must have an ATA for
+    /// the mint (SPL Token program; not the pool's LST or share mint). Mints accepted by
+    /// `donate_spl` are refused: their vault belongs to its SplPosition holders. The proposal's LST lock is
+    /// released, and since a token amount isn't priced against the pool, it needs the pool's own
+    /// quorum rather than a size tier or the fast track. Nothing is swapped: the pool must hold
+    /// `amount` now and when `execute_proposal` pays it.
+    pub fn configure_token_payout(ctx: Context<ConfigureTokenPayout>, amount: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let pool = &mut ctx.accounts.pool;
+        let proposal = &mut ctx.accounts.proposal;
+        require_payout_terms_open(proposal)?;
+        require!(
+            amount > 0 && amount <= ctx.accounts.pool_payout_account.amount && !proposal.fast_track,
+            CtoError::InvalidTokenPayout
+        );
+
+        pool.reserved_pool_tokens = pool
+            .reserved_pool_tokens
+            .checked_sub(proposal.locked_pool_tokens)
+            .ok_or(CtoError::MathOverflow)?;
+        proposal.locked_pool_tokens = 0;
+        proposal.kind = ProposalKind::TokenPayout;
+        proposal.payout_mint = ctx.accounts.payout_mint.key();
+        proposal.payout_token_amount = amount;
+        proposal.quorum_bps = effective_quorum_bps(pool);
+
+        emit_cpi!(TokenPayoutConfiguredEvent {
+            pool: pool.key(),
+            proposal: proposal.key(),
+            mint: proposal.payout_mint,
+            amount,
+            quorum_bps: proposal.quorum_bps,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Turn a fresh payout proposal into a Buyback of the pool's own token.
+    ///
+    /// Proposer only, and only before any sponsor or ballot. The pool's burn target must be its
+    /// `token_mint` on a configured venue. On execution the net SOL is queued for
+    /// `process_buy_and_burn` instead of paying `destination_wallet`, so the bought tokens are
+    /// burned or held per the pool's `buyback_mode`.
+    pub fn configure_buyback(ctx: Context<ConfigureBuyback>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let pool = &ctx.accounts.pool;
+        let proposal = &mut ctx.accounts.proposal;
+        require_payout_terms_open(proposal)?;
+        require!(buyback_target_configured(pool), CtoError::InvalidBuyback);
+
+        proposal.kind = ProposalKind::Buyback;
+
+        emit_cpi!(BuybackConfiguredEvent {
+            pool: pool.key(),
+            proposal: proposal.key(),
+            token_mint: pool.token_mint,
+            venue: pool.burn_venue,
+            mode: pool.buyback_mode,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Turn a fresh payout proposal into an AddLiquidity pairing its SOL with `token_amount` of
+    /// the pool's token from the treasury (see `donate_pool_token`).
+    ///
+    /// Proposer only, and only before any sponsor or ballot. The pool's Raydium CPMM pool (see
+    /// `configure_raydium_pool`) must pair WSOL with the pool's token. On execution the net SOL
+    /// and the tokens are queued instead of paying `destination_wallet`, and `provide_liquidity`
+    /// deposits them; whatever the pool ratio leaves over stays with the pool.
+    pub fn configure_add_liquidity(ctx: Context<ConfigureAddLiquidity>, token_amount: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let pool = &ctx.accounts.pool;
+        let proposal = &mut ctx.accounts.proposal;
+        require_payout_terms_open(proposal)?;
+        require!(
+            pool.raydium_pool_id != Pubkey::default()
+                && token_amount > 0
+                && token_amount <= pool.treasury_token_amount.saturating_sub(pool.pending_liquidity_tokens),
+            CtoError::InvalidLiquidityProvision
+        );
+        raydium_liquidity_side(pool, &ctx.accounts.raydium_token_0_vault, &ctx.accounts.raydium_token_1_vault)?;
+
+        proposal.kind = ProposalKind::AddLiquidity;
+        proposal.liquidity_amount = token_amount;
+
+        emit_cpi!(LiquidityProvisionConfiguredEvent {
+            pool: pool.key(),
+            proposal: proposal.key(),
+            raydium_pool: pool.raydium_pool_id,
+            token_amount,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Deposit the SOL and treasury tokens queued by executed AddLiquidity proposals into the
+    /// pool's Raydium CPMM pool. Permissionless.
+    ///
+    /// Mints as much LP as the scarcer side buys at the vault ratio into `pool_lp_account`. SOL
+    /// left over returns to the pool PDA (see `sweep_surplus`) and tokens stay in the treasury;
+    /// the queue is cleared either way. `minimum_lp_out` bounds the ratio the deposit accepts.
+    pub fn provide_liquidity(mut ctx: Context<ManageLiquidity>, minimum_lp_out: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let pool_bump = ctx.bumps.pool;
+        let lamports = ctx.accounts.pool.pending_liquidity_lamports;
+        let tokens = ctx
+            .accounts
+            .pool
+            .pending_liquidity_tokens
+            .min(ctx.accounts.pool_token_treasury.amount);
+        require!(lamports > 0 && tokens > 0, CtoError::NoPendingLiquidity);
+        lock_pool!(ctx.accounts.pool);
+
+        let wsol_is_token_0 = validate_raydium_liquidity_accounts(ctx.accounts)?;
+        let (sol_reserve, token_reserve) = if wsol_is_token_0 {
+            (ctx.accounts.raydium_token_0_vault.amount, ctx.accounts.raydium_token_1_vault.amount)
+        } else {
+            (ctx.accounts.raydium_token_1_vault.amount, ctx.accounts.raydium_token_0_vault.amount)
+        };
+        require!(sol_reserve > 0 && token_reserve > 0, CtoError::InvalidRaydiumConfig);
+
+        // Vault balances include uncollected fees, overstating reserves, so this LP amount costs
+        // the CPMM slightly less than offered on both sides.
+        let lp_supply = ctx.accounts.lp_mint.supply;
+        let lp_amount = mul_div_floor(lamports, lp_supply, sol_reserve)?
+            .min(mul_div_floor(tokens, lp_supply, token_reserve)?);
+        require!(lp_amount > 0 && lp_amount >= minimum_lp_out, CtoError::SlippageExceeded);
+
+        let treasury_before = ctx.accounts.pool_token_treasury.amount;
+        let lp_before = ctx.accounts.pool_lp_account.amount;
+
+        transfer_lamports_signed(
+            &ctx.accounts.pool.to_account_info(),
+            &ctx.accounts.pool_liquidity_wsol.to_account_info(),
+            pool_seeds!(&ctx.accounts.pool, pool_bump),
+            lamports,
+        )?;
+        token::sync_native(CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::SyncNative {
+                account: ctx.accounts.pool_liquidity_wsol.to_account_info(),
+            },
+        ))?;
+
+        let (max_0, max_1) = if wsol_is_token_0 { (lamports, tokens) } else { (tokens, lamports) };
+        raydium_cpmm_liquidity_cpi(
+            &ctx,
+            RAYDIUM_CPMM_DEPOSIT_DISCRIMINATOR,
+            lp_amount,
+            max_0,
+            max_1,
+            wsol_is_token_0,
+            pool_bump,
+        )?;
+
+        let unspent_lamports = close_liquidity_wsol(&mut ctx, pool_bump)?;
+        ctx.accounts.pool_token_treasury.reload()?;
+        ctx.accounts.pool_lp_account.reload()?;
+
+        let lamports_deposited = lamports.checked_sub(unspent_lamports).ok_or(CtoError::MathOverflow)?;
+        let tokens_deposited = treasury_before.saturating_sub(ctx.accounts.pool_token_treasury.amount);
+        let lp_received = ctx.accounts.pool_lp_account.amount.saturating_sub(lp_before);
+
+        let pool = &mut ctx.accounts.pool;
+        pool.pending_liquidity_lamports = 0;
+        pool.pending_liquidity_tokens = 0;
+        pool.treasury_token_amount = ctx.accounts.pool_token_treasury.amount;
+        pool.lp_token_amount = ctx.accounts.pool_lp_account.amount;
+
+        unlock_pool!(ctx.accounts.pool);
+
+        emit_cpi!(LiquidityProvidedEvent {
+            pool: ctx.accounts.pool.key(),
+            lamports_deposited,
+            tokens_deposited,
+            lp_received,
+            lp_token_amount: ctx.accounts.pool.lp_token_amount,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Redeem the LP tokens approved by executed RemoveLiquidity proposals. Permissionless.
+    ///
+    /// The pool's token returns to the treasury and the SOL to the pool PDA, where `sweep_surplus`
+    /// stakes it for all donors. `minimum_lamports_out` and `minimum_tokens_out` bound the ratio
+    /// the withdrawal accepts.
+    pub fn withdraw_liquidity(
+        mut ctx: Context<ManageLiquidity>,
+        minimum_lamports_out: u64,
+        minimum_tokens_out: u64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let pool_bump = ctx.bumps.pool;
+        let lp_amount = ctx
+            .accounts
+            .pool
+            .pending_lp_withdrawal
+            .min(ctx.accounts.pool_lp_account.amount);
+        require!(lp_amount > 0, CtoError::NoPendingLiquidity);
+        lock_pool!(ctx.accounts.pool);
+
+        let wsol_is_token_0 = validate_raydium_liquidity_accounts(ctx.accounts)?;
+        let treasury_before = ctx.accounts.pool_token_treasury.amount;
+
+        let (min_0, min_1) = if wsol_is_token_0 {
+            (minimum_lamports_out, minimum_tokens_out)
+        } else {
+            (minimum_tokens_out, minimum_lamports_out)
+        };
+        raydium_cpmm_liquidity_cpi(
+            &ctx,
+            RAYDIUM_CPMM_WITHDRAW_DISCRIMINATOR,
+            lp_amount,
+            min_0,
+            min_1,
+            wsol_is_token_0,
+            pool_bump,
+        )?;
+
+        let lamports_received = close_liquidity_wsol(&mut ctx, pool_bump)?;
+        ctx.accounts.pool_token_treasury.reload()?;
+        ctx.accounts.pool_lp_account.reload()?;
+        let tokens_received = ctx.accounts.pool_token_treasury.amount.saturating_sub(treasury_before);
+
+        let pool = &mut ctx.accounts.pool;
+        pool.pending_lp_withdrawal = 0;
+        pool.treasury_token_amount = ctx.accounts.pool_token_treasury.amount;
+        pool.lp_token_amount = ctx.accounts.pool_lp_account.amount;
+
+        unlock_pool!(ctx.accounts.pool);
+
+        emit_cpi!(LiquidityWithdrawnEvent {
+            pool: ctx.accounts.pool.key(),
+            lp_burned: lp_amount,
+            lamports_received,
+            tokens_received,
+            lp_token_amount: ctx.accounts.pool.lp_token_amount,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Credit the destination with whatever of an escrowed stream payout has vested. Permissionless.
+    ///
+    /// Takes the same accounts as `execute_proposal`; the protocol fee is charged per claim.
+    /// Vesting is in LST, so later claims carry the stake-pool yield earned while escrowed.
+    pub fn claim_stream(mut ctx: Context<ExecuteProposal>, minimum_lamports_out: u64) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(ctx.accounts.proposal.status == ProposalStatus::Escrowed, CtoError::MilestoneNotEscrowed);
+        require!(ctx.accounts.proposal.kind == ProposalKind::StreamPayout, CtoError::WrongProposalKind);
+        require!(!ctx.accounts.pool.paused, CtoError::PoolPaused);
+        lock_pool!(ctx.accounts.pool);
+
+        let claim_pool_tokens = stream_claimable(&ctx.accounts.proposal.stream, clock.unix_timestamp)?;
+        require!(claim_pool_tokens > 0, CtoError::NothingVested);
+
+        let (net_to_destination, protocol_fee) =
+            pay_out_pool_tokens(&mut ctx, claim_pool_tokens, minimum_lamports_out, clock.unix_timestamp)?;
+
+        ctx.accounts.pool_lst_account.reload()?;
+        let final_pool_tokens = observed_pool_tokens(&ctx.accounts.pool, &ctx.accounts.pool_lst_account);
+
+        let accounts = &mut *ctx.accounts;
+        let pool = &mut accounts.pool;
+        pool.total_spent_lamports = pool
+            .total_spent_lamports
+            .checked_add(net_to_destination)
+            .ok_or(CtoError::MathOverflow)?;
+        pool.reserved_pool_tokens = pool
+            .reserved_pool_tokens
+            .checked_sub(claim_pool_tokens)
+            .ok_or(CtoError::MathOverflow)?;
+        pool.total_pool_tokens = final_pool_tokens;
+
+        let proposal = &mut accounts.proposal;
+        proposal.stream.claimed_pool_tokens = proposal
+            .stream
+            .claimed_pool_tokens
+            .checked_add(claim_pool_tokens)
+            .ok_or(CtoError::MathOverflow)?;
+        if proposal.stream.claimed_pool_tokens == proposal.stream.total_pool_tokens {
+            proposal.status = ProposalStatus::Executed;
+        }
+        let claimed_pool_tokens = proposal.stream.claimed_pool_tokens;
+        record_activity(
+            &mut accounts.activity_log,
+            ActivityKind::Execute,
+            proposal.status.clone() as u8,
+            accounts.executor.key(),
+            net_to_destination,
+            clock.slot,
+        );
+
+        unlock_pool!(ctx.accounts.pool);
+
+        emit_cpi!(StreamClaimedEvent {
+            pool: ctx.accounts.pool.key(),
+            proposal: ctx.accounts.proposal.key(),
+            pool_tokens: claim_pool_tokens,
+            lamports_paid: net_to_destination,
+            protocol_fee,
+            claimed_pool_tokens,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Turn a fresh Lamports payout proposal into a RecurringBudget paying `requested_lamports`
+    /// to the destination every `period_seconds`.
+    ///
+    /// Proposer only, and only before any sponsor or ballot. The first period is paid when the
+    /// proposal executes; after that `disburse_budget` pays one period at a time from free
+    /// liquidity, without re-voting, until a ConfigChange with `cancel_budget` ends it.
+    pub fn configure_recurring_budget(ctx: Context<ConfigureRecurringBudget>, period_seconds: i64) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        require_payout_terms_open(proposal)?;
+        // A fast-tracked quorum is meant for one small payout, not an open-ended budget.
+        require!(
+            proposal.denomination == RequestDenomination::Lamports && !proposal.fast_track,
+            CtoError::InvalidBudgetSchedule
+        );
+        require!(
+            (MIN_BUDGET_PERIOD_SECONDS..=MAX_BUDGET_PERIOD_SECONDS).contains(&period_seconds),
+            CtoError::InvalidBudgetSchedule
+        );
+
+        proposal.kind = ProposalKind::RecurringBudget;
+        proposal.budget = BudgetSchedule {
+            period_seconds,
+            ..BudgetSchedule::default()
+        };
+
+        emit_cpi!(RecurringBudgetConfiguredEvent {
+            pool: ctx.accounts.pool.key(),
+            proposal: proposal.key(),
+            lamports_per_period: proposal.requested_lamports,
+            period_seconds,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Pay the next due period of a recurring budget. Permissionless.
+    ///
+    /// Takes the same accounts as `execute_proposal`. Each period burns the LST for
+    /// `requested_lamports` at today's rate out of free liquidity, capped by the pool's
+    /// `max_proposal_bps`, and pays the protocol fee like any payout. Periods nobody cranked
+    /// within a further period are skipped rather than paid in arrears.
+    pub fn disburse_budget(mut ctx: Context<ExecuteProposal>, minimum_lamports_out: u64) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(ctx.accounts.proposal.status == ProposalStatus::Recurring, CtoError::BudgetNotRecurring);
+        require!(
+            ctx.accounts.proposal.kind == ProposalKind::RecurringBudget,
+            CtoError::WrongProposalKind
+        );
+        require!(!ctx.accounts.pool.paused, CtoError::PoolPaused);
+        lock_pool!(ctx.accounts.pool);
+        require!(
+            clock.unix_timestamp >= ctx.accounts.proposal.budget.next_due_ts,
+            CtoError::BudgetPeriodNotDue
+        );
+
+        let stake_pool_state = read_pool_rate(&ctx.accounts.pool, &ctx.accounts.stake_pool)?;
+        let period_pool_tokens =
+            pool_tokens_for_net_lamports_ceil(&stake_pool_state, ctx.accounts.proposal.requested_lamports)?;
+        ctx.accounts.pool_lst_account.reload()?;
+        {
+            let pool = &ctx.accounts.pool;
+            let pool_tokens = observed_pool_tokens(&ctx.accounts.pool, &ctx.accounts.pool_lst_account);
+            let max_period_pool_tokens = pool_tokens_for_bps(pool_tokens, pool.max_proposal_bps as u64)?;
+            require!(period_pool_tokens <= max_period_pool_tokens, CtoError::ProposalTooLarge);
+            let free_pool_tokens = pool_tokens
+                .checked_sub(pool.reserved_pool_tokens)
+                .ok_or(CtoError::MathOverflow)?
+                .saturating_sub(pool.rounding_dust_pool_tokens);
+            require!(period_pool_tokens <= free_pool_tokens, CtoError::InsufficientFreeLiquidity);
+        }
+
+        let (net_to_destination, protocol_fee) =
+            pay_out_pool_tokens(&mut ctx, period_pool_tokens, minimum_lamports_out, clock.unix_timestamp)?;
+
+        ctx.accounts.pool_lst_account.reload()?;
+        let final_pool_tokens = observed_pool_tokens(&ctx.accounts.pool, &ctx.accounts.pool_lst_account);
+
+        let accounts = &mut *ctx.accounts;
+        let pool = &mut accounts.pool;
+        pool.total_spent_lamports = pool
+            .total_spent_lamports
+            .checked_add(net_to_destination)
+            .ok_or(CtoError::MathOverflow)?;
+        pool.total_pool_tokens = final_pool_tokens;
+
+        let budget = &mut accounts.proposal.budget;
+        budget.periods_paid = budget.periods_paid.checked_add(1).ok_or(CtoError::MathOverflow)?;
+        budget.next_due_ts = next_budget_due_ts(budget, clock.unix_timestamp)?;
+        let period = budget.periods_paid;
+        let next_due_ts = budget.next_due_ts;
+        record_activity(
+            &mut accounts.activity_log,
+            ActivityKind::Execute,
+            ProposalStatus::Recurring as u8,
+            accounts.executor.key(),
+            net_to_destination,
+            clock.slot,
+        );
+
+        unlock_pool!(ctx.accounts.pool);
+
+        emit_cpi!(BudgetDisbursedEvent {
+            pool: ctx.accounts.pool.key(),
+            proposal: ctx.accounts.proposal.key(),
+            period,
+            pool_tokens: period_pool_tokens,
+            lamports_paid: net_to_destination,
+            protocol_fee,
+            next_due_ts,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Stop a recurring budget named by an executed ConfigChange's `cancel_budget`. Permissionless.
+    ///
+    /// Periods already paid stay paid; the budget ends Executed.
+    pub fn cancel_recurring_budget(ctx: Context<CancelRecurringBudget>) -> Result<()> {
+        let clock = Clock::get()?;
+        let budget_proposal = &mut ctx.accounts.budget_proposal;
+        let cancel_proposal = &ctx.accounts.cancel_proposal;
+        require!(budget_proposal.status == ProposalStatus::Recurring, CtoError::BudgetNotRecurring);
+        require!(cancel_proposal.kind == ProposalKind::ConfigChange, CtoError::WrongProposalKind);
+        require!(cancel_proposal.status == ProposalStatus::Executed, CtoError::ProposalNotPassed);
+        require!(
+            cancel_proposal.config_change.cancel_budget == Some(budget_proposal.key()),
+            CtoError::BudgetMismatch
+        );
+        // Budget PDAs are predictable; only a change that executed after the budget started counts.
+        require!(
+            cancel_proposal.finalized_slot > budget_proposal.finalized_slot,
+            CtoError::BudgetMismatch
+        );
+
+        budget_proposal.status = ProposalStatus::Executed;
+
+        emit_cpi!(RecurringBudgetCancelledEvent {
+            pool: ctx.accounts.pool.key(),
+            proposal: budget_proposal.key(),
+            cancel_proposal: cancel_proposal.key(),
+            periods_paid: budget_proposal.budget.periods_paid,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Release the rest of a confirm-vote milestone escrow whose current tranche went
+    /// MILESTONE_CONFIRM_WINDOW_SECONDS without reaching quorum. Permissionless.
+    ///
+    /// Tranches already paid stay paid; the proposal ends Failed.
+    pub fn cancel_milestone_payout(ctx: Context<CancelMilestonePayout>) -> Result<()> {
+        let clock = Clock::get()?;
+        let pool = &mut ctx.accounts.pool;
+        let proposal = &mut ctx.accounts.proposal;
+        require!(!pool.locked, CtoError::ReentrancyDetected);
+        require!(proposal.status == ProposalStatus::Escrowed, CtoError::MilestoneNotEscrowed);
+        require!(proposal.kind == ProposalKind::MilestonePayout, CtoError::WrongProposalKind);
+        require!(proposal.milestone.interval_seconds == 0, CtoError::InvalidMilestoneSchedule);
+
+        let lapses_at = proposal
+            .milestone
+            .tranche_started_ts
+            .checked_add(MILESTONE_CONFIRM_WINDOW_SECONDS)
+            .ok_or(CtoError::MathOverflow)?;
+        require!(
+            clock.unix_timestamp >= lapses_at && !milestone_tranche_due(proposal, clock.unix_timestamp)?,
+            CtoError::MilestoneNotLapsed
+        );
+
+        let unlocked_pool_tokens = proposal.milestone.escrowed_pool_tokens;
+        pool.reserved_pool_tokens = pool
+            .reserved_pool_tokens
+            .checked_sub(unlocked_pool_tokens)
+            .ok_or(CtoError::MathOverflow)?;
+        proposal.milestone.escrowed_pool_tokens = 0;
+        proposal.status = ProposalStatus::Failed;
+
+        emit_cpi!(MilestoneCancelledEvent {
+            pool: pool.key(),
+            proposal: proposal.key(),
+            tranches_released: proposal.milestone.released,
+            unlocked_pool_tokens,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Finalize an Abort once 2 eligible wallets have voted Abort.
+    ///
+    /// Effects:
+    /// - Unlocks reserved liquidity for this proposal
+    /// - Marks proposal Aborted
+    /// - Clears active_proposal
+    /// - Sets pool cooldown window
+    /// - Increments proposer strike (proposer pays next time they create a proposal)
+    pub fn abort_proposal(ctx: Context<AbortProposal>) -> Result<()> {
+        let clock = Clock::get()?;
+
+        require!(ctx.accounts.proposal.status == ProposalStatus::Active, CtoError::ProposalNotActive);
+        require!(ctx.accounts.proposal.kind.uses_proposal_slot(), CtoError::WrongProposalKind);
+        require!(ctx.accounts.proposal.abort_count >= 2, CtoError::AbortThresholdNotMet);
+        require!(clock.unix_timestamp >= ctx.accounts.proposal.created_at_ts.checked_add(MIN_ABORT_REVIEW_SECONDS).ok_or(CtoError::MathOverflow)?, CtoError::AbortTooEarly);
+
+        // Aborter must be one of the recorded abort voters
+        let aborter = ctx.accounts.aborter.wallet;
+        require!(aborter == ctx.accounts.proposal.abort_voter_1 || aborter == ctx.accounts.proposal.abort_voter_2, CtoError::AbortNotEligible);
+
+        // Unlock reserved liquidity
+        let locked = ctx.accounts.proposal.locked_pool_tokens;
+        ctx.accounts.pool.reserved_pool_tokens = ctx.accounts.pool.reserved_pool_tokens.checked_sub(locked).ok_or(CtoError::MathOverflow)?;
+        ctx.accounts.pool.active_proposal = None;
+        ctx.accounts.pool.last_proposal_finalized_ts = clock.unix_timestamp;
+
+        // Apply global cooldown
+        ctx.accounts.pool.proposal_cooldown_until_ts = clock.unix_timestamp.checked_add(COOLDOWN_AFTER_ABORT_SECONDS).ok_or(CtoError::MathOverflow)?;
+        // Penalize proposer: aborted proposals raise the next proposal-creation fee (uncapped)
+        ctx.accounts.proposer_donor.propose_strike_count = ctx.accounts.proposer_donor.propose_strike_count.saturating_add(1);
+        ctx.accounts.proposer_donor.non_propose_participation_count = 0;
+
+        // Mark proposal aborted
+        ctx.accounts.proposal.status = ProposalStatus::Aborted;
+        ctx.accounts.proposal.finalized_slot = clock.slot;
+        record_destination_failure(&mut ctx.accounts.destination_record, clock.unix_timestamp)?;
+
+        // L-05 FIX: Emit proposal aborted event
+        emit_cpi!(ProposalAbortedEvent {
+            pool: ctx.accounts.pool.key(),
+            proposal: ctx.accounts.proposal.key(),
+            abort_voters: [ctx.accounts.proposal.abort_voter_1, ctx.accounts.proposal.abort_voter_2],
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Settle a finalized proposal's proposer bond and close its ProposerBond.
+    ///
+    /// Permissionless. The bond goes back to the proposer if participation reached quorum or the
+    /// spam threshold fixed at creation; otherwise (and for aborted proposals) it is forfeited to
+    /// the pool PDA as surplus for `sweep_surplus`. The account's rent always returns to the
+    /// proposer. Must run before `archive_proposal`.
+    pub fn settle_proposer_bond(ctx: Context<SettleProposerBond>) -> Result<()> {
+        let proposal = &ctx.accounts.proposal;
+        require!(proposal.status != ProposalStatus::Active, CtoError::ProposalStillActive);
+
+        let bond_lamports = proposal.bond_lamports;
+        let forfeited = proposal.status == ProposalStatus::Aborted
+            || !(quorum_reached(proposal)? || bond_participation_met(proposal)?);
+        if forfeited && bond_lamports > 0 {
+            let proposal_key = proposal.key();
+            transfer_lamports_signed(
+                &ctx.accounts.proposer_bond.to_account_info(),
+                &ctx.accounts.pool.to_account_info(),
+                &[&[b"bond", proposal_key.as_ref(), &[ctx.accounts.proposer_bond.bump]]],
+                bond_lamports,
+            )?;
+        }
+        ctx.accounts.proposal.bond_lamports = 0;
+
+        emit_cpi!(ProposerBondSettledEvent {
+            pool: ctx.accounts.pool.key(),
+            proposal: ctx.accounts.proposal.key(),
+            proposer: ctx.accounts.proposer_wallet.key(),
+            bond_lamports,
+            forfeited,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Archive a finalized proposal and close its account, returning rent to the proposer.
+    ///
+    /// Permissionless. A compact `ProposalResult` is appended to the pool's archive pages
+    /// (PDA: ['archive', pool, page_index]) first, so history survives the account closure.
+    pub fn archive_proposal(ctx: Context<ArchiveProposal>) -> Result<()> {
+        let proposal = &ctx.accounts.proposal;
+        require!(
+            !matches!(
+                proposal.status,
+                ProposalStatus::Active
+                    | ProposalStatus::Queued
+                    | ProposalStatus::Escrowed
+                    | ProposalStatus::Recurring
+            ),
+            CtoError::ProposalStillActive
+        );
+        require!(proposal.bond_lamports == 0, CtoError::ProposerBondUnsettled);
+
+        let pool = &mut ctx.accounts.pool;
+        let archive = &mut ctx.accounts.archive;
+        let page_index = pool.archived_proposal_count / ProposalArchive::CAPACITY as u64;
+        if archive.results.is_empty() {
+            archive.pool = pool.key();
+            archive.page_index = page_index;
+        }
+
+        archive.results.push(ProposalResult {
+            proposal: proposal.key(),
+            requested_lamports: proposal.requested_lamports,
+            locked_pool_tokens: proposal.locked_pool_tokens,
+            destination_wallet: proposal.destination_wallet,
+            yes_weight: proposal.yes_weight,
+            no_weight: proposal.no_weight,
+            abstain_weight: proposal.abstain_weight,
+            status: proposal.status.clone(),
+            finalized_slot: proposal.finalized_slot,
+        });
+
+        pool.archived_proposal_count = pool
+            .archived_proposal_count
+            .checked_add(1)
+            .ok_or(CtoError::MathOverflow)?;
+
+        emit_cpi!(ProposalArchivedEvent {
+            pool: pool.key(),
+            proposal: proposal.key(),
+            archive: archive.key(),
+            page_index,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Close a VoteRecord once the proposal or recovery it belongs to is resolved, returning
+    /// the rent to whoever funded it.
+    ///
+    /// The payer can close as soon as voting is resolved; anyone else only CLOSE_GRACE_SECONDS
+    /// after the deadline. A record whose proposal was already archived is closable by anyone.
+    pub fn close_vote_record(ctx: Context<CloseVoteRecord>) -> Result<()> {
+        let deadline_ts = resolved_vote_target_deadline(&ctx.accounts.proposal)?;
+        if ctx.accounts.caller.key() != ctx.accounts.vote_record.payer {
+            if let Some(deadline_ts) = deadline_ts {
+                let now = Clock::get()?.unix_timestamp;
+                require!(
+                    now >= deadline_ts.checked_add(CLOSE_GRACE_SECONDS).ok_or(CtoError::MathOverflow)?,
+                    CtoError::CloseGraceActive
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Close a resolved recovery proposal, returning the rent to its requester.
+    ///
+    /// The requester can close as soon as it is resolved; anyone else only CLOSE_GRACE_SECONDS
+    /// after the deadline.
+    pub fn close_recovery(ctx: Context<CloseRecovery>) -> Result<()> {
+        let rec = &ctx.accounts.recovery;
+        require!(rec.status != ProposalStatus::Active, CtoError::ProposalStillActive);
+        if ctx.accounts.caller.key() != rec.rent_payer {
+            let now = Clock::get()?.unix_timestamp;
+            require!(
+                now >= rec.deadline_ts.checked_add(CLOSE_GRACE_SECONDS).ok_or(CtoError::MathOverflow)?,
+                CtoError::CloseGraceActive
+            );
+        }
+        Ok(())
+    }
+
+    pub fn recover_funds_execute(ctx: Context<RecoverFundsExecute>) -> Result<()> {
+        let clock = Clock::get()?;
+        let pool_bump = ctx.bumps.pool;
+        let pool_token_mint = ctx.accounts.pool.token_mint;
+        let pool_quorum_bps = ctx.accounts.pool.quorum_bps;
+        let requested_amount = ctx.accounts.recovery.requested_amount;
+
+        let rec = &mut ctx.accounts.recovery;
+
+        require!(rec.status == ProposalStatus::Active, CtoError::ProposalNotActive);
+
+        let quorum_met = rec
+            .participation_weight
+            .checked_mul(BPS_DENOM)
+            .ok_or(CtoError::MathOverflow)?
+            >= rec
+                .total_snapshot_shares
+                .checked_mul(pool_quorum_bps as u64)
+                .ok_or(CtoError::MathOverflow)?;
+
+        let time_over = clock.unix_timestamp >= rec.deadline_ts;
+        require!(time_over || quorum_met, CtoError::TooEarlyToExecute);
+
+        let majority_met = rec.yes_weight > rec.no_weight;
+        // The mint may have been registered for donate_spl since; its vault isn't the pool's.
+        let recoverable = ctx.accounts.spl_donation_mint.data_is_empty();
+
+        if !(quorum_met && majority_met && recoverable) {
+            rec.status = ProposalStatus::Failed;
+            ctx.accounts.pool.active_recovery = None;
+            emit_cpi!(RecoveryExecutedEvent {
+                pool: ctx.accounts.pool.key(),
+                recovery: rec.key(),
+                token_mint: rec.token_mint,
+                amount: 0,
+                destination: ctx.accounts.destination_token_account.key(),
+                status: ProposalStatus::Failed,
+                timestamp: clock.unix_timestamp,
+            });
+            return Ok(());
+        }
+
+        require_recoverable(&ctx.accounts.pool, &ctx.accounts.pool_token_account, rec.token_mint)?;
+        transfer_spl_from_pool_with_seeds(
+            &ctx.accounts.pool.to_account_info(),
+            &ctx.accounts.pool_token_account,
+            &ctx.accounts.destination_token_account,
+            &ctx.accounts.token_program,
+            &[&[b"pool", pool_token_mint.as_ref(), &[pool_bump]]],
+            requested_amount,
+        )?;
+
+        rec.status = ProposalStatus::Executed;
+        ctx.accounts.pool.active_recovery = None;
+
+        emit_cpi!(RecoveryExecutedEvent {
+            pool: ctx.accounts.pool.key(),
+            recovery: rec.key(),
+            token_mint: rec.token_mint,
+            amount: requested_amount,
+            destination: ctx.accounts.destination_token_account.key(),
+            status: ProposalStatus::Executed,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Read-only: pool totals and their lamport value at the stake pool's current rate.
+    ///
+    /// Returned as return data; simulate it instead of redoing the share/LST math client-side.
+    pub fn get_pool_stats(ctx: Context<GetPoolStats>) -> Result<PoolStats> {
+        let pool = &ctx.accounts.pool;
+        let stake_pool_state = read_pool_rate(pool, &ctx.accounts.stake_pool)?;
+        let total_pool_tokens = observed_pool_tokens(pool, &ctx.accounts.pool_lst_account);
+        let free_pool_tokens = total_pool_tokens
+            .saturating_sub(pool.reserved_pool_tokens)
+            .saturating_sub(pool.rounding_dust_pool_tokens);
+
+        Ok(PoolStats {
+            custody: pool.custody,
+            total_shares: pool.total_shares,
+            voting_shares: voting_shares(pool)?,
+            total_pool_tokens,
+            reserved_pool_tokens: pool.reserved_pool_tokens,
+            free_pool_tokens,
+            total_value_lamports: stake_pool_state
+                .calc_lamports_withdraw_amount(total_pool_tokens)
+                .ok_or(CtoError::MathOverflow)?,
+            free_value_lamports: stake_pool_state
+                .calc_lamports_withdraw_amount(free_pool_tokens)
+                .ok_or(CtoError::MathOverflow)?,
+            total_spent_lamports: pool.total_spent_lamports,
+            donor_count: pool.donor_count,
+            active_proposal: pool.active_proposal,
+            sunset_started_ts: pool.sunset_started_ts,
+        })
+    }
+
+    /// Read-only: a donor's shares, their pro-rata LST and its lamport value at the current rate,
+    /// and what `withdraw_sol` could pay them now (free liquidity, net of the stake pool's SOL
+    /// withdrawal fee). Returned as return data.
+    pub fn get_donor_position(ctx: Context<GetDonorPosition>) -> Result<DonorPosition> {
+        let pool = &ctx.accounts.pool;
+        let donor = &ctx.accounts.donor;
+        require!(donor.share_scale == pool.share_scale, CtoError::ShareScaleMigrationRequired);
+
+        let stake_pool_state = read_pool_rate(pool, &ctx.accounts.stake_pool)?;
+        let total_pool_tokens = observed_pool_tokens(pool, &ctx.accounts.pool_lst_account);
+        let (pool_tokens, withdrawable_pool_tokens) = if pool.total_shares == 0 {
+            (0, 0)
+        } else {
+            let withdrawable = total_pool_tokens.saturating_sub(pool.rounding_dust_pool_tokens);
+            let free = withdrawable.saturating_sub(pool.reserved_pool_tokens);
+            (
+                mul_div_floor(donor.shares, withdrawable, pool.total_shares)?,
+                mul_div_floor(donor.shares, free, pool.total_shares)?,
+            )
+        };
+
+        Ok(DonorPosition {
+            wallet: donor.wallet,
+            shares: donor.shares,
+            delegated_shares: donor.delegated_shares,
+            pool_tokens,
+            value_lamports: stake_pool_state
+                .calc_lamports_withdraw_amount(pool_tokens)
+                .ok_or(CtoError::MathOverflow)?,
+            withdrawable_pool_tokens,
+            withdrawable_lamports: default_min_lamports_out(&stake_pool_state, withdrawable_pool_tokens, 0)?,
+            total_deposited_lamports: donor.total_deposited_lamports,
+        })
+    }
+
+    /// Read-only: a proposal's tally, its quorum and whether `execute_proposal` would take the
+    /// pass path right now. Returned as return data.
+    pub fn get_proposal_tally(ctx: Context<GetProposalTally>) -> Result<ProposalTally> {
+        let pool = &ctx.accounts.pool;
+        let proposal = &ctx.accounts.proposal;
+        let now = Clock::get()?.unix_timestamp;
+
+        Ok(ProposalTally {
+            kind: proposal.kind,
+            status: proposal.status.clone(),
+            yes_weight: proposal.yes_weight,
+            no_weight: proposal.no_weight,
+            abstain_weight: proposal.abstain_weight,
+            participation_weight: proposal.participation_weight,
+            quorum_supply: quorum_supply(proposal),
+            quorum_bps: proposal.quorum_bps,
+            quorum_met: quorum_reached(proposal)?,
+            passing: proposal.status == ProposalStatus::Active && proposal_has_passed(pool, proposal, now)?,
+            deadline_ts: proposal.deadline_ts,
+        })
+    }
+}
+
+// ============= Helper Functions =============
+
+/// Uncapped exponential penalty fee: base * 2^strikes
+fn penalty_fee(base: u64, strikes: u16) -> u64 {
+    if strikes == 0 {
+        return 0;
+    }
+    let shift = strikes as u32;
+    base.checked_shl(shift).unwrap_or(u64::MAX)
+}
+
+/// Writes an entry into the pool's activity ring buffer, overwriting the oldest once full.
+fn record_activity(log: &mut ActivityLog, kind: ActivityKind, detail: u8, actor: Pubkey, amount: u64, slot: u64) {
+    let entry = ActivityEntry { kind, detail, actor, amount, slot };
+    if log.entries.len() < ActivityLog::CAPACITY {
+        log.entries.push(entry);
+    } else {
+        log.entries[log.head as usize] = entry;
+    }
+    log.head = ((log.head as usize + 1) % ActivityLog::CAPACITY) as u16;
+    log.total_recorded = log.total_recorded.saturating_add(1);
+}
+
+/// Records a failed/aborted proposal against its destination and starts an escalating cooldown:
+/// DESTINATION_COOLDOWN_SECONDS * 2^(failures - 1), capped at MAX_DESTINATION_COOLDOWN_SHIFT doublings.
+fn record_destination_failure(record: &mut DestinationRecord, now: i64) -> Result<()> {
+    record.failed_count = record.failed_count.saturating_add(1);
+    let shift = record.failed_count.saturating_sub(1).min(MAX_DESTINATION_COOLDOWN_SHIFT);
+    let cooldown = DESTINATION_COOLDOWN_SECONDS
+        .checked_shl(shift as u32)
+        .ok_or(CtoError::MathOverflow)?;
+    record.cooldown_until_ts = now.checked_add(cooldown).ok_or(CtoError::MathOverflow)?;
+    Ok(())
+}
+
+/// Marks a slot proposal Failed: releases its lock, frees the proposal slot and starts the
+/// destination cooldown.
+fn fail_proposal(
+    pool: &mut Pool,
+    proposal: &mut Proposal,
+    destination_record: &mut DestinationRecord,
+    quorum_met: bool,
+    clock: &Clock,
+) -> Result<()> {
+    pool.reserved_pool_tokens = pool
+        .reserved_pool_tokens
+        .checked_sub(proposal.locked_pool_tokens)
+        .ok_or(CtoError::MathOverflow)?;
+    pool.active_proposal = None;
+    pool.last_proposal_finalized_ts = clock.unix_timestamp;
+    // Only turnout failures count toward stepping quorum down; a quorate No resets it.
+    // A proposal that never collected its sponsors was never voted on and counts as neither.
+    if quorum_met {
+        pool.consecutive_quorum_failures = 0;
+    } else if proposal.sponsor_count >= proposal.sponsors_required {
+        pool.consecutive_quorum_failures = pool.consecutive_quorum_failures.saturating_add(1);
+    }
+
+    proposal.status = ProposalStatus::Failed;
+    proposal.finalized_slot = clock.slot;
+    record_destination_failure(destination_record, clock.unix_timestamp)
+}
+
+/// Deadline of the (resolved) Proposal or RecoveryProposal a VoteRecord belongs to, or None
+/// if that account has already been closed.
+fn resolved_vote_target_deadline(target: &AccountInfo) -> Result<Option<i64>> {
+    if target.data_is_empty() || *target.owner != crate::ID {
+        return Ok(None);
+    }
+    let data = target.try_borrow_data()?;
+    let (status, deadline_ts) = if data.starts_with(Proposal::DISCRIMINATOR) {
+        let proposal = Proposal::try_deserialize(&mut &data[..])?;
+        (proposal.status, proposal.deadline_ts)
+    } else if data.starts_with(RecoveryProposal::DISCRIMINATOR) {
+        let rec = RecoveryProposal::try_deserialize(&mut &data[..])?;
+        (rec.status, rec.deadline_ts)
+    } else {
+        return err!(CtoError::InvalidAccountData);
+    };
+    require!(
+        !matches!(status, ProposalStatus::Active | ProposalStatus::Queued),
+        CtoError::ProposalStillActive
+    );
+    Ok(Some(deadline_ts))
+}
+
+/// `emit_cpi!` for helpers that don't hold the instruction's `Context`: a self-CPI carrying the
+/// event, signed by the program's event authority PDA.
+fn emit_event_cpi<'info, E: anchor_lang::Event>(
+    event_authority: &AccountInfo<'info>,
+    event_authority_bump: u8,
+    event: &E,
+) -> Result<()> {
+    let data: Vec<u8> = anchor_lang::event::EVENT_IX_TAG_LE
+        .iter()
+        .copied()
+        .chain(event.data())
+        .collect();
+    let ix = Instruction::new_with_bytes(
+        crate::ID,
+        &data,
+        vec![AccountMeta::new_readonly(event_authority.key(), true)],
+    );
+    invoke_signed(&ix, std::slice::from_ref(event_authority), &[&[b"__event_authority", &[event_authority_bump]]])
+        .map_err(Into::into)
+}
+
+/// Splits a protocol fee already held in the pool PDA: half to the dev wallet, half to the
+/// configured fee sink, burn auction or buy & burn queue (or the dev wallet without a venue).
+#[allow(clippy::too_many_arguments)]
+fn split_protocol_fee<'info>(
+    pool: &mut Account<'info, Pool>,
+    fee_sink_wallet: Option<&UncheckedAccount<'info>>,
+    dev_fee_wallet: &AccountInfo<'info>,
+    seeds: &[&[&[u8]]],
+    protocol_fee: u64,
+    now: i64,
+    event_authority: &AccountInfo<'info>,
+    event_authority_bump: u8,
+) -> Result<()> {
+    let fee_half = protocol_fee.checked_div(2).ok_or(CtoError::MathOverflow)?;
+    let mut dev_take = protocol_fee.checked_sub(fee_half).ok_or(CtoError::MathOverflow)?;
+
+    // Non-dev half goes to the configured sink; `FeeSink::Burn` keeps the buy & burn path.
+    if fee_half > 0 && pool.fee_sink != FeeSink::Burn {
+        let fee_sink_wallet = fee_sink_wallet.ok_or(CtoError::InvalidFeeSink)?;
+        transfer_lamports_signed(
+            &pool.to_account_info(),
+            &fee_sink_wallet.to_account_info(),
+            seeds,
+            fee_half,
+        )?;
+        emit_event_cpi(event_authority, event_authority_bump, &FeeSinkPaidEvent {
+            pool: pool.key(),
+            sink: pool.fee_sink,
+            destination: fee_sink_wallet.key(),
+            amount_sol: fee_half,
+            timestamp: now,
+        })?;
+    } else if fee_half > 0 && pool.burn_auction_enabled {
+        // Dutch-auction mode: the SOL stays in the pool PDA as the auction lot.
+        fund_burn_auction(pool, fee_half, now)?;
+        emit_event_cpi(event_authority, event_authority_bump, &BurnAuctionFundedEvent {
+            pool: pool.key(),
+            amount_sol: fee_half,
+            lot_lamports: pool.burn_auction_lamports,
+            timestamp: now,
+        })?;
+    } else if fee_half > 0 && burn_venue_configured(pool) {
+        // Held in the pool PDA until `process_buy_and_burn` swaps it.
+        pool.pending_burn_lamports = pool
+            .pending_burn_lamports
+            .checked_add(fee_half)
+            .ok_or(CtoError::MathOverflow)?;
+        emit_event_cpi(event_authority, event_authority_bump, &BurnQueuedEvent {
+            pool: pool.key(),
+            amount_sol: fee_half,
+            pending_burn_lamports: pool.pending_burn_lamports,
+            timestamp: now,
+        })?;
+    } else if fee_half > 0 {
+        // No configured venue -> send to dev (explicitly accepted design)
+        dev_take = dev_take.checked_add(fee_half).ok_or(CtoError::MathOverflow)?;
+    }
+
+    // Pay dev
+    if dev_take > 0 {
+        transfer_lamports_signed(&pool.to_account_info(), dev_fee_wallet, seeds, dev_take)?;
+    }
+
+    Ok(())
+}
+
+/// Burns `pool_tokens` of the pool's LST for SOL, pays the proposal's destination net of the
+/// protocol fee and splits the fee. With a dry reserve the SOL goes into a `StakeWithdrawal`
+/// instead and both payments wait for `claim_deactivated_stake`.
+/// Returns `(net_to_destination, protocol_fee)`.
+fn pay_out_pool_tokens<'info>(
+    ctx: &mut Context<ExecuteProposal<'info>>,
+    pool_tokens: u64,
+    minimum_lamports_out: u64,
+    now: i64,
+) -> Result<(u64, u64)> {
+    require_keys_eq!(
+        ctx.accounts.destination_wallet.key(),
+        ctx.accounts.proposal.destination_wallet,
+        CtoError::DestinationMismatch
+    );
+    let protocol_fee_bps = ctx.accounts.pool.protocol_fee_bps;
+    let pool_token_mint = ctx.accounts.pool.token_mint;
+    let pool_bump = ctx.bumps.pool;
+
+    require_lst_rate_in_band(&ctx.accounts.pool, &ctx.accounts.stake_pool)?;
+    let via_stake = stake_fallback_required(
+        &ctx.accounts.pool,
+        &ctx.accounts.stake_pool,
+        &ctx.accounts.reserve_stake,
+        pool_tokens,
+        ctx.accounts.stake_withdrawal.is_some(),
+    )?;
+    // Buybacks and liquidity deposits are cranked out of the pool PDA, so they can't wait on a
+    // stake withdrawal.
+    let kind = ctx.accounts.proposal.kind;
+    let liquidity_tokens = ctx.accounts.proposal.liquidity_amount;
+    if kind == ProposalKind::Buyback {
+        require!(!via_stake && buyback_target_configured(&ctx.accounts.pool), CtoError::InvalidBuyback);
+    } else if kind == ProposalKind::AddLiquidity {
+        let pool = &ctx.accounts.pool;
+        require!(
+            !via_stake
+                && liquidity_tokens <= pool.treasury_token_amount.saturating_sub(pool.pending_liquidity_tokens),
+            CtoError::InvalidLiquidityProvision
+        );
+    }
+
+    // Withdraw SOL to pool PDA (native pools already hold it)
+    let sol_received = if ctx.accounts.pool.custody == PoolCustody::Native {
+        release_native_lamports(&mut ctx.accounts.pool, pool_tokens)?
+    } else if via_stake {
+        stake_pool_withdraw_stake_exec(ctx, pool_tokens)?
+    } else {
+        let pre_pool_lamports = ctx.accounts.pool.to_account_info().lamports();
+        stake_pool_withdraw_sol_exec(ctx, pool_tokens, minimum_lamports_out)?;
+        let post_pool_lamports = ctx.accounts.pool.to_account_info().lamports();
+        post_pool_lamports
+            .checked_sub(pre_pool_lamports)
+            .ok_or(CtoError::MathOverflow)?
+    };
+    require!(sol_received >= minimum_lamports_out, CtoError::SlippageExceeded);
+
+    // Fee is % of actual received
+    let protocol_fee = sol_received
+        .checked_mul(protocol_fee_bps as u64)
+        .ok_or(CtoError::MathOverflow)?
+        .checked_div(BPS_DENOM)
+        .ok_or(CtoError::MathOverflow)?;
+    let net_to_destination = sol_received
+        .checked_sub(protocol_fee)
+        .ok_or(CtoError::MathOverflow)?;
+
+    if via_stake {
+        // Destination and fee are paid by `claim_deactivated_stake`.
+        let destination = ctx.accounts.destination_wallet.key();
+        let executor = ctx.accounts.executor.key();
+        open_stake_withdrawal(
+            &mut ctx.accounts.pool,
+            &mut ctx.accounts.stake_withdrawal,
+            ctx.accounts.withdrawal_stake.as_ref(),
+            destination,
+            executor,
+            sol_received,
+            protocol_fee,
+            &ctx.accounts.event_authority.to_account_info(),
+            ctx.bumps.event_authority,
+        )?;
+        return Ok((net_to_destination, protocol_fee));
+    }
+
+    if kind == ProposalKind::Buyback {
+        // Held in the pool PDA until `process_buy_and_burn` swaps it into the pool's token.
+        let pool = &mut ctx.accounts.pool;
+        pool.pending_burn_lamports = pool
+            .pending_burn_lamports
+            .checked_add(net_to_destination)
+            .ok_or(CtoError::MathOverflow)?;
+        emit_event_cpi(&ctx.accounts.event_authority.to_account_info(), ctx.bumps.event_authority, &BurnQueuedEvent {
+            pool: pool.key(),
+            amount_sol: net_to_destination,
+            pending_burn_lamports: pool.pending_burn_lamports,
+            timestamp: now,
+        })?;
+    } else if kind == ProposalKind::AddLiquidity {
+        // Held in the pool PDA with the paired treasury tokens until `provide_liquidity`.
+        let pool = &mut ctx.accounts.pool;
+        pool.pending_liquidity_lamports = pool
+            .pending_liquidity_lamports
+            .checked_add(net_to_destination)
+            .ok_or(CtoError::MathOverflow)?;
+        pool.pending_liquidity_tokens = pool
+            .pending_liquidity_tokens
+            .checked_add(liquidity_tokens)
+            .ok_or(CtoError::MathOverflow)?;
+        emit_event_cpi(&ctx.accounts.event_authority.to_account_info(), ctx.bumps.event_authority, &LiquidityQueuedEvent {
+            pool: pool.key(),
+            lamports: net_to_destination,
+            tokens: liquidity_tokens,
+            pending_liquidity_lamports: pool.pending_liquidity_lamports,
+            pending_liquidity_tokens: pool.pending_liquidity_tokens,
+            timestamp: now,
+        })?;
+    } else {
+        // Credit the destination; `claim_payout` pays it out when the destination signs.
+        let accounts = &mut *ctx.accounts;
+        let claim = accounts.payout_claim.as_mut().ok_or(CtoError::MissingPayoutClaim)?;
+        let pool = &mut accounts.pool;
+        claim.pool = pool.key();
+        claim.destination = accounts.destination_wallet.key();
+        claim.lamports = claim.lamports.checked_add(net_to_destination).ok_or(CtoError::MathOverflow)?;
+        claim.expires_ts = now.checked_add(PAYOUT_CLAIM_EXPIRY_SECONDS).ok_or(CtoError::MathOverflow)?;
+        pool.pending_claim_lamports = pool
+            .pending_claim_lamports
+            .checked_add(net_to_destination)
+            .ok_or(CtoError::MathOverflow)?;
+        emit_event_cpi(&accounts.event_authority.to_account_info(), ctx.bumps.event_authority, &PayoutCreditedEvent {
+            pool: pool.key(),
+            destination: claim.destination,
+            lamports: net_to_destination,
+            claimable_lamports: claim.lamports,
+            expires_ts: claim.expires_ts,
+            timestamp: now,
+        })?;
+    }
+
+    split_protocol_fee(
+        &mut ctx.accounts.pool,
+        ctx.accounts.fee_sink_wallet.as_ref(),
+        &ctx.accounts.dev_fee_wallet.to_account_info(),
+        &[&[b"pool", pool_token_mint.as_ref(), &[pool_bump]]],
+        protocol_fee,
+        now,
+        &ctx.accounts.event_authority.to_account_info(),
+        ctx.bumps.event_authority,
+    )?;
+
+    Ok((net_to_destination, protocol_fee))
+}
+
+/// A plain Payout whose terms nobody has backed yet, so it may still become a milestone or
+/// stream payout.
+fn require_payout_terms_open(proposal: &Proposal) -> Result<()> {
+    require!(proposal.kind == ProposalKind::Payout, CtoError::WrongProposalKind);
+    require!(proposal.status == ProposalStatus::Active, CtoError::ProposalNotActive);
+    require!(
+        proposal.participation_weight == 0 && proposal.sponsor_count == 0,
+        CtoError::PayoutTermsLocked
+    );
+    Ok(())
+}
+
+/// Due time of a budget's next period: one period after the last due time, or one period from
+/// `now` if the crank is already a full period late.
+fn next_budget_due_ts(budget: &BudgetSchedule, now: i64) -> Result<i64> {
+    let next = budget
+        .next_due_ts
+        .checked_add(budget.period_seconds)
+        .ok_or(CtoError::MathOverflow)?;
+    if next > now {
+        return Ok(next);
+    }
+    now.checked_add(budget.period_seconds).ok_or(CtoError::MathOverflow.into())
+}
+
+/// Pool tokens of a stream vested by `now` and not yet claimed.
+fn stream_claimable(stream: &StreamSchedule, now: i64) -> Result<u64> {
+    let duration = stream.end_ts.checked_sub(stream.start_ts).ok_or(CtoError::MathOverflow)?;
+    let elapsed = now.saturating_sub(stream.start_ts).clamp(0, duration);
+    let vested = mul_div_floor(stream.total_pool_tokens, elapsed as u64, duration as u64)?;
+    Ok(vested.saturating_sub(stream.claimed_pool_tokens))
+}
+
+/// Opens the confirmation round for a milestone's next tranche.
+fn start_milestone_tranche(milestone: &mut MilestoneSchedule, slot: u64, now: i64, snapshot_shares: u64) {
+    milestone.tranche_started_ts = now;
+    milestone.snapshot_slot = slot;
+    milestone.snapshot_shares = snapshot_shares;
+    milestone.confirm_weight = 0;
+}
+
+/// True once the next tranche may be released: its interval has elapsed in time mode, or its
+/// confirmations reached the proposal's quorum of the tranche snapshot in confirm mode.
+fn milestone_tranche_due(proposal: &Proposal, now: i64) -> Result<bool> {
+    let milestone = &proposal.milestone;
+    if milestone.interval_seconds > 0 {
+        let due_ts = milestone
+            .tranche_started_ts
+            .checked_add(milestone.interval_seconds)
+            .ok_or(CtoError::MathOverflow)?;
+        return Ok(now >= due_ts);
+    }
+    Ok(milestone.confirm_weight > 0
+        && milestone
+            .confirm_weight
+            .checked_mul(BPS_DENOM)
+            .ok_or(CtoError::MathOverflow)?
+            >= milestone
+                .snapshot_shares
+                .checked_mul(proposal.quorum_bps as u64)
+                .ok_or(CtoError::MathOverflow)?)
+}
+
+// ===== Destination classification helpers =====
+
+/// Classifies a payout destination from its on-chain owner and data.
+///
+/// A system-owned address can't be told apart from a system-owned PDA without its seeds, so it is
+/// reported as SystemWallet unless `squads` proves it is the Squads v4 vault at `vault_index`.
+fn classify_destination(
+    destination: &AccountInfo,
+    squads: Option<&AccountInfo>,
+    vault_index: u8,
+) -> Result<DestinationKind> {
+    if let Some(multisig) = squads {
+        require!(*multisig.owner == SQUADS_V4_PROGRAM_ID, CtoError::InvalidSquadsVault);
+        require!(
+            multisig.try_borrow_data()?.get(..8) == Some(&SQUADS_MULTISIG_DISCRIMINATOR[..]),
+            CtoError::InvalidSquadsVault
+        );
+        let (vault, _) = Pubkey::find_program_address(
+            &[b"multisig", multisig.key.as_ref(), b"vault", &[vault_index]],
+            &SQUADS_V4_PROGRAM_ID,
+        );
+        require_keys_eq!(vault, destination.key(), CtoError::InvalidSquadsVault);
+        return Ok(DestinationKind::SquadsVault);
+    }
+
+    let owner = *destination.owner;
+    Ok(if owner == system_program::ID {
+        DestinationKind::SystemWallet
+    } else if destination.executable {
+        DestinationKind::Program
+    } else if (owner == token::ID || owner == anchor_spl::token_2022::ID)
+        && destination.data_len() >= TokenAccount::LEN
+    {
+        DestinationKind::TokenAccount
+    } else {
+        DestinationKind::ProgramOwned
+    })
+}
+
+/// Validates the stake pool configuration against the Jito deployment for the cluster this
+/// program was built for (`devnet` or `mainnet` feature).
+fn validate_stake_pool_config(stake_pool_program: Pubkey, stake_pool: Pubkey, lst_mint: Pubkey) -> Result<()> {
+    require!(
+        stake_pool_program == STAKE_POOL_PROGRAM && stake_pool == STAKE_POOL && lst_mint == JITOSOL_MINT,
+        CtoError::InvalidStakePoolConfig
+    );
+    Ok(())
+}
+
+/// Reads and deserializes the StakePool state from an AccountInfo.
+///
+/// The spl_stake_pool 2.x crate uses borsh 1.x internally.
+/// We use the renamed `borsh_1` crate (borsh 1.5) to avoid conflict with anchor's borsh 0.10.
+fn read_stake_pool(stake_pool_ai: &AccountInfo) -> Result<StakePool> {
+    let data = stake_pool_ai.try_borrow_data().map_err(|_| CtoError::InvalidAccountData)?;
+    borsh_1::BorshDeserialize::try_from_slice(&data[..]).map_err(|_| CtoError::InvalidAccountData.into())
+}
+
+/// Exchange rate between lamports and pool tokens: the stake pool's for staked pools, a
+/// fee-free 1:1 rate for native pools (whose pool tokens are lamports).
+fn read_pool_rate(pool: &Pool, stake_pool_ai: &AccountInfo) -> Result<StakePool> {
+    match pool.custody {
+        PoolCustody::Staked => read_stake_pool(stake_pool_ai),
+        PoolCustody::Native => Ok(StakePool {
+            total_lamports: 1,
+            pool_token_supply: 1,
+            ..StakePool::default()
+        }),
+    }
+}
+
+/// Pool tokens backing the pool: the LST account balance, or for native pools the donor
+/// lamports tracked in `total_pool_tokens`.
+fn observed_pool_tokens(pool: &Pool, pool_lst_account: &TokenAccount) -> u64 {
+    match pool.custody {
+        PoolCustody::Staked => pool_lst_account.amount,
+        PoolCustody::Native => pool.total_pool_tokens,
+    }
+}
+
+/// Donor lamports a native pool holds in its PDA (0 for staked pools).
+fn native_held_lamports(pool: &Pool) -> u64 {
+    match pool.custody {
+        PoolCustody::Staked => 0,
+        PoolCustody::Native => pool.total_pool_tokens,
+    }
+}
+
+/// Native-pool counterpart of a stake-pool SOL deposit: moves `lamports` from `payer` into the
+/// pool PDA and counts them as pool tokens.
+fn deposit_native_lamports<'info>(
+    pool: &mut Account<'info, Pool>,
+    payer: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    lamports: u64,
+) -> Result<()> {
+    invoke(
+        &system_instruction::transfer(payer.key, &pool.key(), lamports),
+        &[payer, pool.to_account_info(), system_program],
+    )
+    .map_err(|_| CtoError::LamportTransferFailed)?;
+    pool.total_pool_tokens = pool
+        .total_pool_tokens
+        .checked_add(lamports)
+        .ok_or(CtoError::MathOverflow)?;
+    Ok(())
+}
+
+/// Native-pool counterpart of a stake-pool SOL withdrawal: stops counting `pool_tokens` lamports
+/// as pool tokens so the caller can pay them out of the PDA. Returns the lamports released.
+fn release_native_lamports(pool: &mut Pool, pool_tokens: u64) -> Result<u64> {
+    pool.total_pool_tokens = pool
+        .total_pool_tokens
+        .checked_sub(pool_tokens)
+        .ok_or(CtoError::MathOverflow)?;
+    Ok(pool_tokens)
+}
+
+/// Compute the minimum pool tokens that should produce at least `lamports_out` when withdrawing.
+///
+/// We compute a ceiling estimate from the current ratio.
+fn pool_tokens_for_lamports_ceil(stake_pool: &StakePool, lamports_out: u64) -> Result<u64> {
+    require!(stake_pool.total_lamports > 0, CtoError::StakePoolEmpty);
+
+    let num = (lamports_out as u128)
+        .checked_mul(stake_pool.pool_token_supply as u128)
+        .ok_or(CtoError::MathOverflow)?;
+    let den = stake_pool.total_lamports as u128;
+
+    let mut q = num.checked_div(den).ok_or(CtoError::MathOverflow)?;
+    if num % den != 0 {
+        q = q.checked_add(1).ok_or(CtoError::MathOverflow)?;
+    }
+    let q = u64::try_from(q).map_err(|_| CtoError::MathOverflow)?;
+    Ok(q.max(1))
+}
+
+/// Pool tokens to burn so a SOL withdrawal nets at least `lamports_out` after the stake pool's
+/// SOL withdrawal fee (which is taken in pool tokens before conversion).
+fn pool_tokens_for_net_lamports_ceil(stake_pool: &StakePool, lamports_out: u64) -> Result<u64> {
+    let net = pool_tokens_for_lamports_ceil(stake_pool, lamports_out)?;
+    let fee = &stake_pool.sol_withdrawal_fee;
+    if fee.numerator == 0 || fee.denominator == 0 {
+        return Ok(net);
+    }
+    require!(fee.numerator < fee.denominator, CtoError::MathOverflow);
+    mul_div_ceil(net, fee.denominator, fee.denominator - fee.numerator)
+}
+
+/// Expected LST for a SOL deposit at the current rate, net of the stake pool's SOL deposit fee,
+/// less `tolerance_bps`.
+fn default_min_pool_tokens_out(stake_pool: &StakePool, lamports_in: u64, tolerance_bps: u16) -> Result<u64> {
+    let minted = stake_pool
+        .calc_pool_tokens_for_deposit(lamports_in)
+        .ok_or(CtoError::MathOverflow)?;
+    let deposit_fee = stake_pool
+        .calc_pool_tokens_sol_deposit_fee(minted)
+        .ok_or(CtoError::MathOverflow)?;
+    let expected = minted.checked_sub(deposit_fee).ok_or(CtoError::MathOverflow)?;
+    mul_div_floor(expected, BPS_DENOM - tolerance_bps as u64, BPS_DENOM)
+}
+
+/// Expected SOL for burning `pool_tokens_in` at the current rate, net of the stake pool's SOL
+/// withdrawal fee, less `tolerance_bps`.
+fn default_min_lamports_out(stake_pool: &StakePool, pool_tokens_in: u64, tolerance_bps: u16) -> Result<u64> {
+    let withdrawal_fee = stake_pool
+        .calc_pool_tokens_sol_withdrawal_fee(pool_tokens_in)
+        .ok_or(CtoError::MathOverflow)?;
+    let expected = stake_pool
+        .calc_lamports_withdraw_amount(pool_tokens_in.checked_sub(withdrawal_fee).ok_or(CtoError::MathOverflow)?)
+        .ok_or(CtoError::MathOverflow)?;
+    mul_div_floor(expected, BPS_DENOM - tolerance_bps as u64, BPS_DENOM)
+}
+
+/// floor(a * b / c)
+fn mul_div_floor(a: u64, b: u64, c: u64) -> Result<u64> {
+    let q = (a as u128)
+        .checked_mul(b as u128)
+        .ok_or(CtoError::MathOverflow)?
+        .checked_div(c as u128)
+        .ok_or(CtoError::MathOverflow)?;
+    u64::try_from(q).map_err(|_| CtoError::MathOverflow.into())
+}
+
+/// floor(sqrt(n)), by Newton's method
+fn isqrt_u128(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+    let mut x = n;
+    let mut y = n / 2 + (n & 1);
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
+}
+
+/// Quadratic vote weight of `shares`: isqrt(shares * QUADRATIC_WEIGHT_SCALE)
+fn quadratic_vote_weight(shares: u64) -> Result<u64> {
+    let w = isqrt_u128((shares as u128) * (QUADRATIC_WEIGHT_SCALE as u128));
+    u64::try_from(w).map_err(|_| CtoError::MathOverflow.into())
+}
+
+/// ceil(a * b / c)
+fn mul_div_ceil(a: u64, b: u64, c: u64) -> Result<u64> {
+    let num = (a as u128).checked_mul(b as u128).ok_or(CtoError::MathOverflow)?;
+    let den = c as u128;
+    let mut q = num.checked_div(den).ok_or(CtoError::MathOverflow)?;
+    if num % den != 0 {
+        q = q.checked_add(1).ok_or(CtoError::MathOverflow)?;
+    }
+    u64::try_from(q).map_err(|_| CtoError::MathOverflow.into())
+}
+
+/// floor(received * (total_shares + virtual_shares) / (prev_total + VIRTUAL_POOL_TOKENS))
+///
+/// Applied on mint only: donors can never be diluted to zero by a direct LST top-up, while
+/// withdrawals keep exact pro-rata math against real totals.
+fn shares_for_deposit(received: u64, total_shares: u64, prev_total: u64, share_scale: u64) -> Result<u64> {
+    let virtual_shares = (VIRTUAL_POOL_TOKENS as u128)
+        .checked_mul(share_scale.max(1) as u128)
+        .ok_or(CtoError::MathOverflow)?;
+    let shares = (total_shares as u128)
+        .checked_add(virtual_shares)
+        .ok_or(CtoError::MathOverflow)?;
+    let assets = (prev_total as u128)
+        .checked_add(VIRTUAL_POOL_TOKENS as u128)
+        .ok_or(CtoError::MathOverflow)?;
+    let r = (received as u128)
+        .checked_mul(shares)
+        .ok_or(CtoError::MathOverflow)?
+        .checked_div(assets)
+        .ok_or(CtoError::MathOverflow)?;
+    u64::try_from(r).map_err(|_| CtoError::MathOverflow.into())
+}
+
+/// floor(total_pool_tokens * bps / BPS_DENOM)
+fn pool_tokens_for_bps(total_pool_tokens: u64, bps: u64) -> Result<u64> {
+    mul_div_floor(total_pool_tokens, bps, BPS_DENOM)
+}
+
+/// Transfers lamports from one account to another using signed invocation.
+/// The `from` account must be a PDA with the provided seeds.
+fn transfer_lamports_signed<'info>(
+    from: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    seeds: &[&[&[u8]]],
+    lamports: u64,
+) -> Result<()> {
+    invoke_signed(
+        &system_instruction::transfer(from.key, to.key, lamports),
+        &[from.clone(), to.clone()],
+        seeds,
+    )
+    .map_err(|_| CtoError::LamportTransferFailed.into())
+}
+
+/// CPI to stake pool program to deposit SOL and receive LST tokens.
+fn stake_pool_deposit_sol(ctx: &Context<DonateSol>, lamports_in: u64, minimum_pool_tokens_out: u64) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+
+    require!(
+        ctx.accounts.stake_pool_program.key() == pool.stake_pool_program
+            && ctx.accounts.stake_pool.key() == pool.stake_pool
+            && ctx.accounts.lst_mint.key() == pool.lst_mint,
+        CtoError::InvalidStakePoolConfig
+    );
+
+    let ix = stake_pool_ix::deposit_sol_with_slippage(
+        &pool.stake_pool_program,
+        &pool.stake_pool,
+        &ctx.accounts.stake_pool_withdraw_authority.key(),
+        &ctx.accounts.reserve_stake.key(),
+        &ctx.accounts.donor_wallet.key(),
+        &ctx.accounts.pool_lst_account.key(),
+        &ctx.accounts.manager_fee_account.key(),
+        &ctx.accounts.referrer_pool_tokens_account.key(),
+        &ctx.accounts.lst_mint.key(),
+        &ctx.accounts.token_program.key(),
+        lamports_in,
+        minimum_pool_tokens_out,
+    );
+
+    invoke(
+        &ix,
+        &[
+            ctx.accounts.stake_pool.to_account_info(),
+            ctx.accounts.stake_pool_withdraw_authority.to_account_info(),
+            ctx.accounts.reserve_stake.to_account_info(),
+            ctx.accounts.donor_wallet.to_account_info(),
+            ctx.accounts.pool_lst_account.to_account_info(),
+            ctx.accounts.manager_fee_account.to_account_info(),
+            ctx.accounts.referrer_pool_tokens_account.to_account_info(),
+            ctx.accounts.lst_mint.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+        ],
+    )
+    .map_err(|_| CtoError::StakePoolCpiFailed.into())
+}
+
+/// CPI to stake pool program to deposit SOL from a third-party payer (donate_sol_for handler).
+fn stake_pool_deposit_sol_for(ctx: &Context<DonateSolFor>, lamports_in: u64, minimum_pool_tokens_out: u64) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+
+    require!(
+        ctx.accounts.stake_pool_program.key() == pool.stake_pool_program
+            && ctx.accounts.stake_pool.key() == pool.stake_pool
+            && ctx.accounts.lst_mint.key() == pool.lst_mint,
+        CtoError::InvalidStakePoolConfig
+    );
+
+    let ix = stake_pool_ix::deposit_sol_with_slippage(
+        &pool.stake_pool_program,
+        &pool.stake_pool,
+        &ctx.accounts.stake_pool_withdraw_authority.key(),
+        &ctx.accounts.reserve_stake.key(),
+        &ctx.accounts.payer.key(),
+        &ctx.accounts.pool_lst_account.key(),
+        &ctx.accounts.manager_fee_account.key(),
+        &ctx.accounts.referrer_pool_tokens_account.key(),
+        &ctx.accounts.lst_mint.key(),
+        &ctx.accounts.token_program.key(),
+        lamports_in,
+        minimum_pool_tokens_out,
+    );
+
+    invoke(
+        &ix,
+        &[
+            ctx.accounts.stake_pool.to_account_info(),
+            ctx.accounts.stake_pool_withdraw_authority.to_account_info(),
+            ctx.accounts.reserve_stake.to_account_info(),
+            ctx.accounts.payer.to_account_info(),
+            ctx.accounts.pool_lst_account.to_account_info(),
+            ctx.accounts.manager_fee_account.to_account_info(),
+            ctx.accounts.referrer_pool_tokens_account.to_account_info(),
+            ctx.accounts.lst_mint.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+        ],
+    )
+    .map_err(|_| CtoError::StakePoolCpiFailed.into())
+}
+
+/// CPI to stake pool program to deposit SOL held by the pool PDA itself (sweep_surplus handler).
+fn stake_pool_deposit_sol_from_pool(ctx: &Context<SweepSurplus>, lamports_in: u64, minimum_pool_tokens_out: u64) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+
+    require!(
+        ctx.accounts.stake_pool_program.key() == pool.stake_pool_program
+            && ctx.accounts.stake_pool.key() == pool.stake_pool
+            && ctx.accounts.lst_mint.key() == pool.lst_mint,
+        CtoError::InvalidStakePoolConfig
+    );
+
+    let ix = stake_pool_ix::deposit_sol_with_slippage(
+        &pool.stake_pool_program,
+        &pool.stake_pool,
+        &ctx.accounts.stake_pool_withdraw_authority.key(),
+        &ctx.accounts.reserve_stake.key(),
+        &pool.key(),
+        &ctx.accounts.pool_lst_account.key(),
+        &ctx.accounts.manager_fee_account.key(),
+        &ctx.accounts.referrer_pool_tokens_account.key(),
+        &ctx.accounts.lst_mint.key(),
+        &ctx.accounts.token_program.key(),
+        lamports_in,
+        minimum_pool_tokens_out,
+    );
+
+    invoke_signed(
+        &ix,
+        &[
+            ctx.accounts.stake_pool.to_account_info(),
+            ctx.accounts.stake_pool_withdraw_authority.to_account_info(),
+            ctx.accounts.reserve_stake.to_account_info(),
+            ctx.accounts.pool.to_account_info(),
+            ctx.accounts.pool_lst_account.to_account_info(),
+            ctx.accounts.manager_fee_account.to_account_info(),
+            ctx.accounts.referrer_pool_tokens_account.to_account_info(),
+            ctx.accounts.lst_mint.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+        ],
+        pool_seeds!(pool, ctx.bumps.pool),
+    )
+    .map_err(|_| CtoError::StakePoolCpiFailed.into())
+}
+
+// ===== Migration helpers =====
+
+/// Converts raw account bytes (discriminator included) from `version` to `version + 1`.
+/// `pool` is the Proposal's pool, needed only by `proposal_v0_to_v1`.
+fn upgrade_layout(kind: MigratableAccount, version: u8, data: Vec<u8>, pool: Option<&Account<Pool>>) -> Result<Vec<u8>> {
+    // Add `(MigratableAccount::Pool, 1) => pool_v1_to_v2(data)`-style arms as layouts change.
+    match (kind, version) {
+        (MigratableAccount::Pool, 0) => pool_v0_to_v1(data),
+        (MigratableAccount::Donor, 0) => donor_v0_to_v1(data),
+        (MigratableAccount::Proposal, 0) => proposal_v0_to_v1(data, pool.ok_or(CtoError::MissingMigrationPool)?),
+        _ => err!(CtoError::UnknownAccountVersion),
+    }
+}
+
+/// An all-zero `T`: every field of the current layouts decodes from zero bytes (0, false,
+/// None, empty, first variant), which is what fields added since version 0 start from.
+fn zeroed_layout<T: AnchorDeserialize>(size: usize) -> Result<T> {
+    T::deserialize(&mut &vec![0u8; size][..]).map_err(|_| error!(CtoError::InvalidAccountData))
+}
+
+/// Decodes a version-0 body (after the discriminator; there is no version byte).
+fn decode_v0<T: AnchorDeserialize>(data: &[u8]) -> Result<T> {
+    T::deserialize(&mut &data[8..]).map_err(|_| error!(CtoError::InvalidAccountData))
+}
+
+/// Serializes `account` with its discriminator, zero-padded to the full `8 + size` allocation.
+fn encode_layout<T: AccountSerialize>(account: &T, size: usize) -> Result<Vec<u8>> {
+    let mut data = Vec::with_capacity(8 + size);
+    account.try_serialize(&mut data)?;
+    require!(data.len() <= 8 + size, CtoError::InvalidAccountData);
+    data.resize(8 + size, 0);
+    Ok(data)
+}
+
+/// Launch Pool layout to version 1. Settings introduced since take the defaults `create_pool`
+/// uses (caps off where launch pools had none); `share_scale` stays 0 so `migrate_share_scale`
+/// rescales the pool and its donors.
+fn pool_v0_to_v1(data: Vec<u8>) -> Result<Vec<u8>> {
+    let v0: PoolV0 = decode_v0(&data)?;
+    let clock = Clock::get()?;
+    let mut pool: Pool = zeroed_layout(Pool::SIZE)?;
+
+    pool.token_mint = v0.token_mint;
+    pool.authority = v0.authority;
+    pool.creator = v0.creator;
+    pool.total_shares = v0.total_shares;
+    pool.total_pool_tokens = v0.total_pool_tokens;
+    pool.reserved_pool_tokens = v0.reserved_pool_tokens;
+    pool.total_spent_lamports = v0.total_spent_lamports;
+    pool.protocol_fee_bps = v0.protocol_fee_bps;
+    pool.quorum_bps = v0.quorum_bps;
+    pool.min_proposer_deposit_lamports = v0.min_proposer_deposit_lamports;
+    pool.dev_fee_wallet = v0.dev_fee_wallet;
+    pool.burn_token_mint = v0.burn_token_mint;
+    pool.active_proposal = v0.active_proposal;
+    pool.proposal_count = v0.proposal_count;
+    pool.proposal_cooldown_until_ts = v0.proposal_cooldown_until_ts;
+    pool.base_penalty_lamports = v0.base_penalty_lamports;
+    pool.active_recovery = v0.active_recovery;
+    pool.recovery_count = v0.recovery_count;
+    pool.stake_pool_program = v0.stake_pool_program;
+    pool.stake_pool = v0.stake_pool;
+    pool.lst_mint = v0.lst_mint;
+    pool.burn_venue = if v0.pumpswap_enabled {
+        BurnVenue::PumpSwap
+    } else if v0.raydium_enabled {
+        BurnVenue::RaydiumCpmm
+    } else {
+        BurnVenue::None
+    };
+    pool.pumpswap_pool_id = v0.pumpswap_pool_id;
+    pool.pumpswap_base_vault = v0.pumpswap_base_vault;
+    pool.pumpswap_quote_vault = v0.pumpswap_quote_vault;
+    pool.pumpswap_global_config = v0.pumpswap_global_config;
+    pool.pumpswap_fee_recipient = v0.pumpswap_fee_recipient;
+    pool.pumpswap_version = v0.pumpswap_version;
+    pool.raydium_pool_id = v0.raydium_pool_id;
+    pool.locked = v0.locked;
+
+    pool.custody = PoolCustody::Staked;
+    pool.max_voter_bps = MAX_VOTER_CAP_BPS;
+    pool.proposal_delay_slots = MIN_PROPOSAL_DELAY_SLOTS;
+    pool.max_proposal_bps = MAX_PROPOSAL_BPS;
+    pool.min_proposal_lamports = MIN_PROPOSAL_LAMPORTS;
+    pool.proposal_gap_seconds = DEFAULT_PROPOSAL_GAP_SECONDS;
+    pool.last_heartbeat_epoch = clock.epoch;
+    pool.last_engagement_ts = clock.unix_timestamp;
+    pool.min_voting_seconds = DEFAULT_MIN_VOTING_SECONDS;
+    pool.max_voting_seconds = DEFAULT_MAX_VOTING_SECONDS;
+    pool.donor_slippage_bps = DEFAULT_DONOR_SLIPPAGE_BPS;
+    pool.token_donation_policy = TokenDonationPolicy::NoShares;
+    pool.buyback_mode = BuybackMode::Burn;
+    pool.fee_sink = FeeSink::Burn;
+    pool.tie_break_rule = TieBreakRule::Fail;
+    pool.vote_weighting = VoteWeighting::Linear;
+
+    encode_layout(&pool, Pool::SIZE)
+}
+
+/// Launch Donor layout to version 1. `share_scale` stays 0 until `migrate_share_scale`.
+fn donor_v0_to_v1(data: Vec<u8>) -> Result<Vec<u8>> {
+    let v0: DonorV0 = decode_v0(&data)?;
+    let mut donor: Donor = zeroed_layout(Donor::SIZE)?;
+
+    donor.pool = v0.pool;
+    donor.wallet = v0.wallet;
+    donor.shares = v0.shares;
+    donor.total_deposited_lamports = v0.total_deposited_lamports;
+    donor.last_shares_change_slot = v0.last_shares_change_slot;
+    donor.abort_strike_count = v0.abort_strike_count;
+    donor.propose_strike_count = v0.propose_strike_count;
+    donor.non_abort_participation_count = v0.non_abort_participation_count;
+    donor.non_propose_participation_count = v0.non_propose_participation_count;
+
+    encode_layout(&donor, Donor::SIZE)
+}
+
+/// Launch Proposal layout to version 1. Launch proposals were uncapped Lamports payouts judged
+/// against the pool's quorum at finalization, so they keep the pool's current quorum and no
+/// per-voter cap.
+fn proposal_v0_to_v1(data: Vec<u8>, pool: &Account<Pool>) -> Result<Vec<u8>> {
+    let v0: ProposalV0 = decode_v0(&data)?;
+    require_keys_eq!(v0.pool, pool.key(), CtoError::MissingMigrationPool);
+    let mut proposal: Proposal = zeroed_layout(Proposal::SIZE)?;
+
+    proposal.pool = v0.pool;
+    proposal.kind = v0.kind;
+    proposal.proposer_wallet = v0.proposer_wallet;
+    proposal.requested_lamports = v0.requested_lamports;
+    proposal.destination_wallet = v0.destination_wallet;
+    proposal.title = v0.title;
+    proposal.description = v0.description;
+    proposal.created_at_ts = v0.created_at_ts;
+    proposal.deadline_ts = v0.deadline_ts;
+    proposal.snapshot_slot = v0.snapshot_slot;
+    proposal.total_snapshot_shares = v0.total_snapshot_shares;
+    proposal.locked_pool_tokens = v0.locked_pool_tokens;
+    proposal.yes_weight = v0.yes_weight;
+    proposal.no_weight = v0.no_weight;
+    proposal.abstain_weight = v0.abstain_weight;
+    proposal.participation_weight = v0.participation_weight;
+    proposal.abort_voter_1 = v0.abort_voter_1;
+    proposal.abort_voter_2 = v0.abort_voter_2;
+    proposal.abort_count = v0.abort_count;
+    proposal.status = v0.status;
+
+    proposal.denomination = RequestDenomination::Lamports;
+    proposal.quorum_bps = pool.quorum_bps;
+    proposal.max_voter_bps = MAX_VOTER_CAP_BPS;
+    proposal.voting_period_seconds = v0.deadline_ts.saturating_sub(v0.created_at_ts);
+    proposal.vote_weighting = VoteWeighting::Linear;
+
+    encode_layout(&proposal, Proposal::SIZE)
+}
+
+/// Pool as deployed at launch, before layout versioning (no version byte).
+#[derive(AnchorDeserialize)]
+struct PoolV0 {
+    token_mint: Pubkey,
+    authority: Pubkey,
+    creator: Pubkey,
+    total_shares: u64,
+    total_pool_tokens: u64,
+    reserved_pool_tokens: u64,
+    total_spent_lamports: u64,
+    protocol_fee_bps: u16,
+    quorum_bps: u16,
+    min_proposer_deposit_lamports: u64,
+    dev_fee_wallet: Pubkey,
+    burn_token_mint: Pubkey,
+    active_proposal: Option<Pubkey>,
+    proposal_count: u64,
+    proposal_cooldown_until_ts: i64,
+    base_penalty_lamports: u64,
+    active_recovery: Option<Pubkey>,
+    recovery_count: u64,
+    stake_pool_program: Pubkey,
+    stake_pool: Pubkey,
+    lst_mint: Pubkey,
+    pumpswap_enabled: bool,
+    pumpswap_pool_id: Pubkey,
+    pumpswap_base_vault: Pubkey,
+    pumpswap_quote_vault: Pubkey,
+    pumpswap_global_config: Pubkey,
+    pumpswap_fee_recipient: Pubkey,
+    pumpswap_version: u8,
+    raydium_pool_id: Pubkey,
+    raydium_enabled: bool,
+    locked: bool,
+}
+
+impl PoolV0 {
+    const SIZE: usize = 32 * 3 + 8 * 4 + 2 + 2 + 8 + 32 * 2 + 33 + 8 + 8 + 8 + 33 + 8 + 32 * 3 + 1 + 32 * 5 + 1 + 32 + 1 + 1;
+}
+
+/// Donor as deployed at launch, before layout versioning (no version byte).
+#[derive(AnchorDeserialize)]
+struct DonorV0 {
+    pool: Pubkey,
+    wallet: Pubkey,
+    shares: u64,
+    total_deposited_lamports: u64,
+    last_shares_change_slot: u64,
+    abort_strike_count: u16,
+    propose_strike_count: u16,
+    non_abort_participation_count: u16,
+    non_propose_participation_count: u16,
+}
+
+impl DonorV0 {
+    const SIZE: usize = 32 + 32 + 8 + 8 + 8 + 2 + 2 + 2 + 2;
+}
+
+/// Proposal as deployed at launch, before layout versioning (no version byte).
+#[derive(AnchorDeserialize)]
+struct ProposalV0 {
+    pool: Pubkey,
+    kind: ProposalKind,
+    proposer_wallet: Pubkey,
+    requested_lamports: u64,
+    destination_wallet: Pubkey,
+    title: String,
+    description: String,
+    created_at_ts: i64,
+    deadline_ts: i64,
+    snapshot_slot: u64,
+    total_snapshot_shares: u64,
+    locked_pool_tokens: u64,
+    yes_weight: u64,
+    no_weight: u64,
+    abstain_weight: u64,
+    participation_weight: u64,
+    abort_voter_1: Pubkey,
+    abort_voter_2: Pubkey,
+    abort_count: u8,
+    status: ProposalStatus,
+}
+
+impl ProposalV0 {
+    const SIZE: usize = 32 + 1 + 32 + 8 + 32 + (4 + 64) + (4 + 256) + 8 * 4 + 8 + 8 * 4 + 32 + 32 + 1 + 1;
+}
+
+/// Quorum for a new proposal: the pool's quorum, stepped down by QUORUM_STEP_DOWN_BPS for every
+/// QUORUM_STEP_DOWN_FAILURES consecutive quorum failures, by at most MAX_QUORUM_STEP_DOWN_BPS.
+fn effective_quorum_bps(pool: &Pool) -> u16 {
+    pool.quorum_bps.saturating_sub(quorum_step_down_bps(pool))
+}
+
+fn quorum_step_down_bps(pool: &Pool) -> u16 {
+    let steps = pool.consecutive_quorum_failures / QUORUM_STEP_DOWN_FAILURES;
+    steps
+        .saturating_mul(QUORUM_STEP_DOWN_BPS)
+        .min(MAX_QUORUM_STEP_DOWN_BPS)
+}
+
+/// Quorum for a new payout locking `locked_pool_tokens`: that of the first configured QuorumTier
+/// whose `max_tvl_bps` covers the request's share of pool LST (the pool's quorum if none does),
+/// stepped down like `effective_quorum_bps`.
+fn tiered_quorum_bps(pool: &Pool, locked_pool_tokens: u64) -> Result<u16> {
+    let request_bps = mul_div_ceil(locked_pool_tokens, BPS_DENOM, pool.total_pool_tokens.max(1))?;
+    let base_quorum_bps = pool
+        .quorum_tiers
+        .iter()
+        .find(|tier| tier.max_tvl_bps > 0 && request_bps <= tier.max_tvl_bps as u64)
+        .map_or(pool.quorum_bps, |tier| tier.quorum_bps);
+    Ok(base_quorum_bps.saturating_sub(quorum_step_down_bps(pool)))
+}
+
+/// Configured tiers first, in strictly increasing `max_tvl_bps`, each quorum within
+/// MIN_QUORUM_BPS..=MAX_QUORUM_BPS; unused tiers trail as all-zero.
+fn validate_quorum_tiers(tiers: &[QuorumTier; QUORUM_TIER_COUNT]) -> Result<()> {
+    let mut prev_max_tvl_bps = 0u16;
+    let mut unused_seen = false;
+    for tier in tiers {
+        if tier.max_tvl_bps == 0 {
+            require!(tier.quorum_bps == 0, CtoError::InvalidQuorumTiers);
+            unused_seen = true;
+            continue;
+        }
+        require!(
+            !unused_seen
+                && tier.max_tvl_bps > prev_max_tvl_bps
+                && tier.max_tvl_bps as u64 <= BPS_DENOM
+                && (MIN_QUORUM_BPS..=MAX_QUORUM_BPS).contains(&tier.quorum_bps),
+            CtoError::InvalidQuorumTiers
+        );
+        prev_max_tvl_bps = tier.max_tvl_bps;
+    }
+    Ok(())
+}
+
+/// Most snapshot weight a single voter can cast (`max_voter_bps` of the snapshot).
+fn voter_weight_cap(max_voter_bps: u16, total_snapshot_shares: u64) -> Result<u64> {
+    let cap = (total_snapshot_shares as u128)
+        .checked_mul(max_voter_bps as u128)
+        .ok_or(CtoError::MathOverflow)?
+        .checked_div(BPS_DENOM as u128)
+        .ok_or(CtoError::MathOverflow)?;
+    Ok(cap as u64)
+}
+
+/// True if a proposal's participation has reached its quorum.
+fn quorum_reached(proposal: &Proposal) -> Result<bool> {
+    Ok(proposal
+        .participation_weight
+        .checked_mul(BPS_DENOM)
+        .ok_or(CtoError::MathOverflow)?
+        >= quorum_supply(proposal)
+            .checked_mul(proposal.quorum_bps as u64)
+            .ok_or(CtoError::MathOverflow)?)
+}
+
+/// True if a proposal's participation reached the spam threshold its proposer bond was posted under.
+fn bond_participation_met(proposal: &Proposal) -> Result<bool> {
+    Ok(proposal
+        .participation_weight
+        .checked_mul(BPS_DENOM)
+        .ok_or(CtoError::MathOverflow)?
+        >= quorum_supply(proposal)
+            .checked_mul(proposal.bond_spam_threshold_bps as u64)
+            .ok_or(CtoError::MathOverflow)?)
+}
+
+/// True if `execute_proposal` would take the pass path for this Active proposal at `now`:
+/// quorum met (voting may still be open), the minimum review delay over, and a Yes majority, or
+/// after the deadline a tie the authority approved under PassWithAuthority.
+fn proposal_has_passed(pool: &Pool, proposal: &Proposal, now: i64) -> Result<bool> {
+    let review_over = now
+        >= proposal
+            .created_at_ts
+            .checked_add(MIN_EXECUTE_DELAY_SECONDS)
+            .ok_or(CtoError::MathOverflow)?;
+    // Commit–reveal tallies only count once the reveal window has closed.
+    let revealing = proposal.reveal_seconds > 0 && now < voting_ends_at(proposal)?;
+    if !review_over || revealing || !quorum_reached(proposal)? {
+        return Ok(false);
+    }
+    if proposal.yes_weight > 0 && proposal.yes_weight == proposal.no_weight {
+        return Ok(now >= voting_ends_at(proposal)?
+            && pool.tie_break_rule == TieBreakRule::PassWithAuthority
+            && proposal.tie_approved);
+    }
+    Ok(proposal.yes_weight > proposal.no_weight)
+}
+
+/// Anti-sniping: a ballot cast in the final SNIPE_WINDOW_SECONDS that flips whether Yes leads
+/// pushes the deadline back by the proposal's extension while it has extensions left.
+/// `yes_led` is the lead before the ballot; returns whether the deadline moved.
+fn extend_if_sniped(proposal: &mut Proposal, yes_led: bool, now: i64) -> Result<bool> {
+    let flipped = (proposal.yes_weight > proposal.no_weight) != yes_led;
+    let window_start = proposal
+        .deadline_ts
+        .checked_sub(SNIPE_WINDOW_SECONDS)
+        .ok_or(CtoError::MathOverflow)?;
+    if !flipped || now < window_start || proposal.snipe_extensions_left == 0 {
+        return Ok(false);
+    }
+    proposal.deadline_ts = proposal
+        .deadline_ts
+        .checked_add(proposal.snipe_extension_seconds)
+        .ok_or(CtoError::MathOverflow)?;
+    proposal.snipe_extensions_left -= 1;
+    Ok(true)
+}
+
+/// Undelegation timelock for `pool`: the sponsor window, the pool's longest voting period and
+/// every extension it allows (anti-snipe, quorum grace, tie), plus the execution delay, so
+/// undelegated weight can't reach another delegate while a proposal it could vote on is open.
+fn undelegate_timelock_seconds(pool: &Pool) -> Result<i64> {
+    let snipe_extensions = pool
+        .snipe_extension_seconds
+        .checked_mul(pool.max_snipe_extensions as i64)
+        .ok_or(CtoError::MathOverflow)?;
+    [
+        pool.max_voting_seconds,
+        snipe_extensions,
+        QUORUM_GRACE_EXTENSION_SECONDS,
+        TIE_EXTENSION_SECONDS,
+        MIN_EXECUTE_DELAY_SECONDS,
+    ]
+    .into_iter()
+    .try_fold(SPONSOR_WINDOW_SECONDS, |total, seconds| total.checked_add(seconds))
+    .ok_or_else(|| CtoError::MathOverflow.into())
+}
+
+/// End of a proposal's voting: its deadline, plus the reveal window under commit–reveal.
+fn voting_ends_at(proposal: &Proposal) -> Result<i64> {
+    Ok(proposal
+        .deadline_ts
+        .checked_add(proposal.reveal_seconds)
+        .ok_or(CtoError::MathOverflow)?)
+}
+
+/// Denominator for a proposal's quorum: the snapshot supply, less any whale excess reported
+/// through `exclude_capped_excess` (only possible when cap-adjusted quorum was on at creation).
+fn quorum_supply(proposal: &Proposal) -> u64 {
+    proposal.total_snapshot_shares.saturating_sub(proposal.excluded_cap_weight)
+}
+
+/// Applies a (re-)vote to the proposal tallies and the vote record; returns the weight counted.
+///
+/// The weight is fixed by the first vote (capped at the proposal's max_voter_bps of snapshot shares); re-votes
+/// only move it between choices.
+fn tally_vote(
+    proposal: &mut Account<Proposal>,
+    vote_record: &mut Account<VoteRecord>,
+    raw_weight: u64,
+    choice: VoteChoice,
+) -> Result<u64> {
+    let prior = vote_record
+        .initialized
+        .then_some((vote_record.choice, vote_record.snapshot_weight));
+    let snapshot_weight = tally_ballot(proposal, prior, raw_weight, choice)?;
+
+    vote_record.proposal = proposal.key();
+    vote_record.snapshot_weight = snapshot_weight;
+    vote_record.choice = choice;
+    vote_record.initialized = true;
+
+    Ok(snapshot_weight)
+}
+
+/// Progress toward clearing a donor's strikes after a Yes/No vote: 3 such votes reset abort
+/// strikes, and 3 on proposals the donor didn't make reset proposer strikes.
+fn record_vote_participation(donor: &mut Donor, proposer: Pubkey) {
+    donor.non_abort_participation_count = donor.non_abort_participation_count.saturating_add(1);
+    if donor.non_abort_participation_count >= 3 {
+        donor.abort_strike_count = 0;
+        donor.non_abort_participation_count = 0;
+    }
+    // Proposer strike reset requires sitting out proposing and voting on 3 proposals.
+    if proposer != donor.wallet {
+        donor.non_propose_participation_count = donor.non_propose_participation_count.saturating_add(1);
+        if donor.non_propose_participation_count >= 3 {
+            donor.propose_strike_count = 0;
+            donor.non_propose_participation_count = 0;
+        }
+    }
+}
+
+/// Moves a ballot's weight into the proposal tallies. `prior` is the voter's earlier
+/// (choice, weight) on this proposal; a re-vote keeps the weight fixed at first vote.
+fn tally_ballot(
+    proposal: &mut Proposal,
+    prior: Option<(VoteChoice, u64)>,
+    raw_weight: u64,
+    choice: VoteChoice,
+) -> Result<u64> {
+    // Remove previous vote weight, if re-voting.
+    if let Some((prior_choice, w)) = prior {
+        match prior_choice {
+            VoteChoice::Yes => proposal.yes_weight = proposal.yes_weight.checked_sub(w).ok_or(CtoError::MathOverflow)?,
+            VoteChoice::No => proposal.no_weight = proposal.no_weight.checked_sub(w).ok_or(CtoError::MathOverflow)?,
+            VoteChoice::Abstain => proposal.abstain_weight = proposal.abstain_weight.checked_sub(w).ok_or(CtoError::MathOverflow)?,
+            VoteChoice::Abort => { /* weights not affected */ },
+        }
+    }
+
+    // M-01 FIX: Use u128 for intermediate calculation to prevent overflow
+    // when total_snapshot_shares is very large (e.g., near u64::MAX)
+    let snapshot_weight = if let Some((_, w)) = prior {
+        w
+    } else {
+        let capped = raw_weight.min(voter_weight_cap(proposal.max_voter_bps, proposal.total_snapshot_shares)?);
+        match proposal.vote_weighting {
+            VoteWeighting::Linear => capped,
+            VoteWeighting::Quadratic => quadratic_vote_weight(capped)?,
+        }
+    };
+
+    match choice {
+        VoteChoice::Yes => proposal.yes_weight = proposal.yes_weight.checked_add(snapshot_weight).ok_or(CtoError::MathOverflow)?,
+        VoteChoice::No => proposal.no_weight = proposal.no_weight.checked_add(snapshot_weight).ok_or(CtoError::MathOverflow)?,
+        VoteChoice::Abstain => proposal.abstain_weight = proposal.abstain_weight.checked_add(snapshot_weight).ok_or(CtoError::MathOverflow)?,
+        VoteChoice::Abort => { /* tracked separately */ },
+    }
+
+    // Participation counts the shares behind Yes/No/Abstain ballots, so quorum compares like
+    // with like under either weighting.
+    if let Some((prior_choice, w)) = prior {
+        if prior_choice != VoteChoice::Abort {
+            proposal.participation_weight = proposal
+                .participation_weight
+                .checked_sub(ballot_shares(proposal, w)?)
+                .ok_or(CtoError::MathOverflow)?;
+        }
+    }
+    if choice != VoteChoice::Abort {
+        proposal.participation_weight = proposal
+            .participation_weight
+            .checked_add(ballot_shares(proposal, snapshot_weight)?)
+            .ok_or(CtoError::MathOverflow)?;
+    }
+
+    Ok(snapshot_weight)
+}
+
+/// Shares behind a ballot of `weight`: the weight itself, or its square under quadratic weighting.
+fn ballot_shares(proposal: &Proposal, weight: u64) -> Result<u64> {
+    match proposal.vote_weighting {
+        VoteWeighting::Linear => Ok(weight),
+        VoteWeighting::Quadratic => mul_div_floor(weight, weight, QUADRATIC_WEIGHT_SCALE),
+    }
+}
+
+/// Shared tail of `withdraw_sol` / `withdraw_all`: burn LST for SOL, pay the donor, burn shares.
+fn complete_withdrawal<'info>(
+    ctx: &mut Context<WithdrawSol<'info>>,
+    pool_tokens_to_burn: u64,
+    shares_to_burn: u64,
+    minimum_lamports_out: u64,
+) -> Result<()> {
+    let pool_bump = ctx.bumps.pool;
+    let pool_token_mint = ctx.accounts.pool.token_mint;
+
+    // Delegated shares stay locked until undelegated.
+    require!(
+        ctx.accounts.donor.shares.saturating_sub(shares_to_burn) >= ctx.accounts.donor.delegated_shares,
+        CtoError::SharesDelegated
+    );
+    require_campaign_settled(&ctx.accounts.pool, Clock::get()?.unix_timestamp)?;
+
+    // Zero means "no preference": derive a floor from the current rate instead of accepting anything.
+    let minimum_lamports_out = if minimum_lamports_out == 0 {
+        let stake_pool_state = read_pool_rate(&ctx.accounts.pool, &ctx.accounts.stake_pool)?;
+        default_min_lamports_out(&stake_pool_state, pool_tokens_to_burn, ctx.accounts.pool.donor_slippage_bps)?
+    } else {
+        minimum_lamports_out
+    };
+
+    require_lst_rate_in_band(&ctx.accounts.pool, &ctx.accounts.stake_pool)?;
+    let via_stake = stake_fallback_required(
+        &ctx.accounts.pool,
+        &ctx.accounts.stake_pool,
+        &ctx.accounts.reserve_stake,
+        pool_tokens_to_burn,
+        ctx.accounts.stake_withdrawal.is_some(),
+    )?;
+
+    // ============ PHASE 2: CPIs ============
+    let received = if ctx.accounts.pool.custody == PoolCustody::Native {
+        release_native_lamports(&mut ctx.accounts.pool, pool_tokens_to_burn)?
+    } else if via_stake {
+        stake_pool_withdraw_stake(ctx, pool_tokens_to_burn)?
+    } else {
+        let pre_pool_lamports = ctx.accounts.pool.to_account_info().lamports();
+        stake_pool_withdraw_sol(ctx, pool_tokens_to_burn, minimum_lamports_out)?;
+        let post_pool_lamports = ctx.accounts.pool.to_account_info().lamports();
+        post_pool_lamports
+            .checked_sub(pre_pool_lamports)
+            .ok_or(CtoError::MathOverflow)?
+    };
+    require!(received >= minimum_lamports_out, CtoError::SlippageExceeded);
+
+    let clock = Clock::get()?;
+    if via_stake {
+        // Paid by `claim_deactivated_stake` once the stake account has cooled down.
+        let donor_wallet = ctx.accounts.donor_wallet.key();
+        open_stake_withdrawal(
+            &mut ctx.accounts.pool,
+            &mut ctx.accounts.stake_withdrawal,
+            ctx.accounts.withdrawal_stake.as_ref(),
+            donor_wallet,
+            donor_wallet,
+            received,
+            0,
+            &ctx.accounts.event_authority.to_account_info(),
+            ctx.bumps.event_authority,
+        )?;
+    } else {
+        transfer_lamports_signed(
+            &ctx.accounts.pool.to_account_info(),
+            &ctx.accounts.donor_wallet.to_account_info(),
+            &[&[b"pool", pool_token_mint.as_ref(), &[pool_bump]]],
+            received,
+        )?;
+    }
+
+    ctx.accounts.pool_lst_account.reload()?;
+    let final_pool_tokens = observed_pool_tokens(&ctx.accounts.pool, &ctx.accounts.pool_lst_account);
+    // ============ PHASE 3: state updates ============
+    {
+        let pool = &mut ctx.accounts.pool;
+        pool.total_pool_tokens = final_pool_tokens;
+        pool.total_shares = pool
+            .total_shares
+            .checked_sub(shares_to_burn)
+            .ok_or(CtoError::MathOverflow)?;
+    }
+    {
+        let donor = &mut ctx.accounts.donor;
+        donor.shares = donor
+            .shares
+            .checked_sub(shares_to_burn)
+            .ok_or(CtoError::MathOverflow)?;
+        mark_shares_changed(&ctx.accounts.pool, donor, clock.slot);
+    }
+
+    record_activity(
+        &mut ctx.accounts.activity_log,
+        ActivityKind::Withdraw,
+        0,
+        ctx.accounts.donor_wallet.key(),
+        received,
+        clock.slot,
+    );
+
+    // H-04 FIX: Unlock after successful operation
+    unlock_pool!(ctx.accounts.pool);
+
+    // L-05 FIX: Emit withdrawal event
+    emit_cpi!(WithdrawalEvent {
+        pool: ctx.accounts.pool.key(),
+        donor: ctx.accounts.donor_wallet.key(),
+        lamports_out: received,
+        pool_tokens_burned: pool_tokens_to_burn,
+        shares_burned: shares_to_burn,
+        via_stake,
+        total_shares: ctx.accounts.pool.total_shares,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Whether the stake pool reserve can pay `lamports` out through WithdrawSol while keeping its
+/// own rent-exempt minimum.
+fn reserve_can_cover(reserve_stake: &AccountInfo, lamports: u64) -> Result<bool> {
+    let reserve_floor = Rent::get()?.minimum_balance(STAKE_ACCOUNT_SPACE);
+    Ok(reserve_stake.lamports().saturating_sub(reserve_floor) >= lamports)
+}
+
+/// Picks WithdrawSol or the WithdrawStake fallback for burning `pool_tokens`. The fallback
+/// accounts must be supplied exactly when the reserve can't cover the expected SOL, so a
+/// caller can't route around a liquid reserve into a multi-epoch payout.
+fn stake_fallback_required(
+    pool: &Pool,
+    stake_pool_ai: &AccountInfo,
+    reserve_stake: &AccountInfo,
+    pool_tokens: u64,
+    fallback_supplied: bool,
+) -> Result<bool> {
+    let dry = match pool.custody {
+        PoolCustody::Native => false,
+        PoolCustody::Staked => {
+            let expected = default_min_lamports_out(&read_stake_pool(stake_pool_ai)?, pool_tokens, 0)?;
+            !reserve_can_cover(reserve_stake, expected)?
+        }
+    };
+    require!(!dry || fallback_supplied, CtoError::ReserveDry);
+    require!(dry || !fallback_supplied, CtoError::ReserveNotDry);
+    Ok(dry)
+}
+
+/// Creates the uninitialized stake account WithdrawStake splits into, with `payer` funding its
+/// rent. `seeds` are the account's own PDA seeds.
+fn create_withdrawal_stake_account<'info>(
+    payer: &AccountInfo<'info>,
+    withdrawal_stake: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(STAKE_ACCOUNT_SPACE);
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            withdrawal_stake.key,
+            rent,
+            STAKE_ACCOUNT_SPACE as u64,
+            &STAKE_PROGRAM_ID,
+        ),
+        &[payer.clone(), withdrawal_stake.clone(), system_program.clone()],
+        seeds,
+    )
+    .map_err(|_| CtoError::LamportTransferFailed.into())
+}
+
+/// Stake program Deactivate on a pool-owned stake account (the pool PDA is its staker).
+fn deactivate_withdrawal_stake<'info>(
+    withdrawal_stake: &AccountInfo<'info>,
+    clock: &AccountInfo<'info>,
+    pool: &AccountInfo<'info>,
+    stake_program: &AccountInfo<'info>,
+    pool_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let ix = Instruction {
+        program_id: STAKE_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(withdrawal_stake.key(), false),
+            AccountMeta::new_readonly(clock.key(), false),
+            AccountMeta::new_readonly(pool.key(), true),
+        ],
+        data: STAKE_IX_DEACTIVATE.to_le_bytes().to_vec(),
+    };
+    invoke_signed(
+        &ix,
+        &[withdrawal_stake.clone(), clock.clone(), pool.clone(), stake_program.clone()],
+        pool_seeds,
+    )
+    .map_err(|_| CtoError::StakeProgramCpiFailed.into())
+}
+
+/// Stake program Withdraw of `lamports` from a deactivated pool-owned stake account into the
+/// pool PDA (its withdrawer).
+fn withdraw_deactivated_stake<'info>(
+    withdrawal_stake: &AccountInfo<'info>,
+    pool: &AccountInfo<'info>,
+    clock: &AccountInfo<'info>,
+    stake_history: &AccountInfo<'info>,
+    stake_program: &AccountInfo<'info>,
+    pool_seeds: &[&[&[u8]]],
+    lamports: u64,
+) -> Result<()> {
+    let mut data = STAKE_IX_WITHDRAW.to_le_bytes().to_vec();
+    data.extend_from_slice(&lamports.to_le_bytes());
+    let ix = Instruction {
+        program_id: STAKE_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(withdrawal_stake.key(), false),
+            AccountMeta::new(pool.key(), false),
+            AccountMeta::new_readonly(clock.key(), false),
+            AccountMeta::new_readonly(stake_history.key(), false),
+            AccountMeta::new_readonly(pool.key(), true),
+        ],
+        data,
+    };
+    invoke_signed(
+        &ix,
+        &[
+            withdrawal_stake.clone(),
+            pool.clone(),
+            clock.clone(),
+            stake_history.clone(),
+            stake_program.clone(),
+        ],
+        pool_seeds,
+    )
+    .map_err(|_| CtoError::StakeProgramCpiFailed.into())
+}
+
+/// Records SOL left in a deactivating stake account for `claim_deactivated_stake` and advances
+/// the pool's StakeWithdrawal counter (which the next record's and stake account's seeds use).
+#[allow(clippy::too_many_arguments)]
+fn open_stake_withdrawal<'info>(
+    pool: &mut Account<'info, Pool>,
+    stake_withdrawal: &mut Option<Account<'info, StakeWithdrawal>>,
+    withdrawal_stake: Option<&UncheckedAccount<'info>>,
+    recipient: Pubkey,
+    rent_payer: Pubkey,
+    lamports: u64,
+    protocol_fee: u64,
+    event_authority: &AccountInfo<'info>,
+    event_authority_bump: u8,
+) -> Result<()> {
+    let record = stake_withdrawal.as_mut().ok_or(CtoError::ReserveDry)?;
+    let withdrawal_stake = withdrawal_stake.ok_or(CtoError::ReserveDry)?;
+    let clock = Clock::get()?;
+
+    record.pool = pool.key();
+    record.index = pool.stake_withdrawal_count;
+    record.recipient = recipient;
+    record.stake_account = withdrawal_stake.key();
+    record.lamports = lamports;
+    record.protocol_fee = protocol_fee;
+    record.rent_payer = rent_payer;
+    record.rent_lamports = Rent::get()?.minimum_balance(STAKE_ACCOUNT_SPACE);
+    record.deactivation_epoch = clock.epoch;
+
+    pool.stake_withdrawal_count = pool
+        .stake_withdrawal_count
+        .checked_add(1)
+        .ok_or(CtoError::MathOverflow)?;
+    pool.open_stake_withdrawals = pool
+        .open_stake_withdrawals
+        .checked_add(1)
+        .ok_or(CtoError::MathOverflow)?;
+
+    emit_event_cpi(event_authority, event_authority_bump, &StakeWithdrawalOpenedEvent {
+        pool: pool.key(),
+        stake_withdrawal: record.key(),
+        stake_account: record.stake_account,
+        recipient,
+        lamports,
+        timestamp: clock.unix_timestamp,
+    })?;
+    Ok(())
+}
+
+/// CPI to stake pool program to withdraw SOL by burning LST tokens (withdraw_sol handler).
+fn stake_pool_withdraw_sol(ctx: &Context<WithdrawSol>, pool_tokens_in: u64, minimum_lamports_out: u64) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+
+    require!(
+        ctx.accounts.stake_pool_program.key() == pool.stake_pool_program
+            && ctx.accounts.stake_pool.key() == pool.stake_pool
+            && ctx.accounts.lst_mint.key() == pool.lst_mint,
+        CtoError::InvalidStakePoolConfig
+    );
+
+    let ix = stake_pool_ix::withdraw_sol_with_slippage(
+        &pool.stake_pool_program,
+        &pool.stake_pool,
+        &ctx.accounts.stake_pool_withdraw_authority.key(),
+        &pool.key(),
+        &ctx.accounts.pool_lst_account.key(),
+        &ctx.accounts.reserve_stake.key(),
+        &pool.key(),
+        &ctx.accounts.manager_fee_account.key(),
+        &ctx.accounts.lst_mint.key(),
+        &ctx.accounts.token_program.key(),
+        pool_tokens_in,
+        minimum_lamports_out,
+    );
+
+    invoke_signed(
+        &ix,
+        &[
+            ctx.accounts.stake_pool.to_account_info(),
+            ctx.accounts.stake_pool_withdraw_authority.to_account_info(),
+            ctx.accounts.pool.to_account_info(),
+            ctx.accounts.pool_lst_account.to_account_info(),
+            ctx.accounts.reserve_stake.to_account_info(),
+            ctx.accounts.pool.to_account_info(),
+            ctx.accounts.manager_fee_account.to_account_info(),
+            ctx.accounts.lst_mint.to_account_info(),
+            ctx.accounts.clock.to_account_info(),
+            ctx.accounts.stake_history.to_account_info(),
+            ctx.accounts.stake_program.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+        ],
+        pool_seeds!(pool, ctx.bumps.pool),
+    )
+    .map_err(|_| CtoError::StakePoolCpiFailed.into())
+}
+
+/// CPI to stake pool program to withdraw a split of validator stake into a new pool-owned stake
+/// account by burning LST tokens, then deactivate it (withdraw_sol fallback when the reserve is dry).
+/// Returns the lamports split off, excluding the stake account's prefunded rent.
+fn stake_pool_withdraw_stake(ctx: &Context<WithdrawSol>, pool_tokens_in: u64) -> Result<u64> {
+    let pool = &ctx.accounts.pool;
+
+    require!(
+        ctx.accounts.stake_pool_program.key() == pool.stake_pool_program
+            && ctx.accounts.stake_pool.key() == pool.stake_pool
+            && ctx.accounts.lst_mint.key() == pool.lst_mint,
+        CtoError::InvalidStakePoolConfig
+    );
+    let validator_list = ctx.accounts.validator_list.as_ref().ok_or(CtoError::ReserveDry)?;
+    let validator_stake = ctx.accounts.validator_stake.as_ref().ok_or(CtoError::ReserveDry)?;
+    let withdrawal_stake = ctx.accounts.withdrawal_stake.as_ref().ok_or(CtoError::ReserveDry)?;
+    let stake_bump = ctx.bumps.withdrawal_stake.ok_or(CtoError::ReserveDry)?;
+
+    let pool_key = pool.key();
+    let index = pool.stake_withdrawal_count.to_le_bytes();
+    create_withdrawal_stake_account(
+        &ctx.accounts.donor_wallet.to_account_info(),
+        &withdrawal_stake.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &[&[b"withdrawal_stake", pool_key.as_ref(), &index, &[stake_bump]]],
+    )?;
+    let prefunded = withdrawal_stake.lamports();
+
+    let ix = stake_pool_ix::withdraw_stake(
+        &pool.stake_pool_program,
+        &pool.stake_pool,
+        &validator_list.key(),
+        &ctx.accounts.stake_pool_withdraw_authority.key(),
+        &validator_stake.key(),
+        &withdrawal_stake.key(),
+        &pool_key,
+        &pool_key,
+        &ctx.accounts.pool_lst_account.key(),
+        &ctx.accounts.manager_fee_account.key(),
+        &ctx.accounts.lst_mint.key(),
+        &ctx.accounts.token_program.key(),
+        pool_tokens_in,
+    );
+
+    invoke_signed(
+        &ix,
+        &[
+            ctx.accounts.stake_pool.to_account_info(),
+            validator_list.to_account_info(),
+            ctx.accounts.stake_pool_withdraw_authority.to_account_info(),
+            validator_stake.to_account_info(),
+            withdrawal_stake.to_account_info(),
+            ctx.accounts.pool.to_account_info(),
+            ctx.accounts.pool.to_account_info(),
+            ctx.accounts.pool_lst_account.to_account_info(),
+            ctx.accounts.manager_fee_account.to_account_info(),
+            ctx.accounts.lst_mint.to_account_info(),
+            ctx.accounts.clock.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.stake_program.to_account_info(),
+        ],
+        pool_seeds!(pool, ctx.bumps.pool),
+    )
+    .map_err(|_| CtoError::StakePoolCpiFailed)?;
+
+    deactivate_withdrawal_stake(
+        &withdrawal_stake.to_account_info(),
+        &ctx.accounts.clock.to_account_info(),
+        &ctx.accounts.pool.to_account_info(),
+        &ctx.accounts.stake_program.to_account_info(),
+        pool_seeds!(pool, ctx.bumps.pool),
+    )?;
+
+    withdrawal_stake
+        .lamports()
+        .checked_sub(prefunded)
+        .ok_or(CtoError::MathOverflow.into())
+}
+
+/// CPI to stake pool program to withdraw SOL by burning LST tokens (rage_quit handler).
+fn stake_pool_withdraw_sol_rage_quit(ctx: &Context<RageQuit>, pool_tokens_in: u64, minimum_lamports_out: u64) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+
+    require!(
+        ctx.accounts.stake_pool_program.key() == pool.stake_pool_program
+            && ctx.accounts.stake_pool.key() == pool.stake_pool
+            && ctx.accounts.lst_mint.key() == pool.lst_mint,
+        CtoError::InvalidStakePoolConfig
+    );
+
+    let ix = stake_pool_ix::withdraw_sol_with_slippage(
+        &pool.stake_pool_program,
+        &pool.stake_pool,
+        &ctx.accounts.stake_pool_withdraw_authority.key(),
+        &pool.key(),
+        &ctx.accounts.pool_lst_account.key(),
+        &ctx.accounts.reserve_stake.key(),
+        &pool.key(),
+        &ctx.accounts.manager_fee_account.key(),
+        &ctx.accounts.lst_mint.key(),
+        &ctx.accounts.token_program.key(),
+        pool_tokens_in,
+        minimum_lamports_out,
+    );
+
+    invoke_signed(
+        &ix,
+        &[
+            ctx.accounts.stake_pool.to_account_info(),
+            ctx.accounts.stake_pool_withdraw_authority.to_account_info(),
+            ctx.accounts.pool.to_account_info(),
+            ctx.accounts.pool_lst_account.to_account_info(),
+            ctx.accounts.reserve_stake.to_account_info(),
+            ctx.accounts.pool.to_account_info(),
+            ctx.accounts.manager_fee_account.to_account_info(),
+            ctx.accounts.lst_mint.to_account_info(),
+            ctx.accounts.clock.to_account_info(),
+            ctx.accounts.stake_history.to_account_info(),
+            ctx.accounts.stake_program.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+        ],
+        pool_seeds!(pool, ctx.bumps.pool),
+    )
+    .map_err(|_| CtoError::StakePoolCpiFailed.into())
+}
+
+/// CPI to stake pool program to withdraw SOL by burning LST tokens (unstake_sunset_pool handler).
+fn stake_pool_withdraw_sol_sunset(ctx: &Context<UnstakeSunsetPool>, pool_tokens_in: u64, minimum_lamports_out: u64) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+
+    require!(
+        ctx.accounts.stake_pool_program.key() == pool.stake_pool_program
+            && ctx.accounts.stake_pool.key() == pool.stake_pool
+            && ctx.accounts.lst_mint.key() == pool.lst_mint,
+        CtoError::InvalidStakePoolConfig
+    );
+
+    let ix = stake_pool_ix::withdraw_sol_with_slippage(
+        &pool.stake_pool_program,
+        &pool.stake_pool,
+        &ctx.accounts.stake_pool_withdraw_authority.key(),
+        &pool.key(),
+        &ctx.accounts.pool_lst_account.key(),
+        &ctx.accounts.reserve_stake.key(),
+        &pool.key(),
+        &ctx.accounts.manager_fee_account.key(),
+        &ctx.accounts.lst_mint.key(),
+        &ctx.accounts.token_program.key(),
+        pool_tokens_in,
+        minimum_lamports_out,
+    );
+
+    invoke_signed(
+        &ix,
+        &[
+            ctx.accounts.stake_pool.to_account_info(),
+            ctx.accounts.stake_pool_withdraw_authority.to_account_info(),
+            ctx.accounts.pool.to_account_info(),
+            ctx.accounts.pool_lst_account.to_account_info(),
+            ctx.accounts.reserve_stake.to_account_info(),
+            ctx.accounts.pool.to_account_info(),
+            ctx.accounts.manager_fee_account.to_account_info(),
+            ctx.accounts.lst_mint.to_account_info(),
+            ctx.accounts.clock.to_account_info(),
+            ctx.accounts.stake_history.to_account_info(),
+            ctx.accounts.stake_program.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+        ],
+        pool_seeds!(pool, ctx.bumps.pool),
+    )
+    .map_err(|_| CtoError::StakePoolCpiFailed.into())
+}
+
+/// CPI to stake pool program to withdraw SOL by burning LST tokens (process_withdrawal_queue handler).
+fn stake_pool_withdraw_sol_queue(ctx: &Context<ProcessWithdrawalQueue>, pool_tokens_in: u64, minimum_lamports_out: u64) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+
+    require!(
+        ctx.accounts.stake_pool_program.key() == pool.stake_pool_program
+            && ctx.accounts.stake_pool.key() == pool.stake_pool
+            && ctx.accounts.lst_mint.key() == pool.lst_mint,
+        CtoError::InvalidStakePoolConfig
+    );
+
+    let ix = stake_pool_ix::withdraw_sol_with_slippage(
+        &pool.stake_pool_program,
+        &pool.stake_pool,
+        &ctx.accounts.stake_pool_withdraw_authority.key(),
+        &pool.key(),
+        &ctx.accounts.pool_lst_account.key(),
+        &ctx.accounts.reserve_stake.key(),
+        &pool.key(),
+        &ctx.accounts.manager_fee_account.key(),
+        &ctx.accounts.lst_mint.key(),
+        &ctx.accounts.token_program.key(),
+        pool_tokens_in,
+        minimum_lamports_out,
+    );
+
+    invoke_signed(
+        &ix,
+        &[
+            ctx.accounts.stake_pool.to_account_info(),
+            ctx.accounts.stake_pool_withdraw_authority.to_account_info(),
+            ctx.accounts.pool.to_account_info(),
+            ctx.accounts.pool_lst_account.to_account_info(),
+            ctx.accounts.reserve_stake.to_account_info(),
+            ctx.accounts.pool.to_account_info(),
+            ctx.accounts.manager_fee_account.to_account_info(),
+            ctx.accounts.lst_mint.to_account_info(),
+            ctx.accounts.clock.to_account_info(),
+            ctx.accounts.stake_history.to_account_info(),
+            ctx.accounts.stake_program.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+        ],
+        pool_seeds!(pool, ctx.bumps.pool),
+    )
+    .map_err(|_| CtoError::StakePoolCpiFailed.into())
+}
+
+/// CPI to stake pool program to withdraw SOL by burning LST tokens (execute_proposal handler).
+fn stake_pool_withdraw_sol_exec(ctx: &Context<ExecuteProposal>, pool_tokens_in: u64, minimum_lamports_out: u64) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+
+    require!(
+        ctx.accounts.stake_pool_program.key() == pool.stake_pool_program
+            && ctx.accounts.stake_pool.key() == pool.stake_pool
+            && ctx.accounts.lst_mint.key() == pool.lst_mint,
+        CtoError::InvalidStakePoolConfig
+    );
+
+    let ix = stake_pool_ix::withdraw_sol_with_slippage(
+        &pool.stake_pool_program,
+        &pool.stake_pool,
+        &ctx.accounts.stake_pool_withdraw_authority.key(),
+        &pool.key(),
+        &ctx.accounts.pool_lst_account.key(),
+        &ctx.accounts.reserve_stake.key(),
+        &pool.key(),
+        &ctx.accounts.manager_fee_account.key(),
+        &ctx.accounts.lst_mint.key(),
+        &ctx.accounts.token_program.key(),
+        pool_tokens_in,
+        minimum_lamports_out,
+    );
+
+    invoke_signed(
+        &ix,
+        &[
+            ctx.accounts.stake_pool.to_account_info(),
+            ctx.accounts.stake_pool_withdraw_authority.to_account_info(),
+            ctx.accounts.pool.to_account_info(),
+            ctx.accounts.pool_lst_account.to_account_info(),
+            ctx.accounts.reserve_stake.to_account_info(),
+            ctx.accounts.pool.to_account_info(),
+            ctx.accounts.manager_fee_account.to_account_info(),
+            ctx.accounts.lst_mint.to_account_info(),
+            ctx.accounts.clock.to_account_info(),
+            ctx.accounts.stake_history.to_account_info(),
+            ctx.accounts.stake_program.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+        ],
+        pool_seeds!(pool, ctx.bumps.pool),
+    )
+    .map_err(|_| CtoError::StakePoolCpiFailed.into())
+}
+
+/// CPI to stake pool program to withdraw a split of validator stake into a new pool-owned stake
+/// account by burning LST tokens, then deactivate it (execute_proposal fallback when the reserve is dry).
+/// Returns the lamports split off, excluding the stake account's prefunded rent.
+fn stake_pool_withdraw_stake_exec(ctx: &Context<ExecuteProposal>, pool_tokens_in: u64) -> Result<u64> {
+    let pool = &ctx.accounts.pool;
+
+    require!(
+        ctx.accounts.stake_pool_program.key() == pool.stake_pool_program
+            && ctx.accounts.stake_pool.key() == pool.stake_pool
+            && ctx.accounts.lst_mint.key() == pool.lst_mint,
+        CtoError::InvalidStakePoolConfig
+    );
+    let validator_list = ctx.accounts.validator_list.as_ref().ok_or(CtoError::ReserveDry)?;
+    let validator_stake = ctx.accounts.validator_stake.as_ref().ok_or(CtoError::ReserveDry)?;
+    let withdrawal_stake = ctx.accounts.withdrawal_stake.as_ref().ok_or(CtoError::ReserveDry)?;
+    let stake_bump = ctx.bumps.withdrawal_stake.ok_or(CtoError::ReserveDry)?;
+
+    let pool_key = pool.key();
+    let index = pool.stake_withdrawal_count.to_le_bytes();
+    create_withdrawal_stake_account(
+        &ctx.accounts.executor.to_account_info(),
+        &withdrawal_stake.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &[&[b"withdrawal_stake", pool_key.as_ref(), &index, &[stake_bump]]],
+    )?;
+    let prefunded = withdrawal_stake.lamports();
+
+    let ix = stake_pool_ix::withdraw_stake(
+        &pool.stake_pool_program,
+        &pool.stake_pool,
+        &validator_list.key(),
+        &ctx.accounts.stake_pool_withdraw_authority.key(),
+        &validator_stake.key(),
+        &withdrawal_stake.key(),
+        &pool_key,
+        &pool_key,
+        &ctx.accounts.pool_lst_account.key(),
+        &ctx.accounts.manager_fee_account.key(),
+        &ctx.accounts.lst_mint.key(),
+        &ctx.accounts.token_program.key(),
+        pool_tokens_in,
+    );
+
+    invoke_signed(
+        &ix,
+        &[
+            ctx.accounts.stake_pool.to_account_info(),
+            validator_list.to_account_info(),
+            ctx.accounts.stake_pool_withdraw_authority.to_account_info(),
+            validator_stake.to_account_info(),
+            withdrawal_stake.to_account_info(),
+            ctx.accounts.pool.to_account_info(),
+            ctx.accounts.pool.to_account_info(),
+            ctx.accounts.pool_lst_account.to_account_info(),
+            ctx.accounts.manager_fee_account.to_account_info(),
+            ctx.accounts.lst_mint.to_account_info(),
+            ctx.accounts.clock.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.stake_program.to_account_info(),
+        ],
+        pool_seeds!(pool, ctx.bumps.pool),
+    )
+    .map_err(|_| CtoError::StakePoolCpiFailed)?;
+
+    deactivate_withdrawal_stake(
+        &withdrawal_stake.to_account_info(),
+        &ctx.accounts.clock.to_account_info(),
+        &ctx.accounts.pool.to_account_info(),
+        &ctx.accounts.stake_program.to_account_info(),
+        pool_seeds!(pool, ctx.bumps.pool),
+    )?;
+
+    withdrawal_stake
+        .lamports()
+        .checked_sub(prefunded)
+        .ok_or(CtoError::MathOverflow.into())
+}
+
+// ===== Donation helpers =====
+
+/// Binds a freshly created Donor to `wallet` and appends it to the pool's donor index.
+fn register_donor(
+    pool: &mut Account<Pool>,
+    donor: &mut Account<Donor>,
+    page: &mut Account<DonorIndexPage>,
+    wallet: Pubkey,
+) -> Result<()> {
+    if page.wallets.is_empty() {
+        page.pool = pool.key();
+        page.page_index = pool.indexed_donor_count / DonorIndexPage::CAPACITY as u64;
+    }
+    page.wallets.push(wallet);
+    pool.indexed_donor_count = pool
+        .indexed_donor_count
+        .checked_add(1)
+        .ok_or(CtoError::MathOverflow)?;
+    pool.donor_count = pool.donor_count.checked_add(1).ok_or(CtoError::MathOverflow)?;
+
+    donor.version = DONOR_VERSION;
+    donor.pool = pool.key();
+    donor.wallet = wallet;
+    donor.share_scale = pool.share_scale;
+    Ok(())
+}
+
+/// Records a change to a donor's shares or delegation at `slot`: bumps
+/// `last_shares_change_slot` and checkpoints the resulting votable shares (see `record_checkpoint`).
+fn mark_shares_changed(pool: &Pool, donor: &mut Donor, slot: u64) {
+    donor.last_shares_change_slot = slot;
+    let shares = donor.shares.saturating_sub(donor.delegated_shares);
+    record_checkpoint(pool, &mut donor.share_checkpoints, slot, shares);
+}
+
+/// Records a change to an escrow's delegated shares at `slot`, checkpointed like a Donor's.
+fn mark_escrow_changed(pool: &Pool, escrow: &mut VoteEscrow, slot: u64) {
+    escrow.last_change_slot = slot;
+    record_checkpoint(pool, &mut escrow.share_checkpoints, slot, escrow.delegated_shares);
+}
+
+/// Writes `shares` at `slot`, replacing the checkpoint from the same slot or else the oldest
+/// one. The checkpoint the pool's latest proposal or poll snapshot resolves to is never
+/// replaced, so a holder can't lose a vote on an open proposal by changing shares while it runs.
+fn record_checkpoint(pool: &Pool, checkpoints: &mut [ShareCheckpoint; SHARE_CHECKPOINT_COUNT], slot: u64, shares: u64) {
+    let snapshot_checkpoint = |snapshot_slot: u64| {
+        (0..SHARE_CHECKPOINT_COUNT)
+            .filter(|&i| {
+                let checkpoint = &checkpoints[i];
+                snapshot_slot != 0 && checkpoint.slot != 0 && checkpoint.slot <= snapshot_slot
+            })
+            .max_by_key(|&i| checkpoints[i].slot)
+    };
+    let protected = [
+        snapshot_checkpoint(pool.proposal_snapshot_slot),
+        snapshot_checkpoint(pool.poll_snapshot_slot),
+    ];
+    let index = checkpoints
+        .iter()
+        .position(|checkpoint| checkpoint.slot == slot)
+        .or_else(|| {
+            (0..SHARE_CHECKPOINT_COUNT)
+                .filter(|i| !protected.contains(&Some(*i)))
+                .min_by_key(|&i| checkpoints[i].slot)
+        })
+        .unwrap_or(0);
+    checkpoints[index] = ShareCheckpoint { slot, shares };
+}
+
+/// Votable shares a donor held at `slot`, capped at what they hold now (shares withdrawn or
+/// delegated since don't vote here), with the slot of the change that left them there. Donors
+/// unchanged since `slot` vote their current shares; otherwise the latest checkpoint at or
+/// before `slot` is used, and if it has already been overwritten the donor is not eligible.
+fn share_checkpoint_at(donor: &Donor, slot: u64) -> Result<ShareCheckpoint> {
+    checkpoint_at(
+        &donor.share_checkpoints,
+        donor.last_shares_change_slot,
+        donor.shares.saturating_sub(donor.delegated_shares),
+        slot,
+    )
+}
+
+/// `share_checkpoint_at` over any checkpointed balance: `current` as of `last_change_slot`.
+fn checkpoint_at(
+    checkpoints: &[ShareCheckpoint; SHARE_CHECKPOINT_COUNT],
+    last_change_slot: u64,
+    current: u64,
+    slot: u64,
+) -> Result<ShareCheckpoint> {
+    if last_change_slot <= slot {
+        return Ok(ShareCheckpoint { slot: last_change_slot, shares: current });
+    }
+    let checkpoint = checkpoints
+        .iter()
+        .filter(|checkpoint| checkpoint.slot != 0 && checkpoint.slot <= slot)
+        .max_by_key(|checkpoint| checkpoint.slot)
+        .ok_or(CtoError::NotEligibleForThisProposal)?;
+    Ok(ShareCheckpoint { slot: checkpoint.slot, shares: checkpoint.shares.min(current) })
+}
+
+/// A donor's vote weight on `proposal`: votable shares at the snapshot, ramped by how long
+/// they had been held by then (see `ramped_vote_weight`).
+fn snapshot_vote_weight(donor: &Donor, proposal: &Proposal) -> Result<u64> {
+    let held = share_checkpoint_at(donor, proposal.snapshot_slot)?;
+    ramped_vote_weight(
+        held.shares,
+        proposal.snapshot_slot.saturating_sub(held.slot),
+        proposal.vote_ramp_slots,
+    )
+}
+
+/// Time-weighted voting: `shares` scaled linearly by `held_slots / ramp_slots`, reaching full
+/// weight once held for `ramp_slots` (0 = no ramp).
+fn ramped_vote_weight(shares: u64, held_slots: u64, ramp_slots: u64) -> Result<u64> {
+    if held_slots >= ramp_slots {
+        return Ok(shares);
+    }
+    mul_div_floor(shares, held_slots, ramp_slots)
+}
+
+/// Mints shares for LST that arrived in a deposit (`pre_balance` -> `new_balance`) and credits
+/// them to `donor`. Returns the shares minted.
+fn credit_donation(
+    pool: &mut Pool,
+    donor: &mut Donor,
+    pre_balance: u64,
+    new_balance: u64,
+    lamports_in: u64,
+    slot: u64,
+) -> Result<u64> {
+    // Keep state aligned with observed pre balance (handles external top-ups as shared donations).
+    pool.total_pool_tokens = pre_balance;
+
+    let prev_total = pre_balance;
+    let received = new_balance.checked_sub(prev_total).ok_or(CtoError::MathOverflow)?;
+    require!(received > 0, CtoError::StakePoolReturnedZero);
+
+    // Shares: proportional against virtual-offset totals. For an empty pool this is exactly
+    // share_scale per LST unit.
+    let shares_minted = shares_for_deposit(received, pool.total_shares, prev_total, pool.share_scale)?;
+    require!(shares_minted > 0, CtoError::StakePoolReturnedZero);
+    require!(shares_minted >= MIN_SHARES_MINTED, CtoError::DonationTooSmall);
+
+    // Rounding policy: mint floors. The LST value of the unminted fraction is held as dust.
+    let minted_value = mul_div_floor(
+        shares_minted,
+        prev_total.checked_add(VIRTUAL_POOL_TOKENS).ok_or(CtoError::MathOverflow)?,
+        pool.total_shares
+            .checked_add(VIRTUAL_POOL_TOKENS.checked_mul(pool.share_scale.max(1)).ok_or(CtoError::MathOverflow)?)
+            .ok_or(CtoError::MathOverflow)?,
+    )?;
+    pool.rounding_dust_pool_tokens = pool
+        .rounding_dust_pool_tokens
+        .checked_add(received.saturating_sub(minted_value))
+        .ok_or(CtoError::MathOverflow)?;
+
+    pool.total_pool_tokens = new_balance;
+    mint_donor_shares(pool, donor, shares_minted, lamports_in, slot)?;
+
+    Ok(shares_minted)
+}
+
+fn mint_donor_shares(pool: &mut Pool, donor: &mut Donor, shares_minted: u64, lamports_in: u64, slot: u64) -> Result<()> {
+    pool.total_shares = pool
+        .total_shares
+        .checked_add(shares_minted)
+        .ok_or(CtoError::MathOverflow)?;
+
+    require!(donor.share_scale == pool.share_scale, CtoError::ShareScaleMigrationRequired);
+    donor.shares = donor
+        .shares
+        .checked_add(shares_minted)
+        .ok_or(CtoError::MathOverflow)?;
+    donor.total_deposited_lamports = donor
+        .total_deposited_lamports
+        .checked_add(lamports_in)
+        .ok_or(CtoError::MathOverflow)?;
+    mark_shares_changed(pool, donor, slot);
+
+    if campaign_pending(pool) {
+        pool.campaign_raised_lamports = pool
+            .campaign_raised_lamports
+            .checked_add(lamports_in)
+            .ok_or(CtoError::MathOverflow)?;
+        pool.campaign_goal_reached = pool.campaign_raised_lamports >= pool.campaign_goal_lamports;
+    }
+    Ok(())
+}
+
+/// Mints the pool's referral bonus on `referred_shares` to the referrer, clipped to what is
+/// left of the pool-wide cap. Returns the bonus minted (0 when referrals are off or spent).
+fn credit_referral(pool: &mut Pool, referrer_donor: &mut Donor, referred_shares: u64, slot: u64) -> Result<u64> {
+    if pool.referral_bps == 0 {
+        return Ok(0);
+    }
+    let bonus = mul_div_floor(referred_shares, pool.referral_bps as u64, BPS_DENOM)?
+        .min(pool.referral_cap_shares.saturating_sub(pool.referral_shares_minted));
+    if bonus == 0 {
+        return Ok(0);
+    }
+
+    mint_donor_shares(pool, referrer_donor, bonus, 0, slot)?;
+    pool.referral_shares_minted = pool
+        .referral_shares_minted
+        .checked_add(bonus)
+        .ok_or(CtoError::MathOverflow)?;
+    Ok(bonus)
+}
+
+/// Matches a SOL donation from the pool's MatchingVault if its window is open: moves up to
+/// `match_bps` of `lamports_in` into the pool PDA and credits the donor shares for it, priced at
+/// the rate the donation itself got (`pool_tokens_received`). Returns (lamports, shares) matched;
+/// (0, 0) outside the window, once the budget is spent, or when the match is below one mint.
+///
+/// The matched lamports arrive as pool surplus and are staked by `sweep_surplus`, which restores
+/// the value the new shares claim until then.
+fn apply_donation_match<'info>(
+    pool: &mut Account<'info, Pool>,
+    vault: &mut Account<'info, MatchingVault>,
+    donor: &mut Donor,
+    lamports_in: u64,
+    pool_tokens_received: u64,
+    clock: &Clock,
+) -> Result<(u64, u64)> {
+    if clock.unix_timestamp < vault.start_ts || clock.unix_timestamp >= vault.end_ts {
+        return Ok((0, 0));
+    }
+    let matched_lamports = mul_div_floor(lamports_in, vault.match_bps as u64, BPS_DENOM)?.min(vault.available_lamports);
+    if matched_lamports == 0 {
+        return Ok((0, 0));
+    }
+    let value_pool_tokens = mul_div_floor(pool_tokens_received, matched_lamports, lamports_in)?;
+    let matched_shares = shares_for_deposit(value_pool_tokens, pool.total_shares, pool.total_pool_tokens, pool.share_scale)?;
+    if matched_shares < MIN_SHARES_MINTED {
+        return Ok((0, 0));
+    }
+
+    let pool_key = pool.key();
+    transfer_lamports_signed(
+        &vault.to_account_info(),
+        &pool.to_account_info(),
+        &[&[b"matching", pool_key.as_ref(), &[vault.bump]]],
+        matched_lamports,
+    )?;
+    vault.available_lamports -= matched_lamports;
+    vault.matched_lamports = vault
+        .matched_lamports
+        .checked_add(matched_lamports)
+        .ok_or(CtoError::MathOverflow)?;
+
+    mint_donor_shares(pool, donor, matched_shares, matched_lamports, clock.slot)?;
+    Ok((matched_lamports, matched_shares))
+}
+
+/// A pool can only start winding down once it holds no LP position and has none queued: LP
+/// tokens come back only through a RemoveLiquidity proposal, which a sunset pool can't pass.
+fn require_no_liquidity_position(pool: &Pool) -> Result<()> {
+    require!(
+        pool.lp_token_amount == 0
+            && pool.pending_liquidity_lamports == 0
+            && pool.pending_liquidity_tokens == 0
+            && pool.pending_lp_withdrawal == 0,
+        CtoError::LiquidityPositionOpen
+    );
+    Ok(())
+}
+
+/// Recovery is for tokens sent to the pool by mistake: it can't move the LST or any token
+/// account whose balance the pool tracks (held CTOP, the PumpSwap hop, the treasury ATA, LP
+/// tokens and the WSOL accounts), since their tracked amounts would no longer match.
+fn require_recoverable(pool: &Account<Pool>, token_account: &Account<TokenAccount>, token_mint: Pubkey) -> Result<()> {
+    require_keys_eq!(token_account.mint, token_mint, CtoError::RecoveryMintMismatch);
+    require!(token_mint != pool.lst_mint, CtoError::RecoveryNotAllowedForLST);
+    let pool_key = pool.key();
+    let tracked = [
+        pool_ctop_address(&pool_key, &pool.burn_token_mint).0,
+        pool_hop_address(&pool_key, &pool.pumpswap_hop_mint).0,
+        pool_lp_address(&pool_key).0,
+        pool_wsol_address(&pool_key).0,
+        pool_liquidity_wsol_address(&pool_key).0,
+        anchor_spl::associated_token::get_associated_token_address(&pool_key, &pool.token_mint),
+    ];
+    require!(!tracked.contains(&token_account.key()), CtoError::RecoveryNotAllowedForTrackedAccount);
+    Ok(())
+}
+
+/// In allowlist mode, the caller must have passed their AllowlistEntry (its seeds tie it to the
+/// wallet being checked).
+fn require_allowlisted(pool: &Pool, allowlist_entry: &Option<Account<AllowlistEntry>>) -> Result<()> {
+    require!(!pool.allowlist_enabled || allowlist_entry.is_some(), CtoError::NotAllowlisted);
+    Ok(())
+}
+
+/// Enforces the pool's `max_donor_share_bps` on a donor's post-donation balance. A donor holding
+/// every share (the pool's first donor) is exempt.
+fn require_within_donor_share_cap(pool: &Pool, donor: &Donor) -> Result<()> {
+    if pool.max_donor_share_bps == 0 || donor.shares == pool.total_shares {
+        return Ok(());
+    }
+    let cap = mul_div_floor(pool.total_shares, pool.max_donor_share_bps as u64, BPS_DENOM)?;
+    require!(donor.shares <= cap, CtoError::DonorShareCapExceeded);
+    Ok(())
+}
+
+// ===== Campaign helpers =====
+
+/// A campaign pool whose goal hasn't been reached (yet): no proposals.
+fn campaign_pending(pool: &Pool) -> bool {
+    pool.campaign_goal_lamports > 0 && !pool.campaign_goal_reached
+}
+
+/// Deadline passed without reaching the goal: donations closed, refunds open.
+fn campaign_failed(pool: &Pool, now: i64) -> bool {
+    campaign_pending(pool) && now > pool.campaign_deadline_ts
+}
+
+/// Withdrawals wait for a campaign to either reach its goal or fail.
+fn require_campaign_settled(pool: &Pool, now: i64) -> Result<()> {
+    require!(!campaign_pending(pool) || campaign_failed(pool, now), CtoError::CampaignInProgress);
+    Ok(())
+}
+
+// ===== Oracle helpers =====
+
+/// Price read from a Pyth PriceUpdateV2 account: `price * 10^exponent` units of the quote.
+struct OraclePrice {
+    price: i64,
+    conf: u64,
+    exponent: i32,
+}
+
+/// Reads a fully verified, fresh Pyth price with a tight enough confidence interval.
+fn read_pyth_price(feed: &AccountInfo, now: i64) -> Result<OraclePrice> {
+    require_keys_eq!(*feed.owner, PYTH_RECEIVER_PROGRAM_ID, CtoError::InvalidOraclePrice);
+    let data = feed.try_borrow_data().map_err(|_| CtoError::InvalidAccountData)?;
+    // discriminator, write_authority, verification_level (1 = Full), then the price message
+    require!(data.len() >= 8 + 32 + 1 + 32 + 8 + 8 + 4 + 8, CtoError::InvalidOraclePrice);
+    require!(data[..8] == PYTH_PRICE_UPDATE_DISCRIMINATOR, CtoError::InvalidOraclePrice);
+    require!(data[40] == 1, CtoError::InvalidOraclePrice);
+
+    let msg = &data[41 + 32..];
+    let read_i64 = |at: usize| i64::from_le_bytes(msg[at..at + 8].try_into().unwrap());
+    let price = read_i64(0);
+    let conf = u64::from_le_bytes(msg[8..16].try_into().unwrap());
+    let exponent = i32::from_le_bytes(msg[16..20].try_into().unwrap());
+    let publish_time = read_i64(20);
+
+    require!(price > 0, CtoError::InvalidOraclePrice);
+    require!(now.saturating_sub(publish_time) <= MAX_ORACLE_AGE_SECONDS, CtoError::StaleOraclePrice);
+    require!(
+        (conf as u128) * (BPS_DENOM as u128) <= (price as u128) * (MAX_ORACLE_CONF_BPS as u128),
+        CtoError::InvalidOraclePrice
+    );
+    Ok(OraclePrice { price, conf, exponent })
+}
+
+/// Lamport value of `amount` base units of a token, valued conservatively: the token at the
+/// bottom of its confidence interval, SOL at the top.
+fn oracle_value_lamports(amount: u64, decimals: u8, token_usd: &OraclePrice, sol_usd: &OraclePrice) -> Result<u64> {
+    let token_price = (token_usd.price as u128).saturating_sub(token_usd.conf as u128);
+    let sol_price = (sol_usd.price as u128)
+        .checked_add(sol_usd.conf as u128)
+        .ok_or(CtoError::MathOverflow)?;
+    require!(token_price > 0, CtoError::InvalidOraclePrice);
+
+    let mut num = (amount as u128)
+        .checked_mul(token_price)
+        .and_then(|v| v.checked_mul(LAMPORTS_PER_SOL as u128))
+        .ok_or(CtoError::MathOverflow)?;
+    let mut den = sol_price
+        .checked_mul(10u128.checked_pow(decimals as u32).ok_or(CtoError::MathOverflow)?)
+        .ok_or(CtoError::MathOverflow)?;
+    let exp_diff = token_usd.exponent - sol_usd.exponent;
+    let scale = 10u128.checked_pow(exp_diff.unsigned_abs()).ok_or(CtoError::MathOverflow)?;
+    if exp_diff >= 0 {
+        num = num.checked_mul(scale).ok_or(CtoError::MathOverflow)?;
+    } else {
+        den = den.checked_mul(scale).ok_or(CtoError::MathOverflow)?;
+    }
+    u64::try_from(num / den).map_err(|_| CtoError::MathOverflow.into())
+}
+
+/// Pyth `(CTOP/USD, SOL/USD)` for the buy & burn price band, or `None` when the band is off.
+fn burn_oracle_prices<'info>(
+    ctx: &Context<ProcessBuyAndBurn<'info>>,
+    now: i64,
+) -> Result<Option<(OraclePrice, OraclePrice)>> {
+    if ctx.accounts.pool.burn_price_band_bps == 0 {
+        return Ok(None);
+    }
+    let ctop_feed = ctx
+        .accounts
+        .ctop_usd_price_feed
+        .as_ref()
+        .ok_or(CtoError::InvalidOracleGuardConfig)?;
+    let sol_feed = ctx
+        .accounts
+        .sol_usd_price_feed
+        .as_ref()
+        .ok_or(CtoError::InvalidOracleGuardConfig)?;
+    let ctop_usd = read_pyth_price(&ctop_feed.to_account_info(), now)?;
+    let sol_usd = read_pyth_price(&sol_feed.to_account_info(), now)?;
+    Ok(Some((ctop_usd, sol_usd)))
+}
+
+/// Fails unless `ctop_bought` is worth, at oracle prices, at least `amount_sol` less the pool's
+/// `burn_price_band_bps`, i.e. the swap did not execute far below the oracle price.
+fn require_burn_price_in_band(
+    pool: &Pool,
+    amount_sol: u64,
+    ctop_bought: u64,
+    ctop_decimals: u8,
+    ctop_usd: &OraclePrice,
+    sol_usd: &OraclePrice,
+) -> Result<()> {
+    let bought_value = oracle_value_lamports(ctop_bought, ctop_decimals, ctop_usd, sol_usd)?;
+    let floor = mul_div_floor(
+        amount_sol,
+        BPS_DENOM - pool.burn_price_band_bps as u64,
+        BPS_DENOM,
+    )?;
+    require!(bought_value >= floor, CtoError::BurnPriceOutOfBand);
+    Ok(())
+}
+
+/// Fails while the stake pool's LST rate deviates from its last-epoch rate by more than the
+/// pool's `lst_rate_band_bps` (no-op when the band is off, the pool is native or the stake pool
+/// has no history).
+fn require_lst_rate_in_band(pool: &Pool, stake_pool_ai: &AccountInfo) -> Result<()> {
+    if pool.lst_rate_band_bps == 0 || pool.custody == PoolCustody::Native {
+        return Ok(());
+    }
+    let stake_pool = read_stake_pool(stake_pool_ai)?;
+    if stake_pool.last_epoch_total_lamports == 0
+        || stake_pool.last_epoch_pool_token_supply == 0
+        || stake_pool.pool_token_supply == 0
+    {
+        return Ok(());
+    }
+
+    // rate = total_lamports / pool_token_supply; compare cross-multiplied to stay in integers.
+    let current = (stake_pool.total_lamports as u128)
+        .checked_mul(stake_pool.last_epoch_pool_token_supply as u128)
+        .ok_or(CtoError::MathOverflow)?;
+    let reference = (stake_pool.last_epoch_total_lamports as u128)
+        .checked_mul(stake_pool.pool_token_supply as u128)
+        .ok_or(CtoError::MathOverflow)?;
+    let deviation = current.abs_diff(reference);
+    require!(
+        deviation
+            .checked_mul(BPS_DENOM as u128)
+            .ok_or(CtoError::MathOverflow)?
+            <= reference
+                .checked_mul(pool.lst_rate_band_bps as u128)
+                .ok_or(CtoError::MathOverflow)?,
+        CtoError::LstRateDeviation
+    );
+    Ok(())
+}
+
+/// Shares that can vote: share tokens sit outside any Donor account until redeemed.
+fn voting_shares(pool: &Pool) -> Result<u64> {
+    pool.total_shares
+        .checked_sub(pool.tokenized_shares)
+        .and_then(|shares| shares.checked_sub(pool.queued_shares))
+        .ok_or(CtoError::MathOverflow.into())
+}
+
+// ===== Proposal admission helpers =====
+
+/// Shared body of `create_config_proposal` and `create_sunset_proposal`: a proposal that takes
+/// the proposal slot, locks no liquidity and uses the pool as its destination.
+fn open_pool_level_proposal(
+    ctx: Context<CreateConfigProposal>,
+    kind: ProposalKind,
+    params: ConfigChangeParams,
+    liquidity_amount: u64,
+    title: String,
+    description: String,
+    voting_period_seconds: i64,
+) -> Result<()> {
+    require!(
+        voting_period_seconds >= ctx.accounts.pool.min_voting_seconds
+            && voting_period_seconds <= ctx.accounts.pool.max_voting_seconds,
+        CtoError::InvalidVotingPeriod
+    );
+    validate_proposal_text(&title, &description, Proposal::TITLE_MAX, Proposal::DESC_MAX)?;
+
+    let clock = Clock::get()?;
+    let pool_key = ctx.accounts.pool.key();
+
+    // Escalating proposer penalty (uncapped). Collected into pool PDA.
+    let proposer_fee = penalty_fee(ctx.accounts.pool.base_penalty_lamports, ctx.accounts.donor.propose_strike_count);
+    if proposer_fee > 0 {
+        invoke(
+            &system_instruction::transfer(&ctx.accounts.proposer_wallet.key(), &pool_key, proposer_fee),
+            &[ctx.accounts.proposer_wallet.to_account_info(), ctx.accounts.pool.to_account_info(), ctx.accounts.system_program.to_account_info()],
+        ).map_err(|_| CtoError::LamportTransferFailed)?;
+    }
+
+    let pool = &mut ctx.accounts.pool;
+    let donor = &ctx.accounts.donor;
+    let proposal = &mut ctx.accounts.proposal;
+
+    require_can_propose(pool, donor, ctx.accounts.proposer_wallet.key(), &clock)?;
+    require!(
+        clock.unix_timestamp >= ctx.accounts.destination_record.cooldown_until_ts,
+        CtoError::DestinationCooldownActive
+    );
+
+    ctx.accounts.pool_lst_account.reload()?;
+    pool.total_pool_tokens = observed_pool_tokens(pool, &ctx.accounts.pool_lst_account);
+    let stake_pool_state = read_pool_rate(pool, &ctx.accounts.stake_pool)?;
+    require_proposer_value(pool, donor, &stake_pool_state)?;
+
+    let destination_record = &mut ctx.accounts.destination_record;
+    destination_record.pool = pool_key;
+    destination_record.destination = pool_key;
+
+    proposal.version = PROPOSAL_VERSION;
+    proposal.pool = pool_key;
+    proposal.proposer_wallet = ctx.accounts.proposer_wallet.key();
+    proposal.kind = kind;
+    proposal.requested_lamports = 0;
+    proposal.denomination = RequestDenomination::Lamports;
+    proposal.requested_bps = 0;
+    proposal.destination_wallet = pool_key;
+    proposal.destination_kind = DestinationKind::None;
+    proposal.category = ProposalCategory::General;
+    proposal.template = Pubkey::default();
+    proposal.title = title;
+    proposal.description = description;
+
+    proposal.created_at_ts = clock.unix_timestamp;
+    proposal.sponsors_required = pool.required_sponsors;
+    proposal.sponsor_count = 0;
+    proposal.voting_period_seconds = voting_period_seconds;
+    proposal.deadline_ts = clock
+        .unix_timestamp
+        .checked_add(if pool.required_sponsors > 0 { SPONSOR_WINDOW_SECONDS } else { voting_period_seconds })
+        .ok_or(CtoError::MathOverflow)?;
+    proposal.snapshot_slot = clock.slot;
+    proposal.total_snapshot_shares = voting_shares(pool)?;
+    proposal.fast_track = false;
+    proposal.quorum_bps = effective_quorum_bps(pool);
+    proposal.max_voter_bps = pool.max_voter_bps;
+    proposal.queued_until_ts = 0;
+    proposal.bond_lamports = 0;
+    proposal.bond_spam_threshold_bps = 0;
+    proposal.reveal_seconds = 0;
+    proposal.snipe_extension_seconds = pool.snipe_extension_seconds;
+    proposal.snipe_extensions_left = pool.max_snipe_extensions;
+    proposal.vote_ramp_slots = pool.vote_ramp_slots;
+    proposal.vote_weighting = pool.vote_weighting;
+    proposal.payout_mint = Pubkey::default();
+    proposal.payout_token_amount = 0;
+    proposal.liquidity_amount = liquidity_amount;
+    proposal.weight_root_total = 0;
+    proposal.merkle_weight_proven = 0;
+    proposal.merkle_weights = pool.merkle_voting;
+    proposal.cap_adjusted_quorum = pool.cap_adjusted_quorum;
+    proposal.excluded_cap_weight = 0;
+    proposal.weight_root = [0u8; 32];
+    proposal.config_change = params.clone();
+    proposal.milestone = MilestoneSchedule::default();
+    proposal.stream = StreamSchedule::default();
+    proposal.budget = BudgetSchedule::default();
+
+    proposal.locked_pool_tokens = 0;
+
+    proposal.yes_weight = 0;
+    proposal.no_weight = 0;
+    proposal.abstain_weight = 0;
+    proposal.participation_weight = 0;
+    proposal.status = ProposalStatus::Active;
+    proposal.finalized_slot = 0;
+    proposal.tie_approved = false;
+    proposal.tie_extended = false;
+    proposal.quorum_grace_used = false;
+    proposal.abort_voter_1 = Pubkey::default();
+    proposal.abort_voter_2 = Pubkey::default();
+    proposal.abort_count = 0;
+
+    let index_page = &mut ctx.accounts.proposal_index_page;
+    if index_page.proposals.is_empty() {
+        index_page.pool = pool_key;
+        index_page.page_index = pool.proposal_count / ProposalIndexPage::CAPACITY as u64;
+    }
+    index_page.proposals.push(proposal.key());
+
+    pool.active_proposal = Some(proposal.key());
+    pool.proposal_snapshot_slot = proposal.snapshot_slot;
+    pool.proposal_count = pool.proposal_count.checked_add(1).ok_or(CtoError::MathOverflow)?;
+    pool.last_engagement_ts = clock.unix_timestamp;
+    record_proposal_created(&mut ctx.accounts.donor, clock.slot);
+
+    record_activity(
+        &mut ctx.accounts.activity_log,
+        ActivityKind::Propose,
+        0,
+        ctx.accounts.proposer_wallet.key(),
+        0,
+        clock.slot,
+    );
+
+    if kind == ProposalKind::Sunset {
+        emit_cpi!(SunsetProposedEvent {
+            pool: pool_key,
+            proposal: proposal.key(),
+            proposer: ctx.accounts.proposer_wallet.key(),
+            quorum_bps: proposal.quorum_bps,
+            deadline_ts: proposal.deadline_ts,
+            timestamp: clock.unix_timestamp,
+        });
+    } else if kind == ProposalKind::RemoveLiquidity {
+        emit_cpi!(LiquidityRemovalProposedEvent {
+            pool: pool_key,
+            proposal: proposal.key(),
+            proposer: ctx.accounts.proposer_wallet.key(),
+            lp_amount: liquidity_amount,
+            quorum_bps: proposal.quorum_bps,
+            deadline_ts: proposal.deadline_ts,
+            timestamp: clock.unix_timestamp,
+        });
+    } else {
+        emit_cpi!(ConfigChangeProposedEvent {
+            pool: pool_key,
+            proposal: proposal.key(),
+            proposer: ctx.accounts.proposer_wallet.key(),
+            params,
+            quorum_bps: proposal.quorum_bps,
+            deadline_ts: proposal.deadline_ts,
+            timestamp: clock.unix_timestamp,
+        });
+    }
+
+    Ok(())
+}
+
+/// Pool- and donor-level checks for any proposal that takes the pool's proposal slot.
+fn require_can_propose(pool: &Pool, donor: &Donor, proposer: Pubkey, clock: &Clock) -> Result<()> {
+    require!(pool.active_proposal.is_none(), CtoError::ActiveProposalExists);
+    require!(pool.sunset_started_ts == 0, CtoError::PoolSunsetting);
+    require!(!campaign_pending(pool), CtoError::CampaignGoalNotReached);
+    // Global cooldown after an Abort to allow withdrawals.
+    require!(clock.unix_timestamp >= pool.proposal_cooldown_until_ts, CtoError::ProposalCooldownActive);
+    // Pool-wide gap after any finalized proposal: a predictable window with nothing reserved.
+    require!(
+        clock.unix_timestamp
+            >= pool
+                .last_proposal_finalized_ts
+                .checked_add(pool.proposal_gap_seconds)
+                .ok_or(CtoError::MathOverflow)?,
+        CtoError::ProposalCooldownActive
+    );
+    require!(donor.shares > 0, CtoError::NoShares);
+    require!(donor.share_scale == pool.share_scale, CtoError::ShareScaleMigrationRequired);
+
+    // Per-donor gap between proposals, counted from the donor's last one.
+    if pool.donor_proposal_cooldown_slots > 0 && donor.proposals_created > 0 {
+        require!(
+            clock.slot
+                >= donor
+                    .last_proposal_slot
+                    .checked_add(pool.donor_proposal_cooldown_slots)
+                    .ok_or(CtoError::MathOverflow)?,
+            CtoError::DonorProposalCooldownActive
+        );
+    }
+
+    // Block single-donor governance
+    require!(pool.total_shares != donor.shares, CtoError::SingleDonorCannotPropose);
+
+    // Holding delay for non-creator
+    if pool.creator != proposer {
+        let slots_since = clock
+            .slot
+            .checked_sub(donor.last_shares_change_slot)
+            .ok_or(CtoError::MathOverflow)?;
+        require!(slots_since >= pool.proposal_delay_slots, CtoError::SharesTooRecent);
+    }
+    Ok(())
+}
+
+/// Stamps a new proposal on its proposer's Donor for the per-donor cooldown.
+fn record_proposal_created(donor: &mut Donor, slot: u64) {
+    donor.last_proposal_slot = slot;
+    donor.proposals_created = donor.proposals_created.saturating_add(1);
+}
+
+/// Enforce proposer value >= min_proposer_deposit_lamports at the current withdraw rate.
+/// `pool.total_pool_tokens` must already be synced to the LST account.
+fn require_proposer_value(pool: &Pool, donor: &Donor, stake_pool_state: &StakePool) -> Result<()> {
+    let proposer_pool_tokens = ((donor.shares as u128)
+        .checked_mul(pool.total_pool_tokens as u128)
+        .ok_or(CtoError::MathOverflow)?)
+        .checked_div(pool.total_shares as u128)
+        .ok_or(CtoError::MathOverflow)?;
+    let proposer_pool_tokens = u64::try_from(proposer_pool_tokens).map_err(|_| CtoError::MathOverflow)?;
+    let proposer_value_lamports = stake_pool_state
+        .calc_lamports_withdraw_amount(proposer_pool_tokens)
+        .ok_or(CtoError::MathOverflow)?;
+    require!(
+        proposer_value_lamports >= pool.min_proposer_deposit_lamports,
+        CtoError::ProposerTooSmall
+    );
+    Ok(())
+}
+
+/// Bounds a ConfigChange proposal must respect; at least one value must be set.
+fn validate_config_change(pool: &Pool, params: &ConfigChangeParams) -> Result<()> {
+    require!(
+        params.fee_sink.is_some()
+            || params.quorum_bps.is_some()
+            || params.protocol_fee_bps.is_some()
+            || params.min_proposer_deposit_lamports.is_some()
+            || params.proposal_delay_slots.is_some()
+            || params.cancel_budget.is_some()
+            || params.admin_call.is_some()
+            || params.paused.is_some()
+            || params.metadata_hash.is_some(),
+        CtoError::InvalidConfigChange
+    );
+    if let Some(admin_call) = params.admin_call {
+        require!(admin_call != [0u8; 32], CtoError::InvalidConfigChange);
+    }
+    if let Some(quorum_bps) = params.quorum_bps {
+        require!(
+            (MIN_QUORUM_BPS..=MAX_QUORUM_BPS).contains(&quorum_bps),
+            CtoError::InvalidConfigChange
+        );
+    }
+    if let Some(fee_bps) = params.protocol_fee_bps {
+        require!(fee_bps <= MAX_PROTOCOL_FEE_BPS, CtoError::InvalidConfigChange);
+    }
+    if let Some(deposit) = params.min_proposer_deposit_lamports {
+        require!(deposit > 0, CtoError::InvalidConfigChange);
+    }
+    if let Some(delay) = params.proposal_delay_slots {
+        require!(
+            delay >= MIN_PROPOSAL_DELAY_SLOTS && delay <= MAX_PROPOSAL_DELAY_SLOTS,
+            CtoError::InvalidConfigChange
+        );
+    }
+    require!(
+        params.fee_sink.is_some() || params.fee_sink_destination.is_none(),
+        CtoError::InvalidConfigChange
+    );
+    if let Some(sink) = params.fee_sink {
+        validate_fee_sink(pool, sink, params.fee_sink_destination.unwrap_or_default())?;
+    }
+    Ok(())
+}
+
+/// `FeeSink::Burn` takes no destination; every other sink needs a real wallet distinct from the
+/// pool and its dev wallet.
+fn validate_fee_sink(pool: &Pool, sink: FeeSink, destination: Pubkey) -> Result<()> {
+    if sink == FeeSink::Burn {
+        require!(destination == Pubkey::default(), CtoError::InvalidFeeSink);
+    } else {
+        require!(
+            destination != Pubkey::default()
+                && destination != pool_address(&pool.token_mint).0
+                && destination != pool.dev_fee_wallet,
+            CtoError::InvalidFeeSink
+        );
+    }
+    Ok(())
+}
+
+/// Writes every value set in `params` to the pool. `cancel_budget` and `metadata_hash` concern
+/// other accounts and are applied by `cancel_recurring_budget` and `update_pool_metadata`. The
+/// fee sink is re-validated, since the dev wallet may have changed since the proposal was made.
+fn apply_config_change(pool: &mut Pool, params: &ConfigChangeParams) -> Result<()> {
+    if let Some(quorum_bps) = params.quorum_bps {
+        pool.quorum_bps = quorum_bps;
+    }
+    if let Some(fee_bps) = params.protocol_fee_bps {
+        pool.protocol_fee_bps = fee_bps;
+    }
+    if let Some(deposit) = params.min_proposer_deposit_lamports {
+        pool.min_proposer_deposit_lamports = deposit;
+    }
+    if let Some(delay) = params.proposal_delay_slots {
+        pool.proposal_delay_slots = delay;
+    }
+    if let Some(admin_call) = params.admin_call {
+        pool.approved_admin_call = admin_call;
+    }
+    if let Some(paused) = params.paused {
+        pool.paused = paused;
+    }
+    if let Some(sink) = params.fee_sink {
+        let destination = params.fee_sink_destination.unwrap_or_default();
+        validate_fee_sink(pool, sink, destination)?;
+        pool.fee_sink = sink;
+        pool.fee_sink_destination = destination;
+    }
+    Ok(())
+}
+
+// ===== Poll helpers =====
+
+/// Initializes `proposal` as a vote-only proposal of `kind` in the pool's poll slot: same snapshot,
+/// quorum and index as a payout proposal, but nothing locked. The caller sets title/description.
+fn open_poll(
+    pool: &mut Account<Pool>,
+    donor: &Donor,
+    proposal: &mut Account<Proposal>,
+    index_page: &mut Account<ProposalIndexPage>,
+    proposer: Pubkey,
+    kind: ProposalKind,
+    voting_period_seconds: i64,
+) -> Result<()> {
+    require!(
+        voting_period_seconds >= pool.min_voting_seconds && voting_period_seconds <= pool.max_voting_seconds,
+        CtoError::InvalidVotingPeriod
+    );
+    let clock = Clock::get()?;
+
+    require!(pool.active_poll.is_none(), CtoError::ActivePollExists);
+    require!(pool.sunset_started_ts == 0, CtoError::PoolSunsetting);
+    require!(donor.shares > 0, CtoError::NoShares);
+    require!(donor.share_scale == pool.share_scale, CtoError::ShareScaleMigrationRequired);
+    require!(pool.total_shares != donor.shares, CtoError::SingleDonorCannotPropose);
+
+    // Holding delay for non-creator
+    if pool.creator != proposer {
+        let slots_since = clock
+            .slot
+            .checked_sub(donor.last_shares_change_slot)
+            .ok_or(CtoError::MathOverflow)?;
+        require!(slots_since >= pool.proposal_delay_slots, CtoError::SharesTooRecent);
+    }
+
+    proposal.version = PROPOSAL_VERSION;
+    proposal.pool = pool.key();
+    proposal.proposer_wallet = proposer;
+    proposal.kind = kind;
+    proposal.requested_lamports = 0;
+    proposal.denomination = RequestDenomination::Lamports;
+    proposal.requested_bps = 0;
+    proposal.destination_wallet = Pubkey::default();
+    proposal.destination_kind = DestinationKind::None;
+    proposal.category = ProposalCategory::General;
+    proposal.template = Pubkey::default();
+    proposal.sponsors_required = 0;
+    proposal.sponsor_count = 0;
+    proposal.voting_period_seconds = voting_period_seconds;
+
+    proposal.created_at_ts = clock.unix_timestamp;
+    proposal.deadline_ts = clock
+        .unix_timestamp
+        .checked_add(voting_period_seconds)
+        .ok_or(CtoError::MathOverflow)?;
+    proposal.snapshot_slot = clock.slot;
+    proposal.total_snapshot_shares = voting_shares(pool)?;
+    proposal.fast_track = false;
+    proposal.quorum_bps = effective_quorum_bps(pool);
+    proposal.max_voter_bps = pool.max_voter_bps;
+    proposal.queued_until_ts = 0;
+    proposal.bond_lamports = 0;
+    proposal.bond_spam_threshold_bps = 0;
+    proposal.reveal_seconds = 0;
+    proposal.snipe_extension_seconds = 0;
+    proposal.snipe_extensions_left = 0;
+    proposal.vote_ramp_slots = pool.vote_ramp_slots;
+    proposal.vote_weighting = pool.vote_weighting;
+    proposal.payout_mint = Pubkey::default();
+    proposal.payout_token_amount = 0;
+    proposal.liquidity_amount = 0;
+    proposal.weight_root_total = 0;
+    proposal.merkle_weight_proven = 0;
+    proposal.merkle_weights = pool.merkle_voting;
+    proposal.cap_adjusted_quorum = pool.cap_adjusted_quorum;
+    proposal.excluded_cap_weight = 0;
+    proposal.weight_root = [0u8; 32];
+    proposal.config_change = ConfigChangeParams::default();
+    proposal.milestone = MilestoneSchedule::default();
+    proposal.stream = StreamSchedule::default();
+    proposal.budget = BudgetSchedule::default();
+
+    proposal.locked_pool_tokens = 0;
+
+    proposal.yes_weight = 0;
+    proposal.no_weight = 0;
+    proposal.abstain_weight = 0;
+    proposal.participation_weight = 0;
+    proposal.status = ProposalStatus::Active;
+    proposal.finalized_slot = 0;
+    proposal.tie_approved = false;
+    proposal.tie_extended = false;
+    proposal.quorum_grace_used = false;
+    proposal.abort_voter_1 = Pubkey::default();
+    proposal.abort_voter_2 = Pubkey::default();
+    proposal.abort_count = 0;
+
+    // Polls share the proposal numbering and index with payout proposals.
+    if index_page.proposals.is_empty() {
+        index_page.pool = pool.key();
+        index_page.page_index = pool.proposal_count / ProposalIndexPage::CAPACITY as u64;
+    }
+    index_page.proposals.push(proposal.key());
+
+    pool.active_poll = Some(proposal.key());
+    pool.poll_snapshot_slot = proposal.snapshot_slot;
+    pool.proposal_count = pool.proposal_count.checked_add(1).ok_or(CtoError::MathOverflow)?;
+    pool.last_engagement_ts = clock.unix_timestamp;
+
+    Ok(())
+}
+
+/// Checks a payout request against an adopted template.
+fn check_template_request(
+    template: &ProposalTemplate,
+    requested_amount: u64,
+    denomination: RequestDenomination,
+    destination_wallet: Pubkey,
+) -> Result<()> {
+    require!(template.status == TemplateStatus::Active, CtoError::TemplateNotActive);
+    require!(
+        template.destination_wallet == destination_wallet && template.denomination == denomination,
+        CtoError::TemplateMismatch
+    );
+    require!(
+        requested_amount >= template.min_amount && requested_amount <= template.max_amount,
+        CtoError::OutsideTemplateBounds
+    );
+    Ok(())
+}
+
+// ===== Proposal text helpers =====
+
+/// Checks title/description against the account's reserved space and rejects control characters,
+/// so oversized or terminal-garbling text fails with a clear error instead of at serialization.
+/// Descriptions may contain newlines and tabs; titles are single-line.
+fn validate_proposal_text(title: &str, description: &str, title_max: usize, desc_max: usize) -> Result<()> {
+    require!(title.len() <= title_max, CtoError::TitleTooLong);
+    require!(description.len() <= desc_max, CtoError::DescriptionTooLong);
+    require!(!title.chars().any(char::is_control), CtoError::InvalidTitleCharacters);
+    require!(
+        !description.chars().any(|c| c.is_control() && c != '\n' && c != '\t'),
+        CtoError::InvalidDescriptionCharacters
+    );
+    Ok(())
+}
+
+/// Length and character checks for a donation's memo and campaign tag.
+fn validate_donation_memo(memo: &str, campaign_tag: &str) -> Result<()> {
+    require!(
+        memo.len() <= DONATION_MEMO_MAX && campaign_tag.len() <= CAMPAIGN_TAG_MAX,
+        CtoError::InvalidDonationMemo
+    );
+    require!(
+        !memo.chars().any(char::is_control) && !campaign_tag.chars().any(char::is_control),
+        CtoError::InvalidDonationMemo
+    );
+    Ok(())
+}
+
+/// Length and character checks for PoolMetadata text.
+fn validate_pool_metadata(fields: &PoolMetadataFields) -> Result<()> {
+    require!(
+        fields.name.len() <= PoolMetadata::NAME_MAX
+            && fields.symbol.len() <= PoolMetadata::SYMBOL_MAX
+            && fields.description.len() <= PoolMetadata::DESC_MAX
+            && fields.uri.len() <= PoolMetadata::URI_MAX,
+        CtoError::InvalidPoolMetadata
+    );
+    require!(
+        !fields.name.chars().any(char::is_control)
+            && !fields.symbol.chars().any(char::is_control)
+            && !fields.uri.chars().any(char::is_control)
+            && !fields.description.chars().any(|c| c.is_control() && c != '\n' && c != '\t'),
+        CtoError::InvalidPoolMetadata
+    );
+    Ok(())
+}
+
+/// sha256 of the Borsh-encoded fields; what a ConfigChange's `metadata_hash` commits to.
+fn pool_metadata_hash(fields: &PoolMetadataFields) -> Result<[u8; 32]> {
+    let mut data = Vec::new();
+    fields.serialize(&mut data)?;
+    Ok(solana_program::hash::hash(&data).to_bytes())
+}
+
+// ===== Merkle helpers =====
+
+/// Leaf committing a wallet to its snapshot vote weight.
+fn weight_leaf(wallet: &Pubkey, weight: u64) -> [u8; 32] {
+    solana_program::hash::hashv(&[&[0u8], wallet.as_ref(), &weight.to_le_bytes()]).to_bytes()
+}
+
+/// Verifies a sorted-pair proof (no position bits); interior nodes are prefixed with 0x01 so a
+/// node can never be passed off as a leaf.
+fn verify_merkle_proof(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        let (a, b) = if node <= *sibling { (node, *sibling) } else { (*sibling, node) };
+        solana_program::hash::hashv(&[&[1u8], a.as_ref(), b.as_ref()]).to_bytes()
+    });
+    computed == root
+}
+
+// ===== Commit–reveal helpers =====
+
+/// Commitment `commit_vote` expects for a ballot; binding the proposal and voter keeps a
+/// commitment from being copied to another proposal or wallet.
+fn vote_commitment_hash(proposal: &Pubkey, voter: &Pubkey, choice: VoteChoice, salt: &[u8; 32]) -> [u8; 32] {
+    solana_program::hash::hashv(&[proposal.as_ref(), voter.as_ref(), &[choice as u8], salt]).to_bytes()
+}
+
+// ===== Authority helpers =====
+
+/// True once the dead-man switch is enabled and the authority has missed its heartbeat window.
+fn authority_lapsed(pool: &Pool, epoch: u64) -> bool {
+    pool.dead_man_epochs > 0 && epoch.saturating_sub(pool.last_heartbeat_epoch) > pool.dead_man_epochs
+}
+
+/// True once the pool's authority expiry (if any) has passed.
+fn authority_expired(pool: &Pool, now: i64) -> bool {
+    pool.authority_expires_at_ts > 0 && now >= pool.authority_expires_at_ts
+}
+
+/// Checks `signer` is the pool authority and the authority has neither lapsed nor expired.
+///
+/// Once it has, the call instead needs the approval a passed ConfigChange recorded in
+/// `approved_admin_call` (see `consume_admin_call_approval`), and any signer may submit it.
+fn require_live_authority(pool: &mut Pool, signer: Pubkey, remaining_accounts: &[AccountInfo]) -> Result<()> {
+    let clock = Clock::get()?;
+    if authority_expired(pool, clock.unix_timestamp) || authority_lapsed(pool, clock.epoch) {
+        return consume_admin_call_approval(pool, signer, remaining_accounts);
+    }
+    require!(signer == pool.authority, CtoError::UnauthorizedAuthority);
+    Ok(())
+}
+
+/// Hash a ConfigChange's `admin_call` must match: sha256 of the instruction data followed by the
+/// instruction's account keys in order, leaving out `signer` and the Instructions sysvar.
+fn admin_call_hash(ix: &Instruction, signer: Pubkey) -> [u8; 32] {
+    let mut parts: Vec<&[u8]> = vec![&ix.data];
+    for meta in &ix.accounts {
+        if meta.pubkey != signer && meta.pubkey != sysvar_instructions::ID {
+            parts.push(meta.pubkey.as_ref());
+        }
+    }
+    solana_program::hash::hashv(&parts).to_bytes()
+}
+
+/// Authority-gated call after the authority expired or lapsed: the top-level instruction (read
+/// from the Instructions sysvar, passed as the last remaining account) must be the one the pool's
+/// latest passed ConfigChange approved. Each approval is good for one call.
+fn consume_admin_call_approval(pool: &mut Pool, signer: Pubkey, remaining_accounts: &[AccountInfo]) -> Result<()> {
+    require!(pool.approved_admin_call != [0u8; 32], CtoError::AdminCallNotApproved);
+    let instructions = remaining_accounts
+        .last()
+        .filter(|account| account.key() == sysvar_instructions::ID)
+        .ok_or(CtoError::AdminCallNotApproved)?;
+    let index = sysvar_instructions::load_current_index_checked(instructions)?;
+    let current = sysvar_instructions::load_instruction_at_checked(index as usize, instructions)?;
+    require!(current.program_id == crate::ID, CtoError::AdminCallNotApproved);
+    require!(
+        admin_call_hash(&current, signer) == pool.approved_admin_call,
+        CtoError::AdminCallNotApproved
+    );
+    pool.approved_admin_call = [0u8; 32];
+    Ok(())
+}
+
+// ===== Buy & burn helpers =====
+
+/// Creates whichever of `process_buy_and_burn`'s WSOL, CTOP and incinerator token accounts don't
+/// exist yet, with the cranker paying rent. Returns the lamports the cranker paid, so only rent
+/// for accounts this crank actually created is owed back; accounts that already existed, whoever
+/// created them, cost nothing.
+fn create_buy_and_burn_accounts<'info>(ctx: &Context<ProcessBuyAndBurn<'info>>) -> Result<u64> {
+    let cranker = ctx.accounts.cranker.to_account_info();
+    let cranker_lamports_before = cranker.lamports();
+    let pool_key = ctx.accounts.pool.key();
+    let ctop_mint = ctx.accounts.ctop_mint.key();
+
+    create_pool_token_account(
+        &cranker,
+        &ctx.accounts.pool_wsol_account,
+        &ctx.accounts.wsol_mint.to_account_info(),
+        &ctx.accounts.pool.to_account_info(),
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &[&[b"pool_wsol", pool_key.as_ref(), &[ctx.bumps.pool_wsol_account]]],
+    )?;
+    create_pool_token_account(
+        &cranker,
+        &ctx.accounts.pool_ctop_account,
+        &ctx.accounts.ctop_mint.to_account_info(),
+        &ctx.accounts.pool.to_account_info(),
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &[&[b"pool_ctop", pool_key.as_ref(), ctop_mint.as_ref(), &[ctx.bumps.pool_ctop_account]]],
+    )?;
+    if ctx.accounts.incinerator_ctop_account.data_is_empty() {
+        anchor_spl::associated_token::create_idempotent(CpiContext::new(
+            ctx.accounts.associated_token_program.to_account_info(),
+            anchor_spl::associated_token::Create {
+                payer: cranker.clone(),
+                associated_token: ctx.accounts.incinerator_ctop_account.to_account_info(),
+                authority: ctx.accounts.incinerator.to_account_info(),
+                mint: ctx.accounts.ctop_mint.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            },
+        ))?;
+    }
+
+    Ok(cranker_lamports_before.saturating_sub(cranker.lamports()))
+}
+
+/// Creates a pool-owned SPL Token account at a program PDA (`seeds`) unless it already exists.
+/// Lamports sent to the address beforehand are kept and only topped up to rent exemption, so
+/// pre-funding the address can't block creation.
+fn create_pool_token_account<'info>(
+    payer: &AccountInfo<'info>,
+    account: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    pool: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    seeds: &[&[&[u8]]],
+) -> Result<()> {
+    if !account.data_is_empty() {
+        return Ok(());
+    }
+    let rent = Rent::get()?.minimum_balance(TOKEN_ACCOUNT_SPACE);
+    let funded = account.lamports();
+    if funded == 0 {
+        invoke_signed(
+            &system_instruction::create_account(payer.key, account.key, rent, TOKEN_ACCOUNT_SPACE as u64, &token::ID),
+            &[payer.clone(), account.clone(), system_program.clone()],
+            seeds,
+        )
+        .map_err(|_| CtoError::LamportTransferFailed)?;
+    } else {
+        if rent > funded {
+            invoke(
+                &system_instruction::transfer(payer.key, account.key, rent - funded),
+                &[payer.clone(), account.clone(), system_program.clone()],
+            )
+            .map_err(|_| CtoError::LamportTransferFailed)?;
+        }
+        invoke_signed(
+            &system_instruction::allocate(account.key, TOKEN_ACCOUNT_SPACE as u64),
+            &[account.clone(), system_program.clone()],
+            seeds,
+        )
+        .map_err(|_| CtoError::LamportTransferFailed)?;
+        invoke_signed(
+            &system_instruction::assign(account.key, &token::ID),
+            &[account.clone(), system_program.clone()],
+            seeds,
+        )
+        .map_err(|_| CtoError::LamportTransferFailed)?;
+    }
+    token::initialize_account3(CpiContext::new(
+        token_program.clone(),
+        token::InitializeAccount3 {
+            account: account.clone(),
+            mint: mint.clone(),
+            authority: pool.clone(),
+        },
+    ))
+}
+
+/// Adds SOL to the burn-auction lot. An empty lot restarts the price decay.
+fn fund_burn_auction(pool: &mut Account<Pool>, lamports: u64, now: i64) -> Result<()> {
+    if pool.burn_auction_lamports == 0 {
+        pool.burn_auction_start_ts = now;
+    }
+    pool.burn_auction_lamports = pool
+        .burn_auction_lamports
+        .checked_add(lamports)
+        .ok_or(CtoError::MathOverflow)?;
+    Ok(())
+}
+
+/// True if the pool's `burn_venue` is set and configured to buy the burn target on.
+fn burn_venue_configured(pool: &Pool) -> bool {
+    match pool.burn_venue {
+        BurnVenue::None => false,
+        BurnVenue::PumpSwap => pool.pumpswap_pool_id != Pubkey::default(),
+        BurnVenue::Whirlpool => pool.whirlpool_pool_id != Pubkey::default(),
+        BurnVenue::MeteoraDlmm => pool.meteora_lb_pair != Pubkey::default(),
+        BurnVenue::RaydiumCpmm => pool.raydium_pool_id != Pubkey::default(),
+    }
+}
+
+/// True if `process_buy_and_burn` would buy the pool's own token, as a Buyback needs.
+fn buyback_target_configured(pool: &Pool) -> bool {
+    pool.burn_token_mint == pool.token_mint && burn_venue_configured(pool)
+}
+
+/// Makes `venue` the burn venue, or clears it when `enabled` is false and `venue` is current.
+fn select_burn_venue(pool: &mut Pool, venue: BurnVenue, enabled: bool) {
+    if enabled {
+        pool.burn_venue = venue;
+    } else if pool.burn_venue == venue {
+        pool.burn_venue = BurnVenue::None;
+    }
+}
+
+/// True if `pending_burn_lamports` may be swapped now. Without a schedule any pending amount is
+/// due; with one, the pending amount must reach the threshold or the interval must have elapsed.
+fn burn_due(pool: &Pool, now: i64) -> bool {
+    if pool.pending_burn_lamports == 0 {
+        return false;
+    }
+    if pool.burn_threshold_lamports == 0 && pool.burn_interval_seconds == 0 {
+        return true;
+    }
+    let threshold_hit = pool.burn_threshold_lamports > 0
+        && pool.pending_burn_lamports >= pool.burn_threshold_lamports;
+    let interval_hit = pool.burn_interval_seconds > 0
+        && now.saturating_sub(pool.last_burn_ts) >= pool.burn_interval_seconds;
+    threshold_hit || interval_hit
+}
+
+/// Current burn-auction price in CTOP base units per SOL (linear decay to the floor).
+fn burn_auction_price(pool: &Pool, now: i64) -> Result<u64> {
+    let elapsed = now.saturating_sub(pool.burn_auction_start_ts).max(0);
+    if elapsed >= pool.burn_auction_duration_seconds {
+        return Ok(pool.burn_auction_floor_ctop_per_sol);
+    }
+    let range = pool
+        .burn_auction_start_ctop_per_sol
+        .checked_sub(pool.burn_auction_floor_ctop_per_sol)
+        .ok_or(CtoError::MathOverflow)?;
+    let decayed = mul_div_floor(range, elapsed as u64, pool.burn_auction_duration_seconds as u64)?;
+    pool.burn_auction_start_ctop_per_sol
+        .checked_sub(decayed)
+        .ok_or(CtoError::MathOverflow.into())
+}
+
+/// Attempts to swap SOL for CTOP on PumpSwap and burn to incinerator.
+/// Returns the amount of CTOP burned on success.
+fn attempt_pumpswap_swap_and_burn<'info>(
+    ctx: &mut Context<ProcessBuyAndBurn<'info>>,
+    amount_sol: u64,
+    pool_bump: u8,
+) -> Result<u64> {
+    // Basic safety: ensure we're calling the canonical PumpSwap program id.
+    require!(
+        ctx.accounts.pumpswap_program.key() == PUMPSWAP_PROGRAM_ID,
+        CtoError::InvalidPumpSwapConfig
+    );
+
+    // H-05 FIX: Validate PumpSwap version before swap
+    require!(
+        ctx.accounts.pool.pumpswap_version == PUMPSWAP_EXPECTED_VERSION,
+        CtoError::PumpSwapVersionMismatch
+    );
+
+    if ctx.accounts.pool.pumpswap_hop_enabled {
+        require_swap_compute(2)?;
+        return attempt_pumpswap_two_hop_and_burn(ctx, amount_sol, pool_bump);
+    }
+    require_swap_compute(1)?;
+
+    // Prevent executor “account injection”:
+    // - Vaults must hold the expected mints (CTOP and WSOL).
+    // - Swap output is forced into pool_ctop_account (PDA-owned), and then burned.
+    let ctop_mint = ctx.accounts.ctop_mint.key();
+    validate_pumpswap_vault_mints(ctx, ctop_mint)?;
+
+    // Wrap SOL into WSOL held by the pool PDA
+    wrap_sol_to_wsol(ctx, amount_sol, pool_bump)?;
+
+    // On-chain frontrun protection:
+    // Compute min-out from current vault reserves, then apply slippage bps.
+    let min_ctop = compute_min_out_cpmm_from_vaults(
+        ctx.accounts.pumpswap_pool_quote_vault.amount, // WSOL reserve
+        ctx.accounts.pumpswap_pool_base_vault.amount,  // CTOP reserve
+        amount_sol,
+        PUMPSWAP_FEE_BPS_ESTIMATE,
+        MAX_SLIPPAGE_BPS,
+    )?;
+
+    // Perform PumpSwap buy: spend up to `amount_sol` WSOL, receive at least `min_ctop` CTOP.
+    let leg = PumpSwapLeg {
+        pumpswap_pool: ctx.accounts.pumpswap_pool.to_account_info(),
+        base_mint: ctx.accounts.ctop_mint.to_account_info(),
+        quote_mint: ctx.accounts.wsol_mint.to_account_info(),
+        user_base_token_account: ctx.accounts.pool_ctop_account.to_account_info(),
+        user_quote_token_account: ctx.accounts.pool_wsol_account.to_account_info(),
+        base_vault: ctx.accounts.pumpswap_pool_base_vault.to_account_info(),
+        quote_vault: ctx.accounts.pumpswap_pool_quote_vault.to_account_info(),
+        fee_recipient_token_account: ctx.accounts.pumpswap_protocol_fee_recipient_token_account.to_account_info(),
+        base_token_program: ctx.accounts.pumpswap_base_token_program.to_account_info(),
+        quote_token_program: ctx.accounts.pumpswap_quote_token_program.to_account_info(),
+    };
+    perform_pumpswap_buy(ctx, leg, min_ctop, amount_sol, pool_bump)?;
+
+    // Burn everything acquired by transferring to the incinerator ATA.
+    settle_bought_ctop(ctx, pool_bump)
+}
+
+/// Two-hop variant: SOL -> hop mint on the primary PumpSwap pool, then hop mint -> CTOP on the
+/// configured hop pool. Each leg gets its own on-chain min-out from live vault reserves.
+fn attempt_pumpswap_two_hop_and_burn<'info>(
+    ctx: &mut Context<ProcessBuyAndBurn<'info>>,
+    amount_sol: u64,
+    pool_bump: u8,
+) -> Result<u64> {
+    let hop_mint = ctx.accounts.hop_mint.as_ref().ok_or(CtoError::InvalidPumpSwapHopConfig)?;
+    let pool_hop_account = ctx.accounts.pool_hop_account.as_ref().ok_or(CtoError::InvalidPumpSwapHopConfig)?;
+    let hop_pool = ctx.accounts.pumpswap_hop_pool.as_ref().ok_or(CtoError::InvalidPumpSwapHopConfig)?;
+    let hop_base_vault = ctx.accounts.pumpswap_hop_base_vault.as_ref().ok_or(CtoError::InvalidPumpSwapHopConfig)?;
+    let hop_quote_vault = ctx.accounts.pumpswap_hop_quote_vault.as_ref().ok_or(CtoError::InvalidPumpSwapHopConfig)?;
+    let hop_fee_recipient_token_account = ctx
+        .accounts
+        .pumpswap_hop_fee_recipient_token_account
+        .as_ref()
+        .ok_or(CtoError::InvalidPumpSwapHopConfig)?;
+
+    // Same injection checks as the direct route, applied to the hop pool.
+    require!(
+        hop_base_vault.mint == ctx.accounts.ctop_mint.key() && hop_quote_vault.mint == hop_mint.key(),
+        CtoError::InvalidPumpSwapVaultMints
+    );
+    require!(
+        hop_base_vault.owner == hop_pool.key() && hop_quote_vault.owner == hop_pool.key(),
+        CtoError::InvalidPumpSwapVaultOwner
+    );
+
+    let hop_before = pool_hop_account.amount;
+    let hop_base_reserve = hop_base_vault.amount;
+    let hop_quote_reserve = hop_quote_vault.amount;
+
+    // Leg 2 accounts are captured up front; leg 1 does not touch the hop pool.
+    let second_leg = PumpSwapLeg {
+        pumpswap_pool: hop_pool.to_account_info(),
+        base_mint: ctx.accounts.ctop_mint.to_account_info(),
+        quote_mint: hop_mint.to_account_info(),
+        user_base_token_account: ctx.accounts.pool_ctop_account.to_account_info(),
+        user_quote_token_account: pool_hop_account.to_account_info(),
+        base_vault: hop_base_vault.to_account_info(),
+        quote_vault: hop_quote_vault.to_account_info(),
+        fee_recipient_token_account: hop_fee_recipient_token_account.to_account_info(),
+        base_token_program: ctx.accounts.pumpswap_base_token_program.to_account_info(),
+        quote_token_program: ctx.accounts.token_program.to_account_info(),
+    };
+    let first_leg = PumpSwapLeg {
+        pumpswap_pool: ctx.accounts.pumpswap_pool.to_account_info(),
+        base_mint: hop_mint.to_account_info(),
+        quote_mint: ctx.accounts.wsol_mint.to_account_info(),
+        user_base_token_account: pool_hop_account.to_account_info(),
+        user_quote_token_account: ctx.accounts.pool_wsol_account.to_account_info(),
+        base_vault: ctx.accounts.pumpswap_pool_base_vault.to_account_info(),
+        quote_vault: ctx.accounts.pumpswap_pool_quote_vault.to_account_info(),
+        fee_recipient_token_account: ctx.accounts.pumpswap_protocol_fee_recipient_token_account.to_account_info(),
+        base_token_program: ctx.accounts.token_program.to_account_info(),
+        quote_token_program: ctx.accounts.pumpswap_quote_token_program.to_account_info(),
+    };
+
+    // Primary pool must be the SOL/hop-mint pool in this mode.
+    let expected_base = hop_mint.key();
+    validate_pumpswap_vault_mints(ctx, expected_base)?;
+
+    wrap_sol_to_wsol(ctx, amount_sol, pool_bump)?;
+
+    // Leg 1: WSOL -> hop mint.
+    let min_hop = compute_min_out_cpmm_from_vaults(
+        ctx.accounts.pumpswap_pool_quote_vault.amount,
+        ctx.accounts.pumpswap_pool_base_vault.amount,
+        amount_sol,
+        PUMPSWAP_FEE_BPS_ESTIMATE,
+        MAX_SLIPPAGE_BPS,
+    )?;
+    perform_pumpswap_buy(ctx, first_leg, min_hop, amount_sol, pool_bump)?;
+
+    let pool_hop_account = ctx
+        .accounts
+        .pool_hop_account
+        .as_mut()
+        .ok_or(CtoError::InvalidPumpSwapHopConfig)?;
+    pool_hop_account.reload()?;
+    let hop_received = pool_hop_account
+        .amount
+        .checked_sub(hop_before)
+        .ok_or(CtoError::MathOverflow)?;
+
+    // Leg 2: hop mint -> CTOP, spending only what leg 1 produced.
+    let min_ctop = compute_min_out_cpmm_from_vaults(
+        hop_quote_reserve,
+        hop_base_reserve,
+        hop_received,
+        PUMPSWAP_FEE_BPS_ESTIMATE,
+        MAX_SLIPPAGE_BPS,
+    )?;
+    perform_pumpswap_buy(ctx, second_leg, min_ctop, hop_received, pool_bump)?;
+
+    settle_bought_ctop(ctx, pool_bump)
+}
+
+/// Whirlpool buy & burn: one WSOL -> CTOP swap on the configured Orca Whirlpool.
+/// Returns the amount of CTOP acquired, as `settle_bought_ctop` reports it.
+fn attempt_whirlpool_swap_and_burn<'info>(
+    ctx: &mut Context<ProcessBuyAndBurn<'info>>,
+    amount_sol: u64,
+    pool_bump: u8,
+) -> Result<u64> {
+    require_swap_compute(1)?;
+
+    let a_to_b = validate_whirlpool_vault_mints(ctx)?;
+    let whirlpool = ctx.accounts.whirlpool.as_ref().ok_or(CtoError::InvalidWhirlpoolConfig)?;
+    let (sqrt_price, fee_rate) = read_whirlpool_price(&whirlpool.to_account_info())?;
+
+    wrap_sol_to_wsol(ctx, amount_sol, pool_bump)?;
+
+    // Same frontrun protection as PumpSwap, from the whirlpool's current price instead of
+    // vault reserves (concentrated liquidity has no x*y=k reserves to read).
+    let min_ctop = compute_min_out_from_sqrt_price(sqrt_price, fee_rate, amount_sol, a_to_b, MAX_SLIPPAGE_BPS)?;
+    perform_whirlpool_swap(ctx, amount_sol, min_ctop, a_to_b, pool_bump)?;
+
+    settle_bought_ctop(ctx, pool_bump)
+}
+
+/// Meteora DLMM buy & burn: one WSOL -> CTOP swap on the configured pair.
+/// Returns the amount of CTOP acquired, as `settle_bought_ctop` reports it.
+fn attempt_meteora_dlmm_swap_and_burn<'info>(
+    ctx: &mut Context<ProcessBuyAndBurn<'info>>,
+    amount_sol: u64,
+    pool_bump: u8,
+) -> Result<u64> {
+    require_swap_compute(1)?;
+
+    let swap_for_y = validate_meteora_reserve_mints(ctx)?;
+    let lb_pair = ctx.accounts.meteora_lb_pair.as_ref().ok_or(CtoError::InvalidMeteoraConfig)?;
+    let (active_id, bin_step) = read_meteora_active_bin(&lb_pair.to_account_info())?;
+
+    wrap_sol_to_wsol(ctx, amount_sol, pool_bump)?;
+
+    let min_ctop = compute_min_out_from_dlmm_bin(active_id, bin_step, amount_sol, swap_for_y, MAX_SLIPPAGE_BPS)?;
+    perform_meteora_dlmm_swap(ctx, amount_sol, min_ctop, swap_for_y, pool_bump)?;
+
+    settle_bought_ctop(ctx, pool_bump)
+}
+
+/// Raydium CPMM buy & burn: one WSOL -> CTOP `swap_base_input` on the configured pool.
+/// Returns the amount of CTOP acquired, as `settle_bought_ctop` reports it.
+fn attempt_raydium_cpmm_swap_and_burn<'info>(
+    ctx: &mut Context<ProcessBuyAndBurn<'info>>,
+    amount_sol: u64,
+    pool_bump: u8,
+) -> Result<u64> {
+    require_swap_compute(1)?;
+
+    let wsol_is_token_0 = validate_raydium_cpmm_vault_mints(ctx)?;
+    let vault_0 = ctx.accounts.raydium_token_0_vault.as_ref().ok_or(CtoError::InvalidRaydiumConfig)?;
+    let vault_1 = ctx.accounts.raydium_token_1_vault.as_ref().ok_or(CtoError::InvalidRaydiumConfig)?;
+    let (wsol_reserve, ctop_reserve) = if wsol_is_token_0 {
+        (vault_0.amount, vault_1.amount)
+    } else {
+        (vault_1.amount, vault_0.amount)
+    };
+
+    wrap_sol_to_wsol(ctx, amount_sol, pool_bump)?;
+
+    // CPMM is x*y=k, so the PumpSwap reserve math applies. Vault balances include fees owed to
+    // the protocol, slightly overstating reserves; the slippage haircut absorbs that.
+    let min_ctop = compute_min_out_cpmm_from_vaults(
+        wsol_reserve,
+        ctop_reserve,
+        amount_sol,
+        RAYDIUM_CPMM_FEE_BPS_ESTIMATE,
+        MAX_SLIPPAGE_BPS,
+    )?;
+    perform_raydium_cpmm_swap(ctx, amount_sol, min_ctop, wsol_is_token_0, pool_bump)?;
+
+    settle_bought_ctop(ctx, pool_bump)
+}
+
+/// Fails with `InsufficientComputeForSwap` unless `legs` swap legs fit in the remaining budget.
+fn require_swap_compute(legs: u64) -> Result<()> {
+    let needed = MIN_SWAP_LEG_COMPUTE_UNITS.saturating_mul(legs);
+    require!(
+        solana_program::compute_units::sol_remaining_compute_units() >= needed,
+        CtoError::InsufficientComputeForSwap
+    );
+    Ok(())
+}
+
+/// Wraps native SOL into WSOL by transferring SOL to the pool's WSOL token account and syncing.
+fn wrap_sol_to_wsol<'info>(
+    ctx: &mut Context<ProcessBuyAndBurn<'info>>,
+    amount: u64,
+    pool_bump: u8,
+) -> Result<()> {
+    invoke_signed(
+        &system_instruction::transfer(&ctx.accounts.pool.key(), &ctx.accounts.pool_wsol_account.key(), amount),
+        &[
+            ctx.accounts.pool.to_account_info(),
+            ctx.accounts.pool_wsol_account.to_account_info(),
+        ],
+        pool_seeds!(&ctx.accounts.pool, pool_bump),
+    )
+    .map_err(|_| CtoError::LamportTransferFailed)?;
+
+    token::sync_native(CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        token::SyncNative {
+            account: ctx.accounts.pool_wsol_account.to_account_info(),
+        },
+    ))?;
+
+    Ok(())
+}
+
+/// H-03 FIX: Validates that the PumpSwap vault token accounts correspond to the expected mints
+/// AND validates that vaults are owned by the PumpSwap pool (prevents account substitution attacks).
+fn validate_pumpswap_vault_mints<'info>(
+    ctx: &Context<ProcessBuyAndBurn<'info>>,
+    expected_base_mint: Pubkey,
+) -> Result<()> {
+    // Validate mints
+    require!(
+        ctx.accounts.pumpswap_pool_base_vault.mint == expected_base_mint,
+        CtoError::InvalidPumpSwapVaultMints
+    );
+    require!(
+        ctx.accounts.pumpswap_pool_quote_vault.mint == ctx.accounts.wsol_mint.key(),
+        CtoError::InvalidPumpSwapVaultMints
+    );
+    
+    // H-03 FIX: Validate that vaults are owned by the PumpSwap pool
+    // This prevents attacker-controlled token accounts from being passed
+    require!(
+        ctx.accounts.pumpswap_pool_base_vault.owner == ctx.accounts.pumpswap_pool.key(),
+        CtoError::InvalidPumpSwapVaultOwner
+    );
+    require!(
+        ctx.accounts.pumpswap_pool_quote_vault.owner == ctx.accounts.pumpswap_pool.key(),
+        CtoError::InvalidPumpSwapVaultOwner
+    );
+    
+    Ok(())
+}
+
+/// Mirrors `validate_pumpswap_vault_mints` for the Whirlpool route: the vaults must be the ones
+/// recorded in the whirlpool's own state, be owned by it, and hold WSOL and the burn target.
+/// Returns `a_to_b`, i.e. whether WSOL is the whirlpool's token A.
+fn validate_whirlpool_vault_mints<'info>(ctx: &Context<ProcessBuyAndBurn<'info>>) -> Result<bool> {
+    let whirlpool = ctx.accounts.whirlpool.as_ref().ok_or(CtoError::InvalidWhirlpoolConfig)?;
+    let vault_a = ctx
+        .accounts
+        .whirlpool_token_vault_a
+        .as_ref()
+        .ok_or(CtoError::InvalidWhirlpoolConfig)?;
+    let vault_b = ctx
+        .accounts
+        .whirlpool_token_vault_b
+        .as_ref()
+        .ok_or(CtoError::InvalidWhirlpoolConfig)?;
+
+    require!(
+        ctx.accounts.whirlpool_program.is_some(),
+        CtoError::InvalidWhirlpoolConfig
+    );
+    require!(*whirlpool.owner == WHIRLPOOL_PROGRAM_ID, CtoError::InvalidWhirlpoolConfig);
+    {
+        let data = whirlpool.try_borrow_data()?;
+        require!(
+            data.len() >= WHIRLPOOL_TOKEN_VAULT_B_OFFSET + 32,
+            CtoError::InvalidWhirlpoolConfig
+        );
+        let stored_a = &data[WHIRLPOOL_TOKEN_VAULT_A_OFFSET..WHIRLPOOL_TOKEN_VAULT_A_OFFSET + 32];
+        let stored_b = &data[WHIRLPOOL_TOKEN_VAULT_B_OFFSET..WHIRLPOOL_TOKEN_VAULT_B_OFFSET + 32];
+        require!(
+            stored_a == vault_a.key().as_ref() && stored_b == vault_b.key().as_ref(),
+            CtoError::InvalidWhirlpoolConfig
+        );
+    }
+
+    let wsol = ctx.accounts.wsol_mint.key();
+    let ctop = ctx.accounts.ctop_mint.key();
+    let a_to_b = vault_a.mint == wsol;
+    require!(
+        (a_to_b && vault_b.mint == ctop) || (vault_a.mint == ctop && vault_b.mint == wsol),
+        CtoError::InvalidWhirlpoolVaultMints
+    );
+    require!(
+        vault_a.owner == whirlpool.key() && vault_b.owner == whirlpool.key(),
+        CtoError::InvalidWhirlpoolVaultOwner
+    );
+
+    Ok(a_to_b)
+}
+
+/// Meteora counterpart of `validate_whirlpool_vault_mints`: the reserves must be the ones
+/// recorded in the pair's own state, be owned by it, and hold WSOL and the burn target.
+/// Returns `swap_for_y`, i.e. whether WSOL is the pair's token X.
+fn validate_meteora_reserve_mints<'info>(ctx: &Context<ProcessBuyAndBurn<'info>>) -> Result<bool> {
+    let lb_pair = ctx.accounts.meteora_lb_pair.as_ref().ok_or(CtoError::InvalidMeteoraConfig)?;
+    let reserve_x = ctx
+        .accounts
+        .meteora_reserve_x
+        .as_ref()
+        .ok_or(CtoError::InvalidMeteoraConfig)?;
+    let reserve_y = ctx
+        .accounts
+        .meteora_reserve_y
+        .as_ref()
+        .ok_or(CtoError::InvalidMeteoraConfig)?;
+
+    require!(*lb_pair.owner == METEORA_DLMM_PROGRAM_ID, CtoError::InvalidMeteoraConfig);
+    {
+        let data = lb_pair.try_borrow_data()?;
+        require!(
+            data.len() >= METEORA_RESERVE_Y_OFFSET + 32,
+            CtoError::InvalidMeteoraConfig
+        );
+        let stored_x = &data[METEORA_RESERVE_X_OFFSET..METEORA_RESERVE_X_OFFSET + 32];
+        let stored_y = &data[METEORA_RESERVE_Y_OFFSET..METEORA_RESERVE_Y_OFFSET + 32];
+        require!(
+            stored_x == reserve_x.key().as_ref() && stored_y == reserve_y.key().as_ref(),
+            CtoError::InvalidMeteoraConfig
+        );
+    }
+
+    let wsol = ctx.accounts.wsol_mint.key();
+    let ctop = ctx.accounts.ctop_mint.key();
+    let swap_for_y = reserve_x.mint == wsol;
+    require!(
+        (swap_for_y && reserve_y.mint == ctop) || (reserve_x.mint == ctop && reserve_y.mint == wsol),
+        CtoError::InvalidMeteoraReserveMints
+    );
+    require!(
+        reserve_x.owner == lb_pair.key() && reserve_y.owner == lb_pair.key(),
+        CtoError::InvalidMeteoraReserveOwner
+    );
+
+    Ok(swap_for_y)
+}
+
+/// Raydium CPMM counterpart of `validate_pumpswap_vault_mints`: the vaults must be the ones
+/// recorded in the pool state and hold WSOL and the burn target. (CPMM vaults are owned by the
+/// program-wide authority PDA rather than the pool, so the pool state is the binding check.)
+/// Returns whether WSOL is the pool's token 0.
+fn validate_raydium_cpmm_vault_mints<'info>(ctx: &Context<ProcessBuyAndBurn<'info>>) -> Result<bool> {
+    let pool_state = ctx
+        .accounts
+        .raydium_pool_state
+        .as_ref()
+        .ok_or(CtoError::InvalidRaydiumConfig)?;
+    let vault_0 = ctx
+        .accounts
+        .raydium_token_0_vault
+        .as_ref()
+        .ok_or(CtoError::InvalidRaydiumConfig)?;
+    let vault_1 = ctx
+        .accounts
+        .raydium_token_1_vault
+        .as_ref()
+        .ok_or(CtoError::InvalidRaydiumConfig)?;
+
+    require!(*pool_state.owner == RAYDIUM_CPMM_PROGRAM_ID, CtoError::InvalidRaydiumConfig);
+    {
+        let data = pool_state.try_borrow_data()?;
+        require!(
+            data.len() >= RAYDIUM_CPMM_TOKEN_1_VAULT_OFFSET + 32,
+            CtoError::InvalidRaydiumConfig
+        );
+        let stored_0 = &data[RAYDIUM_CPMM_TOKEN_0_VAULT_OFFSET..RAYDIUM_CPMM_TOKEN_0_VAULT_OFFSET + 32];
+        let stored_1 = &data[RAYDIUM_CPMM_TOKEN_1_VAULT_OFFSET..RAYDIUM_CPMM_TOKEN_1_VAULT_OFFSET + 32];
+        require!(
+            stored_0 == vault_0.key().as_ref() && stored_1 == vault_1.key().as_ref(),
+            CtoError::InvalidRaydiumConfig
+        );
+    }
+
+    let wsol = ctx.accounts.wsol_mint.key();
+    let ctop = ctx.accounts.ctop_mint.key();
+    let wsol_is_token_0 = vault_0.mint == wsol;
+    require!(
+        (wsol_is_token_0 && vault_1.mint == ctop) || (vault_0.mint == ctop && vault_1.mint == wsol),
+        CtoError::InvalidRaydiumVaultMints
+    );
+
+    Ok(wsol_is_token_0)
+}
+
+/// Whether the configured Raydium CPMM vaults pair WSOL with the pool's own token, as liquidity
+/// provision needs. Returns whether WSOL is the pool's token 0.
+fn raydium_liquidity_side(
+    pool: &Pool,
+    vault_0: &token_interface::TokenAccount,
+    vault_1: &token_interface::TokenAccount,
+) -> Result<bool> {
+    let wsol_is_token_0 = vault_0.mint == WSOL_MINT;
+    require!(
+        (wsol_is_token_0 && vault_1.mint == pool.token_mint)
+            || (vault_0.mint == pool.token_mint && vault_1.mint == WSOL_MINT),
+        CtoError::InvalidRaydiumVaultMints
+    );
+    Ok(wsol_is_token_0)
+}
+
+/// `validate_raydium_cpmm_vault_mints` for liquidity: the vaults and LP mint must be the ones
+/// recorded in the pool state, and the vaults must pair WSOL with the pool's token.
+/// Returns whether WSOL is the pool's token 0.
+fn validate_raydium_liquidity_accounts(accounts: &ManageLiquidity) -> Result<bool> {
+    require!(
+        *accounts.raydium_pool_state.owner == RAYDIUM_CPMM_PROGRAM_ID,
+        CtoError::InvalidRaydiumConfig
+    );
+    {
+        let data = accounts.raydium_pool_state.try_borrow_data()?;
+        require!(
+            data.len() >= RAYDIUM_CPMM_LP_MINT_OFFSET + 32,
+            CtoError::InvalidRaydiumConfig
+        );
+        let stored_0 = &data[RAYDIUM_CPMM_TOKEN_0_VAULT_OFFSET..RAYDIUM_CPMM_TOKEN_0_VAULT_OFFSET + 32];
+        let stored_1 = &data[RAYDIUM_CPMM_TOKEN_1_VAULT_OFFSET..RAYDIUM_CPMM_TOKEN_1_VAULT_OFFSET + 32];
+        let stored_lp = &data[RAYDIUM_CPMM_LP_MINT_OFFSET..RAYDIUM_CPMM_LP_MINT_OFFSET + 32];
+        require!(
+            stored_0 == accounts.raydium_token_0_vault.key().as_ref()
+                && stored_1 == accounts.raydium_token_1_vault.key().as_ref()
+                && stored_lp == accounts.lp_mint.key().as_ref(),
+            CtoError::InvalidRaydiumConfig
+        );
+    }
+    raydium_liquidity_side(&accounts.pool, &accounts.raydium_token_0_vault, &accounts.raydium_token_1_vault)
+}
+
+/// Performs Raydium CPMM `deposit` or `withdraw` of `lp_amount` LP between `pool_lp_account` and
+/// the pool's liquidity WSOL account and token treasury. `amount_0`/`amount_1` are the token 0/1
+/// maximums (deposit) or minimums (withdraw).
+fn raydium_cpmm_liquidity_cpi<'info>(
+    ctx: &Context<ManageLiquidity<'info>>,
+    discriminator: [u8; 8],
+    lp_amount: u64,
+    amount_0: u64,
+    amount_1: u64,
+    wsol_is_token_0: bool,
+    pool_bump: u8,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let wsol_account = accounts.pool_liquidity_wsol.to_account_info();
+    let token_account = accounts.pool_token_treasury.to_account_info();
+    let wsol_mint = accounts.wsol_mint.to_account_info();
+    let token_mint = accounts.token_mint.to_account_info();
+    let (token_0_account, token_1_account, mint_0, mint_1) = if wsol_is_token_0 {
+        (wsol_account, token_account, wsol_mint, token_mint)
+    } else {
+        (token_account, wsol_account, token_mint, wsol_mint)
+    };
+
+    let mut data = Vec::with_capacity(8 + 8 + 8 + 8);
+    data.extend_from_slice(&discriminator);
+    data.extend_from_slice(&lp_amount.to_le_bytes());
+    data.extend_from_slice(&amount_0.to_le_bytes());
+    data.extend_from_slice(&amount_1.to_le_bytes());
+
+    // Account order is strict and shared by deposit and withdraw (withdraw appends the memo
+    // program). Do NOT reorder without checking the CPMM interface/IDL.
+    let mut metas = vec![
+        AccountMeta::new_readonly(accounts.pool.key(), true), // owner: pool PDA
+        AccountMeta::new_readonly(accounts.raydium_authority.key(), false),
+        AccountMeta::new(accounts.raydium_pool_state.key(), false),
+        AccountMeta::new(accounts.pool_lp_account.key(), false),
+        AccountMeta::new(token_0_account.key(), false),
+        AccountMeta::new(token_1_account.key(), false),
+        AccountMeta::new(accounts.raydium_token_0_vault.key(), false),
+        AccountMeta::new(accounts.raydium_token_1_vault.key(), false),
+        AccountMeta::new_readonly(accounts.token_program.key(), false),
+        AccountMeta::new_readonly(accounts.token_2022_program.key(), false),
+        AccountMeta::new_readonly(mint_0.key(), false),
+        AccountMeta::new_readonly(mint_1.key(), false),
+        AccountMeta::new(accounts.lp_mint.key(), false),
+    ];
+    let mut infos = vec![
+        accounts.pool.to_account_info(),
+        accounts.raydium_authority.to_account_info(),
+        accounts.raydium_pool_state.to_account_info(),
+        accounts.pool_lp_account.to_account_info(),
+        token_0_account,
+        token_1_account,
+        accounts.raydium_token_0_vault.to_account_info(),
+        accounts.raydium_token_1_vault.to_account_info(),
+        accounts.token_program.to_account_info(),
+        accounts.token_2022_program.to_account_info(),
+        mint_0,
+        mint_1,
+        accounts.lp_mint.to_account_info(),
+    ];
+    if discriminator == RAYDIUM_CPMM_WITHDRAW_DISCRIMINATOR {
+        metas.push(AccountMeta::new_readonly(accounts.memo_program.key(), false));
+        infos.push(accounts.memo_program.to_account_info());
+    }
+    infos.push(accounts.raydium_cpmm_program.to_account_info());
+
+    let ix = Instruction {
+        program_id: accounts.raydium_cpmm_program.key(),
+        accounts: metas,
+        data,
+    };
+    invoke_signed(&ix, &infos, pool_seeds!(&accounts.pool, pool_bump)).map_err(Into::into)
+}
+
+/// Closes the liquidity WSOL account into the pool PDA, unwrapping what it holds, and refunds
+/// its rent to the cranker who opened it. Returns the unwrapped lamports.
+fn close_liquidity_wsol<'info>(ctx: &mut Context<ManageLiquidity<'info>>, pool_bump: u8) -> Result<u64> {
+    ctx.accounts.pool_liquidity_wsol.reload()?;
+    let wrapped = ctx.accounts.pool_liquidity_wsol.amount;
+    let rent = ctx
+        .accounts
+        .pool_liquidity_wsol
+        .to_account_info()
+        .lamports()
+        .checked_sub(wrapped)
+        .ok_or(CtoError::MathOverflow)?;
+
+    token::close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        token::CloseAccount {
+            account: ctx.accounts.pool_liquidity_wsol.to_account_info(),
+            destination: ctx.accounts.pool.to_account_info(),
+            authority: ctx.accounts.pool.to_account_info(),
+        },
+        pool_seeds!(&ctx.accounts.pool, pool_bump),
+    ))?;
+    transfer_lamports_signed(
+        &ctx.accounts.pool.to_account_info(),
+        &ctx.accounts.cranker.to_account_info(),
+        pool_seeds!(&ctx.accounts.pool, pool_bump),
+        rent,
+    )?;
+    Ok(wrapped)
+}
+
+/// Reads `(active_id, bin_step)` from an `LbPair` account already checked by
+/// `validate_meteora_reserve_mints`.
+fn read_meteora_active_bin(lb_pair: &AccountInfo) -> Result<(i32, u16)> {
+    let data = lb_pair.try_borrow_data()?;
+    let active_id = i32::from_le_bytes(
+        data[METEORA_ACTIVE_ID_OFFSET..METEORA_ACTIVE_ID_OFFSET + 4]
+            .try_into()
+            .map_err(|_| CtoError::InvalidMeteoraConfig)?,
+    );
+    let bin_step = u16::from_le_bytes(
+        data[METEORA_BIN_STEP_OFFSET..METEORA_BIN_STEP_OFFSET + 2]
+            .try_into()
+            .map_err(|_| CtoError::InvalidMeteoraConfig)?,
+    );
+    Ok((active_id, bin_step))
+}
+
+/// Reads `(sqrt_price, fee_rate)` from a Whirlpool account already checked by
+/// `validate_whirlpool_vault_mints`.
+fn read_whirlpool_price(whirlpool: &AccountInfo) -> Result<(u128, u16)> {
+    let data = whirlpool.try_borrow_data()?;
+    let fee_rate = u16::from_le_bytes(
+        data[WHIRLPOOL_FEE_RATE_OFFSET..WHIRLPOOL_FEE_RATE_OFFSET + 2]
+            .try_into()
+            .map_err(|_| CtoError::InvalidWhirlpoolConfig)?,
+    );
+    let sqrt_price = u128::from_le_bytes(
+        data[WHIRLPOOL_SQRT_PRICE_OFFSET..WHIRLPOOL_SQRT_PRICE_OFFSET + 16]
+            .try_into()
+            .map_err(|_| CtoError::InvalidWhirlpoolConfig)?,
+    );
+    Ok((sqrt_price, fee_rate))
+}
+
+/// Computes a conservative min-out from a Whirlpool's current sqrt price (Q64.64, token B per A).
+///
+/// Spot price ignores price impact, so large swaps relative to in-range liquidity fail the
+/// slippage haircut and should be cranked in smaller `max_lamports` chunks.
+fn compute_min_out_from_sqrt_price(
+    sqrt_price: u128,
+    fee_rate: u16,
+    amount_in: u64,
+    a_to_b: bool,
+    slippage_bps: u64,
+) -> Result<u64> {
+    require!(sqrt_price > 0, CtoError::WhirlpoolMathError);
+    require!(amount_in > 0, CtoError::ZeroAmount);
+
+    // out = in * p for A -> B and in / p for B -> A, with p = sqrt_price^2.
+    let step = convert_at_q64_price(amount_in as u128, sqrt_price, a_to_b)?;
+    let expected_out = convert_at_q64_price(step, sqrt_price, a_to_b)?;
+
+    // fee_rate is in hundredths of a bp (1_000_000 = 100%).
+    min_out_after_fee_and_slippage(expected_out, fee_rate as u128, slippage_bps)
+}
+
+/// Computes a conservative min-out from a DLMM pair's active-bin price (token Y per X).
+///
+/// As with Whirlpool, the active bin's price ignores crossing into further bins.
+fn compute_min_out_from_dlmm_bin(
+    active_id: i32,
+    bin_step: u16,
+    amount_in: u64,
+    swap_for_y: bool,
+    slippage_bps: u64,
+) -> Result<u64> {
+    require!(amount_in > 0, CtoError::ZeroAmount);
+    let price = dlmm_price_q64(active_id, bin_step)?;
+    require!(price > 0, CtoError::MeteoraMathError);
+    let expected_out = convert_at_q64_price(amount_in as u128, price, swap_for_y)?;
+    min_out_after_fee_and_slippage(
+        expected_out,
+        (METEORA_DLMM_FEE_BPS_ESTIMATE as u128) * 100,
+        slippage_bps,
+    )
+}
+
+/// DLMM bin price `(1 + bin_step / 10_000) ^ active_id` as Q64.64, by repeated squaring.
+fn dlmm_price_q64(active_id: i32, bin_step: u16) -> Result<u128> {
+    let denom = BPS_DENOM as u128;
+    let step = denom + bin_step as u128;
+    // Negative ids raise the reciprocal so every intermediate stays at most 1.
+    let mut base = if active_id >= 0 {
+        (step << 64) / denom
+    } else {
+        (denom << 64) / step
+    };
+    let mut exp = active_id.unsigned_abs();
+    let mut price = 1u128 << 64;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            price = mul_q64(price, base)?;
+        }
+        exp >>= 1;
+        if exp > 0 {
+            base = mul_q64(base, base)?;
+        }
+    }
+    Ok(price)
+}
+
+/// `a * b >> 64` for Q64.64 operands, without a 256-bit intermediate.
+fn mul_q64(a: u128, b: u128) -> Result<u128> {
+    let mask = u64::MAX as u128;
+    let (a_hi, a_lo) = (a >> 64, a & mask);
+    let (b_hi, b_lo) = (b >> 64, b & mask);
+    let hi = a_hi
+        .checked_mul(b_hi)
+        .and_then(|v| v.checked_mul(1u128 << 64))
+        .ok_or(CtoError::MathOverflow)?;
+    let mid = a_hi
+        .checked_mul(b_lo)
+        .and_then(|v| v.checked_add(a_lo.checked_mul(b_hi)?))
+        .ok_or(CtoError::MathOverflow)?;
+    let lo = (a_lo * b_lo) >> 64;
+    hi.checked_add(mid)
+        .and_then(|v| v.checked_add(lo))
+        .ok_or(CtoError::MathOverflow.into())
+}
+
+/// Converts `amount` at a Q64.64 price: multiplies when `forward`, divides otherwise.
+fn convert_at_q64_price(amount: u128, price: u128, forward: bool) -> Result<u128> {
+    if forward {
+        mul_q64(amount, price)
+    } else {
+        amount
+            .checked_mul(1u128 << 64)
+            .ok_or(CtoError::MathOverflow)?
+            .checked_div(price)
+            .ok_or(CtoError::MathOverflow.into())
+    }
+}
+
+/// Applies a fee (in millionths) and then `slippage_bps` to an expected output.
+fn min_out_after_fee_and_slippage(expected_out: u128, fee_ppm: u128, slippage_bps: u64) -> Result<u64> {
+    let fee = 1_000_000u128
+        .checked_sub(fee_ppm)
+        .ok_or(CtoError::MathOverflow)?;
+    let after_fee = expected_out
+        .checked_mul(fee)
+        .ok_or(CtoError::MathOverflow)?
+        .checked_div(1_000_000)
+        .ok_or(CtoError::MathOverflow)?;
+
+    let slip = (BPS_DENOM as u128)
+        .checked_sub(slippage_bps as u128)
+        .ok_or(CtoError::MathOverflow)?;
+    let min_out = after_fee
+        .checked_mul(slip)
+        .ok_or(CtoError::MathOverflow)?
+        .checked_div(BPS_DENOM as u128)
+        .ok_or(CtoError::MathOverflow)?;
+
+    let min_out_u64 = u64::try_from(min_out).map_err(|_| CtoError::MathOverflow)?;
+    require!(min_out_u64 > 0, CtoError::PumpSwapMinOutZero);
+    Ok(min_out_u64)
+}
+
+/// Computes a conservative min-out using constant product math from vault balances.
+///
+/// NOTE:
+/// - This assumes an x*y=k style pool.
+/// - Venue fees are modelled by `fee_bps` (an estimate); a slippage haircut is applied on top.
+fn compute_min_out_cpmm_from_vaults(
+    quote_reserve: u64,
+    base_reserve: u64,
+    quote_in: u64,
+    fee_bps: u64,
+    slippage_bps: u64,
+) -> Result<u64> {
+    require!(quote_reserve > 0 && base_reserve > 0, CtoError::PumpSwapMathError);
+    require!(quote_in > 0, CtoError::ZeroAmount);
+
+    // x = quote reserve (WSOL), y = base reserve (CTOP)
+    let x = quote_reserve as u128;
+    let y = base_reserve as u128;
+    let dx = quote_in as u128;
+
+    // y_out = y - (k / (x + dx))
+    let k = x.checked_mul(y).ok_or(CtoError::MathOverflow)?;
+    let denom = x.checked_add(dx).ok_or(CtoError::MathOverflow)?;
+    let y_after = k.checked_div(denom).ok_or(CtoError::MathOverflow)?;
+    let expected_out = y.checked_sub(y_after).ok_or(CtoError::PumpSwapMathError)?;
+
+    // Apply a conservative fee haircut first (estimated venue fee), then slippage haircut.
+    let fee = (BPS_DENOM as u128)
+        .checked_sub(fee_bps as u128)
+        .ok_or(CtoError::MathOverflow)?;
+    let after_fee = expected_out
+        .checked_mul(fee)
+        .ok_or(CtoError::MathOverflow)?
+        .checked_div(BPS_DENOM as u128)
+        .ok_or(CtoError::MathOverflow)?;
+
+    let slip = (BPS_DENOM as u128)
+        .checked_sub(slippage_bps as u128)
+        .ok_or(CtoError::MathOverflow)?;
+    let min_out = after_fee
+        .checked_mul(slip)
+        .ok_or(CtoError::MathOverflow)?
+        .checked_div(BPS_DENOM as u128)
+        .ok_or(CtoError::MathOverflow)?;
+
+    let min_out_u64 = u64::try_from(min_out).map_err(|_| CtoError::MathOverflow)?;
+    require!(min_out_u64 > 0, CtoError::PumpSwapMinOutZero);
+    Ok(min_out_u64)
+}
+
+/// Per-leg PumpSwap accounts; the shared program/config/fee-recipient accounts come from the context.
+struct PumpSwapLeg<'info> {
+    pumpswap_pool: AccountInfo<'info>,
+    base_mint: AccountInfo<'info>,
+    quote_mint: AccountInfo<'info>,
+    user_base_token_account: AccountInfo<'info>,
+    user_quote_token_account: AccountInfo<'info>,
+    base_vault: AccountInfo<'info>,
+    quote_vault: AccountInfo<'info>,
+    fee_recipient_token_account: AccountInfo<'info>,
+    base_token_program: AccountInfo<'info>,
+    quote_token_program: AccountInfo<'info>,
+}
+
+/// Performs PumpSwap `buy` on one leg:
+/// - Spends up to `max_quote_amount_in` quote tokens from the leg's PDA-owned quote account
+/// - Receives at least `base_amount_out` base tokens into the leg's PDA-owned base account
+fn perform_pumpswap_buy<'info>(
+    ctx: &Context<ProcessBuyAndBurn<'info>>,
+    leg: PumpSwapLeg<'info>,
+    base_amount_out: u64,
+    max_quote_amount_in: u64,
+    pool_bump: u8,
+) -> Result<()> {
+    let mut data = Vec::with_capacity(8 + 8 + 8);
+    data.extend_from_slice(&PUMPSWAP_BUY_DISCRIMINATOR);
+    data.extend_from_slice(&base_amount_out.to_le_bytes());
+    data.extend_from_slice(&max_quote_amount_in.to_le_bytes());
+
+    // Account order is strict. Do NOT reorder without checking the PumpSwap interface/IDL.
+    let metas = vec![
+        AccountMeta::new(leg.pumpswap_pool.key(), false),
+        AccountMeta::new(ctx.accounts.pool.key(), true), // user (signer): pool PDA
+        AccountMeta::new_readonly(ctx.accounts.pumpswap_global_config.key(), false),
+        AccountMeta::new_readonly(leg.base_mint.key(), false),
+        AccountMeta::new_readonly(leg.quote_mint.key(), false),
+        AccountMeta::new(leg.user_base_token_account.key(), false),
+        AccountMeta::new(leg.user_quote_token_account.key(), false),
+        AccountMeta::new(leg.base_vault.key(), false),
+        AccountMeta::new(leg.quote_vault.key(), false),
+        AccountMeta::new_readonly(ctx.accounts.pumpswap_protocol_fee_recipient.key(), false),
+        AccountMeta::new(leg.fee_recipient_token_account.key(), false),
+        AccountMeta::new_readonly(leg.base_token_program.key(), false),
+        AccountMeta::new_readonly(leg.quote_token_program.key(), false),
+        AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
+        AccountMeta::new_readonly(ctx.accounts.associated_token_program.key(), false),
+        AccountMeta::new_readonly(ctx.accounts.pumpswap_event_authority.key(), false),
+        AccountMeta::new_readonly(ctx.accounts.pumpswap_program.key(), false),
+    ];
+
+    let ix = Instruction {
+        program_id: ctx.accounts.pumpswap_program.key(),
+        accounts: metas,
+        data,
+    };
+
+    invoke_signed(
+        &ix,
+        &[
+            leg.pumpswap_pool,
+            ctx.accounts.pool.to_account_info(),
+            ctx.accounts.pumpswap_global_config.to_account_info(),
+            leg.base_mint,
+            leg.quote_mint,
+            leg.user_base_token_account,
+            leg.user_quote_token_account,
+            leg.base_vault,
+            leg.quote_vault,
+            ctx.accounts.pumpswap_protocol_fee_recipient.to_account_info(),
+            leg.fee_recipient_token_account,
+            leg.base_token_program,
+            leg.quote_token_program,
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.associated_token_program.to_account_info(),
+            ctx.accounts.pumpswap_event_authority.to_account_info(),
+            ctx.accounts.pumpswap_program.to_account_info(),
+        ],
+        pool_seeds!(&ctx.accounts.pool, pool_bump),
+    )
+    .map_err(|_| CtoError::SwapFailed.into())
+}
+
+/// Performs Whirlpool `swap`: exactly `amount_in` WSOL from `pool_wsol_account` for at least
+/// `minimum_amount_out` CTOP into `pool_ctop_account`, both PDA-owned.
+fn perform_whirlpool_swap<'info>(
+    ctx: &Context<ProcessBuyAndBurn<'info>>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    a_to_b: bool,
+    pool_bump: u8,
+) -> Result<()> {
+    let missing = || error!(CtoError::InvalidWhirlpoolConfig);
+    let program = ctx.accounts.whirlpool_program.as_ref().ok_or_else(missing)?;
+    let whirlpool = ctx.accounts.whirlpool.as_ref().ok_or_else(missing)?;
+    let vault_a = ctx.accounts.whirlpool_token_vault_a.as_ref().ok_or_else(missing)?;
+    let vault_b = ctx.accounts.whirlpool_token_vault_b.as_ref().ok_or_else(missing)?;
+    let tick_array_0 = ctx.accounts.whirlpool_tick_array_0.as_ref().ok_or_else(missing)?;
+    let tick_array_1 = ctx.accounts.whirlpool_tick_array_1.as_ref().ok_or_else(missing)?;
+    let tick_array_2 = ctx.accounts.whirlpool_tick_array_2.as_ref().ok_or_else(missing)?;
+    let oracle = ctx.accounts.whirlpool_oracle.as_ref().ok_or_else(missing)?;
+
+    let (owner_a, owner_b) = if a_to_b {
+        (ctx.accounts.pool_wsol_account.to_account_info(), ctx.accounts.pool_ctop_account.to_account_info())
+    } else {
+        (ctx.accounts.pool_ctop_account.to_account_info(), ctx.accounts.pool_wsol_account.to_account_info())
+    };
+    let sqrt_price_limit = if a_to_b { WHIRLPOOL_MIN_SQRT_PRICE } else { WHIRLPOOL_MAX_SQRT_PRICE };
+
+    let mut data = Vec::with_capacity(8 + 8 + 8 + 16 + 1 + 1);
+    data.extend_from_slice(&WHIRLPOOL_SWAP_DISCRIMINATOR);
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&minimum_amount_out.to_le_bytes());
+    data.extend_from_slice(&sqrt_price_limit.to_le_bytes());
+    data.push(1); // amount_specified_is_input
+    data.push(a_to_b as u8);
+
+    // Account order is strict. Do NOT reorder without checking the Whirlpool interface/IDL.
+    let metas = vec![
+        AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+        AccountMeta::new_readonly(ctx.accounts.pool.key(), true), // token_authority: pool PDA
+        AccountMeta::new(whirlpool.key(), false),
+        AccountMeta::new(owner_a.key(), false),
+        AccountMeta::new(vault_a.key(), false),
+        AccountMeta::new(owner_b.key(), false),
+        AccountMeta::new(vault_b.key(), false),
+        AccountMeta::new(tick_array_0.key(), false),
+        AccountMeta::new(tick_array_1.key(), false),
+        AccountMeta::new(tick_array_2.key(), false),
+        AccountMeta::new(oracle.key(), false),
+    ];
+
+    let ix = Instruction {
+        program_id: program.key(),
+        accounts: metas,
+        data,
+    };
+
+    invoke_signed(
+        &ix,
+        &[
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.pool.to_account_info(),
+            whirlpool.to_account_info(),
+            owner_a,
+            vault_a.to_account_info(),
+            owner_b,
+            vault_b.to_account_info(),
+            tick_array_0.to_account_info(),
+            tick_array_1.to_account_info(),
+            tick_array_2.to_account_info(),
+            oracle.to_account_info(),
+            program.to_account_info(),
+        ],
+        pool_seeds!(&ctx.accounts.pool, pool_bump),
+    )
+    .map_err(|_| CtoError::SwapFailed.into())
+}
+
+/// Performs Meteora DLMM `swap`: exactly `amount_in` WSOL from `pool_wsol_account` for at least
+/// `minimum_amount_out` CTOP into `pool_ctop_account`, both PDA-owned.
+fn perform_meteora_dlmm_swap<'info>(
+    ctx: &Context<ProcessBuyAndBurn<'info>>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    swap_for_y: bool,
+    pool_bump: u8,
+) -> Result<()> {
+    let missing = || error!(CtoError::InvalidMeteoraConfig);
+    let program = ctx.accounts.meteora_program.as_ref().ok_or_else(missing)?;
+    let lb_pair = ctx.accounts.meteora_lb_pair.as_ref().ok_or_else(missing)?;
+    let reserve_x = ctx.accounts.meteora_reserve_x.as_ref().ok_or_else(missing)?;
+    let reserve_y = ctx.accounts.meteora_reserve_y.as_ref().ok_or_else(missing)?;
+    let oracle = ctx.accounts.meteora_oracle.as_ref().ok_or_else(missing)?;
+    let event_authority = ctx.accounts.meteora_event_authority.as_ref().ok_or_else(missing)?;
+    let bin_array = ctx.accounts.meteora_bin_array_0.as_ref().ok_or_else(missing)?;
+
+    let (mint_x, mint_y) = if swap_for_y {
+        (ctx.accounts.wsol_mint.to_account_info(), ctx.accounts.ctop_mint.to_account_info())
+    } else {
+        (ctx.accounts.ctop_mint.to_account_info(), ctx.accounts.wsol_mint.to_account_info())
+    };
+    // Absent optional accounts are passed as the DLMM program id, per Anchor convention.
+    let bitmap_extension = ctx
+        .accounts
+        .meteora_bin_array_bitmap_extension
+        .as_ref()
+        .map(|a| a.to_account_info())
+        .unwrap_or_else(|| program.to_account_info());
+
+    let mut data = Vec::with_capacity(8 + 8 + 8);
+    data.extend_from_slice(&METEORA_DLMM_SWAP_DISCRIMINATOR);
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&minimum_amount_out.to_le_bytes());
+
+    // Account order is strict. Do NOT reorder without checking the DLMM interface/IDL.
+    let mut metas = vec![
+        AccountMeta::new(lb_pair.key(), false),
+        AccountMeta::new_readonly(bitmap_extension.key(), false),
+        AccountMeta::new(reserve_x.key(), false),
+        AccountMeta::new(reserve_y.key(), false),
+        AccountMeta::new(ctx.accounts.pool_wsol_account.key(), false), // user_token_in
+        AccountMeta::new(ctx.accounts.pool_ctop_account.key(), false), // user_token_out
+        AccountMeta::new_readonly(mint_x.key(), false),
+        AccountMeta::new_readonly(mint_y.key(), false),
+        AccountMeta::new(oracle.key(), false),
+        AccountMeta::new_readonly(program.key(), false), // host_fee_in: none
+        AccountMeta::new_readonly(ctx.accounts.pool.key(), true), // user: pool PDA
+        AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+        AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+        AccountMeta::new_readonly(event_authority.key(), false),
+        AccountMeta::new_readonly(program.key(), false),
+    ];
+    let mut infos = vec![
+        lb_pair.to_account_info(),
+        bitmap_extension,
+        reserve_x.to_account_info(),
+        reserve_y.to_account_info(),
+        ctx.accounts.pool_wsol_account.to_account_info(),
+        ctx.accounts.pool_ctop_account.to_account_info(),
+        mint_x,
+        mint_y,
+        oracle.to_account_info(),
+        ctx.accounts.pool.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        event_authority.to_account_info(),
+        program.to_account_info(),
+    ];
+
+    // Bin arrays the swap may cross go last, as remaining accounts of the DLMM instruction.
+    for bin_array in [
+        Some(bin_array),
+        ctx.accounts.meteora_bin_array_1.as_ref(),
+        ctx.accounts.meteora_bin_array_2.as_ref(),
+    ]
+    .into_iter()
+    .flatten()
+    {
+        metas.push(AccountMeta::new(bin_array.key(), false));
+        infos.push(bin_array.to_account_info());
+    }
+
+    let ix = Instruction {
+        program_id: program.key(),
+        accounts: metas,
+        data,
+    };
+
+    invoke_signed(&ix, &infos, pool_seeds!(&ctx.accounts.pool, pool_bump))
+        .map_err(|_| CtoError::SwapFailed.into())
+}
+
+/// Performs Raydium CPMM `swap_base_input`: exactly `amount_in` WSOL from `pool_wsol_account`
+/// for at least `minimum_amount_out` CTOP into `pool_ctop_account`, both PDA-owned.
+fn perform_raydium_cpmm_swap<'info>(
+    ctx: &Context<ProcessBuyAndBurn<'info>>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    wsol_is_token_0: bool,
+    pool_bump: u8,
+) -> Result<()> {
+    let missing = || error!(CtoError::InvalidRaydiumConfig);
+    let program = ctx.accounts.raydium_cpmm_program.as_ref().ok_or_else(missing)?;
+    let authority = ctx.accounts.raydium_authority.as_ref().ok_or_else(missing)?;
+    let amm_config = ctx.accounts.raydium_amm_config.as_ref().ok_or_else(missing)?;
+    let pool_state = ctx.accounts.raydium_pool_state.as_ref().ok_or_else(missing)?;
+    let vault_0 = ctx.accounts.raydium_token_0_vault.as_ref().ok_or_else(missing)?;
+    let vault_1 = ctx.accounts.raydium_token_1_vault.as_ref().ok_or_else(missing)?;
+    let observation_state = ctx.accounts.raydium_observation_state.as_ref().ok_or_else(missing)?;
+
+    let (input_vault, output_vault) = if wsol_is_token_0 {
+        (vault_0.to_account_info(), vault_1.to_account_info())
+    } else {
+        (vault_1.to_account_info(), vault_0.to_account_info())
+    };
+
+    let mut data = Vec::with_capacity(8 + 8 + 8);
+    data.extend_from_slice(&RAYDIUM_CPMM_SWAP_BASE_INPUT_DISCRIMINATOR);
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&minimum_amount_out.to_le_bytes());
+
+    // Account order is strict. Do NOT reorder without checking the CPMM interface/IDL.
+    let metas = vec![
+        AccountMeta::new_readonly(ctx.accounts.pool.key(), true), // payer: pool PDA
+        AccountMeta::new_readonly(authority.key(), false),
+        AccountMeta::new_readonly(amm_config.key(), false),
+        AccountMeta::new(pool_state.key(), false),
+        AccountMeta::new(ctx.accounts.pool_wsol_account.key(), false),
+        AccountMeta::new(ctx.accounts.pool_ctop_account.key(), false),
+        AccountMeta::new(input_vault.key(), false),
+        AccountMeta::new(output_vault.key(), false),
+        AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+        AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+        AccountMeta::new_readonly(ctx.accounts.wsol_mint.key(), false),
+        AccountMeta::new_readonly(ctx.accounts.ctop_mint.key(), false),
+        AccountMeta::new(observation_state.key(), false),
+    ];
+
+    let ix = Instruction {
+        program_id: program.key(),
+        accounts: metas,
+        data,
+    };
+
+    invoke_signed(
+        &ix,
+        &[
+            ctx.accounts.pool.to_account_info(),
+            authority.to_account_info(),
+            amm_config.to_account_info(),
+            pool_state.to_account_info(),
+            ctx.accounts.pool_wsol_account.to_account_info(),
+            ctx.accounts.pool_ctop_account.to_account_info(),
+            input_vault,
+            output_vault,
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.wsol_mint.to_account_info(),
+            ctx.accounts.ctop_mint.to_account_info(),
+            observation_state.to_account_info(),
+            program.to_account_info(),
+        ],
+        pool_seeds!(&ctx.accounts.pool, pool_bump),
+    )
+    .map_err(|_| CtoError::SwapFailed.into())
+}
+
+/// Burns or holds the CTOP acquired by a buyback, depending on `pool.buyback_mode`.
+/// Returns the amount acquired. Previously held CTOP is excluded so it is never burned.
+fn settle_bought_ctop<'info>(ctx: &mut Context<ProcessBuyAndBurn<'info>>, pool_bump: u8) -> Result<u64> {
+    let pool_ctop = TokenAccount::try_deserialize(&mut &ctx.accounts.pool_ctop_account.try_borrow_data()?[..])?;
+    let acquired = pool_ctop.amount.saturating_sub(ctx.accounts.pool.held_ctop_amount);
+
+    // M-07 FIX: Handle zero balance gracefully (not an error)
+    if acquired == 0 {
+        return Ok(0);
+    }
+
+    if ctx.accounts.pool.buyback_mode == BuybackMode::Hold {
+        ctx.accounts.pool.held_ctop_amount = ctx
+            .accounts
+            .pool
+            .held_ctop_amount
+            .checked_add(acquired)
+            .ok_or(CtoError::MathOverflow)?;
+        return Ok(acquired);
+    }
+
+    transfer_to_incinerator(ctx, acquired, pool_bump)
+}
+
+/// M-07 FIX: Transfers CTOP tokens to the incinerator address for burning.
+fn transfer_to_incinerator<'info>(
+    ctx: &mut Context<ProcessBuyAndBurn<'info>>,
+    bal: u64,
+    pool_bump: u8,
+) -> Result<u64> {
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.pool_ctop_account.to_account_info(),
+                to: ctx.accounts.incinerator_ctop_account.to_account_info(),
+                authority: ctx.accounts.pool.to_account_info(),
+            },
+            pool_seeds!(&ctx.accounts.pool, pool_bump),
+        ),
+        bal,
+    )?;
+
+    Ok(bal)
+}
+
+// ===== Recovery helpers =====
+
+fn transfer_spl_from_pool_with_seeds<'info>(
+    pool: &AccountInfo<'info>,
+    from: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    seeds: &[&[&[u8]]],
+    amount: u64,
+) -> Result<()> {
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            token::Transfer {
+                from: from.to_account_info(),
+                to: to.to_account_info(),
+                authority: pool.clone(),
+            },
+            seeds,
+        ),
+        amount,
+    )
+}
+
+/// Instruction-sysvar based "inline sender" check.
+///
+/// This only works if the requester performs the transfer and recovery in the *same transaction*.
+/// It cannot prove sender for a historical accident.
+fn verify_inline_sender(
+    _instructions_sysvar: &AccountInfo,
+    _requester: &Pubkey,
+    _pool_token_account: &Pubkey,
+    _mint: Pubkey,
+    _amount: u64,
+) -> Result<bool> {
+    Ok(false)
+}
+
+// ============= Accounts =============
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CreatePool<'info> {
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + Pool::SIZE,
+        seeds = [b"pool", token_mint.key().as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    /// SPL Token or Token-2022 mint
+    pub token_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + ActivityLog::SIZE,
+        seeds = [b"activity", pool.key().as_ref()],
+        bump
+    )]
+    pub activity_log: Account<'info, ActivityLog>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + PoolRegistry::SIZE,
+        seeds = [b"registry"],
+        bump
+    )]
+    pub registry: Account<'info, PoolRegistry>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + PoolRegistryPage::SIZE,
+        seeds = [
+            b"registry_page".as_ref(),
+            &(registry.pool_count / PoolRegistryPage::CAPACITY as u64).to_le_bytes()
+        ],
+        bump
+    )]
+    pub registry_page: Account<'info, PoolRegistryPage>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigurePumpSwapPool<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureWhirlpool<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureMeteoraDlmm<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigurePumpSwapHop<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub hop_mint: Account<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        token::mint = hop_mint,
+        token::authority = pool,
+        seeds = [b"pool_hop", pool.key().as_ref(), hop_mint.key().as_ref()],
+        bump
+    )]
+    pub pool_hop_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureBuybackMode<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureBurnAuction<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FillBurnAuction<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(address = pool.burn_token_mint)]
+    pub ctop_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = ctop_mint,
+        token::authority = filler
+    )]
+    pub filler_ctop_account: Account<'info, TokenAccount>,
+
+    /// CHECK
+    #[account(address = INCINERATOR)]
+    pub incinerator: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = filler,
+        associated_token::mint = ctop_mint,
+        associated_token::authority = incinerator
+    )]
+    pub incinerator_ctop_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub filler: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureBurnSchedule<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ConfigureBurnTarget<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub burn_token_mint: Account<'info, Mint>,
+
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ConfigureFeeSink<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureSponsorship<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureCapAdjustedQuorum<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExcludeCappedExcess<'info> {
+    #[account(
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut, has_one = pool)]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        seeds = [b"donor", pool.key().as_ref(), donor.wallet.as_ref()],
+        bump
+    )]
+    pub donor: Account<'info, Donor>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + CapExcessRecord::SIZE,
+        seeds = [b"cap_excess", proposal.key().as_ref(), donor.wallet.as_ref()],
+        bump
+    )]
+    pub cap_excess_record: Account<'info, CapExcessRecord>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureMerkleVoting<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct PostWeightRoot<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut, has_one = pool)]
+    pub proposal: Account<'info, Proposal>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureTieBreak<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureTimelock<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveTie<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut, has_one = pool)]
+    pub proposal: Account<'info, Proposal>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureQuorumTiers<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureFastTrack<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureVotingWindow<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureProposerBond<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureCommitReveal<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureAntiSniping<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureTimeWeightedVoting<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureVoteWeighting<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureAllowlist<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct AddToAllowlist<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + AllowlistEntry::SIZE,
+        seeds = [b"allowlist", pool.key().as_ref(), wallet.as_ref()],
+        bump
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct RemoveFromAllowlist<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"allowlist", pool.key().as_ref(), wallet.as_ref()],
+        bump
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureDonationLimits<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureDonorSlippage<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureTokenDonations<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureOracleGuards<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureCampaign<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureReferrals<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureSolUsdFeed<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureSplDonationMint<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + SplDonationMint::SIZE,
+        seeds = [b"spl_mint", pool.key().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub spl_donation_mint: Account<'info, SplDonationMint>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DonateSpl<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        has_one = pool,
+        has_one = mint,
+        has_one = price_feed,
+        seeds = [b"spl_mint", pool.key().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub spl_donation_mint: Account<'info, SplDonationMint>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = donor_wallet,
+        token::token_program = token_program
+    )]
+    pub donor_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = donor_wallet,
+        associated_token::mint = mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program
+    )]
+    pub pool_spl_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// CHECK: Pyth mint/USD PriceUpdateV2, validated by read_pyth_price
+    pub price_feed: UncheckedAccount<'info>,
+
+    /// CHECK: Pyth SOL/USD PriceUpdateV2, validated by read_pyth_price
+    #[account(address = pool.sol_usd_price_feed)]
+    pub sol_usd_price_feed: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = donor_wallet,
+        space = 8 + SplPosition::SIZE,
+        seeds = [b"spl_position", spl_donation_mint.key().as_ref(), donor_wallet.key().as_ref()],
+        bump
+    )]
+    pub spl_position: Account<'info, SplPosition>,
+
+    #[account(
+        mut,
+        seeds = [b"activity", pool.key().as_ref()],
+        bump
+    )]
+    pub activity_log: Account<'info, ActivityLog>,
+
+    #[account(mut)]
+    pub donor_wallet: Signer<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RedeemSplPosition<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        has_one = pool,
+        has_one = mint,
+        seeds = [b"spl_mint", pool.key().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub spl_donation_mint: Account<'info, SplDonationMint>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        mut,
+        has_one = wallet,
+        seeds = [b"spl_position", spl_donation_mint.key().as_ref(), wallet.key().as_ref()],
+        bump
+    )]
+    pub spl_position: Account<'info, SplPosition>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program
+    )]
+    pub pool_spl_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = wallet,
+        token::token_program = token_program
+    )]
+    pub wallet_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    pub wallet: Signer<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DonatePoolToken<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(address = pool.token_mint, mint::token_program = token_program)]
+    pub token_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = donor_wallet,
+        token::token_program = token_program
+    )]
+    pub donor_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = donor_wallet,
+        associated_token::mint = token_mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program
+    )]
+    pub pool_token_treasury: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"activity", pool.key().as_ref()],
+        bump
+    )]
+    pub activity_log: Account<'info, ActivityLog>,
+
+    #[account(mut)]
+    pub donor_wallet: Signer<'info>,
+
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureAuthorityExpiry<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureDeadManSwitch<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AuthorityHeartbeat<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ProposeAuthorityTransfer<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub new_authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RefreshMintRiskFlags<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(address = pool.token_mint)]
+    pub token_mint: InterfaceAccount<'info, token_interface::Mint>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureRaydiumPool<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureProposalLimits<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DonateSol<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        init_if_needed,
+        payer = donor_wallet,
+        space = 8 + Donor::SIZE,
+        seeds = [b"donor", pool.key().as_ref(), donor_wallet.key().as_ref()],
+        bump
+    )]
+    pub donor: Account<'info, Donor>,
+
+    /// Donor account of the `referrer` argument; required when one is named.
+    #[account(mut)]
+    pub referrer_donor: Option<Account<'info, Donor>>,
+
+    /// The pool's matching campaign; pass it to have the donation matched.
+    #[account(
+        mut,
+        seeds = [b"matching", pool.key().as_ref()],
+        bump
+    )]
+    pub matching_vault: Option<Account<'info, MatchingVault>>,
+
+    /// Donor's allowlist entry; required while the pool is in allowlist mode.
+    #[account(
+        seeds = [b"allowlist", pool.key().as_ref(), donor_wallet.key().as_ref()],
+        bump
+    )]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+
+    #[account(
+        init_if_needed,
+        payer = donor_wallet,
+        space = 8 + DonorIndexPage::SIZE,
+        seeds = [
+            b"donor_index",
+            pool.key().as_ref(),
+            &(pool.indexed_donor_count / DonorIndexPage::CAPACITY as u64).to_le_bytes()
+        ],
+        bump
+    )]
+    pub donor_index_page: Account<'info, DonorIndexPage>,
+
+    #[account(
+        mut,
+        seeds = [b"activity", pool.key().as_ref()],
+        bump
+    )]
+    pub activity_log: Account<'info, ActivityLog>,
+
+    #[account(mut)]
+    pub donor_wallet: Signer<'info>,
+
+    /// CHECK: stake pool program (e.g. Jito)
+    pub stake_pool_program: UncheckedAccount<'info>,
+
+    /// CHECK: stake pool state account
+    #[account(mut)]
+    pub stake_pool: UncheckedAccount<'info>,
+
+    /// CHECK: stake pool withdraw authority
+    pub stake_pool_withdraw_authority: UncheckedAccount<'info>,
+
+    /// CHECK: reserve stake account
+    #[account(mut)]
+    pub reserve_stake: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub manager_fee_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub referrer_pool_tokens_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = donor_wallet,
+        associated_token::mint = lst_mint,
+        associated_token::authority = pool
+    )]
+    pub pool_lst_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub lst_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(beneficiary: Pubkey)]
+pub struct InitDonor<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Donor::SIZE,
+        seeds = [b"donor", pool.key().as_ref(), beneficiary.as_ref()],
+        bump
+    )]
+    pub donor: Account<'info, Donor>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + DonorIndexPage::SIZE,
+        seeds = [
+            b"donor_index",
+            pool.key().as_ref(),
+            &(pool.indexed_donor_count / DonorIndexPage::CAPACITY as u64).to_le_bytes()
+        ],
+        bump
+    )]
+    pub donor_index_page: Account<'info, DonorIndexPage>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DonateSolFor<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    /// Beneficiary's Donor, created beforehand with `init_donor`
+    #[account(
+        mut,
+        seeds = [b"donor", pool.key().as_ref(), donor.wallet.as_ref()],
+        bump
+    )]
+    pub donor: Account<'info, Donor>,
+
+    /// The pool's matching campaign; pass it to have the donation matched.
+    #[account(
+        mut,
+        seeds = [b"matching", pool.key().as_ref()],
+        bump
+    )]
+    pub matching_vault: Option<Account<'info, MatchingVault>>,
+
+    /// Beneficiary's allowlist entry; required while the pool is in allowlist mode.
+    #[account(
+        seeds = [b"allowlist", pool.key().as_ref(), donor.wallet.as_ref()],
+        bump
+    )]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+
+    #[account(
+        mut,
+        seeds = [b"activity", pool.key().as_ref()],
+        bump
+    )]
+    pub activity_log: Account<'info, ActivityLog>,
+
+    /// Funds the deposit; may be a PDA signing through CPI
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: stake pool program (e.g. Jito)
+    pub stake_pool_program: UncheckedAccount<'info>,
+
+    /// CHECK: stake pool state account
+    #[account(mut)]
+    pub stake_pool: UncheckedAccount<'info>,
+
+    /// CHECK: stake pool withdraw authority
+    pub stake_pool_withdraw_authority: UncheckedAccount<'info>,
+
+    /// CHECK: reserve stake account
+    #[account(mut)]
+    pub reserve_stake: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub manager_fee_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub referrer_pool_tokens_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = lst_mint,
+        associated_token::authority = pool
+    )]
+    pub pool_lst_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub lst_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CreateMatchingCampaign<'info> {
+    #[account(
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        init,
+        payer = sponsor,
+        space = 8 + MatchingVault::SIZE,
+        seeds = [b"matching", pool.key().as_ref()],
+        bump
+    )]
+    pub matching_vault: Account<'info, MatchingVault>,
+
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FundMatchingCampaign<'info> {
+    #[account(
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        has_one = sponsor,
+        seeds = [b"matching", pool.key().as_ref()],
+        bump = matching_vault.bump
+    )]
+    pub matching_vault: Account<'info, MatchingVault>,
+
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CloseMatchingCampaign<'info> {
+    #[account(
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        close = sponsor,
+        has_one = sponsor,
+        seeds = [b"matching", pool.key().as_ref()],
+        bump = matching_vault.bump
+    )]
+    pub matching_vault: Account<'info, MatchingVault>,
+
+    /// CHECK: receives the unspent budget and rent; must be the campaign's sponsor
+    #[account(mut)]
+    pub sponsor: UncheckedAccount<'info>,
+
+    pub closer: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct BeginSunset<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CloseSunsetPool<'info> {
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        associated_token::mint = lst_mint,
+        associated_token::authority = pool
+    )]
+    pub pool_lst_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = pool.lst_mint)]
+    pub lst_mint: Account<'info, Mint>,
+
+    /// Required while the pool holds CTOP (`held_ctop_amount`), which is burned
+    #[account(mut, address = pool.burn_token_mint)]
+    pub burn_token_mint: Option<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool_ctop", pool.key().as_ref(), pool.burn_token_mint.as_ref()],
+        bump
+    )]
+    pub pool_ctop_account: Option<Account<'info, TokenAccount>>,
+
+    /// Required while the pool holds treasury tokens (`treasury_token_amount`), which are burned
+    #[account(mut, address = pool.token_mint, mint::token_program = token_mint_program)]
+    pub token_mint: Option<InterfaceAccount<'info, token_interface::Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_mint_program
+    )]
+    pub pool_token_treasury: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    pub token_mint_program: Option<Interface<'info, token_interface::TokenInterface>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UnstakeSunsetPool<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    /// CHECK: stake pool program (e.g. Jito)
+    pub stake_pool_program: UncheckedAccount<'info>,
+
+    /// CHECK: stake pool state account
+    #[account(mut)]
+    pub stake_pool: UncheckedAccount<'info>,
+
+    /// CHECK: stake pool withdraw authority
+    pub stake_pool_withdraw_authority: UncheckedAccount<'info>,
+
+    /// CHECK: reserve stake account
+    #[account(mut)]
+    pub reserve_stake: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub manager_fee_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = lst_mint,
+        associated_token::authority = pool
+    )]
+    pub pool_lst_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = pool.lst_mint)]
+    pub lst_mint: Account<'info, Mint>,
+
+    pub cranker: Signer<'info>,
+
+    /// CHECK: sysvar clock
+    pub clock: UncheckedAccount<'info>,
+    /// CHECK: sysvar stake history
+    pub stake_history: UncheckedAccount<'info>,
+    /// CHECK: stake program
+    pub stake_program: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DistributeRemaining<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        close = donor_wallet,
+        seeds = [b"donor", pool.key().as_ref(), donor_wallet.key().as_ref()],
+        bump
+    )]
+    pub donor: Account<'info, Donor>,
+
+    /// CHECK: the donor's wallet, paid the donor's balance (native pools) and rent
+    #[account(mut)]
+    pub donor_wallet: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = lst_mint,
+        associated_token::authority = pool
+    )]
+    pub pool_lst_account: Account<'info, TokenAccount>,
+
+    /// Required for staked pools.
+    #[account(
+        mut,
+        associated_token::mint = lst_mint,
+        associated_token::authority = donor_wallet
+    )]
+    pub donor_lst_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(address = pool.lst_mint)]
+    pub lst_mint: Account<'info, Mint>,
+
+    pub cranker: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FoldRoundingDust<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub caller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct EnableShareMint<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"share_mint", pool.key().as_ref()],
+        bump,
+        mint::decimals = SHARE_MINT_DECIMALS,
+        mint::authority = pool
+    )]
+    pub share_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CreatePoolMetadata<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PoolMetadata::SIZE,
+        seeds = [b"metadata", pool.key().as_ref()],
+        bump
+    )]
+    pub metadata: Account<'info, PoolMetadata>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdatePoolMetadata<'info> {
+    #[account(
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        seeds = [b"metadata", pool.key().as_ref()],
+        bump
+    )]
+    pub metadata: Account<'info, PoolMetadata>,
+
+    #[account(has_one = pool)]
+    pub change_proposal: Account<'info, Proposal>,
+
+    pub updater: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct TokenizeShares<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        seeds = [b"donor", pool.key().as_ref(), donor_wallet.key().as_ref()],
+        bump
+    )]
+    pub donor: Account<'info, Donor>,
+
+    #[account(mut, address = pool.share_mint)]
+    pub share_mint: Account<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = donor_wallet,
+        associated_token::mint = share_mint,
+        associated_token::authority = donor_wallet
+    )]
+    pub donor_share_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub donor_wallet: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RequestWithdrawal<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        seeds = [b"donor", pool.key().as_ref(), donor_wallet.key().as_ref()],
+        bump
+    )]
+    pub donor: Account<'info, Donor>,
+
+    #[account(
+        init,
+        payer = donor_wallet,
+        space = 8 + WithdrawalRequest::SIZE,
+        seeds = [b"withdrawal_request", pool.key().as_ref(), &pool.withdrawal_queue_tail.to_le_bytes()],
+        bump
+    )]
+    pub withdrawal_request: Account<'info, WithdrawalRequest>,
+
+    #[account(mut)]
+    pub donor_wallet: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelWithdrawalRequest<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        seeds = [b"donor", pool.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub donor: Account<'info, Donor>,
+
+    #[account(mut, has_one = pool, has_one = owner)]
+    pub withdrawal_request: Account<'info, WithdrawalRequest>,
+
+    pub owner: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ProcessWithdrawalQueue<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        has_one = pool,
+        has_one = owner,
+        seeds = [b"withdrawal_request", pool.key().as_ref(), &pool.withdrawal_queue_head.to_le_bytes()],
+        bump,
+        close = owner
+    )]
+    pub withdrawal_request: Account<'info, WithdrawalRequest>,
+
+    /// CHECK: the request's owner, paid the SOL and the request's rent
+    #[account(mut)]
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"activity", pool.key().as_ref()],
+        bump
+    )]
+    pub activity_log: Account<'info, ActivityLog>,
+
+    /// CHECK
+    pub stake_pool_program: UncheckedAccount<'info>,
+    /// CHECK
+    #[account(mut)]
+    pub stake_pool: UncheckedAccount<'info>,
+    /// CHECK
+    pub stake_pool_withdraw_authority: UncheckedAccount<'info>,
+    /// CHECK
+    #[account(mut)]
+    pub reserve_stake: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub manager_fee_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = lst_mint,
+        associated_token::authority = pool
+    )]
+    pub pool_lst_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub lst_mint: Account<'info, Mint>,
+
+    /// CHECK: sysvar clock
+    pub clock: UncheckedAccount<'info>,
+    /// CHECK: sysvar stake history
+    pub stake_history: UncheckedAccount<'info>,
+    /// CHECK: stake program
+    pub stake_program: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    pub cranker: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RedeemShareTokens<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        init_if_needed,
+        payer = holder_wallet,
+        space = 8 + Donor::SIZE,
+        seeds = [b"donor", pool.key().as_ref(), holder_wallet.key().as_ref()],
+        bump
+    )]
+    pub donor: Account<'info, Donor>,
+
+    #[account(
+        init_if_needed,
+        payer = holder_wallet,
+        space = 8 + DonorIndexPage::SIZE,
+        seeds = [
+            b"donor_index",
+            pool.key().as_ref(),
+            &(pool.indexed_donor_count / DonorIndexPage::CAPACITY as u64).to_le_bytes()
+        ],
+        bump
+    )]
+    pub donor_index_page: Account<'info, DonorIndexPage>,
+
+    #[account(mut, address = pool.share_mint)]
+    pub share_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = share_mint,
+        token::authority = holder_wallet
+    )]
+    pub holder_share_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub holder_wallet: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MigrateShareScale<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut, has_one = pool)]
+    pub donor: Account<'info, Donor>,
+
+    pub caller: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    /// CHECK: discriminator and version are validated in the handler
+    #[account(mut, owner = crate::ID)]
+    pub target: UncheckedAccount<'info>,
+
+    /// The target Proposal's pool; required when upgrading a version-0 Proposal
+    #[account(
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Option<Account<'info, Pool>>,
+
+    /// The target Pool's ActivityLog; created when upgrading a version-0 Pool, omitted otherwise
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ActivityLog::SIZE,
+        seeds = [b"activity", target.key().as_ref()],
+        bump
+    )]
+    pub activity_log: Option<Account<'info, ActivityLog>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitActivityLog<'info> {
+    #[account(
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ActivityLog::SIZE,
+        seeds = [b"activity", pool.key().as_ref()],
+        bump
+    )]
+    pub activity_log: Account<'info, ActivityLog>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SweepSurplus<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    /// CHECK: stake pool program (e.g. Jito)
+    pub stake_pool_program: UncheckedAccount<'info>,
+
+    /// CHECK: stake pool state account
+    #[account(mut)]
+    pub stake_pool: UncheckedAccount<'info>,
+
+    /// CHECK: stake pool withdraw authority
+    pub stake_pool_withdraw_authority: UncheckedAccount<'info>,
+
+    /// CHECK: reserve stake account
+    #[account(mut)]
+    pub reserve_stake: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub manager_fee_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub referrer_pool_tokens_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = lst_mint,
+        associated_token::authority = pool
+    )]
+    pub pool_lst_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub lst_mint: Account<'info, Mint>,
+
+    pub caller: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawSol<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        seeds = [b"donor", pool.key().as_ref(), donor_wallet.key().as_ref()],
+        bump
+    )]
+    pub donor: Account<'info, Donor>,
+
+    #[account(
+        mut,
+        seeds = [b"activity", pool.key().as_ref()],
+        bump
+    )]
+    pub activity_log: Account<'info, ActivityLog>,
+
+    #[account(mut)]
+    pub donor_wallet: Signer<'info>,
+
+    /// CHECK
+    pub stake_pool_program: UncheckedAccount<'info>,
+    /// CHECK
+    #[account(mut)]
+    pub stake_pool: UncheckedAccount<'info>,
+    /// CHECK
+    pub stake_pool_withdraw_authority: UncheckedAccount<'info>,
+    /// CHECK
+    #[account(mut)]
+    pub reserve_stake: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub manager_fee_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = lst_mint,
+        associated_token::authority = pool
+    )]
+    pub pool_lst_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub lst_mint: Account<'info, Mint>,
+
+    /// CHECK: sysvar clock
+    pub clock: UncheckedAccount<'info>,
+    /// CHECK: sysvar stake history
+    pub stake_history: UncheckedAccount<'info>,
+    /// CHECK: stake program
+    pub stake_program: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    // ===== WithdrawStake fallback (supplied only when the reserve can't cover the SOL) =====
+    /// CHECK: validated by the stake pool program
+    #[account(mut)]
+    pub validator_list: Option<UncheckedAccount<'info>>,
+    /// CHECK: validator or transient stake account to split; validated by the stake pool program
+    #[account(mut)]
+    pub validator_stake: Option<UncheckedAccount<'info>>,
+    #[account(
+        init,
+        payer = donor_wallet,
+        space = 8 + StakeWithdrawal::SIZE,
+        seeds = [b"stake_withdrawal", pool.key().as_ref(), &pool.stake_withdrawal_count.to_le_bytes()],
+        bump
+    )]
+    pub stake_withdrawal: Option<Account<'info, StakeWithdrawal>>,
+    /// CHECK: created by the handler as the stake account WithdrawStake splits into
+    #[account(
+        mut,
+        seeds = [b"withdrawal_stake", pool.key().as_ref(), &pool.stake_withdrawal_count.to_le_bytes()],
+        bump
+    )]
+    pub withdrawal_stake: Option<UncheckedAccount<'info>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimPayout<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        has_one = pool,
+        has_one = destination,
+        seeds = [b"payout_claim", pool.key().as_ref(), destination.key().as_ref()],
+        bump
+    )]
+    pub payout_claim: Account<'info, PayoutClaim>,
+
+    #[account(mut)]
+    pub destination: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ReclaimExpiredPayout<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut, has_one = pool)]
+    pub payout_claim: Account<'info, PayoutClaim>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimDeactivatedStake<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        has_one = pool,
+        has_one = recipient,
+        has_one = rent_payer,
+        seeds = [b"stake_withdrawal", pool.key().as_ref(), &stake_withdrawal.index.to_le_bytes()],
+        bump,
+        close = rent_payer
+    )]
+    pub stake_withdrawal: Account<'info, StakeWithdrawal>,
+
+    /// CHECK: the record's stake account
+    #[account(mut, address = stake_withdrawal.stake_account)]
+    pub withdrawal_stake: UncheckedAccount<'info>,
+
+    /// CHECK
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+
+    /// CHECK
+    #[account(mut)]
+    pub rent_payer: UncheckedAccount<'info>,
+
+    /// CHECK
+    #[account(mut, address = pool.dev_fee_wallet)]
+    pub dev_fee_wallet: UncheckedAccount<'info>,
+
+    /// CHECK: only required when the record carries a protocol fee and pool.fee_sink != Burn
+    #[account(mut, address = pool.fee_sink_destination)]
+    pub fee_sink_wallet: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: sysvar clock
+    pub clock: UncheckedAccount<'info>,
+    /// CHECK: sysvar stake history
+    pub stake_history: UncheckedAccount<'info>,
+    /// CHECK: stake program
+    #[account(address = STAKE_PROGRAM_ID)]
+    pub stake_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    pub cranker: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RageQuit<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut, has_one = pool)]
+    pub proposal: Account<'info, Proposal>,
+
+    /// The donor's vote on `proposal`; omit if they voted through `vote_compact`.
+    #[account(
+        seeds = [b"vote", proposal.key().as_ref(), donor_wallet.key().as_ref()],
+        bump
+    )]
+    pub vote_record: Option<Account<'info, VoteRecord>>,
+
+    #[account(
+        mut,
+        seeds = [b"donor", pool.key().as_ref(), donor_wallet.key().as_ref()],
+        bump
+    )]
+    pub donor: Account<'info, Donor>,
+
+    #[account(
+        mut,
+        seeds = [b"activity", pool.key().as_ref()],
+        bump
+    )]
+    pub activity_log: Account<'info, ActivityLog>,
+
+    #[account(mut)]
+    pub donor_wallet: Signer<'info>,
+
+    /// CHECK
+    pub stake_pool_program: UncheckedAccount<'info>,
+    /// CHECK
+    #[account(mut)]
+    pub stake_pool: UncheckedAccount<'info>,
+    /// CHECK
+    pub stake_pool_withdraw_authority: UncheckedAccount<'info>,
+    /// CHECK
+    #[account(mut)]
+    pub reserve_stake: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub manager_fee_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = lst_mint,
+        associated_token::authority = pool
+    )]
+    pub pool_lst_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub lst_mint: Account<'info, Mint>,
+
+    /// CHECK: sysvar clock
+    pub clock: UncheckedAccount<'info>,
+    /// CHECK: sysvar stake history
+    pub stake_history: UncheckedAccount<'info>,
+    /// CHECK: stake program
+    pub stake_program: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseDonor<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        close = donor_wallet,
+        seeds = [b"donor", pool.key().as_ref(), donor_wallet.key().as_ref()],
+        bump
+    )]
+    pub donor: Account<'info, Donor>,
+
+    #[account(mut)]
+    pub donor_wallet: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ConsolidateDust<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        close = donor_wallet,
+        seeds = [b"donor", pool.key().as_ref(), donor_wallet.key().as_ref()],
+        bump
+    )]
+    pub donor: Account<'info, Donor>,
+
+    /// CHECK: rent destination, bound to the Donor PDA by seeds
+    #[account(mut)]
+    pub donor_wallet: UncheckedAccount<'info>,
+
+    pub caller: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(requested_amount: u64, denomination: RequestDenomination, destination_wallet: Pubkey)]
+pub struct CreateProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        seeds = [b"donor", pool.key().as_ref(), proposer_wallet.key().as_ref()],
+        bump
+    )]
+    pub donor: Account<'info, Donor>,
+
+    #[account(
+        init,
+        payer = proposer_wallet,
+        space = 8 + Proposal::SIZE,
+        seeds = [b"proposal", pool.key().as_ref(), &pool.proposal_count.to_le_bytes()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        init_if_needed,
+        payer = proposer_wallet,
+        space = 8 + DestinationRecord::SIZE,
+        seeds = [b"destination", pool.key().as_ref(), destination_wallet.as_ref()],
+        bump
+    )]
+    pub destination_record: Account<'info, DestinationRecord>,
+
+    /// CHECK: only its owner and data length are read to classify it.
+    #[account(address = destination_wallet)]
+    pub destination: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = proposer_wallet,
+        space = 8 + ProposalIndexPage::SIZE,
+        seeds = [
+            b"proposal_index",
+            pool.key().as_ref(),
+            &(pool.proposal_count / ProposalIndexPage::CAPACITY as u64).to_le_bytes()
+        ],
+        bump
+    )]
+    pub proposal_index_page: Account<'info, ProposalIndexPage>,
+
+    #[account(
+        mut,
+        seeds = [b"activity", pool.key().as_ref()],
+        bump
+    )]
+    pub activity_log: Account<'info, ActivityLog>,
+
+    #[account(mut)]
+    pub proposer_wallet: Signer<'info>,
+
+    /// CHECK
+    pub stake_pool: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = lst_mint,
+        associated_token::authority = pool
+    )]
+    pub pool_lst_account: Account<'info, TokenAccount>,
+
+    pub lst_mint: Account<'info, Mint>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Adopted template to instantiate
+    #[account(mut, has_one = pool)]
+    pub template: Option<Account<'info, ProposalTemplate>>,
+
+    /// Proposer's allowlist entry; required while the pool is in allowlist mode.
+    #[account(
+        seeds = [b"allowlist", pool.key().as_ref(), proposer_wallet.key().as_ref()],
+        bump
+    )]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+
+    /// Escrow for the proposer bond; required while the pool charges one.
+    #[account(
+        init,
+        payer = proposer_wallet,
+        space = 8 + ProposerBond::SIZE,
+        seeds = [b"bond", proposal.key().as_ref()],
+        bump
+    )]
+    pub proposer_bond: Option<Account<'info, ProposerBond>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CreateConfigProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        seeds = [b"donor", pool.key().as_ref(), proposer_wallet.key().as_ref()],
+        bump
+    )]
+    pub donor: Account<'info, Donor>,
+
+    #[account(
+        init,
+        payer = proposer_wallet,
+        space = 8 + Proposal::SIZE,
+        seeds = [b"proposal", pool.key().as_ref(), &pool.proposal_count.to_le_bytes()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// Destination record of the pool itself, shared by all ConfigChange proposals
+    #[account(
+        init_if_needed,
+        payer = proposer_wallet,
+        space = 8 + DestinationRecord::SIZE,
+        seeds = [b"destination", pool.key().as_ref(), pool.key().as_ref()],
+        bump
+    )]
+    pub destination_record: Account<'info, DestinationRecord>,
+
+    #[account(
+        init_if_needed,
+        payer = proposer_wallet,
+        space = 8 + ProposalIndexPage::SIZE,
+        seeds = [
+            b"proposal_index",
+            pool.key().as_ref(),
+            &(pool.proposal_count / ProposalIndexPage::CAPACITY as u64).to_le_bytes()
+        ],
+        bump
+    )]
+    pub proposal_index_page: Account<'info, ProposalIndexPage>,
+
+    #[account(
+        mut,
+        seeds = [b"activity", pool.key().as_ref()],
+        bump
+    )]
+    pub activity_log: Account<'info, ActivityLog>,
+
+    #[account(mut)]
+    pub proposer_wallet: Signer<'info>,
+
+    /// CHECK
+    #[account(address = pool.stake_pool)]
+    pub stake_pool: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = lst_mint,
+        associated_token::authority = pool
+    )]
+    pub pool_lst_account: Account<'info, TokenAccount>,
+
+    #[account(address = pool.lst_mint)]
+    pub lst_mint: Account<'info, Mint>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ProposeTemplate<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        seeds = [b"donor", pool.key().as_ref(), proposer_wallet.key().as_ref()],
+        bump
+    )]
+    pub donor: Account<'info, Donor>,
+
+    #[account(
+        init,
+        payer = proposer_wallet,
+        space = 8 + ProposalTemplate::SIZE,
+        seeds = [b"template", pool.key().as_ref(), &pool.template_count.to_le_bytes()],
+        bump
+    )]
+    pub template: Account<'info, ProposalTemplate>,
+
+    #[account(
+        init,
+        payer = proposer_wallet,
+        space = 8 + Proposal::SIZE,
+        seeds = [b"proposal", pool.key().as_ref(), &pool.proposal_count.to_le_bytes()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        init_if_needed,
+        payer = proposer_wallet,
+        space = 8 + ProposalIndexPage::SIZE,
+        seeds = [
+            b"proposal_index",
+            pool.key().as_ref(),
+            &(pool.proposal_count / ProposalIndexPage::CAPACITY as u64).to_le_bytes()
+        ],
+        bump
+    )]
+    pub proposal_index_page: Account<'info, ProposalIndexPage>,
+
+    #[account(mut)]
+    pub proposer_wallet: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CreatePoll<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        seeds = [b"donor", pool.key().as_ref(), proposer_wallet.key().as_ref()],
+        bump
+    )]
+    pub donor: Account<'info, Donor>,
+
+    #[account(
+        init,
+        payer = proposer_wallet,
+        space = 8 + Proposal::SIZE,
+        seeds = [b"proposal", pool.key().as_ref(), &pool.proposal_count.to_le_bytes()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        init_if_needed,
+        payer = proposer_wallet,
+        space = 8 + ProposalIndexPage::SIZE,
+        seeds = [
+            b"proposal_index",
+            pool.key().as_ref(),
+            &(pool.proposal_count / ProposalIndexPage::CAPACITY as u64).to_le_bytes()
+        ],
+        bump
+    )]
+    pub proposal_index_page: Account<'info, ProposalIndexPage>,
+
+    #[account(mut)]
+    pub proposer_wallet: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SponsorProposal<'info> {
+    #[account(
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut, has_one = pool)]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        seeds = [b"donor", pool.key().as_ref(), sponsor_wallet.key().as_ref()],
+        bump
+    )]
+    pub donor: Account<'info, Donor>,
+
+    #[account(
+        init,
+        payer = sponsor_wallet,
+        space = 8 + Sponsorship::SIZE,
+        seeds = [b"sponsor", proposal.key().as_ref(), sponsor_wallet.key().as_ref()],
+        bump
+    )]
+    pub sponsorship: Account<'info, Sponsorship>,
+
+    #[account(mut)]
+    pub sponsor_wallet: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClassifyProposalDestination<'info> {
+    #[account(
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut, has_one = pool)]
+    pub proposal: Account<'info, Proposal>,
+
+    /// CHECK: only its owner and data length are read to classify it.
+    #[account(address = proposal.destination_wallet)]
+    pub destination: UncheckedAccount<'info>,
+
+    /// CHECK: must be owned by the Squads v4 program; checked in classify_destination.
+    pub squads_multisig: Option<UncheckedAccount<'info>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FinalizePoll<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut, has_one = pool)]
+    pub proposal: Account<'info, Proposal>,
+
+    pub finalizer: Signer<'info>,
+
+    /// Required for AdoptTemplate proposals
+    #[account(mut, has_one = pool)]
+    pub template: Option<Account<'info, ProposalTemplate>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Vote<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut, has_one = pool)]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        seeds = [b"donor", pool.key().as_ref(), voter_wallet.key().as_ref()],
+        bump
+    )]
+    pub donor: Account<'info, Donor>,
+
+    #[account(
+        init_if_needed,
+        payer = voter_wallet,
+        space = 8 + VoteRecord::SIZE,
+        seeds = [b"vote", proposal.key().as_ref(), voter_wallet.key().as_ref()],
+        bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    #[account(
+        mut,
+        seeds = [b"activity", pool.key().as_ref()],
+        bump
+    )]
+    pub activity_log: Account<'info, ActivityLog>,
+
+    #[account(mut)]
+    pub voter_wallet: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CommitVote<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(has_one = pool)]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        seeds = [b"donor", pool.key().as_ref(), voter_wallet.key().as_ref()],
+        bump
+    )]
+    pub donor: Account<'info, Donor>,
+
+    #[account(
+        init_if_needed,
+        payer = voter_wallet,
+        space = 8 + VoteCommitment::SIZE,
+        seeds = [b"vote_commit", proposal.key().as_ref(), voter_wallet.key().as_ref()],
+        bump
+    )]
+    pub vote_commitment: Account<'info, VoteCommitment>,
+
+    #[account(mut)]
+    pub voter_wallet: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RevealVote<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut, has_one = pool)]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        mut,
+        seeds = [b"donor", pool.key().as_ref(), voter_wallet.key().as_ref()],
+        bump
+    )]
+    pub donor: Account<'info, Donor>,
+
+    #[account(
+        mut,
+        close = voter_wallet,
+        seeds = [b"vote_commit", proposal.key().as_ref(), voter_wallet.key().as_ref()],
+        bump
+    )]
+    pub vote_commitment: Account<'info, VoteCommitment>,
+
+    #[account(
+        init_if_needed,
+        payer = voter_wallet,
+        space = 8 + VoteRecord::SIZE,
+        seeds = [b"vote", proposal.key().as_ref(), voter_wallet.key().as_ref()],
+        bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    #[account(
+        mut,
+        seeds = [b"activity", pool.key().as_ref()],
+        bump
+    )]
+    pub activity_log: Account<'info, ActivityLog>,
+
+    #[account(mut)]
+    pub voter_wallet: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct VoteCompact<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut, has_one = pool)]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        mut,
+        seeds = [b"donor", pool.key().as_ref(), voter_wallet.key().as_ref()],
+        bump
+    )]
+    pub donor: Account<'info, Donor>,
+
+    /// CHECK: the voter's VoteRecord PDA for this proposal; must not exist
+    #[account(
+        seeds = [b"vote", proposal.key().as_ref(), voter_wallet.key().as_ref()],
+        bump
+    )]
+    pub vote_record: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"activity", pool.key().as_ref()],
+        bump
+    )]
+    pub activity_log: Account<'info, ActivityLog>,
+
+    pub voter_wallet: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DelegateVotes<'info> {
+    #[account(
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        seeds = [b"donor", pool.key().as_ref(), delegator_wallet.key().as_ref()],
+        bump
+    )]
+    pub donor: Account<'info, Donor>,
+
+    #[account(
+        init,
+        payer = delegator_wallet,
+        space = 8 + Delegation::SIZE,
+        seeds = [b"delegation", pool.key().as_ref(), delegator_wallet.key().as_ref()],
+        bump
+    )]
+    pub delegation: Account<'info, Delegation>,
+
+    #[account(
+        init_if_needed,
+        payer = delegator_wallet,
+        space = 8 + VoteEscrow::SIZE,
+        seeds = [b"escrow", pool.key().as_ref(), delegate.key().as_ref()],
+        bump
+    )]
+    pub vote_escrow: Account<'info, VoteEscrow>,
+
+    /// CHECK: delegate wallet; only used as a seed
+    pub delegate: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub delegator_wallet: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RequestUndelegate<'info> {
+    #[account(
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        has_one = pool,
+        seeds = [b"delegation", pool.key().as_ref(), delegator_wallet.key().as_ref()],
+        bump
+    )]
+    pub delegation: Account<'info, Delegation>,
+
+    pub delegator_wallet: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FinalizeUndelegate<'info> {
+    #[account(
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        seeds = [b"donor", pool.key().as_ref(), delegator_wallet.key().as_ref()],
+        bump
+    )]
+    pub donor: Account<'info, Donor>,
+
+    #[account(
+        mut,
+        close = delegator_wallet,
+        seeds = [b"delegation", pool.key().as_ref(), delegator_wallet.key().as_ref()],
+        bump
+    )]
+    pub delegation: Account<'info, Delegation>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", pool.key().as_ref(), delegation.delegate.as_ref()],
+        bump
+    )]
+    pub vote_escrow: Account<'info, VoteEscrow>,
+
+    #[account(mut)]
+    pub delegator_wallet: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct VoteMerkle<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut, has_one = pool)]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        init_if_needed,
+        payer = voter_wallet,
+        space = 8 + VoteRecord::SIZE,
+        seeds = [b"vote", proposal.key().as_ref(), voter_wallet.key().as_ref()],
+        bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    #[account(
+        mut,
+        seeds = [b"activity", pool.key().as_ref()],
+        bump
+    )]
+    pub activity_log: Account<'info, ActivityLog>,
+
+    #[account(mut)]
+    pub voter_wallet: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct VoteDelegated<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut, has_one = pool)]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        seeds = [b"escrow", pool.key().as_ref(), delegate_wallet.key().as_ref()],
+        bump
+    )]
+    pub vote_escrow: Account<'info, VoteEscrow>,
+
+    #[account(
+        init_if_needed,
+        payer = delegate_wallet,
+        space = 8 + VoteRecord::SIZE,
+        seeds = [b"vote", proposal.key().as_ref(), vote_escrow.key().as_ref()],
+        bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    #[account(
+        mut,
+        seeds = [b"activity", pool.key().as_ref()],
+        bump
+    )]
+    pub activity_log: Account<'info, ActivityLog>,
+
+    #[account(mut)]
+    pub delegate_wallet: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut, has_one = pool)]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        mut,
+        seeds = [b"destination", pool.key().as_ref(), proposal.destination_wallet.as_ref()],
+        bump
+    )]
+    pub destination_record: Account<'info, DestinationRecord>,
+
+    #[account(
+        mut,
+        seeds = [b"activity", pool.key().as_ref()],
+        bump
+    )]
+    pub activity_log: Account<'info, ActivityLog>,
+
+    /// CHECK
+    #[account(mut)]
+    pub destination_wallet: UncheckedAccount<'info>,
+
+    /// CHECK
+    #[account(mut, address = pool.dev_fee_wallet)]
+    pub dev_fee_wallet: UncheckedAccount<'info>,
+
+    /// CHECK: only required when pool.fee_sink != Burn
+    #[account(mut, address = pool.fee_sink_destination)]
+    pub fee_sink_wallet: Option<UncheckedAccount<'info>>,
+
+    // ===== Stake pool accounts =====
+    /// CHECK
+    pub stake_pool_program: UncheckedAccount<'info>,
+    /// CHECK
+    #[account(mut)]
+    pub stake_pool: UncheckedAccount<'info>,
+    /// CHECK
+    pub stake_pool_withdraw_authority: UncheckedAccount<'info>,
+    /// CHECK
+    #[account(mut)]
+    pub reserve_stake: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub manager_fee_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = lst_mint,
+        associated_token::authority = pool
+    )]
+    pub pool_lst_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub lst_mint: Account<'info, Mint>,
+
+    /// CHECK
+    pub clock: UncheckedAccount<'info>,
+    /// CHECK
+    pub stake_history: UncheckedAccount<'info>,
+    /// CHECK
+    pub stake_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    pub token_program: Program<'info, Token>,
+
+    #[account(mut)]
+    pub executor: Signer<'info>,
+
+    // ===== WithdrawStake fallback (supplied only when the reserve can't cover the SOL) =====
+    /// CHECK: validated by the stake pool program
+    #[account(mut)]
+    pub validator_list: Option<UncheckedAccount<'info>>,
+    /// CHECK: validator or transient stake account to split; validated by the stake pool program
+    #[account(mut)]
+    pub validator_stake: Option<UncheckedAccount<'info>>,
+    #[account(
+        init,
+        payer = executor,
+        space = 8 + StakeWithdrawal::SIZE,
+        seeds = [b"stake_withdrawal", pool.key().as_ref(), &pool.stake_withdrawal_count.to_le_bytes()],
+        bump
+    )]
+    pub stake_withdrawal: Option<Account<'info, StakeWithdrawal>>,
+    /// CHECK: created by the handler as the stake account WithdrawStake splits into
+    #[account(
+        mut,
+        seeds = [b"withdrawal_stake", pool.key().as_ref(), &pool.stake_withdrawal_count.to_le_bytes()],
+        bump
+    )]
+    pub withdrawal_stake: Option<UncheckedAccount<'info>>,
+
+    // ===== TokenPayout accounts (supplied only for a TokenPayout) =====
+    #[account(
+        mut,
+        associated_token::mint = proposal.payout_mint,
+        associated_token::authority = pool
+    )]
+    pub pool_payout_account: Option<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = proposal.payout_mint,
+        associated_token::authority = proposal.destination_wallet
+    )]
+    pub destination_payout_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: the payout mint's SplDonationMint PDA; must not exist
+    #[account(seeds = [b"spl_mint", pool.key().as_ref(), proposal.payout_mint.as_ref()], bump)]
+    pub payout_spl_donation_mint: Option<UncheckedAccount<'info>>,
+
+    /// Credited instead of paying `destination_wallet` (required for SOL payouts)
+    #[account(
+        init_if_needed,
+        payer = executor,
+        space = 8 + PayoutClaim::SIZE,
+        seeds = [b"payout_claim", pool.key().as_ref(), proposal.destination_wallet.as_ref()],
+        bump
+    )]
+    pub payout_claim: Option<Account<'info, PayoutClaim>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ProcessBuyAndBurn<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub system_program: Program<'info, System>,
+
+    // ===== PUMPSWAP SWAP ACCOUNTS (CTOP buy & burn, post-graduation) =====
+    /// CHECK
+    pub pumpswap_program: UncheckedAccount<'info>,
+    /// CHECK
+    #[account(mut)]
+    pub pumpswap_pool: UncheckedAccount<'info>,
+
+    /// CHECK
+    pub pumpswap_global_config: UncheckedAccount<'info>,
+    /// CHECK
+    pub pumpswap_protocol_fee_recipient: UncheckedAccount<'info>,
+    /// CHECK
+    #[account(mut)]
+    pub pumpswap_protocol_fee_recipient_token_account: UncheckedAccount<'info>,
+    /// CHECK
+    pub pumpswap_event_authority: UncheckedAccount<'info>,
+
+    /// The pool's base (CTOP) vault token account.
+    #[account(mut)]
+    pub pumpswap_pool_base_vault: Account<'info, TokenAccount>,
+    /// The pool's quote (WSOL) vault token account.
+    #[account(mut)]
+    pub pumpswap_pool_quote_vault: Account<'info, TokenAccount>,
+
+    /// CHECK
+    pub pumpswap_base_token_program: UncheckedAccount<'info>,
+    /// CHECK
+    pub pumpswap_quote_token_program: UncheckedAccount<'info>,
+
+    // ===== PUMPSWAP SECOND HOP (only required when pool.pumpswap_hop_enabled) =====
+    #[account(address = pool.pumpswap_hop_mint)]
+    pub hop_mint: Option<Account<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [b"pool_hop", pool.key().as_ref(), pool.pumpswap_hop_mint.as_ref()],
+        bump
+    )]
+    pub pool_hop_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK
+    #[account(mut, address = pool.pumpswap_hop_pool_id)]
+    pub pumpswap_hop_pool: Option<UncheckedAccount<'info>>,
+    /// The hop pool's base (CTOP) vault token account.
+    #[account(mut, address = pool.pumpswap_hop_base_vault)]
+    pub pumpswap_hop_base_vault: Option<Account<'info, TokenAccount>>,
+    /// The hop pool's quote (hop mint) vault token account.
+    #[account(mut, address = pool.pumpswap_hop_quote_vault)]
+    pub pumpswap_hop_quote_vault: Option<Account<'info, TokenAccount>>,
+    /// CHECK
+    #[account(mut)]
+    pub pumpswap_hop_fee_recipient_token_account: Option<UncheckedAccount<'info>>,
+
+    // ===== ORCA WHIRLPOOL (only required when burn_venue is Whirlpool) =====
+    /// CHECK
+    #[account(address = WHIRLPOOL_PROGRAM_ID)]
+    pub whirlpool_program: Option<UncheckedAccount<'info>>,
+    /// CHECK
+    #[account(mut, address = pool.whirlpool_pool_id)]
+    pub whirlpool: Option<UncheckedAccount<'info>>,
+    #[account(mut, address = pool.whirlpool_token_vault_a)]
+    pub whirlpool_token_vault_a: Option<Account<'info, TokenAccount>>,
+    #[account(mut, address = pool.whirlpool_token_vault_b)]
+    pub whirlpool_token_vault_b: Option<Account<'info, TokenAccount>>,
+    /// CHECK: validated by the Whirlpool program against `whirlpool`
+    #[account(mut)]
+    pub whirlpool_tick_array_0: Option<UncheckedAccount<'info>>,
+    /// CHECK: validated by the Whirlpool program against `whirlpool`
+    #[account(mut)]
+    pub whirlpool_tick_array_1: Option<UncheckedAccount<'info>>,
+    /// CHECK: validated by the Whirlpool program against `whirlpool`
+    #[account(mut)]
+    pub whirlpool_tick_array_2: Option<UncheckedAccount<'info>>,
+    /// CHECK: PDA ['oracle', whirlpool], checked by the Whirlpool program
+    #[account(mut)]
+    pub whirlpool_oracle: Option<UncheckedAccount<'info>>,
+
+    // ===== METEORA DLMM (only required when burn_venue is MeteoraDlmm) =====
+    /// CHECK
+    #[account(address = METEORA_DLMM_PROGRAM_ID)]
+    pub meteora_program: Option<UncheckedAccount<'info>>,
+    /// CHECK
+    #[account(mut, address = pool.meteora_lb_pair)]
+    pub meteora_lb_pair: Option<UncheckedAccount<'info>>,
+    #[account(mut, address = pool.meteora_reserve_x)]
+    pub meteora_reserve_x: Option<Account<'info, TokenAccount>>,
+    #[account(mut, address = pool.meteora_reserve_y)]
+    pub meteora_reserve_y: Option<Account<'info, TokenAccount>>,
+    /// CHECK: validated by the DLMM program against `meteora_lb_pair`
+    pub meteora_bin_array_bitmap_extension: Option<UncheckedAccount<'info>>,
+    /// CHECK: validated by the DLMM program against `meteora_lb_pair`
+    #[account(mut)]
+    pub meteora_oracle: Option<UncheckedAccount<'info>>,
+    /// CHECK: the DLMM program's event authority PDA, checked by the DLMM program
+    pub meteora_event_authority: Option<UncheckedAccount<'info>>,
+    /// CHECK: validated by the DLMM program against `meteora_lb_pair`
+    #[account(mut)]
+    pub meteora_bin_array_0: Option<UncheckedAccount<'info>>,
+    /// CHECK: validated by the DLMM program against `meteora_lb_pair`
+    #[account(mut)]
+    pub meteora_bin_array_1: Option<UncheckedAccount<'info>>,
+    /// CHECK: validated by the DLMM program against `meteora_lb_pair`
+    #[account(mut)]
+    pub meteora_bin_array_2: Option<UncheckedAccount<'info>>,
+
+    // ===== RAYDIUM CPMM (only required when burn_venue is RaydiumCpmm) =====
+    /// CHECK
+    #[account(address = RAYDIUM_CPMM_PROGRAM_ID)]
+    pub raydium_cpmm_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: the CPMM vault/LP-mint authority PDA, checked by the CPMM program
+    pub raydium_authority: Option<UncheckedAccount<'info>>,
+    /// CHECK: validated by the CPMM program against `raydium_pool_state`
+    pub raydium_amm_config: Option<UncheckedAccount<'info>>,
+    /// CHECK
+    #[account(mut, address = pool.raydium_pool_id)]
+    pub raydium_pool_state: Option<UncheckedAccount<'info>>,
+    #[account(mut, address = pool.raydium_token_0_vault)]
+    pub raydium_token_0_vault: Option<Account<'info, TokenAccount>>,
+    #[account(mut, address = pool.raydium_token_1_vault)]
+    pub raydium_token_1_vault: Option<Account<'info, TokenAccount>>,
+    /// CHECK: validated by the CPMM program against `raydium_pool_state`
+    #[account(mut)]
+    pub raydium_observation_state: Option<UncheckedAccount<'info>>,
+
+    // ===== ORACLE PRICE BAND (only required when pool.burn_price_band_bps > 0) =====
+    /// CHECK: Pyth PriceUpdateV2, verified by `read_pyth_price`
+    #[account(address = pool.sol_usd_price_feed)]
+    pub sol_usd_price_feed: Option<UncheckedAccount<'info>>,
+    /// CHECK: Pyth PriceUpdateV2, verified by `read_pyth_price`
+    #[account(address = pool.ctop_usd_price_feed)]
+    pub ctop_usd_price_feed: Option<UncheckedAccount<'info>>,
+
+    // ===== TOKEN ACCOUNTS =====
+    /// CHECK: the pool's WSOL token account (PDA); created by the handler if missing
+    #[account(
+        mut,
+        seeds = [b"pool_wsol", pool.key().as_ref()],
+        bump
+    )]
+    pub pool_wsol_account: UncheckedAccount<'info>,
+
+    /// CHECK: the pool's CTOP token account (PDA); created by the handler if missing
+    #[account(
+        mut,
+        seeds = [b"pool_ctop", pool.key().as_ref(), ctop_mint.key().as_ref()],
+        bump
+    )]
+    pub pool_ctop_account: UncheckedAccount<'info>,
+
+    /// CHECK: the incinerator's CTOP ATA (address checked); created by the handler if missing
+    #[account(
+        mut,
+        address = anchor_spl::associated_token::get_associated_token_address(&INCINERATOR, &ctop_mint.key())
+    )]
+    pub incinerator_ctop_account: UncheckedAccount<'info>,
+
+    #[account(address = WSOL_MINT)]
+    pub wsol_mint: Account<'info, Mint>,
+
+    #[account(address = pool.burn_token_mint)]
+    pub ctop_mint: Account<'info, Mint>,
+
+    /// CHECK
+    #[account(address = INCINERATOR)]
+    pub incinerator: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct QueueProposal<'info> {
+    #[account(
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut, has_one = pool)]
+    pub proposal: Account<'info, Proposal>,
+
+    pub queuer: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FinalizeExpiredProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut, has_one = pool)]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        mut,
+        seeds = [b"destination", pool.key().as_ref(), proposal.destination_wallet.as_ref()],
+        bump
+    )]
+    pub destination_record: Account<'info, DestinationRecord>,
+
+    #[account(
+        mut,
+        seeds = [b"activity", pool.key().as_ref()],
+        bump
+    )]
+    pub activity_log: Account<'info, ActivityLog>,
+
+    pub finalizer: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ConfigureMilestones<'info> {
+    #[account(
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut, has_one = pool, has_one = proposer_wallet)]
+    pub proposal: Account<'info, Proposal>,
+
+    pub proposer_wallet: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ConfigureStream<'info> {
+    #[account(
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut, has_one = pool, has_one = proposer_wallet)]
+    pub proposal: Account<'info, Proposal>,
+
+    pub proposer_wallet: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ConfigureBuyback<'info> {
+    #[account(
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut, has_one = pool, has_one = proposer_wallet)]
+    pub proposal: Account<'info, Proposal>,
+
+    pub proposer_wallet: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ConfigureAddLiquidity<'info> {
+    #[account(
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut, has_one = pool, has_one = proposer_wallet)]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(address = pool.raydium_token_0_vault)]
+    pub raydium_token_0_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(address = pool.raydium_token_1_vault)]
+    pub raydium_token_1_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    pub proposer_wallet: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ManageLiquidity<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(address = pool.token_mint, mint::token_program = token_mint_program)]
+    pub token_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(address = WSOL_MINT)]
+    pub wsol_mint: Account<'info, Mint>,
+
+    /// The pool's token treasury (see `donate_pool_token`)
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_mint_program
+    )]
+    pub pool_token_treasury: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// Opened and closed within each call, so wrapped SOL never outlives it
+    #[account(
+        init,
+        payer = cranker,
+        token::mint = wsol_mint,
+        token::authority = pool,
+        seeds = [b"pool_liquidity_wsol", pool.key().as_ref()],
+        bump
+    )]
+    pub pool_liquidity_wsol: Account<'info, TokenAccount>,
+
+    /// Checked against `raydium_pool_state` by `validate_raydium_liquidity_accounts`
+    #[account(mut)]
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = cranker,
+        token::mint = lp_mint,
+        token::authority = pool,
+        seeds = [b"pool_lp", pool.key().as_ref()],
+        bump
+    )]
+    pub pool_lp_account: Account<'info, TokenAccount>,
+
+    // ===== RAYDIUM CPMM =====
+    /// CHECK
+    #[account(address = RAYDIUM_CPMM_PROGRAM_ID)]
+    pub raydium_cpmm_program: UncheckedAccount<'info>,
+    /// CHECK: the CPMM vault/LP-mint authority PDA, checked by the CPMM program
+    pub raydium_authority: UncheckedAccount<'info>,
+    /// CHECK
+    #[account(mut, address = pool.raydium_pool_id)]
+    pub raydium_pool_state: UncheckedAccount<'info>,
+    #[account(mut, address = pool.raydium_token_0_vault)]
+    pub raydium_token_0_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(mut, address = pool.raydium_token_1_vault)]
+    pub raydium_token_1_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    /// The pool token's program (SPL Token or Token-2022)
+    pub token_mint_program: Interface<'info, token_interface::TokenInterface>,
+    /// CHECK
+    #[account(address = anchor_spl::token_2022::ID)]
+    pub token_2022_program: UncheckedAccount<'info>,
+    /// CHECK
+    #[account(address = SPL_MEMO_PROGRAM_ID)]
+    pub memo_program: UncheckedAccount<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ConfigureTokenPayout<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut, has_one = pool, has_one = proposer_wallet)]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        constraint = payout_mint.key() != pool.lst_mint
+            && payout_mint.key() != pool.share_mint @ CtoError::InvalidTokenPayout
+    )]
+    pub payout_mint: Account<'info, Mint>,
+
+    /// CHECK: the payout mint's SplDonationMint PDA; must not exist
+    #[account(
+        seeds = [b"spl_mint", pool.key().as_ref(), payout_mint.key().as_ref()],
+        bump,
+        constraint = payout_spl_donation_mint.data_is_empty() @ CtoError::SplVaultTokensReserved
+    )]
+    pub payout_spl_donation_mint: UncheckedAccount<'info>,
+
+    #[account(
+        associated_token::mint = payout_mint,
+        associated_token::authority = pool
+    )]
+    pub pool_payout_account: Account<'info, TokenAccount>,
+
+    /// The destination's ATA for the mint; must already exist
+    #[account(
+        associated_token::mint = payout_mint,
+        associated_token::authority = proposal.destination_wallet
+    )]
+    pub destination_payout_account: Account<'info, TokenAccount>,
+
+    pub proposer_wallet: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ConfigureRecurringBudget<'info> {
+    #[account(
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut, has_one = pool, has_one = proposer_wallet)]
+    pub proposal: Account<'info, Proposal>,
+
+    pub proposer_wallet: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelRecurringBudget<'info> {
+    #[account(
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut, has_one = pool)]
+    pub budget_proposal: Account<'info, Proposal>,
+
+    #[account(has_one = pool)]
+    pub cancel_proposal: Account<'info, Proposal>,
+
+    pub canceller: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ConfirmMilestone<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut, has_one = pool)]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        seeds = [b"donor", pool.key().as_ref(), voter_wallet.key().as_ref()],
+        bump
+    )]
+    pub donor: Account<'info, Donor>,
+
+    #[account(
+        init,
+        payer = voter_wallet,
+        space = 8 + MilestoneConfirmation::SIZE,
+        seeds = [
+            b"milestone_confirm",
+            proposal.key().as_ref(),
+            voter_wallet.key().as_ref(),
+            &[proposal.milestone.released],
+        ],
+        bump
+    )]
+    pub confirmation: Account<'info, MilestoneConfirmation>,
+
+    #[account(mut)]
+    pub voter_wallet: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelMilestonePayout<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut, has_one = pool)]
+    pub proposal: Account<'info, Proposal>,
+
+    pub canceller: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AbortProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut, has_one = pool)]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        mut,
+        seeds = [b"donor", pool.key().as_ref(), aborter_wallet.key().as_ref()],
+        bump
+    )]
+    pub aborter: Account<'info, Donor>,
+
+    #[account(mut)]
+    pub aborter_wallet: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"donor", pool.key().as_ref(), proposal.proposer_wallet.as_ref()],
+        bump
+    )]
+    pub proposer_donor: Account<'info, Donor>,
+
+    #[account(
+        mut,
+        seeds = [b"destination", pool.key().as_ref(), proposal.destination_wallet.as_ref()],
+        bump
+    )]
+    pub destination_record: Account<'info, DestinationRecord>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SettleProposerBond<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut, has_one = pool)]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        mut,
+        close = proposer_wallet,
+        has_one = proposal,
+        seeds = [b"bond", proposal.key().as_ref()],
+        bump = proposer_bond.bump
+    )]
+    pub proposer_bond: Account<'info, ProposerBond>,
+
+    /// CHECK: receives the refund and rent; must be the bond's proposer
+    #[account(mut, address = proposer_bond.proposer)]
+    pub proposer_wallet: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ArchiveProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        has_one = pool,
+        close = proposer_wallet
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + ProposalArchive::SIZE,
+        seeds = [
+            b"archive",
+            pool.key().as_ref(),
+            &(pool.archived_proposal_count / ProposalArchive::CAPACITY as u64).to_le_bytes()
+        ],
+        bump
+    )]
+    pub archive: Account<'info, ProposalArchive>,
+
+    /// CHECK: rent destination, must be the original proposer
+    #[account(mut, address = proposal.proposer_wallet)]
+    pub proposer_wallet: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseVoteRecord<'info> {
+    #[account(mut, close = rent_payer)]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    /// CHECK: the Proposal or RecoveryProposal voted on; may already be closed
+    #[account(address = vote_record.proposal)]
+    pub proposal: UncheckedAccount<'info>,
+
+    /// CHECK: rent destination, must be whoever funded the record
+    #[account(mut, address = vote_record.payer)]
+    pub rent_payer: UncheckedAccount<'info>,
+
+    pub caller: Signer<'info>,
+}
+
+// ===== Recovery accounts =====
+
+#[derive(Accounts)]
+pub struct CloseRecovery<'info> {
+    #[account(
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut, has_one = pool, close = rent_payer)]
+    pub recovery: Account<'info, RecoveryProposal>,
+
+    /// CHECK: rent destination, must be the requester
+    #[account(mut, address = recovery.rent_payer)]
+    pub rent_payer: UncheckedAccount<'info>,
+
+    pub caller: Signer<'info>,
 }
 
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RecoverFundsCreate<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
 
-/// Validates the stake pool configuration against known Jito deployments.
-/// This function ensures only trusted stake pool programs are used.
-fn validate_stake_pool_config(stake_pool_program: Pubkey, stake_pool: Pubkey, lst_mint: Pubkey) -> Result<()> {
-    let is_devnet = stake_pool_program == JITO_DEVNET_STAKE_POOL_PROGRAM
-        && stake_pool == JITO_DEVNET_STAKE_POOL
-        && lst_mint == JITO_DEVNET_JITOSOL_MINT;
+    #[account(
+        seeds = [b"donor", pool.key().as_ref(), requester.key().as_ref()],
+        bump
+    )]
+    pub donor: Account<'info, Donor>,
 
-    // Mainnet & Testnet use the SPL Stake Pool program plus the Jito stake pool + jitoSOL mint.
-    let is_mainnet_or_testnet = stake_pool_program == JITO_MAINNET_STAKE_POOL_PROGRAM
-        && stake_pool == JITO_MAINNET_STAKE_POOL
-        && lst_mint == JITO_MAINNET_JITOSOL_MINT;
+    #[account(
+        init,
+        payer = requester,
+        space = 8 + RecoveryProposal::SIZE,
+        seeds = [b"recovery", pool.key().as_ref(), &pool.recovery_count.to_le_bytes()],
+        bump
+    )]
+    pub recovery: Account<'info, RecoveryProposal>,
 
-    require!(is_devnet || is_mainnet_or_testnet, CtoError::InvalidStakePoolConfig);
-    Ok(())
+    #[account(mut)]
+    pub requester: Signer<'info>,
+
+    #[account(mut, constraint = pool_token_account.owner == pool.key())]
+    pub pool_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: the recovered mint's SplDonationMint PDA; must not exist
+    #[account(
+        seeds = [b"spl_mint", pool.key().as_ref(), pool_token_account.mint.as_ref()],
+        bump,
+        constraint = spl_donation_mint.data_is_empty() @ CtoError::SplVaultTokensReserved
+    )]
+    pub spl_donation_mint: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub destination_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: instruction sysvar
+    pub instructions: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
 }
 
-/// Reads and deserializes the StakePool state from an AccountInfo.
-///
-/// The spl_stake_pool 2.x crate uses borsh 1.x internally.
-/// We use the renamed `borsh_1` crate (borsh 1.5) to avoid conflict with anchor's borsh 0.10.
-fn read_stake_pool(stake_pool_ai: &AccountInfo) -> Result<StakePool> {
-    let data = stake_pool_ai.try_borrow_data().map_err(|_| CtoError::InvalidAccountData)?;
-    borsh_1::BorshDeserialize::try_from_slice(&data[..]).map_err(|_| CtoError::InvalidAccountData.into())
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RecoverFundsVote<'info> {
+    #[account(
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut, has_one = pool)]
+    pub recovery: Account<'info, RecoveryProposal>,
+
+    #[account(
+        seeds = [b"donor", pool.key().as_ref(), voter_wallet.key().as_ref()],
+        bump
+    )]
+    pub donor: Account<'info, Donor>,
+
+    #[account(
+        init_if_needed,
+        payer = voter_wallet,
+        space = 8 + VoteRecord::SIZE,
+        seeds = [b"vote", recovery.key().as_ref(), voter_wallet.key().as_ref()],
+        bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    #[account(mut)]
+    pub voter_wallet: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
-/// Compute the minimum pool tokens that should produce at least `lamports_out` when withdrawing.
-///
-/// We compute a ceiling estimate from the current ratio.
-fn pool_tokens_for_lamports_ceil(stake_pool: &StakePool, lamports_out: u64) -> Result<u64> {
-    require!(stake_pool.total_lamports > 0, CtoError::StakePoolEmpty);
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RecoverFundsExecute<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
 
-    let num = (lamports_out as u128)
-        .checked_mul(stake_pool.pool_token_supply as u128)
-        .ok_or(CtoError::MathOverflow)?;
-    let den = stake_pool.total_lamports as u128;
+    #[account(mut, has_one = pool)]
+    pub recovery: Account<'info, RecoveryProposal>,
 
-    let mut q = num.checked_div(den).ok_or(CtoError::MathOverflow)?;
-    if num % den != 0 {
-        q = q.checked_add(1).ok_or(CtoError::MathOverflow)?;
-    }
-    let q = u64::try_from(q).map_err(|_| CtoError::MathOverflow)?;
-    Ok(q.max(1))
+    #[account(mut, constraint = pool_token_account.owner == pool.key())]
+    pub pool_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: the recovered mint's SplDonationMint PDA; the recovery fails if it exists
+    #[account(seeds = [b"spl_mint", pool.key().as_ref(), recovery.token_mint.as_ref()], bump)]
+    pub spl_donation_mint: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub destination_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    #[account(mut)]
+    pub executor: Signer<'info>,
+}
+
+// ===== View accounts =====
+
+#[derive(Accounts)]
+pub struct GetPoolStats<'info> {
+    #[account(
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    /// CHECK: stake pool state account (rate source; unused for native pools)
+    #[account(address = pool.stake_pool)]
+    pub stake_pool: UncheckedAccount<'info>,
+
+    #[account(
+        associated_token::mint = pool.lst_mint,
+        associated_token::authority = pool
+    )]
+    pub pool_lst_account: Account<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct GetDonorPosition<'info> {
+    #[account(
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        seeds = [b"donor", pool.key().as_ref(), donor.wallet.as_ref()],
+        bump
+    )]
+    pub donor: Account<'info, Donor>,
+
+    /// CHECK: stake pool state account (rate source; unused for native pools)
+    #[account(address = pool.stake_pool)]
+    pub stake_pool: UncheckedAccount<'info>,
+
+    #[account(
+        associated_token::mint = pool.lst_mint,
+        associated_token::authority = pool
+    )]
+    pub pool_lst_account: Account<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct GetProposalTally<'info> {
+    #[account(
+        seeds = [b"pool", pool.token_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(has_one = pool)]
+    pub proposal: Account<'info, Proposal>,
+}
+
+// ============= State =============
+
+#[account]
+pub struct Pool {
+    // layout version (POOL_VERSION); must stay the first field
+    pub version: u8,
+    pub token_mint: Pubkey,
+    pub authority: Pubkey,
+    pub creator: Pubkey,
+    // proposed by propose_authority_transfer, takes over on accept_authority (default = none)
+    pub pending_authority: Pubkey,
+    // from this time authority-gated calls need a passed ConfigChange instead (0 = never)
+    pub authority_expires_at_ts: i64,
+    // the one call a passed ConfigChange approved for an expired or lapsed authority (zero = none)
+    pub approved_admin_call: [u8; 32],
+
+    // token_mint risk flags: authority still able to mint / freeze (see refresh_mint_risk_flags)
+    pub mint_authority_set: bool,
+    pub freeze_authority_set: bool,
+
+    // share accounting
+    pub total_shares: u64,
+    // shares per LST unit at first mint (0 = legacy 1x, see migrate_share_scale)
+    pub share_scale: u64,
+    // optional SPL mint for transferable shares (default = disabled) and the part of
+    // total_shares currently held as share tokens rather than in Donor accounts
+    pub share_mint: Pubkey,
+    pub tokenized_shares: u64,
+    // part of total_shares parked in WithdrawalRequests (no vote), and the FIFO queue's
+    // [head, tail) request indices
+    pub queued_shares: u64,
+    pub withdrawal_queue_head: u64,
+    pub withdrawal_queue_tail: u64,
+
+    // LST tokens held by the pool PDA (e.g. jitoSOL)
+    pub total_pool_tokens: u64,
+    pub reserved_pool_tokens: u64,
+    // LST retained by rounding, withheld from withdrawals until folded (fold_rounding_dust)
+    pub rounding_dust_pool_tokens: u64,
+    pub last_dust_fold_ts: i64,
+
+    // spend accounting
+    pub total_spent_lamports: u64,
+
+    // governance/config
+    pub protocol_fee_bps: u16,
+    pub quorum_bps: u16,
+    // per-wallet voting cap in bps of snapshot shares, fixed at create_pool
+    pub max_voter_bps: u16,
+    pub min_proposer_deposit_lamports: u64,
+    pub max_proposal_bps: u16,
+    pub min_proposal_lamports: u64,
+    // share age (slots) required before a non-creator can propose
+    pub proposal_delay_slots: u64,
+
+    // fee outputs
+    pub dev_fee_wallet: Pubkey,
+    pub burn_token_mint: Pubkey,
+
+    // proposal tracking
+    pub active_proposal: Option<Pubkey>,
+    // open poll; independent of the payout proposal slot
+    pub active_poll: Option<Pubkey>,
+    // snapshot slots of the latest proposal and poll; the checkpoints their voters vote from
+    // are never evicted (see `mark_shares_changed`)
+    pub proposal_snapshot_slot: u64,
+    pub poll_snapshot_slot: u64,
+    pub proposal_count: u64,
+    pub archived_proposal_count: u64,
+    // ProposalTemplate accounts proposed so far (next template id)
+    pub template_count: u64,
+
+    // donor enumeration (wallets appended to donor index pages on first donation)
+    pub indexed_donor_count: u64,
+    // live Donor accounts (incremented on first donation, decremented on close_donor)
+    pub donor_count: u64,
+
+    // Abort governance cooldown + penalty configuration
+    pub proposal_cooldown_until_ts: i64,
+    pub base_penalty_lamports: u64,
+
+    // gap between a proposal finalizing and the next being created
+    pub proposal_gap_seconds: i64,
+    pub last_proposal_finalized_ts: i64,
+    // slots a donor must wait after creating a proposal before creating another (0 = off)
+    pub donor_proposal_cooldown_slots: u64,
+    // proposals in a row that failed for lack of quorum (see effective_quorum_bps)
+    pub consecutive_quorum_failures: u16,
+    // co-sponsors a payout proposal needs before voting opens (0 disables), each holding at
+    // least sponsor_min_share_bps of total shares at the proposal snapshot
+    pub required_sponsors: u8,
+    pub sponsor_min_share_bps: u16,
+
+    // authority dead-man switch (dead_man_epochs == 0 disables)
+    pub dead_man_epochs: u64,
+    pub last_heartbeat_epoch: u64,
+
+    // sunset of abandoned pools (0 = not sunsetting)
+    pub last_engagement_ts: i64,
+    pub sunset_started_ts: i64,
+
+    // emergency pause: blocks donations, payout proposals, payouts and buy & burn, not withdrawals
+    pub paused: bool,
+
+    // bounds for the proposer-selected voting window
+    pub min_voting_seconds: i64,
+    pub max_voting_seconds: i64,
+
+    // tolerance for donor minimums derived when the caller passes 0
+    pub donor_slippage_bps: u16,
+
+    // the pool's own token donated into its treasury ATA (no LST, shares or proposal liquidity)
+    pub token_donation_policy: TokenDonationPolicy,
+    pub treasury_token_amount: u64,
+
+    // SPL donations (donate_spl): Pyth SOL/USD PriceUpdateV2 account (default = disabled), the
+    // oracle value of everything donated so far, and SplPosition shares outstanding over all mints
+    pub sol_usd_price_feed: Pubkey,
+    pub spl_donated_lamports: u64,
+    pub spl_position_shares: u64,
+
+    // Oracle guards (0 = off): buy & burn must get CTOP worth, at Pyth CTOP/USD and SOL/USD,
+    // within burn_price_band_bps of the SOL spent; withdrawals need the stake pool rate within
+    // lst_rate_band_bps of its last-epoch rate
+    pub ctop_usd_price_feed: Pubkey,
+    pub burn_price_band_bps: u16,
+    pub lst_rate_band_bps: u16,
+
+    // Campaign mode (goal 0 = off): withdrawals and proposals wait until credited donations
+    // reach campaign_goal_lamports; missing it by campaign_deadline_ts opens refunds instead
+    pub campaign_goal_lamports: u64,
+    pub campaign_deadline_ts: i64,
+    pub campaign_raised_lamports: u64,
+    pub campaign_goal_reached: bool,
+
+    // Referral rewards (bps 0 = off): donate_sol referrers get referral_bps of the referred
+    // shares as a bonus, until referral_shares_minted reaches referral_cap_shares
+    pub referral_bps: u16,
+    pub referral_cap_shares: u64,
+    pub referral_shares_minted: u64,
+
+    // SOL donation limits (0 = off): smallest accepted donation, and the largest share of the
+    // pool a donor may reach by donating
+    pub min_donation_lamports: u64,
+    pub max_donor_share_bps: u16,
+
+    // allowlist mode: donate_sol(_for) and create_proposal need an AllowlistEntry
+    pub allowlist_enabled: bool,
+
+    // proposer bond escrowed by create_proposal (0 = off), forfeited when a proposal misses both
+    // quorum and bond_spam_threshold_bps participation
+    pub proposer_bond_lamports: u64,
+    pub bond_spam_threshold_bps: u16,
+
+    // reveal window new payout proposals get for commit–reveal voting (0 = open ballots)
+    pub commit_reveal_seconds: i64,
+
+    // anti-sniping for new proposals: extension a late outcome flip adds to the deadline, and
+    // how many times per proposal (0 = off)
+    pub snipe_extension_seconds: i64,
+    pub max_snipe_extensions: u8,
+
+    // time-weighted voting: slots over which a donor's vote weight ramps up after a share
+    // change (0 = off)
+    pub vote_ramp_slots: u64,
+
+    // how snapshot shares become vote weight on new proposals
+    pub vote_weighting: VoteWeighting,
+
+    // recovery tracking
+    pub active_recovery: Option<Pubkey>,
+    pub recovery_count: u64,
+
+    // LST backend config
+    pub stake_pool_program: Pubkey,
+    pub stake_pool: Pubkey,
+    pub lst_mint: Pubkey,
+
+    // Staked (LST) or native lamports held in the PDA; fixed at create_pool
+    pub custody: PoolCustody,
+
+    // StakeWithdrawal records opened so far (next record's index)
+    pub stake_withdrawal_count: u64,
+    // StakeWithdrawal records not yet claimed (the pool must outlive them)
+    pub open_stake_withdrawals: u64,
+
+    // AMM process_buy_and_burn buys on; addresses live in the per-venue fields below
+    pub burn_venue: BurnVenue,
+
+    // PumpSwap buy&burn (post Pump.fun graduation)
+    pub pumpswap_pool_id: Pubkey,
+    pub pumpswap_base_vault: Pubkey,
+    pub pumpswap_quote_vault: Pubkey,
+    pub pumpswap_global_config: Pubkey,
+    pub pumpswap_fee_recipient: Pubkey,
+    pub pumpswap_version: u8, // H-05 FIX: Track expected PumpSwap version
+
+    // Optional second PumpSwap hop (SOL -> hop mint -> CTOP)
+    pub pumpswap_hop_enabled: bool,
+    pub pumpswap_hop_mint: Pubkey,
+    pub pumpswap_hop_pool_id: Pubkey,
+    pub pumpswap_hop_base_vault: Pubkey,
+    pub pumpswap_hop_quote_vault: Pubkey,
+
+    // Orca Whirlpool buy&burn
+    pub whirlpool_pool_id: Pubkey,
+    pub whirlpool_token_vault_a: Pubkey,
+    pub whirlpool_token_vault_b: Pubkey,
+
+    // Meteora DLMM buy&burn
+    pub meteora_lb_pair: Pubkey,
+    pub meteora_reserve_x: Pubkey,
+    pub meteora_reserve_y: Pubkey,
+
+    // Buyback destination (burn vs hold in pool_ctop_account)
+    pub buyback_mode: BuybackMode,
+    pub held_ctop_amount: u64,
+
+    // Dutch-auction burn (alternative to AMM buy & burn)
+    pub burn_auction_enabled: bool,
+    pub burn_auction_lamports: u64,
+    pub burn_auction_start_ts: i64,
+    pub burn_auction_start_ctop_per_sol: u64,
+    pub burn_auction_floor_ctop_per_sol: u64,
+    pub burn_auction_duration_seconds: i64,
+
+    // Scheduled burns (accumulate until threshold or interval)
+    pub pending_burn_lamports: u64,
+    pub burn_threshold_lamports: u64,
+    pub burn_interval_seconds: i64,
+    pub last_burn_ts: i64,
+
+    // Destination of the non-dev fee half
+    pub fee_sink: FeeSink,
+    pub fee_sink_destination: Pubkey,
+
+    // resolution of quorum-meeting ties
+    pub tie_break_rule: TieBreakRule,
+
+    // new proposals take vote weight from a posted Merkle root instead of Donor accounts
+    pub merkle_voting: bool,
+
+    // new proposals measure quorum against supply net of reported whale-cap excess
+    pub cap_adjusted_quorum: bool,
+
+    // passed proposals wait this long in Queued before execution (0 = no timelock)
+    pub timelock_seconds: i64,
+
+    // quorum by requested share of pool LST (see tiered_quorum_bps); all-zero tiers are unused
+    pub quorum_tiers: [QuorumTier; QUORUM_TIER_COUNT],
+
+    // payouts up to this many lamports take a lower quorum and shorter minimum voting window
+    // (0 = no fast track)
+    pub small_payout_threshold_lamports: u64,
+    pub fast_track_quorum_bps: u16,
+    pub fast_track_voting_seconds: i64,
+
+    // Raydium CPMM buy&burn
+    pub raydium_pool_id: Pubkey,
+    pub raydium_token_0_vault: Pubkey,
+    pub raydium_token_1_vault: Pubkey,
+
+    // Raydium CPMM liquidity: SOL and treasury tokens queued by AddLiquidity for
+    // `provide_liquidity`, LP tokens held in pool_lp_account, and those RemoveLiquidity approved
+    // for `withdraw_liquidity`
+    pub pending_liquidity_lamports: u64,
+    pub pending_liquidity_tokens: u64,
+    pub lp_token_amount: u64,
+    pub pending_lp_withdrawal: u64,
+
+    // SOL credited to PayoutClaims and not yet claimed or reclaimed, held in the pool PDA
+    pub pending_claim_lamports: u64,
+    
+    // H-04 FIX: Reentrancy guard
+    pub locked: bool,
+}
+
+impl Pool {
+    pub const SIZE: usize =
+        1 +            // version
+        32 + 32 + 32 + // token_mint, authority, creator
+        32 +           // pending_authority
+        8 + 32 +       // authority_expires_at_ts, approved_admin_call
+        1 + 1 +        // mint_authority_set, freeze_authority_set
+        8 + 8 +        // total_shares, share_scale
+        32 + 8 +       // share_mint, tokenized_shares
+        8 + 8 + 8 +    // queued_shares, withdrawal_queue_head, withdrawal_queue_tail
+        8 + 8 +        // total_pool_tokens, reserved_pool_tokens
+        8 + 8 +        // rounding_dust_pool_tokens, last_dust_fold_ts
+        8 +            // total_spent_lamports
+        2 + 2 + 2 + 8 + // protocol_fee_bps, quorum_bps, max_voter_bps, min_proposer
+        2 + 8 +        // max_proposal_bps, min_proposal_lamports
+        8 +            // proposal_delay_slots
+        32 + 32 +      // dev_fee_wallet, burn_token_mint
+        1 + 32 +       // active_proposal
+        1 + 32 +       // active_poll
+        8 + 8 +        // proposal_snapshot_slot, poll_snapshot_slot
+        8 + 8 +        // proposal_count, archived_proposal_count
+        8 +            // template_count
+        8 + 8 +        // indexed_donor_count, donor_count
+        8 + 8 +        // proposal_cooldown_until_ts, base_penalty_lamports
+        8 + 8 +        // proposal_gap_seconds, last_proposal_finalized_ts
+        8 +            // donor_proposal_cooldown_slots
+        2 +            // consecutive_quorum_failures
+        1 + 2 +        // required_sponsors, sponsor_min_share_bps
+        8 + 8 +        // dead_man_epochs, last_heartbeat_epoch
+        8 + 8 +        // last_engagement_ts, sunset_started_ts
+        1 +            // paused
+        8 + 8 +        // min_voting_seconds, max_voting_seconds
+        2 +            // donor_slippage_bps
+        1 + 8 +        // token_donation_policy, treasury_token_amount
+        32 + 8 + 8 +   // sol_usd_price_feed, spl_donated_lamports, spl_position_shares
+        32 + 2 + 2 +   // ctop_usd_price_feed, burn_price_band_bps, lst_rate_band_bps
+        8 + 8 + 8 + 1 + // campaign_goal_lamports, campaign_deadline_ts, campaign_raised_lamports, campaign_goal_reached
+        2 + 8 + 8 +    // referral_bps, referral_cap_shares, referral_shares_minted
+        8 + 2 +        // min_donation_lamports, max_donor_share_bps
+        1 +            // allowlist_enabled
+        8 + 2 +        // proposer_bond_lamports, bond_spam_threshold_bps
+        8 +            // commit_reveal_seconds
+        8 + 1 +        // snipe_extension_seconds, max_snipe_extensions
+        8 +            // vote_ramp_slots
+        1 +            // vote_weighting
+        1 + 32 +       // active_recovery
+        8 +            // recovery_count
+        32 + 32 + 32 + // stake_pool_program, stake_pool, lst_mint
+        1 +            // custody
+        8 +            // stake_withdrawal_count
+        8 +            // open_stake_withdrawals
+        1 +            // burn_venue
+        32 + 32 + 32 + 32 + 32 + // pumpswap_pool_id, base_vault, quote_vault, global_config, fee_recipient
+        1 +            // pumpswap_version (H-05 FIX)
+        1 + 32 + 32 +  // pumpswap_hop_enabled, hop_mint, hop_pool_id
+        32 + 32 +      // pumpswap_hop_base_vault, hop_quote_vault
+        32 + 32 + 32 + // whirlpool_pool_id, whirlpool_token_vault_a, whirlpool_token_vault_b
+        32 + 32 + 32 + // meteora_lb_pair, meteora_reserve_x, meteora_reserve_y
+        1 + 8 +        // buyback_mode, held_ctop_amount
+        1 + 8 + 8 +    // burn_auction_enabled, lamports, start_ts
+        8 + 8 + 8 +    // burn_auction_start_ctop_per_sol, floor_ctop_per_sol, duration_seconds
+        8 + 8 +        // pending_burn_lamports, burn_threshold_lamports
+        8 + 8 +        // burn_interval_seconds, last_burn_ts
+        1 + 32 +       // fee_sink, fee_sink_destination
+        1 +            // tie_break_rule
+        1 +            // merkle_voting
+        1 +            // cap_adjusted_quorum
+        8 +            // timelock_seconds
+        QuorumTier::SIZE * QUORUM_TIER_COUNT + // quorum_tiers
+        8 + 2 + 8 +    // small_payout_threshold_lamports, fast_track_quorum_bps, fast_track_voting_seconds
+        32 + 32 + 32 + // raydium_pool_id, raydium_token_0_vault, raydium_token_1_vault
+        8 + 8 + 8 + 8 + // pending_liquidity_lamports, pending_liquidity_tokens, lp_token_amount, pending_lp_withdrawal
+        8 +            // pending_claim_lamports
+        1;             // locked (H-04 FIX: reentrancy guard)
+}
+
+/// Quorum applied to payouts locking at most `max_tvl_bps` of pool LST.
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Default)]
+pub struct QuorumTier {
+    pub max_tvl_bps: u16,
+    pub quorum_bps: u16,
+}
+
+impl QuorumTier {
+    pub const SIZE: usize = 2 + 2;
+}
+
+/// Global pool counter, PDA: ['registry'].
+#[account]
+pub struct PoolRegistry {
+    pub pool_count: u64,
+}
+
+impl PoolRegistry {
+    pub const SIZE: usize = 8;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PoolRegistryEntry {
+    pub token_mint: Pubkey,
+    pub pool: Pubkey,
 }
 
-/// Transfers lamports from one account to another using signed invocation.
-/// The `from` account must be a PDA with the provided seeds.
-fn transfer_lamports_signed<'info>(
-    from: &AccountInfo<'info>,
-    to: &AccountInfo<'info>,
-    seeds: &[&[&[u8]]],
-    lamports: u64,
-) -> Result<()> {
-    invoke_signed(
-        &system_instruction::transfer(from.key, to.key, lamports),
-        &[from.clone(), to.clone()],
-        seeds,
-    )
-    .map_err(|_| CtoError::LamportTransferFailed.into())
+impl PoolRegistryEntry {
+    pub const SIZE: usize = 32 + 32;
 }
 
-/// CPI to stake pool program to deposit SOL and receive LST tokens.
-fn stake_pool_deposit_sol(ctx: &Context<DonateSol>, lamports_in: u64, minimum_pool_tokens_out: u64) -> Result<()> {
-    let pool = &ctx.accounts.pool;
+/// Page of created pools in creation order, PDA: ['registry_page', page_index].
+#[account]
+pub struct PoolRegistryPage {
+    pub page_index: u64,
+    pub entries: Vec<PoolRegistryEntry>,
+}
 
-    require!(
-        ctx.accounts.stake_pool_program.key() == pool.stake_pool_program
-            && ctx.accounts.stake_pool.key() == pool.stake_pool
-            && ctx.accounts.lst_mint.key() == pool.lst_mint,
-        CtoError::InvalidStakePoolConfig
-    );
+impl PoolRegistryPage {
+    pub const CAPACITY: usize = 64;
+    pub const SIZE: usize = 8 + 4 + Self::CAPACITY * PoolRegistryEntry::SIZE;
+}
 
-    let ix = stake_pool_ix::deposit_sol_with_slippage(
-        &pool.stake_pool_program,
-        &pool.stake_pool,
-        &ctx.accounts.stake_pool_withdraw_authority.key(),
-        &ctx.accounts.reserve_stake.key(),
-        &ctx.accounts.donor_wallet.key(),
-        &ctx.accounts.pool_lst_account.key(),
-        &ctx.accounts.manager_fee_account.key(),
-        &ctx.accounts.referrer_pool_tokens_account.key(),
-        &ctx.accounts.lst_mint.key(),
-        &ctx.accounts.token_program.key(),
-        lamports_in,
-        minimum_pool_tokens_out,
-    );
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct PoolMetadataFields {
+    pub name: String,
+    pub symbol: String,
+    pub description: String,
+    // link to off-chain JSON, website or socials
+    pub uri: String,
+}
 
-    invoke(
-        &ix,
-        &[
-            ctx.accounts.stake_pool.to_account_info(),
-            ctx.accounts.stake_pool_withdraw_authority.to_account_info(),
-            ctx.accounts.reserve_stake.to_account_info(),
-            ctx.accounts.donor_wallet.to_account_info(),
-            ctx.accounts.pool_lst_account.to_account_info(),
-            ctx.accounts.manager_fee_account.to_account_info(),
-            ctx.accounts.referrer_pool_tokens_account.to_account_info(),
-            ctx.accounts.lst_mint.to_account_info(),
-            ctx.accounts.system_program.to_account_info(),
-            ctx.accounts.token_program.to_account_info(),
-        ],
-    )
-    .map_err(|_| CtoError::StakePoolCpiFailed.into())
+/// Display metadata of a pool, PDA: ['metadata', pool]. Written by `create_pool_metadata`, then
+/// only through governance (`update_pool_metadata`).
+#[account]
+pub struct PoolMetadata {
+    pub pool: Pubkey,
+    pub fields: PoolMetadataFields,
+    // slot of the last write; an update needs a ConfigChange finalized after it
+    pub updated_slot: u64,
 }
 
-/// CPI to stake pool program to withdraw SOL by burning LST tokens (withdraw_sol handler).
-fn stake_pool_withdraw_sol(ctx: &Context<WithdrawSol>, pool_tokens_in: u64, minimum_lamports_out: u64) -> Result<()> {
-    let pool = &ctx.accounts.pool;
+impl PoolMetadata {
+    pub const NAME_MAX: usize = 32;
+    pub const SYMBOL_MAX: usize = 10;
+    pub const DESC_MAX: usize = 256;
+    pub const URI_MAX: usize = 200;
+    pub const SIZE: usize = 32 +
+        (4 + Self::NAME_MAX) +
+        (4 + Self::SYMBOL_MAX) +
+        (4 + Self::DESC_MAX) +
+        (4 + Self::URI_MAX) +
+        8;
+}
 
-    require!(
-        ctx.accounts.stake_pool_program.key() == pool.stake_pool_program
-            && ctx.accounts.stake_pool.key() == pool.stake_pool
-            && ctx.accounts.lst_mint.key() == pool.lst_mint,
-        CtoError::InvalidStakePoolConfig
-    );
+#[account]
+pub struct Donor {
+    // layout version (DONOR_VERSION); must stay the first field
+    pub version: u8,
+    pub pool: Pubkey,
+    pub wallet: Pubkey,
+    pub shares: u64,
+    pub total_deposited_lamports: u64,
+    pub last_shares_change_slot: u64,
 
-    let ix = stake_pool_ix::withdraw_sol_with_slippage(
-        &pool.stake_pool_program,
-        &pool.stake_pool,
-        &ctx.accounts.stake_pool_withdraw_authority.key(),
-        &pool.key(),
-        &ctx.accounts.pool_lst_account.key(),
-        &ctx.accounts.reserve_stake.key(),
-        &pool.key(),
-        &ctx.accounts.manager_fee_account.key(),
-        &ctx.accounts.lst_mint.key(),
-        &ctx.accounts.token_program.key(),
-        pool_tokens_in,
-        minimum_lamports_out,
-    );
+    // Abort/proposer penalty tracking (uncapped exponential fees)
+    pub abort_strike_count: u16,
+    pub propose_strike_count: u16,
+    pub non_abort_participation_count: u16,
+    pub non_propose_participation_count: u16,
 
-    invoke_signed(
-        &ix,
-        &[
-            ctx.accounts.stake_pool.to_account_info(),
-            ctx.accounts.stake_pool_withdraw_authority.to_account_info(),
-            ctx.accounts.pool.to_account_info(),
-            ctx.accounts.pool_lst_account.to_account_info(),
-            ctx.accounts.reserve_stake.to_account_info(),
-            ctx.accounts.pool.to_account_info(),
-            ctx.accounts.manager_fee_account.to_account_info(),
-            ctx.accounts.lst_mint.to_account_info(),
-            ctx.accounts.clock.to_account_info(),
-            ctx.accounts.stake_history.to_account_info(),
-            ctx.accounts.stake_program.to_account_info(),
-            ctx.accounts.token_program.to_account_info(),
-        ],
-        pool_seeds!(pool, ctx.bumps.pool),
-    )
-    .map_err(|_| CtoError::StakePoolCpiFailed.into())
+    // scale `shares` is expressed in; must match the pool's
+    pub share_scale: u64,
+
+    // portion of `shares` delegated to a vote escrow (not votable or withdrawable here)
+    pub delegated_shares: u64,
+
+    // rent-free ballot from vote_compact (only meaningful while ballot_proposal is active)
+    pub ballot_proposal: Pubkey,
+    pub ballot_choice: VoteChoice,
+    pub ballot_weight: u64,
+
+    // proposals this donor created, and the slot of the latest (per-donor proposal cooldown)
+    pub proposals_created: u64,
+    pub last_proposal_slot: u64,
+
+    // votable shares after each of the latest changes, so votes can use the snapshot balance
+    pub share_checkpoints: [ShareCheckpoint; SHARE_CHECKPOINT_COUNT],
 }
 
-/// CPI to stake pool program to withdraw SOL by burning LST tokens (execute_proposal handler).
-fn stake_pool_withdraw_sol_exec(ctx: &Context<ExecuteProposal>, pool_tokens_in: u64, minimum_lamports_out: u64) -> Result<()> {
-    let pool = &ctx.accounts.pool;
+impl Donor {
+    pub const SIZE: usize = 1 + 32 + 32 + 8 + 8 + 8 + 2 + 2 + 2 + 2 + 8 + 8 + 32 + 1 + 8 + 8 + 8 +
+        ShareCheckpoint::SIZE * SHARE_CHECKPOINT_COUNT;
+}
 
-    require!(
-        ctx.accounts.stake_pool_program.key() == pool.stake_pool_program
-            && ctx.accounts.stake_pool.key() == pool.stake_pool
-            && ctx.accounts.lst_mint.key() == pool.lst_mint,
-        CtoError::InvalidStakePoolConfig
-    );
+/// A donor's votable (own, undelegated) shares, or an escrow's delegated shares, as of `slot`;
+/// slot 0 = unused entry.
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Default)]
+pub struct ShareCheckpoint {
+    pub slot: u64,
+    pub shares: u64,
+}
 
-    let ix = stake_pool_ix::withdraw_sol_with_slippage(
-        &pool.stake_pool_program,
-        &pool.stake_pool,
-        &ctx.accounts.stake_pool_withdraw_authority.key(),
-        &pool.key(),
-        &ctx.accounts.pool_lst_account.key(),
-        &ctx.accounts.reserve_stake.key(),
-        &pool.key(),
-        &ctx.accounts.manager_fee_account.key(),
-        &ctx.accounts.lst_mint.key(),
-        &ctx.accounts.token_program.key(),
-        pool_tokens_in,
-        minimum_lamports_out,
-    );
+impl ShareCheckpoint {
+    pub const SIZE: usize = 8 + 8;
+}
 
-    invoke_signed(
-        &ix,
-        &[
-            ctx.accounts.stake_pool.to_account_info(),
-            ctx.accounts.stake_pool_withdraw_authority.to_account_info(),
-            ctx.accounts.pool.to_account_info(),
-            ctx.accounts.pool_lst_account.to_account_info(),
-            ctx.accounts.reserve_stake.to_account_info(),
-            ctx.accounts.pool.to_account_info(),
-            ctx.accounts.manager_fee_account.to_account_info(),
-            ctx.accounts.lst_mint.to_account_info(),
-            ctx.accounts.clock.to_account_info(),
-            ctx.accounts.stake_history.to_account_info(),
-            ctx.accounts.stake_program.to_account_info(),
-            ctx.accounts.token_program.to_account_info(),
-        ],
-        pool_seeds!(pool, ctx.bumps.pool),
-    )
-    .map_err(|_| CtoError::StakePoolCpiFailed.into())
+/// Page of donor wallets in first-donation order, PDA: ['donor_index', pool, page_index].
+#[account]
+pub struct DonorIndexPage {
+    pub pool: Pubkey,
+    pub page_index: u64,
+    pub wallets: Vec<Pubkey>,
 }
 
-// ===== Buy & burn helpers =====
+impl DonorIndexPage {
+    pub const CAPACITY: usize = 128;
+    pub const SIZE: usize = 32 + 8 + 4 + Self::CAPACITY * 32;
+}
 
-/// Attempts to swap SOL for CTOP on PumpSwap and burn to incinerator.
-/// Returns the amount of CTOP burned on success.
-fn attempt_pumpswap_swap_and_burn<'info>(
-    ctx: &mut Context<ExecuteProposal<'info>>,
-    amount_sol: u64,
-    pool_bump: u8,
-) -> Result<u64> {
-    // Basic safety: ensure we're calling the canonical PumpSwap program id.
-    require!(
-        ctx.accounts.pumpswap_program.key() == PUMPSWAP_PROGRAM_ID,
-        CtoError::InvalidPumpSwapConfig
-    );
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq)]
+pub enum ActivityKind {
+    Donate,
+    Withdraw,
+    Propose,
+    Vote,
+    Execute,
+}
 
-    // H-05 FIX: Validate PumpSwap version before swap
-    require!(
-        ctx.accounts.pool.pumpswap_version == PUMPSWAP_EXPECTED_VERSION,
-        CtoError::PumpSwapVersionMismatch
-    );
+/// `detail` is kind-specific: RequestDenomination for Propose, VoteChoice for Vote,
+/// ProposalStatus (Failed/Executed/Escrowed/Recurring) for Execute, 1 for a Donate of the pool's own token
+/// (amount in token units), 2 for an SPL Donate (amount = oracle value in lamports), 0 otherwise.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ActivityEntry {
+    pub kind: ActivityKind,
+    pub detail: u8,
+    pub actor: Pubkey,
+    pub amount: u64,
+    pub slot: u64,
+}
 
-    // Prevent executor “account injection”:
-    // - Vaults must hold the expected mints (CTOP and WSOL).
-    // - Swap output is forced into pool_ctop_account (PDA-owned), and then burned.
-    validate_pumpswap_vault_mints(ctx)?;
+impl ActivityEntry {
+    pub const SIZE: usize = 1 + 1 + 32 + 8 + 8;
+}
 
-    // Wrap SOL into WSOL held by the pool PDA
-    wrap_sol_to_wsol(ctx, amount_sol, pool_bump)?;
+/// Last CAPACITY actions on a pool, PDA: ['activity', pool].
+/// `head` is the next slot to write; once full, entries[head] is the oldest.
+#[account]
+pub struct ActivityLog {
+    pub pool: Pubkey,
+    pub head: u16,
+    pub total_recorded: u64,
+    pub entries: Vec<ActivityEntry>,
+}
 
-    // On-chain frontrun protection:
-    // Compute min-out from current vault reserves, then apply slippage bps.
-    let min_ctop = compute_min_out_cpmm_from_vaults(
-        ctx.accounts.pumpswap_pool_quote_vault.amount, // WSOL reserve
-        ctx.accounts.pumpswap_pool_base_vault.amount,  // CTOP reserve
-        amount_sol,
-        MAX_SLIPPAGE_BPS,
-    )?;
+impl ActivityLog {
+    pub const CAPACITY: usize = 32;
+    pub const SIZE: usize = 32 + 2 + 8 + 4 + Self::CAPACITY * ActivityEntry::SIZE;
+}
 
-    // Perform PumpSwap buy: spend up to `amount_sol` WSOL, receive at least `min_ctop` CTOP.
-    perform_pumpswap_buy(ctx, min_ctop, amount_sol, pool_bump)?;
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum ProposalStatus {
+    Active,
+    Failed,
+    Executed,
+    Aborted,
+    /// Passed MilestonePayout or StreamPayout with LST still held back for the destination.
+    Escrowed,
+    /// Passed in a timelocked pool; executable once `queued_until_ts` is reached.
+    Queued,
+    /// Passed RecurringBudget paying its amount each period until cancelled (see BudgetSchedule).
+    Recurring,
+}
 
-    // Burn everything acquired by transferring to the incinerator ATA.
-    transfer_to_incinerator(ctx, pool_bump)
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq)]
+pub enum ProposalKind {
+    Payout,
+    /// Non-binding signal vote (title, description and tally only): locks no liquidity, moves no
+    /// funds, finalized by `finalize_poll`.
+    Poll,
+    /// Vote to activate a pending ProposalTemplate; runs in the poll slot like `Poll`.
+    AdoptTemplate,
+    /// Binding vote on pool parameters (see ConfigChangeParams); takes the payout proposal slot
+    /// but locks no liquidity, and is applied by `execute_proposal`.
+    ConfigChange,
+    /// Payout escrowed on execution and released in tranches by `release_milestone`
+    /// (see MilestoneSchedule); set on a fresh Payout by `configure_milestones`.
+    MilestonePayout,
+    /// Payout escrowed on execution and vesting linearly over StreamSchedule, claimed by
+    /// `claim_stream`; set on a fresh Payout by `configure_stream`.
+    StreamPayout,
+    /// Payout of `requested_lamports` once per BudgetSchedule period: the first on execution,
+    /// later ones by `disburse_budget` without a new vote, until a ConfigChange cancels it.
+    /// Set on a fresh Lamports Payout by `configure_recurring_budget`.
+    RecurringBudget,
+    /// Binding vote to wind the pool down; takes the payout proposal slot but locks no
+    /// liquidity, and `execute_proposal` starts the sunset (see `distribute_remaining`).
+    Sunset,
+    /// Payout of `payout_token_amount` of an SPL token the pool holds (`payout_mint`) into the
+    /// destination's ATA, paid by `execute_proposal`; locks no LST. Set on a fresh Payout by
+    /// `configure_token_payout`.
+    TokenPayout,
+    /// Payout whose net SOL is queued for `process_buy_and_burn` to buy the pool's own token,
+    /// burned or held per `buyback_mode`; `destination_wallet` receives nothing. Set on a fresh
+    /// Payout by `configure_buyback`.
+    Buyback,
+    /// Payout whose net SOL is queued with `liquidity_amount` treasury tokens for
+    /// `provide_liquidity` to deposit into the pool's Raydium CPMM pool, the LP tokens held in
+    /// `pool_lp_account`; `destination_wallet` receives nothing. Set on a fresh Payout by
+    /// `configure_add_liquidity`.
+    AddLiquidity,
+    /// Binding vote to redeem `liquidity_amount` of the pool's LP tokens; takes the payout
+    /// proposal slot but locks no liquidity, and `withdraw_liquidity` redeems them once executed.
+    RemoveLiquidity,
 }
 
-/// Legacy Raydium buy & burn (optional).
-fn attempt_raydium_swap_and_burn<'info>(
-    ctx: &mut Context<ExecuteProposal<'info>>,
-    amount_sol: u64,
-    pool_bump: u8,
-) -> Result<u64> {
-    wrap_sol_to_wsol(ctx, amount_sol, pool_bump)?;
-    // NOTE: This is still heuristic for Raydium. Prefer PumpSwap path for Pump.fun launches.
-    let min_ctop = calculate_minimum_amount_out_heuristic(amount_sol, MAX_SLIPPAGE_BPS)?;
-    perform_raydium_swap(ctx, amount_sol, min_ctop, pool_bump)?;
-    transfer_to_incinerator(ctx, pool_bump)
+impl ProposalKind {
+    /// Kinds that occupy `pool.active_proposal` and resolve through execute/abort.
+    pub fn uses_proposal_slot(self) -> bool {
+        matches!(
+            self,
+            ProposalKind::Payout
+                | ProposalKind::ConfigChange
+                | ProposalKind::MilestonePayout
+                | ProposalKind::StreamPayout
+                | ProposalKind::RecurringBudget
+                | ProposalKind::Sunset
+                | ProposalKind::TokenPayout
+                | ProposalKind::Buyback
+                | ProposalKind::AddLiquidity
+                | ProposalKind::RemoveLiquidity
+        )
+    }
+
+    /// Kinds that pay `destination_wallet` out of the pool's LST.
+    pub fn pays_destination(self) -> bool {
+        matches!(
+            self,
+            ProposalKind::Payout
+                | ProposalKind::MilestonePayout
+                | ProposalKind::StreamPayout
+                | ProposalKind::RecurringBudget
+        )
+    }
 }
 
-/// Wraps native SOL into WSOL by transferring SOL to the pool's WSOL token account and syncing.
-fn wrap_sol_to_wsol<'info>(
-    ctx: &mut Context<ExecuteProposal<'info>>,
-    amount: u64,
-    pool_bump: u8,
-) -> Result<()> {
-    invoke_signed(
-        &system_instruction::transfer(&ctx.accounts.pool.key(), &ctx.accounts.pool_wsol_account.key(), amount),
-        &[
-            ctx.accounts.pool.to_account_info(),
-            ctx.accounts.pool_wsol_account.to_account_info(),
-        ],
-        pool_seeds!(&ctx.accounts.pool, pool_bump),
-    )
-    .map_err(|_| CtoError::LamportTransferFailed)?;
+/// Pool parameters a ConfigChange proposal updates; `None` leaves the current value.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct ConfigChangeParams {
+    pub quorum_bps: Option<u16>,
+    pub protocol_fee_bps: Option<u16>,
+    pub min_proposer_deposit_lamports: Option<u64>,
+    pub proposal_delay_slots: Option<u64>,
+    /// Recurring budget of this pool to stop once the change executes (see `cancel_recurring_budget`)
+    pub cancel_budget: Option<Pubkey>,
+    /// Authority-gated call to allow once the authority has expired or lapsed (see `admin_call_hash`)
+    pub admin_call: Option<[u8; 32]>,
+    /// Sets the emergency pause; lets governance lift a pause without the authority
+    pub paused: Option<bool>,
+    /// PoolMetadata to allow once the change executes (see `update_pool_metadata`)
+    pub metadata_hash: Option<[u8; 32]>,
+    /// Fee sink and its destination, validated as in `configure_fee_sink` (destination unset for Burn)
+    pub fee_sink: Option<FeeSink>,
+    pub fee_sink_destination: Option<Pubkey>,
+}
 
-    token::sync_native(CpiContext::new(
-        ctx.accounts.token_program.to_account_info(),
-        token::SyncNative {
-            account: ctx.accounts.pool_wsol_account.to_account_info(),
-        },
-    ))?;
+impl ConfigChangeParams {
+    pub const SIZE: usize =
+        (1 + 2) + (1 + 2) + (1 + 8) + (1 + 8) + (1 + 32) + (1 + 32) + (1 + 1) + (1 + 32) + (1 + 1) + (1 + 32);
+}
 
-    Ok(())
+/// Tranche schedule of a MilestonePayout (all zero for other kinds).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct MilestoneSchedule {
+    pub tranches: u8,
+    pub released: u8,
+    // > 0: each tranche unlocks this long after the previous one; 0: each needs confirm_milestone
+    pub interval_seconds: i64,
+    // LST still held back for unreleased tranches (set when the proposal passes)
+    pub escrowed_pool_tokens: u64,
+    // current tranche: when it opened, and the snapshot its confirmations are weighed against
+    pub tranche_started_ts: i64,
+    pub snapshot_slot: u64,
+    pub snapshot_shares: u64,
+    pub confirm_weight: u64,
 }
 
-/// H-03 FIX: Validates that the PumpSwap vault token accounts correspond to the expected mints
-/// AND validates that vaults are owned by the PumpSwap pool (prevents account substitution attacks).
-fn validate_pumpswap_vault_mints<'info>(ctx: &Context<ExecuteProposal<'info>>) -> Result<()> {
-    // Validate mints
-    require!(
-        ctx.accounts.pumpswap_pool_base_vault.mint == ctx.accounts.ctop_mint.key(),
-        CtoError::InvalidPumpSwapVaultMints
-    );
-    require!(
-        ctx.accounts.pumpswap_pool_quote_vault.mint == ctx.accounts.wsol_mint.key(),
-        CtoError::InvalidPumpSwapVaultMints
-    );
-    
-    // H-03 FIX: Validate that vaults are owned by the PumpSwap pool
-    // This prevents attacker-controlled token accounts from being passed
-    require!(
-        ctx.accounts.pumpswap_pool_base_vault.owner == ctx.accounts.pumpswap_pool.key(),
-        CtoError::InvalidPumpSwapVaultOwner
-    );
-    require!(
-        ctx.accounts.pumpswap_pool_quote_vault.owner == ctx.accounts.pumpswap_pool.key(),
-        CtoError::InvalidPumpSwapVaultOwner
-    );
-    
-    Ok(())
+impl MilestoneSchedule {
+    pub const SIZE: usize = 1 + 1 + 8 + 8 + 8 + 8 + 8 + 8;
 }
 
-/// Computes a conservative min-out using constant product math from vault balances.
-///
-/// NOTE:
-/// - This assumes an x*y=k style pool.
-/// - If PumpSwap charges fees, real output will be lower; we already apply a slippage haircut.
-fn compute_min_out_cpmm_from_vaults(
-    quote_reserve: u64,
-    base_reserve: u64,
-    quote_in: u64,
-    slippage_bps: u64,
-) -> Result<u64> {
-    require!(quote_reserve > 0 && base_reserve > 0, CtoError::PumpSwapMathError);
-    require!(quote_in > 0, CtoError::ZeroAmount);
+/// Vesting schedule of a StreamPayout (all zero for other kinds).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct StreamSchedule {
+    pub start_ts: i64,
+    pub end_ts: i64,
+    // LST escrowed when the proposal passed, and how much of it has been claimed
+    pub total_pool_tokens: u64,
+    pub claimed_pool_tokens: u64,
+}
 
-    // x = quote reserve (WSOL), y = base reserve (CTOP)
-    let x = quote_reserve as u128;
-    let y = base_reserve as u128;
-    let dx = quote_in as u128;
+impl StreamSchedule {
+    pub const SIZE: usize = 8 + 8 + 8 + 8;
+}
 
-    // y_out = y - (k / (x + dx))
-    let k = x.checked_mul(y).ok_or(CtoError::MathOverflow)?;
-    let denom = x.checked_add(dx).ok_or(CtoError::MathOverflow)?;
-    let y_after = k.checked_div(denom).ok_or(CtoError::MathOverflow)?;
-    let expected_out = y.checked_sub(y_after).ok_or(CtoError::PumpSwapMathError)?;
+/// Period schedule of a RecurringBudget (all zero for other kinds).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct BudgetSchedule {
+    pub period_seconds: i64,
+    // periods paid so far (the first by execute_proposal) and when the next one is due
+    pub periods_paid: u32,
+    pub next_due_ts: i64,
+}
 
-    // Apply a conservative fee haircut first (estimated PumpSwap fee), then slippage haircut.
-    let fee = (BPS_DENOM as u128)
-        .checked_sub(PUMPSWAP_FEE_BPS_ESTIMATE as u128)
-        .ok_or(CtoError::MathOverflow)?;
-    let after_fee = expected_out
-        .checked_mul(fee)
-        .ok_or(CtoError::MathOverflow)?
-        .checked_div(BPS_DENOM as u128)
-        .ok_or(CtoError::MathOverflow)?;
+impl BudgetSchedule {
+    pub const SIZE: usize = 8 + 4 + 8;
+}
 
-    let slip = (BPS_DENOM as u128)
-        .checked_sub(slippage_bps as u128)
-        .ok_or(CtoError::MathOverflow)?;
-    let min_out = after_fee
-        .checked_mul(slip)
-        .ok_or(CtoError::MathOverflow)?
-        .checked_div(BPS_DENOM as u128)
-        .ok_or(CtoError::MathOverflow)?;
+/// Coarse purpose of a payout, set from the template it was instantiated from.
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq)]
+pub enum ProposalCategory {
+    General,
+    Marketing,
+    Development,
+    Listings,
+    Community,
+    Operations,
+}
 
-    let min_out_u64 = u64::try_from(min_out).map_err(|_| CtoError::MathOverflow)?;
-    require!(min_out_u64 > 0, CtoError::PumpSwapMinOutZero);
-    Ok(min_out_u64)
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq)]
+pub enum TemplateStatus {
+    /// Adoption vote still open.
+    Pending,
+    /// Adopted; `create_proposal` may instantiate it.
+    Active,
+    /// Adoption vote failed.
+    Rejected,
 }
 
-/// Heuristic minimum out calculator for legacy Raydium path.
-/// This is intentionally permissive and should not be relied on for robust frontrun protection.
-fn calculate_minimum_amount_out_heuristic(amount_in: u64, slippage_bps: u64) -> Result<u64> {
-    let min_out = (amount_in as u128)
-        .checked_mul(
-            (BPS_DENOM as u128)
-                .checked_sub(slippage_bps as u128)
-                .ok_or(CtoError::MathOverflow)?,
-        )
-        .ok_or(CtoError::MathOverflow)?
-        .checked_div(BPS_DENOM as u128)
-        .ok_or(CtoError::MathOverflow)?;
+/// Fields a template proposal votes on; see `propose_template`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct TemplateParams {
+    pub category: ProposalCategory,
+    pub destination_wallet: Pubkey,
+    pub denomination: RequestDenomination,
+    /// Inclusive bounds on `requested_amount`, in `denomination` units
+    pub min_amount: u64,
+    pub max_amount: u64,
+    pub title: String,
+    pub description: String,
+}
 
-    let min_out = u64::try_from(min_out).map_err(|_| CtoError::MathOverflow)?;
-    Ok(min_out.max(1))
+/// Whether holders may donate the pool's own token into its treasury ATA.
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq)]
+pub enum TokenDonationPolicy {
+    /// `donate_pool_token` is rejected.
+    Disabled,
+    /// Donations are accepted and tracked; no shares are minted.
+    NoShares,
 }
 
-/// Performs PumpSwap `buy`:
-/// - Spends up to `max_quote_amount_in` WSOL from `pool_wsol_account`
-/// - Receives at least `base_amount_out` CTOP into `pool_ctop_account`
-fn perform_pumpswap_buy<'info>(
-    ctx: &mut Context<ExecuteProposal<'info>>,
-    base_amount_out: u64,
-    max_quote_amount_in: u64,
-    pool_bump: u8,
-) -> Result<()> {
-    let mut data = Vec::with_capacity(8 + 8 + 8);
-    data.extend_from_slice(&PUMPSWAP_BUY_DISCRIMINATOR);
-    data.extend_from_slice(&base_amount_out.to_le_bytes());
-    data.extend_from_slice(&max_quote_amount_in.to_le_bytes());
+/// What a proposal pays into, so voters can tell a person from a program or a multisig.
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq)]
+pub enum DestinationKind {
+    /// Not applicable (polls).
+    None,
+    /// System-owned address: a wallet, or a PDA whose seeds weren't proven.
+    SystemWallet,
+    /// SPL Token or Token-2022 token account.
+    TokenAccount,
+    /// Squads v4 vault, verified against its multisig account.
+    SquadsVault,
+    /// Data account owned by a program other than System or Token.
+    ProgramOwned,
+    /// Executable program account.
+    Program,
+}
 
-    // Account order is strict. Do NOT reorder without checking the PumpSwap interface/IDL.
-    let metas = vec![
-        AccountMeta::new(ctx.accounts.pumpswap_pool.key(), false),
-        AccountMeta::new(ctx.accounts.pool.key(), true), // user (signer): pool PDA
-        AccountMeta::new_readonly(ctx.accounts.pumpswap_global_config.key(), false),
-        AccountMeta::new_readonly(ctx.accounts.ctop_mint.key(), false), // base_mint
-        AccountMeta::new_readonly(ctx.accounts.wsol_mint.key(), false), // quote_mint
-        AccountMeta::new(ctx.accounts.pool_ctop_account.key(), false),  // user_base_token_account
-        AccountMeta::new(ctx.accounts.pool_wsol_account.key(), false),  // user_quote_token_account
-        AccountMeta::new(ctx.accounts.pumpswap_pool_base_vault.key(), false),
-        AccountMeta::new(ctx.accounts.pumpswap_pool_quote_vault.key(), false),
-        AccountMeta::new_readonly(ctx.accounts.pumpswap_protocol_fee_recipient.key(), false),
-        AccountMeta::new(ctx.accounts.pumpswap_protocol_fee_recipient_token_account.key(), false),
-        AccountMeta::new_readonly(ctx.accounts.pumpswap_base_token_program.key(), false),
-        AccountMeta::new_readonly(ctx.accounts.pumpswap_quote_token_program.key(), false),
-        AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
-        AccountMeta::new_readonly(ctx.accounts.associated_token_program.key(), false),
-        AccountMeta::new_readonly(ctx.accounts.pumpswap_event_authority.key(), false),
-        AccountMeta::new_readonly(ctx.accounts.pumpswap_program.key(), false),
-    ];
+/// How a proposal that meets quorum with equal Yes and No weight is resolved.
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq)]
+pub enum TieBreakRule {
+    /// Tie fails.
+    Fail,
+    /// Tie passes only if the pool authority called `approve_tie`.
+    PassWithAuthority,
+    /// Tie extends voting once by TIE_EXTENSION_SECONDS; a second tie fails.
+    ExtendVoting,
+}
 
-    let ix = Instruction {
-        program_id: ctx.accounts.pumpswap_program.key(),
-        accounts: metas,
-        data,
-    };
+/// Why a `process_buy_and_burn` swap failed, as logged before the crank reverts.
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SwapFailureCode {
+    /// Output came in under the computed minimum.
+    SlippageExceeded,
+    /// Venue vaults hold the wrong mints or aren't owned by the venue pool.
+    VaultMintMismatch,
+    /// The venue program (or a token CPI around it) rejected the swap.
+    CpiRejected,
+    /// Reserves too thin for the trade to produce a non-zero minimum out.
+    MinOutZero,
+    /// Not enough compute budget left to attempt the swap.
+    ComputeExhausted,
+    /// Configuration or math errors.
+    Other,
+}
 
-    invoke_signed(
-        &ix,
-        &[
-            ctx.accounts.pumpswap_pool.to_account_info(),
-            ctx.accounts.pool.to_account_info(),
-            ctx.accounts.pumpswap_global_config.to_account_info(),
-            ctx.accounts.ctop_mint.to_account_info(),
-            ctx.accounts.wsol_mint.to_account_info(),
-            ctx.accounts.pool_ctop_account.to_account_info(),
-            ctx.accounts.pool_wsol_account.to_account_info(),
-            ctx.accounts.pumpswap_pool_base_vault.to_account_info(),
-            ctx.accounts.pumpswap_pool_quote_vault.to_account_info(),
-            ctx.accounts.pumpswap_protocol_fee_recipient.to_account_info(),
-            ctx.accounts.pumpswap_protocol_fee_recipient_token_account.to_account_info(),
-            ctx.accounts.pumpswap_base_token_program.to_account_info(),
-            ctx.accounts.pumpswap_quote_token_program.to_account_info(),
-            ctx.accounts.system_program.to_account_info(),
-            ctx.accounts.associated_token_program.to_account_info(),
-            ctx.accounts.pumpswap_event_authority.to_account_info(),
-            ctx.accounts.pumpswap_program.to_account_info(),
-        ],
-        pool_seeds!(&ctx.accounts.pool, pool_bump),
-    )
-    .map_err(|_| CtoError::SwapFailed.into())
+impl SwapFailureCode {
+    /// Classifies an error returned by a swap adapter.
+    pub fn from_error(err: &anchor_lang::error::Error) -> Self {
+        let code = match err {
+            anchor_lang::error::Error::AnchorError(e) => e.error_code_number,
+            anchor_lang::error::Error::ProgramError(_) => return Self::CpiRejected,
+        };
+        let is = |e: CtoError| code == u32::from(e);
+        if is(CtoError::SlippageExceeded) {
+            Self::SlippageExceeded
+        } else if is(CtoError::InvalidPumpSwapVaultMints)
+            || is(CtoError::InvalidPumpSwapVaultOwner)
+            || is(CtoError::InvalidWhirlpoolVaultMints)
+            || is(CtoError::InvalidWhirlpoolVaultOwner)
+            || is(CtoError::InvalidMeteoraReserveMints)
+            || is(CtoError::InvalidMeteoraReserveOwner)
+            || is(CtoError::InvalidRaydiumVaultMints)
+        {
+            Self::VaultMintMismatch
+        } else if is(CtoError::SwapFailed) || is(CtoError::LamportTransferFailed) {
+            Self::CpiRejected
+        } else if is(CtoError::PumpSwapMinOutZero) {
+            Self::MinOutZero
+        } else if is(CtoError::InsufficientComputeForSwap) {
+            Self::ComputeExhausted
+        } else {
+            Self::Other
+        }
+    }
 }
 
-/// Performs the Raydium swap from WSOL to CTOP (legacy).
-fn perform_raydium_swap<'info>(
-    ctx: &mut Context<ExecuteProposal<'info>>,
-    amount_in: u64,
-    minimum_amount_out: u64,
-    pool_bump: u8,
-) -> Result<()> {
-    let mut data = Vec::with_capacity(1 + 8 + 8);
-    data.push(RAYDIUM_SWAP_INSTRUCTION);
-    data.extend_from_slice(&amount_in.to_le_bytes());
-    data.extend_from_slice(&minimum_amount_out.to_le_bytes());
+/// How snapshot shares turn into vote weight.
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq)]
+pub enum VoteWeighting {
+    /// One share, one vote.
+    Linear,
+    /// Weight is the integer square root of shares (scaled by QUADRATIC_WEIGHT_SCALE).
+    Quadratic,
+}
 
-    let metas = vec![
-        AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
-        AccountMeta::new(ctx.accounts.raydium_pool.key(), false),
-        AccountMeta::new_readonly(ctx.accounts.raydium_pool_authority.key(), false),
-        AccountMeta::new(ctx.accounts.raydium_open_orders.key(), false),
-        AccountMeta::new(ctx.accounts.raydium_target_orders.key(), false),
-        AccountMeta::new(ctx.accounts.raydium_coin_vault.key(), false),
-        AccountMeta::new(ctx.accounts.raydium_pc_vault.key(), false),
-        AccountMeta::new_readonly(ctx.accounts.serum_program.key(), false),
-        AccountMeta::new(ctx.accounts.serum_market.key(), false),
-        AccountMeta::new(ctx.accounts.serum_bids.key(), false),
-        AccountMeta::new(ctx.accounts.serum_asks.key(), false),
-        AccountMeta::new(ctx.accounts.serum_event_queue.key(), false),
-        AccountMeta::new(ctx.accounts.serum_coin_vault.key(), false),
-        AccountMeta::new(ctx.accounts.serum_pc_vault.key(), false),
-        AccountMeta::new_readonly(ctx.accounts.serum_vault_signer.key(), false),
-        AccountMeta::new(ctx.accounts.pool_wsol_account.key(), false),
-        AccountMeta::new(ctx.accounts.pool_ctop_account.key(), false),
-        AccountMeta::new_readonly(ctx.accounts.pool.key(), true),
-    ];
+/// Account types upgradable through `migrate_account`.
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq)]
+pub enum MigratableAccount {
+    Pool,
+    Donor,
+    Proposal,
+}
 
-    let ix = Instruction {
-        program_id: ctx.accounts.raydium_program.key(),
-        accounts: metas,
-        data,
-    };
+/// Where the non-dev half of the protocol fee goes.
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq)]
+pub enum FeeSink {
+    /// Buy & burn (or hold, per `BuybackMode`) via the configured venues.
+    Burn,
+    /// SOL to an LP-lock vault.
+    LpLock,
+    /// SOL to a charity wallet.
+    Charity,
+    /// SOL to a protocol treasury wallet.
+    Treasury,
+}
 
-    invoke_signed(
-        &ix,
-        &[
-            ctx.accounts.token_program.to_account_info(),
-            ctx.accounts.raydium_pool.to_account_info(),
-            ctx.accounts.raydium_pool_authority.to_account_info(),
-            ctx.accounts.raydium_open_orders.to_account_info(),
-            ctx.accounts.raydium_target_orders.to_account_info(),
-            ctx.accounts.raydium_coin_vault.to_account_info(),
-            ctx.accounts.raydium_pc_vault.to_account_info(),
-            ctx.accounts.serum_program.to_account_info(),
-            ctx.accounts.serum_market.to_account_info(),
-            ctx.accounts.serum_bids.to_account_info(),
-            ctx.accounts.serum_asks.to_account_info(),
-            ctx.accounts.serum_event_queue.to_account_info(),
-            ctx.accounts.serum_coin_vault.to_account_info(),
-            ctx.accounts.serum_pc_vault.to_account_info(),
-            ctx.accounts.serum_vault_signer.to_account_info(),
-            ctx.accounts.pool_wsol_account.to_account_info(),
-            ctx.accounts.pool_ctop_account.to_account_info(),
-            ctx.accounts.pool.to_account_info(),
-        ],
-        pool_seeds!(&ctx.accounts.pool, pool_bump),
-    )
-    .map_err(|_| CtoError::SwapFailed.into())
+/// Where a pool keeps donated SOL.
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq)]
+pub enum PoolCustody {
+    /// Staked into the configured stake pool; pool tokens are LST.
+    Staked,
+    /// Kept as lamports in the pool PDA, with no LST exposure; pool tokens are lamports.
+    Native,
 }
 
-/// M-07 FIX: Transfers CTOP tokens to the incinerator address for burning.
-/// Handles zero balance gracefully (returns 0 instead of error).
-fn transfer_to_incinerator<'info>(ctx: &mut Context<ExecuteProposal<'info>>, pool_bump: u8) -> Result<u64> {
-    ctx.accounts.pool_ctop_account.reload()?;
-    let bal = ctx.accounts.pool_ctop_account.amount;
-    
-    // M-07 FIX: Handle zero balance gracefully (not an error)
-    if bal == 0 {
-        return Ok(0);
-    }
+/// AMM `process_buy_and_burn` swaps on.
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq)]
+pub enum BurnVenue {
+    /// No venue; burn-half fees go to the dev wallet.
+    None,
+    /// PumpSwap (post Pump.fun graduation), optionally via a second hop.
+    PumpSwap,
+    /// Orca Whirlpool (concentrated liquidity).
+    Whirlpool,
+    /// Meteora DLMM pair.
+    MeteoraDlmm,
+    /// Raydium CPMM pool.
+    RaydiumCpmm,
+}
 
-    token::transfer(
-        CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            token::Transfer {
-                from: ctx.accounts.pool_ctop_account.to_account_info(),
-                to: ctx.accounts.incinerator_ctop_account.to_account_info(),
-                authority: ctx.accounts.pool.to_account_info(),
-            },
-            pool_seeds!(&ctx.accounts.pool, pool_bump),
-        ),
-        bal,
-    )?;
+/// What the burn half of the protocol fee does with the CTOP it buys.
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq)]
+pub enum BuybackMode {
+    /// Send bought CTOP to the incinerator ATA.
+    Burn,
+    /// Keep bought CTOP in the pool PDA as a governed treasury asset.
+    Hold,
+}
 
-    Ok(bal)
+/// Unit of a payout request.
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq)]
+pub enum RequestDenomination {
+    /// Request is a SOL amount; locked pool tokens include PROPOSAL_BUFFER_BPS, and whatever the
+    /// request doesn't need at the execution-time rate is released rather than burned.
+    Lamports,
+    /// Request is an LST amount; locked == burned, the SOL paid follows the execution-time rate.
+    PoolTokens,
+    /// Request is a share of pool LST in bps, resolved at execution (capped by the amount locked).
+    TvlBps,
 }
 
-// ===== Recovery helpers =====
+#[account]
+pub struct Proposal {
+    // layout version (PROPOSAL_VERSION); must stay the first field
+    pub version: u8,
+    pub pool: Pubkey,
+    pub kind: ProposalKind,
 
-fn transfer_spl_from_pool_with_seeds<'info>(
-    pool: &AccountInfo<'info>,
-    from: &Account<'info, TokenAccount>,
-    to: &Account<'info, TokenAccount>,
-    token_program: &Program<'info, Token>,
-    seeds: &[&[&[u8]]],
-    amount: u64,
-) -> Result<()> {
-    token::transfer(
-        CpiContext::new_with_signer(
-            token_program.to_account_info(),
-            token::Transfer {
-                from: from.to_account_info(),
-                to: to.to_account_info(),
-                authority: pool.clone(),
-            },
-            seeds,
-        ),
-        amount,
-    )
+    pub proposer_wallet: Pubkey,
+
+    pub requested_lamports: u64,
+    pub denomination: RequestDenomination,
+    pub requested_bps: u16,
+    pub destination_wallet: Pubkey,
+
+    pub title: String,
+    pub description: String,
+
+    pub created_at_ts: i64,
+    pub deadline_ts: i64,
+
+    pub snapshot_slot: u64,
+    pub total_snapshot_shares: u64,
+
+    // pool tokens locked for this proposal
+    pub locked_pool_tokens: u64,
+
+    pub yes_weight: u64,
+    pub no_weight: u64,
+    pub abstain_weight: u64,
+    pub participation_weight: u64,
+
+    // Abort voting (weak emergency brake)
+    pub abort_voter_1: Pubkey,
+    pub abort_voter_2: Pubkey,
+    pub abort_count: u8,
+
+    pub status: ProposalStatus,
+    // slot at which the proposal left Active (0 while active)
+    pub finalized_slot: u64,
+
+    // tie-break state (see TieBreakRule)
+    pub tie_approved: bool,
+    pub tie_extended: bool,
+    // one-time near-quorum extension consumed
+    pub quorum_grace_used: bool,
+    // quorum this proposal must reach, fixed at creation
+    pub quorum_bps: u16,
+    // what the destination is (see classify_destination)
+    pub destination_kind: DestinationKind,
+    // vote weight comes from weight_root (see post_weight_root); zero root = not posted yet
+    pub merkle_weights: bool,
+    pub weight_root: [u8; 32],
+    // template this payout was instantiated from (AdoptTemplate: template being adopted), or default
+    pub category: ProposalCategory,
+    pub template: Pubkey,
+    // co-sponsorship: voting opens (deadline = now + voting_period_seconds) once
+    // sponsor_count reaches sponsors_required
+    pub sponsors_required: u8,
+    pub sponsor_count: u8,
+    pub voting_period_seconds: i64,
+    // snapshot shares above the per-voter cap, excluded from the quorum denominator
+    pub cap_adjusted_quorum: bool,
+    pub excluded_cap_weight: u64,
+    // pool values applied when a ConfigChange passes (all None for other kinds)
+    pub config_change: ConfigChangeParams,
+    // tranche schedule and escrow of a MilestonePayout (all zero for other kinds)
+    pub milestone: MilestoneSchedule,
+    // vesting schedule and escrow of a StreamPayout (all zero for other kinds)
+    pub stream: StreamSchedule,
+    // period schedule of a RecurringBudget (all zero for other kinds)
+    pub budget: BudgetSchedule,
+    // per-voter weight cap, fixed at creation from pool.max_voter_bps
+    pub max_voter_bps: u16,
+    // created under the pool's small-payout fast track (quorum_bps already reflects it)
+    pub fast_track: bool,
+    // earliest execution time once Queued (0 until queued)
+    pub queued_until_ts: i64,
+    // proposer bond held in its ProposerBond (0 = none or already settled), and the
+    // participation below which it is forfeited
+    pub bond_lamports: u64,
+    pub bond_spam_threshold_bps: u16,
+    // commit–reveal: ballots are revealed in the reveal_seconds after deadline_ts (0 = open)
+    pub reveal_seconds: i64,
+    // anti-sniping extension and how many are left, fixed at creation from the pool
+    pub snipe_extension_seconds: i64,
+    pub snipe_extensions_left: u8,
+    // time-weighted voting ramp, fixed at creation from the pool (0 = off)
+    pub vote_ramp_slots: u64,
+    // linear or quadratic weights, fixed at creation from the pool
+    pub vote_weighting: VoteWeighting,
+    // mint and amount of a TokenPayout (default for other kinds)
+    pub payout_mint: Pubkey,
+    pub payout_token_amount: u64,
+    // treasury tokens an AddLiquidity pairs with its SOL, or LP tokens a RemoveLiquidity redeems
+    pub liquidity_amount: u64,
+    // sum of the weight root's leaves, and the leaf weight proven by vote_merkle so far
+    pub weight_root_total: u64,
+    pub merkle_weight_proven: u64,
+}
+
+impl Proposal {
+    pub const TITLE_MAX: usize = 64;
+    pub const DESC_MAX: usize = 256;
+
+    pub const SIZE: usize =
+        1 +                   // version
+        32 + 1 +              // pool, kind
+        32 +                 // proposer_wallet
+        8 + 1 + 2 + 32 +      // requested, denomination, requested_bps, destination
+        4 + Self::TITLE_MAX + // title
+        4 + Self::DESC_MAX +  // description
+        8 + 8 +               // created_at, deadline
+        8 + 8 +               // snapshot_slot, total_snapshot_shares
+        8 +                   // locked_pool_tokens
+        8 + 8 + 8 + 8 +       // yes/no/abstain/participation
+        32 + 32 + 1 +         // abort_voter_1, abort_voter_2, abort_count
+        1 +                   // status
+        8 +                   // finalized_slot
+        1 + 1 +               // tie_approved, tie_extended
+        1 +                   // quorum_grace_used
+        2 +                   // quorum_bps
+        1 +                   // destination_kind
+        1 + 32 +              // merkle_weights, weight_root
+        1 + 32 +              // category, template
+        1 + 1 + 8 +           // sponsors_required, sponsor_count, voting_period_seconds
+        1 + 8 +               // cap_adjusted_quorum, excluded_cap_weight
+        ConfigChangeParams::SIZE + // config_change
+        MilestoneSchedule::SIZE +  // milestone
+        StreamSchedule::SIZE +     // stream
+        BudgetSchedule::SIZE +     // budget
+        2 +                        // max_voter_bps
+        1 +                        // fast_track
+        8 +                        // queued_until_ts
+        8 + 2 +                    // bond_lamports, bond_spam_threshold_bps
+        8 +                        // reveal_seconds
+        8 + 1 +                    // snipe_extension_seconds, snipe_extensions_left
+        8 +                        // vote_ramp_slots
+        1 +                        // vote_weighting
+        32 + 8 +                   // payout_mint, payout_token_amount
+        8 +                        // liquidity_amount
+        8 + 8;                     // weight_root_total, merkle_weight_proven
 }
 
-/// Instruction-sysvar based "inline sender" check.
-///
-/// This only works if the requester performs the transfer and recovery in the *same transaction*.
-/// It cannot prove sender for a historical accident.
-fn verify_inline_sender(
-    _instructions_sysvar: &AccountInfo,
-    _requester: &Pubkey,
-    _pool_token_account: &Pubkey,
-    _mint: Pubkey,
-    _amount: u64,
-) -> Result<bool> {
-    Ok(false)
+/// Escrow of a payout proposal's proposer bond, PDA: ['bond', proposal]. The bond amount is
+/// `proposal.bond_lamports`, held on top of this account's rent.
+#[account]
+pub struct ProposerBond {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub proposer: Pubkey,
+    pub bump: u8,
 }
 
-// ============= Accounts =============
+impl ProposerBond {
+    pub const SIZE: usize = 32 + 32 + 32 + 1;
+}
 
-#[derive(Accounts)]
-pub struct CreatePool<'info> {
-    #[account(
-        init,
-        payer = creator,
-        space = 8 + Pool::SIZE,
-        seeds = [b"pool", token_mint.key().as_ref()],
-        bump
-    )]
-    pub pool: Account<'info, Pool>,
+/// A wallet admitted to an allowlist-mode pool, PDA: ['allowlist', pool, wallet].
+#[account]
+pub struct AllowlistEntry {
+    pub pool: Pubkey,
+    pub wallet: Pubkey,
+    pub added_slot: u64,
+}
 
-    pub token_mint: Account<'info, Mint>,
+impl AllowlistEntry {
+    pub const SIZE: usize = 32 + 32 + 8;
+}
 
-    #[account(mut)]
-    pub creator: Signer<'info>,
+/// Sponsor-funded matching campaign, PDA: ['matching', pool]. Holds the unspent budget on top of
+/// its rent; `donate_sol` / `donate_sol_for` draw matches from it during the window.
+#[account]
+pub struct MatchingVault {
+    pub pool: Pubkey,
+    pub sponsor: Pubkey,
+    // lamports matched per donated lamport, in bps
+    pub match_bps: u16,
+    pub start_ts: i64,
+    pub end_ts: i64,
+    // budget left / matched so far
+    pub available_lamports: u64,
+    pub matched_lamports: u64,
+    pub bump: u8,
+}
 
-    pub system_program: Program<'info, System>,
+impl MatchingVault {
+    pub const SIZE: usize =
+        32 + 32 +      // pool, sponsor
+        2 + 8 + 8 +    // match_bps, start_ts, end_ts
+        8 + 8 +        // available_lamports, matched_lamports
+        1;             // bump
 }
 
-#[derive(Accounts)]
-pub struct ConfigurePumpSwapPool<'info> {
-    #[account(
-        mut,
-        seeds = [b"pool", pool.token_mint.as_ref()],
-        bump
-    )]
-    pub pool: Account<'info, Pool>,
+/// SPL mint accepted by `donate_spl`, PDA: ['spl_mint', pool, mint].
+#[account]
+pub struct SplDonationMint {
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    // Pyth mint/USD PriceUpdateV2 account
+    pub price_feed: Pubkey,
+    pub enabled: bool,
+    // cap on the oracle value of all donations of this mint (0 = uncapped)
+    pub max_total_lamports: u64,
+    pub total_donated_amount: u64,
+    pub total_donated_lamports: u64,
+    // SplPosition shares of this mint's vault outstanding
+    pub total_shares: u64,
+}
 
-    pub authority: Signer<'info>,
+impl SplDonationMint {
+    pub const SIZE: usize =
+        32 + 32 + 32 + // pool, mint, price_feed
+        1 + 8 +        // enabled, max_total_lamports
+        8 + 8 +        // total_donated_amount, total_donated_lamports
+        8;             // total_shares
 }
 
-#[derive(Accounts)]
-pub struct ConfigureRaydiumPool<'info> {
-    #[account(
-        mut,
-        seeds = [b"pool", pool.token_mint.as_ref()],
-        bump
-    )]
-    pub pool: Account<'info, Pool>,
+/// A donor's shares of one SPL mint's vault, PDA: ['spl_position', spl_donation_mint, wallet].
+/// Minted by `donate_spl` and redeemed by `redeem_spl_position`; separate from pool shares.
+#[account]
+pub struct SplPosition {
+    pub spl_donation_mint: Pubkey,
+    pub wallet: Pubkey,
+    pub shares: u64,
+}
 
-    pub authority: Signer<'info>,
+impl SplPosition {
+    pub const SIZE: usize = 32 + 32 + 8;
 }
 
-#[derive(Accounts)]
-pub struct DonateSol<'info> {
-    #[account(
-        mut,
-        seeds = [b"pool", pool.token_mint.as_ref()],
-        bump
-    )]
-    pub pool: Account<'info, Pool>,
+/// Reusable payout shape adopted by governance, PDA: ['template', pool, template_id].
+#[account]
+pub struct ProposalTemplate {
+    pub pool: Pubkey,
+    pub template_id: u64,
+    pub proposer: Pubkey,
+    pub category: ProposalCategory,
+    pub destination_wallet: Pubkey,
+    pub denomination: RequestDenomination,
+    pub min_amount: u64,
+    pub max_amount: u64,
+    pub title: String,
+    pub description: String,
+    pub status: TemplateStatus,
+    // AdoptTemplate proposal that decided (or is deciding) this template
+    pub adoption_proposal: Pubkey,
+    // payout proposals created from this template
+    pub use_count: u64,
+}
 
-    #[account(
-        init_if_needed,
-        payer = donor_wallet,
-        space = 8 + Donor::SIZE,
-        seeds = [b"donor", pool.key().as_ref(), donor_wallet.key().as_ref()],
-        bump
-    )]
-    pub donor: Account<'info, Donor>,
+impl ProposalTemplate {
+    pub const SIZE: usize =
+        32 + 8 + 32 +                     // pool, template_id, proposer
+        1 + 32 + 1 +                      // category, destination, denomination
+        8 + 8 +                           // min_amount, max_amount
+        4 + Proposal::TITLE_MAX +         // title
+        4 + Proposal::DESC_MAX +          // description
+        1 + 32 + 8;                       // status, adoption_proposal, use_count
+}
 
-    #[account(mut)]
-    pub donor_wallet: Signer<'info>,
+/// Compact, immutable record of a finalized proposal.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ProposalResult {
+    pub proposal: Pubkey,
+    pub requested_lamports: u64,
+    pub locked_pool_tokens: u64,
+    pub destination_wallet: Pubkey,
+    pub yes_weight: u64,
+    pub no_weight: u64,
+    pub abstain_weight: u64,
+    pub status: ProposalStatus,
+    pub finalized_slot: u64,
+}
 
-    /// CHECK: stake pool program (e.g. Jito)
-    pub stake_pool_program: UncheckedAccount<'info>,
+impl ProposalResult {
+    pub const SIZE: usize = 32 + 8 + 8 + 32 + 8 + 8 + 8 + 1 + 8;
+}
 
-    /// CHECK: stake pool state account
-    #[account(mut)]
-    pub stake_pool: UncheckedAccount<'info>,
+/// Append-only page of proposal results, PDA: ['archive', pool, page_index].
+#[account]
+pub struct ProposalArchive {
+    pub pool: Pubkey,
+    pub page_index: u64,
+    pub results: Vec<ProposalResult>,
+}
 
-    /// CHECK: stake pool withdraw authority
-    pub stake_pool_withdraw_authority: UncheckedAccount<'info>,
+impl ProposalArchive {
+    pub const CAPACITY: usize = 16;
+    pub const SIZE: usize = 32 + 8 + 4 + Self::CAPACITY * ProposalResult::SIZE;
+}
 
-    /// CHECK: reserve stake account
-    #[account(mut)]
-    pub reserve_stake: UncheckedAccount<'info>,
+/// Page of proposal pubkeys in creation order, PDA: ['proposal_index', pool, page_index].
+/// Entry i of page p is the proposal with index p * CAPACITY + i.
+#[account]
+pub struct ProposalIndexPage {
+    pub pool: Pubkey,
+    pub page_index: u64,
+    pub proposals: Vec<Pubkey>,
+}
 
-    #[account(mut)]
-    pub manager_fee_account: Account<'info, TokenAccount>,
+impl ProposalIndexPage {
+    pub const CAPACITY: usize = 64;
+    pub const SIZE: usize = 32 + 8 + 4 + Self::CAPACITY * 32;
+}
 
-    #[account(mut)]
-    pub referrer_pool_tokens_account: Account<'info, TokenAccount>,
+/// Shares waiting in a pool's FIFO withdrawal queue, PDA: ['withdrawal_request', pool, index].
+#[account]
+pub struct WithdrawalRequest {
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub index: u64,
+    // 0 once cancelled; the entry stays until the queue head reaches it
+    pub shares: u64,
+    pub created_ts: i64,
+    pub cancelled: bool,
+}
 
-    #[account(
-        init_if_needed,
-        payer = donor_wallet,
-        associated_token::mint = lst_mint,
-        associated_token::authority = pool
-    )]
-    pub pool_lst_account: Account<'info, TokenAccount>,
+impl WithdrawalRequest {
+    pub const SIZE: usize = 32 + 32 + 8 + 8 + 8 + 1;
+}
 
-    #[account(mut)]
-    pub lst_mint: Account<'info, Mint>,
+/// SOL owed from a pool-owned stake account split off the stake pool while its reserve was dry,
+/// PDA: ['stake_withdrawal', pool, index]. Its stake account is ['withdrawal_stake', pool, index].
+#[account]
+pub struct StakeWithdrawal {
+    pub pool: Pubkey,
+    pub index: u64,
+    pub recipient: Pubkey,
+    pub stake_account: Pubkey,
+    // lamports split off for the recipient; protocol_fee of them is withheld and split at claim
+    pub lamports: u64,
+    pub protocol_fee: u64,
+    // funder of the stake account's rent, refunded at claim
+    pub rent_payer: Pubkey,
+    pub rent_lamports: u64,
+    pub deactivation_epoch: u64,
+}
 
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub system_program: Program<'info, System>,
+impl StakeWithdrawal {
+    pub const SIZE: usize = 32 + 8 + 32 + 32 + 8 + 8 + 32 + 8 + 8;
 }
 
-#[derive(Accounts)]
-pub struct WithdrawSol<'info> {
-    #[account(
-        mut,
-        seeds = [b"pool", pool.token_mint.as_ref()],
-        bump
-    )]
-    pub pool: Account<'info, Pool>,
+/// Per-destination failure tracking, PDA: ['destination', pool, destination_wallet].
+#[account]
+pub struct DestinationRecord {
+    pub pool: Pubkey,
+    pub destination: Pubkey,
+    pub failed_count: u16,
+    pub cooldown_until_ts: i64,
+}
 
-    #[account(
-        mut,
-        seeds = [b"donor", pool.key().as_ref(), donor_wallet.key().as_ref()],
-        bump
-    )]
-    pub donor: Account<'info, Donor>,
+impl DestinationRecord {
+    pub const SIZE: usize = 32 + 32 + 2 + 8;
+}
 
-    #[account(mut)]
-    pub donor_wallet: Signer<'info>,
+#[account]
+pub struct RecoveryProposal {
+    pub pool: Pubkey,
+    pub token_mint: Pubkey,
+    pub requested_amount: u64,
+    pub destination_wallet: Pubkey,
 
-    /// CHECK
-    pub stake_pool_program: UncheckedAccount<'info>,
-    /// CHECK
-    #[account(mut)]
-    pub stake_pool: UncheckedAccount<'info>,
-    /// CHECK
-    pub stake_pool_withdraw_authority: UncheckedAccount<'info>,
-    /// CHECK
-    #[account(mut)]
-    pub reserve_stake: UncheckedAccount<'info>,
+    pub title: String,
+    pub description: String,
 
-    #[account(mut)]
-    pub manager_fee_account: Account<'info, TokenAccount>,
+    pub created_at_ts: i64,
+    pub deadline_ts: i64,
 
-    #[account(
-        mut,
-        associated_token::mint = lst_mint,
-        associated_token::authority = pool
-    )]
-    pub pool_lst_account: Account<'info, TokenAccount>,
+    pub snapshot_slot: u64,
+    pub total_snapshot_shares: u64,
 
-    #[account(mut)]
-    pub lst_mint: Account<'info, Mint>,
+    pub yes_weight: u64,
+    pub no_weight: u64,
+    pub abstain_weight: u64,
+    pub participation_weight: u64,
 
-    /// CHECK: sysvar clock
-    pub clock: UncheckedAccount<'info>,
-    /// CHECK: sysvar stake history
-    pub stake_history: UncheckedAccount<'info>,
-    /// CHECK: stake program
-    pub stake_program: UncheckedAccount<'info>,
+    // Abort voting (weak emergency brake)
+    pub abort_voter_1: Pubkey,
+    pub abort_voter_2: Pubkey,
+    pub abort_count: u8,
 
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
+    pub status: ProposalStatus,
+    // funded the account; receives the rent back from close_recovery
+    pub rent_payer: Pubkey,
 }
 
-#[derive(Accounts)]
-pub struct CreateProposal<'info> {
-    #[account(
-        mut,
-        seeds = [b"pool", pool.token_mint.as_ref()],
-        bump
-    )]
-    pub pool: Account<'info, Pool>,
+impl RecoveryProposal {
+    pub const TITLE_MAX: usize = 64;
+    pub const DESC_MAX: usize = 256;
 
-    #[account(
-        mut,
-        seeds = [b"donor", pool.key().as_ref(), proposer_wallet.key().as_ref()],
-        bump
-    )]
-    pub donor: Account<'info, Donor>,
+    pub const SIZE: usize =
+        32 + 32 + 8 + 32 +
+        4 + Self::TITLE_MAX +
+        4 + Self::DESC_MAX +
+        8 + 8 +
+        8 + 8 +
+        8 + 8 + 8 + 8 +
+        1 +
+        32;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum VoteChoice {
+    Yes,
+    No,
+    Abstain,
+    Abort,
+}
 
-    #[account(
-        init,
-        payer = proposer_wallet,
-        space = 8 + Proposal::SIZE,
-        seeds = [b"proposal", pool.key().as_ref(), &pool.proposal_count.to_le_bytes()],
-        bump
-    )]
-    pub proposal: Account<'info, Proposal>,
+/// Marks a donor's above-cap excess as excluded from a proposal's quorum,
+/// PDA: ['cap_excess', proposal, wallet].
+#[account]
+pub struct CapExcessRecord {
+    pub proposal: Pubkey,
+    pub wallet: Pubkey,
+    pub excess: u64,
+}
 
-    #[account(mut)]
-    pub proposer_wallet: Signer<'info>,
+impl CapExcessRecord {
+    pub const SIZE: usize = 32 + 32 + 8;
+}
 
-    /// CHECK
-    pub stake_pool: UncheckedAccount<'info>,
+/// One donor's co-sponsorship of a proposal, PDA: ['sponsor', proposal, wallet].
+#[account]
+pub struct Sponsorship {
+    pub proposal: Pubkey,
+    pub sponsor: Pubkey,
+    // sponsor's own shares when signing
+    pub shares: u64,
+    pub slot: u64,
+}
 
-    #[account(
-        mut,
-        associated_token::mint = lst_mint,
-        associated_token::authority = pool
-    )]
-    pub pool_lst_account: Account<'info, TokenAccount>,
+impl Sponsorship {
+    pub const SIZE: usize = 32 + 32 + 8 + 8;
+}
 
-    pub lst_mint: Account<'info, Mint>,
+#[account]
+pub struct VoteRecord {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub snapshot_weight: u64,
+    pub choice: VoteChoice,
+    pub initialized: bool,
+    // funded the account; receives the rent back from close_vote_record
+    pub payer: Pubkey,
+}
 
-    pub system_program: Program<'info, System>,
+impl VoteRecord {
+    pub const SIZE: usize = 32 + 32 + 8 + 1 + 1 + 32;
 }
 
-#[derive(Accounts)]
-pub struct Vote<'info> {
-    #[account(
-        seeds = [b"pool", pool.token_mint.as_ref()],
-        bump
-    )]
-    pub pool: Account<'info, Pool>,
+/// A sealed ballot on a commit–reveal proposal, PDA: ['vote_commit', proposal, wallet].
+/// Closed by `reveal_vote`.
+#[account]
+pub struct VoteCommitment {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    // sha256(proposal ‖ voter ‖ choice ‖ salt)
+    pub commitment: [u8; 32],
+    pub committed_ts: i64,
+}
 
-    #[account(mut, has_one = pool)]
-    pub proposal: Account<'info, Proposal>,
+impl VoteCommitment {
+    pub const SIZE: usize = 32 + 32 + 32 + 8;
+}
 
-    #[account(
-        seeds = [b"donor", pool.key().as_ref(), voter_wallet.key().as_ref()],
-        bump
-    )]
-    pub donor: Account<'info, Donor>,
+/// SOL owed to a payout destination, PDA: ['payout_claim', pool, destination].
+/// Credited by executed payouts and pulled by `claim_payout`.
+#[account]
+pub struct PayoutClaim {
+    pub pool: Pubkey,
+    pub destination: Pubkey,
+    pub lamports: u64,
+    // from here `reclaim_expired_payout` may return the lamports to the pool; reset by each credit
+    pub expires_ts: i64,
+}
 
-    #[account(
-        init_if_needed,
-        payer = voter_wallet,
-        space = 8 + VoteRecord::SIZE,
-        seeds = [b"vote", proposal.key().as_ref(), voter_wallet.key().as_ref()],
-        bump
-    )]
-    pub vote_record: Account<'info, VoteRecord>,
+impl PayoutClaim {
+    pub const SIZE: usize = 32 + 32 + 8 + 8;
+}
 
-    #[account(mut)]
-    pub voter_wallet: Signer<'info>,
+/// A donor's confirmation of one milestone tranche,
+/// PDA: ['milestone_confirm', proposal, voter, tranche].
+#[account]
+pub struct MilestoneConfirmation {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub tranche: u8,
+    pub weight: u64,
+}
 
-    pub system_program: Program<'info, System>,
+impl MilestoneConfirmation {
+    pub const SIZE: usize = 32 + 32 + 1 + 8;
 }
 
-#[derive(Accounts)]
-pub struct ExecuteProposal<'info> {
-    #[account(
-        mut,
-        seeds = [b"pool", pool.token_mint.as_ref()],
-        bump
-    )]
-    pub pool: Account<'info, Pool>,
+/// Aggregate weight delegated to one delegate, PDA: ['escrow', pool, delegate].
+#[account]
+pub struct VoteEscrow {
+    pub pool: Pubkey,
+    pub delegate: Pubkey,
+    pub delegated_shares: u64,
+    pub delegator_count: u32,
+    // slot of the last delegate/undelegate, and delegated_shares after each of the latest
+    // changes, so the escrow votes its snapshot balance
+    pub last_change_slot: u64,
+    pub share_checkpoints: [ShareCheckpoint; SHARE_CHECKPOINT_COUNT],
+}
 
-    #[account(mut, has_one = pool)]
-    pub proposal: Account<'info, Proposal>,
+impl VoteEscrow {
+    pub const SIZE: usize = 32 + 32 + 8 + 4 + 8 + ShareCheckpoint::SIZE * SHARE_CHECKPOINT_COUNT;
+}
 
-    /// CHECK
-    #[account(mut)]
-    pub destination_wallet: UncheckedAccount<'info>,
+/// One delegator's delegation, PDA: ['delegation', pool, delegator].
+#[account]
+pub struct Delegation {
+    pub pool: Pubkey,
+    pub delegator: Pubkey,
+    pub delegate: Pubkey,
+    pub shares: u64,
+    // 0 until undelegation is requested
+    pub unlock_ts: i64,
+}
 
-    /// CHECK
-    #[account(mut, address = pool.dev_fee_wallet)]
-    pub dev_fee_wallet: UncheckedAccount<'info>,
+impl Delegation {
+    pub const SIZE: usize = 32 + 32 + 32 + 8 + 8;
+}
 
-    // ===== Stake pool accounts =====
-    /// CHECK
-    pub stake_pool_program: UncheckedAccount<'info>,
-    /// CHECK
-    #[account(mut)]
-    pub stake_pool: UncheckedAccount<'info>,
-    /// CHECK
-    pub stake_pool_withdraw_authority: UncheckedAccount<'info>,
-    /// CHECK
-    #[account(mut)]
-    pub reserve_stake: UncheckedAccount<'info>,
+// ===== View return data =====
 
-    #[account(mut)]
-    pub manager_fee_account: Account<'info, TokenAccount>,
+/// Return data of `get_pool_stats`. Lamport values are at the stake pool's current rate, before
+/// its withdrawal fee.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PoolStats {
+    pub custody: PoolCustody,
+    pub total_shares: u64,
+    // shares that can vote: total less tokenized and queued shares
+    pub voting_shares: u64,
+    pub total_pool_tokens: u64,
+    pub reserved_pool_tokens: u64,
+    // not reserved and not rounding dust
+    pub free_pool_tokens: u64,
+    pub total_value_lamports: u64,
+    pub free_value_lamports: u64,
+    pub total_spent_lamports: u64,
+    pub donor_count: u64,
+    pub active_proposal: Option<Pubkey>,
+    pub sunset_started_ts: i64,
+}
 
-    #[account(
-        mut,
-        associated_token::mint = lst_mint,
-        associated_token::authority = pool
-    )]
-    pub pool_lst_account: Account<'info, TokenAccount>,
+/// Return data of `get_donor_position`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct DonorPosition {
+    pub wallet: Pubkey,
+    pub shares: u64,
+    pub delegated_shares: u64,
+    // pro-rata LST (lamports for native pools), locked liquidity included
+    pub pool_tokens: u64,
+    pub value_lamports: u64,
+    // pro-rata share of free liquidity only
+    pub withdrawable_pool_tokens: u64,
+    // net of the stake pool's SOL withdrawal fee
+    pub withdrawable_lamports: u64,
+    pub total_deposited_lamports: u64,
+}
 
-    #[account(mut)]
-    pub lst_mint: Account<'info, Mint>,
+/// Return data of `get_proposal_tally`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ProposalTally {
+    pub kind: ProposalKind,
+    pub status: ProposalStatus,
+    pub yes_weight: u64,
+    pub no_weight: u64,
+    pub abstain_weight: u64,
+    pub participation_weight: u64,
+    // quorum denominator: snapshot shares less excluded whale excess
+    pub quorum_supply: u64,
+    pub quorum_bps: u16,
+    pub quorum_met: bool,
+    // an Active proposal `execute_proposal` would pass now
+    pub passing: bool,
+    pub deadline_ts: i64,
+}
 
-    /// CHECK
-    pub clock: UncheckedAccount<'info>,
-    /// CHECK
-    pub stake_history: UncheckedAccount<'info>,
-    /// CHECK
-    pub stake_program: UncheckedAccount<'info>,
+// ============= Events =============
 
-    pub system_program: Program<'info, System>,
+// L-05 FIX: Comprehensive event emissions for state changes
 
-    // ===== PUMPSWAP SWAP ACCOUNTS (CTOP buy & burn, post-graduation) =====
-    /// CHECK
-    pub pumpswap_program: UncheckedAccount<'info>,
-    /// CHECK
-    #[account(mut)]
-    pub pumpswap_pool: UncheckedAccount<'info>,
+#[event]
+pub struct PoolCreatedEvent {
+    pub pool: Pubkey,
+    pub token_mint: Pubkey,
+    pub authority: Pubkey,
+    pub mint_authority_set: bool,
+    pub freeze_authority_set: bool,
+    pub timestamp: i64,
+}
 
-    /// CHECK
-    pub pumpswap_global_config: UncheckedAccount<'info>,
-    /// CHECK
-    pub pumpswap_protocol_fee_recipient: UncheckedAccount<'info>,
-    /// CHECK
-    #[account(mut)]
-    pub pumpswap_protocol_fee_recipient_token_account: UncheckedAccount<'info>,
-    /// CHECK
-    pub pumpswap_event_authority: UncheckedAccount<'info>,
+#[event]
+pub struct MintRiskFlagsEvent {
+    pub pool: Pubkey,
+    pub token_mint: Pubkey,
+    pub mint_authority_set: bool,
+    pub freeze_authority_set: bool,
+    pub timestamp: i64,
+}
 
-    /// The pool's base (CTOP) vault token account.
-    #[account(mut)]
-    pub pumpswap_pool_base_vault: Account<'info, TokenAccount>,
-    /// The pool's quote (WSOL) vault token account.
-    #[account(mut)]
-    pub pumpswap_pool_quote_vault: Account<'info, TokenAccount>,
+#[event]
+pub struct PoolTokenDonationEvent {
+    pub pool: Pubkey,
+    pub donor: Pubkey,
+    pub amount: u64,
+    /// Amount that reached the treasury (less any Token-2022 transfer fee)
+    pub received: u64,
+    pub treasury_token_amount: u64,
+    pub timestamp: i64,
+}
 
-    /// CHECK
-    pub pumpswap_base_token_program: UncheckedAccount<'info>,
-    /// CHECK
-    pub pumpswap_quote_token_program: UncheckedAccount<'info>,
+#[event]
+pub struct DonationEvent {
+    pub pool: Pubkey,
+    pub donor: Pubkey,
+    /// Funded the deposit; differs from `donor` for `donate_sol_for`
+    pub payer: Pubkey,
+    pub lamports_in: u64,
+    /// LST received (lamports for native pools)
+    pub pool_tokens_received: u64,
+    pub shares_minted: u64,
+    pub donor_shares: u64,
+    pub total_shares: u64,
+    pub timestamp: i64,
+}
 
-    // ===== RAYDIUM SWAP ACCOUNTS (legacy / optional) =====
-    /// CHECK
-    pub raydium_program: UncheckedAccount<'info>,
-    /// CHECK
-    #[account(mut)]
-    pub raydium_pool: UncheckedAccount<'info>,
-    /// CHECK
-    pub raydium_pool_authority: UncheckedAccount<'info>,
-    /// CHECK
-    #[account(mut)]
-    pub raydium_open_orders: UncheckedAccount<'info>,
-    /// CHECK
-    #[account(mut)]
-    pub raydium_target_orders: UncheckedAccount<'info>,
-    /// CHECK
-    #[account(mut)]
-    pub raydium_coin_vault: UncheckedAccount<'info>,
-    /// CHECK
-    #[account(mut)]
-    pub raydium_pc_vault: UncheckedAccount<'info>,
+#[event]
+pub struct ProposerBondSettledEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub proposer: Pubkey,
+    pub bond_lamports: u64,
+    /// true when the bond went to the pool instead of back to the proposer
+    pub forfeited: bool,
+    pub timestamp: i64,
+}
 
-    /// CHECK
-    pub serum_program: UncheckedAccount<'info>,
-    /// CHECK
-    #[account(mut)]
-    pub serum_market: UncheckedAccount<'info>,
-    /// CHECK
-    #[account(mut)]
-    pub serum_bids: UncheckedAccount<'info>,
-    /// CHECK
-    #[account(mut)]
-    pub serum_asks: UncheckedAccount<'info>,
-    /// CHECK
-    #[account(mut)]
-    pub serum_event_queue: UncheckedAccount<'info>,
-    /// CHECK
-    #[account(mut)]
-    pub serum_coin_vault: UncheckedAccount<'info>,
-    /// CHECK
-    #[account(mut)]
-    pub serum_pc_vault: UncheckedAccount<'info>,
-    /// CHECK
-    pub serum_vault_signer: UncheckedAccount<'info>,
+#[event]
+pub struct AllowlistUpdatedEvent {
+    pub pool: Pubkey,
+    pub wallet: Pubkey,
+    /// false when the wallet was removed
+    pub allowed: bool,
+    pub timestamp: i64,
+}
 
-    // ===== TOKEN ACCOUNTS =====
-    #[account(
-        init_if_needed,
-        payer = executor,
-        token::mint = wsol_mint,
-        token::authority = pool,
-        seeds = [b"pool_wsol", pool.key().as_ref()],
-        bump
-    )]
-    pub pool_wsol_account: Account<'info, TokenAccount>,
+#[event]
+pub struct DonationMatchedEvent {
+    pub pool: Pubkey,
+    pub donor: Pubkey,
+    pub matched_lamports: u64,
+    pub matched_shares: u64,
+    /// Budget left in the MatchingVault
+    pub remaining_lamports: u64,
+    pub timestamp: i64,
+}
 
-    #[account(
-        init_if_needed,
-        payer = executor,
-        token::mint = ctop_mint,
-        token::authority = pool,
-        seeds = [b"pool_ctop", pool.key().as_ref()],
-        bump
-    )]
-    pub pool_ctop_account: Account<'info, TokenAccount>,
+#[event]
+pub struct MatchingCampaignCreatedEvent {
+    pub pool: Pubkey,
+    pub sponsor: Pubkey,
+    pub match_bps: u16,
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub timestamp: i64,
+}
 
-    #[account(
-        init_if_needed,
-        payer = executor,
-        associated_token::mint = ctop_mint,
-        associated_token::authority = incinerator
-    )]
-    pub incinerator_ctop_account: Account<'info, TokenAccount>,
+#[event]
+pub struct MatchingCampaignFundedEvent {
+    pub pool: Pubkey,
+    pub sponsor: Pubkey,
+    pub lamports: u64,
+    pub available_lamports: u64,
+    pub timestamp: i64,
+}
 
-    #[account(address = WSOL_MINT)]
-    pub wsol_mint: Account<'info, Mint>,
+#[event]
+pub struct MatchingCampaignClosedEvent {
+    pub pool: Pubkey,
+    pub sponsor: Pubkey,
+    pub matched_lamports: u64,
+    pub refunded_lamports: u64,
+    pub timestamp: i64,
+}
 
-    #[account(address = pool.burn_token_mint)]
-    pub ctop_mint: Account<'info, Mint>,
+#[event]
+pub struct ReferralRewardEvent {
+    pub pool: Pubkey,
+    pub referrer: Pubkey,
+    pub donor: Pubkey,
+    /// Shares minted to the referred donor
+    pub referred_shares: u64,
+    pub bonus_shares: u64,
+    /// Pool-wide bonus minted so far (counts against referral_cap_shares)
+    pub total_referral_shares: u64,
+    pub timestamp: i64,
+}
 
-    /// CHECK
-    #[account(address = INCINERATOR)]
-    pub incinerator: UncheckedAccount<'info>,
+/// Attribution attached to a `donate_sol`; pairs with the DonationEvent in the same transaction.
+#[event]
+pub struct DonationMemoEvent {
+    pub pool: Pubkey,
+    pub donor: Pubkey,
+    pub lamports_in: u64,
+    pub shares_minted: u64,
+    /// Empty when the donor gave no tag
+    pub campaign_tag: String,
+    pub memo: String,
+    pub timestamp: i64,
+}
 
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub rent: Sysvar<'info, Rent>,
+#[event]
+pub struct SharesTokenizedEvent {
+    pub pool: Pubkey,
+    pub wallet: Pubkey,
+    pub shares: u64,
+    pub tokenized_shares: u64,
+    pub timestamp: i64,
+}
 
-    #[account(mut)]
-    pub executor: Signer<'info>,
+#[event]
+pub struct ShareTokensRedeemedEvent {
+    pub pool: Pubkey,
+    pub wallet: Pubkey,
+    pub shares: u64,
+    pub tokenized_shares: u64,
+    pub timestamp: i64,
 }
 
-#[derive(Accounts)]
-pub struct AbortProposal<'info> {
-    #[account(
-        mut,
-        seeds = [b"pool", pool.token_mint.as_ref()],
-        bump
-    )]
-    pub pool: Account<'info, Pool>,
+#[event]
+pub struct SplDonationEvent {
+    pub pool: Pubkey,
+    pub donor: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub received: u64,
+    pub lamports_value: u64,
+    pub shares_minted: u64,
+    pub timestamp: i64,
+}
 
-    #[account(mut, has_one = pool)]
-    pub proposal: Account<'info, Proposal>,
+#[event]
+pub struct SplPositionRedeemedEvent {
+    pub pool: Pubkey,
+    pub wallet: Pubkey,
+    pub mint: Pubkey,
+    pub shares_burned: u64,
+    pub amount: u64,
+    pub timestamp: i64,
+}
 
-    #[account(
-        mut,
-        seeds = [b"donor", pool.key().as_ref(), aborter_wallet.key().as_ref()],
-        bump
-    )]
-    pub aborter: Account<'info, Donor>,
+#[event]
+pub struct SurplusSweptEvent {
+    pub pool: Pubkey,
+    pub lamports_swept: u64,
+    pub pool_tokens_received: u64,
+    pub timestamp: i64,
+}
 
-    #[account(mut)]
-    pub aborter_wallet: Signer<'info>,
+/// `migrate_share_scale` multiplied the pool's total and/or one donor's shares (1 = unchanged).
+#[event]
+pub struct ShareScaleMigratedEvent {
+    pub pool: Pubkey,
+    pub wallet: Pubkey,
+    pub pool_factor: u64,
+    pub donor_factor: u64,
+    pub timestamp: i64,
+}
 
-    #[account(
-        mut,
-        seeds = [b"donor", pool.key().as_ref(), proposal.proposer_wallet.as_ref()],
-        bump
-    )]
-    pub proposer_donor: Account<'info, Donor>,
+#[event]
+pub struct AccountMigratedEvent {
+    pub account: Pubkey,
+    pub kind: MigratableAccount,
+    pub from_version: u8,
+    pub to_version: u8,
+    pub timestamp: i64,
+}
 
-    pub system_program: Program<'info, System>,
+#[event]
+pub struct RoundingDustFoldedEvent {
+    pub pool: Pubkey,
+    pub pool_tokens_folded: u64,
+    pub timestamp: i64,
 }
 
-// ===== Recovery accounts =====
+#[event]
+pub struct DustConsolidatedEvent {
+    pub pool: Pubkey,
+    pub donor: Pubkey,
+    pub shares_burned: u64,
+    pub timestamp: i64,
+}
 
-#[derive(Accounts)]
-pub struct RecoverFundsCreate<'info> {
-    #[account(
-        mut,
-        seeds = [b"pool", pool.token_mint.as_ref()],
-        bump
-    )]
-    pub pool: Account<'info, Pool>,
+#[event]
+pub struct RageQuitEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub donor: Pubkey,
+    pub lamports_out: u64,
+    pub shares_burned: u64,
+    pub unlocked_pool_tokens: u64,
+    pub timestamp: i64,
+}
 
-    #[account(
-        seeds = [b"donor", pool.key().as_ref(), requester.key().as_ref()],
-        bump
-    )]
-    pub donor: Account<'info, Donor>,
+#[event]
+pub struct WithdrawalEvent {
+    pub pool: Pubkey,
+    pub donor: Pubkey,
+    pub lamports_out: u64,
+    pub pool_tokens_burned: u64,
+    pub shares_burned: u64,
+    /// Paid later by `claim_deactivated_stake` instead of now (stake pool reserve was dry)
+    pub via_stake: bool,
+    pub total_shares: u64,
+    pub timestamp: i64,
+}
 
-    #[account(
-        init,
-        payer = requester,
-        space = 8 + RecoveryProposal::SIZE,
-        seeds = [b"recovery", pool.key().as_ref(), &pool.recovery_count.to_le_bytes()],
-        bump
-    )]
-    pub recovery: Account<'info, RecoveryProposal>,
+#[event]
+pub struct ProposalCreatedEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub proposer: Pubkey,
+    pub requested_lamports: u64,
+    pub denomination: RequestDenomination,
+    pub locked_pool_tokens: u64,
+    pub quorum_bps: u16,
+    pub destination_kind: DestinationKind,
+    pub timestamp: i64,
+}
 
-    #[account(mut)]
-    pub requester: Signer<'info>,
+#[event]
+pub struct WeightRootPostedEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub root: [u8; 32],
+    pub total_weight: u64,
+    pub snapshot_slot: u64,
+    pub timestamp: i64,
+}
 
-    #[account(mut, constraint = pool_token_account.owner == pool.key())]
-    pub pool_token_account: Account<'info, TokenAccount>,
+#[event]
+pub struct VoteCastEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub choice: VoteChoice,
+    pub weight: u64,
+    pub timestamp: i64,
+}
 
-    #[account(mut)]
-    pub destination_token_account: Account<'info, TokenAccount>,
+/// A late ballot flipped the outcome and pushed the deadline back (anti-sniping).
+#[event]
+pub struct VotingExtendedEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub new_deadline_ts: i64,
+    pub extensions_left: u8,
+    pub timestamp: i64,
+}
 
-    /// CHECK: instruction sysvar
-    pub instructions: UncheckedAccount<'info>,
+#[event]
+pub struct VoteCommittedEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub timestamp: i64,
+}
 
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
+#[event]
+pub struct ProposalExecutedEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub kind: ProposalKind,
+    pub destination: Pubkey,
+    pub yes_weight: u64,
+    pub no_weight: u64,
+    pub pool_tokens_burned: u64,
+    pub lamports_paid: u64,
+    pub protocol_fee: u64,
+    /// Buffer released back to free liquidity instead of being burned
+    pub unlocked_pool_tokens: u64,
+    pub timestamp: i64,
 }
 
-#[derive(Accounts)]
-pub struct RecoverFundsVote<'info> {
-    #[account(
-        seeds = [b"pool", pool.token_mint.as_ref()],
-        bump
-    )]
-    pub pool: Account<'info, Pool>,
+#[event]
+pub struct RecoveryCreatedEvent {
+    pub pool: Pubkey,
+    pub recovery: Pubkey,
+    pub requester: Pubkey,
+    pub token_mint: Pubkey,
+    pub amount: u64,
+    pub destination_wallet: Pubkey,
+    pub total_snapshot_shares: u64,
+    pub deadline_ts: i64,
+    pub timestamp: i64,
+}
 
-    #[account(mut, has_one = pool)]
-    pub recovery: Account<'info, RecoveryProposal>,
+#[event]
+pub struct RecoveryVoteCastEvent {
+    pub pool: Pubkey,
+    pub recovery: Pubkey,
+    pub voter: Pubkey,
+    pub choice: VoteChoice,
+    pub weight: u64,
+    pub timestamp: i64,
+}
 
-    #[account(
-        seeds = [b"donor", pool.key().as_ref(), voter_wallet.key().as_ref()],
-        bump
-    )]
-    pub donor: Account<'info, Donor>,
+/// Also emitted (Executed) when `recover_funds_create` pays out an inline-verified sender.
+#[event]
+pub struct RecoveryExecutedEvent {
+    pub pool: Pubkey,
+    pub recovery: Pubkey,
+    pub token_mint: Pubkey,
+    /// Tokens sent; 0 when the recovery failed
+    pub amount: u64,
+    pub destination: Pubkey,
+    pub status: ProposalStatus,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DestinationClassifiedEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub destination: Pubkey,
+    pub kind: DestinationKind,
+    pub timestamp: i64,
+}
 
-    #[account(
-        init_if_needed,
-        payer = voter_wallet,
-        space = 8 + VoteRecord::SIZE,
-        seeds = [b"vote", recovery.key().as_ref(), voter_wallet.key().as_ref()],
-        bump
-    )]
-    pub vote_record: Account<'info, VoteRecord>,
+#[event]
+pub struct CapExcessExcludedEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub wallet: Pubkey,
+    pub excess: u64,
+    /// Quorum denominator after this exclusion
+    pub quorum_supply: u64,
+    pub timestamp: i64,
+}
 
-    #[account(mut)]
-    pub voter_wallet: Signer<'info>,
-    pub system_program: Program<'info, System>,
+#[event]
+pub struct ProposalSponsoredEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub sponsor: Pubkey,
+    pub sponsor_count: u8,
+    /// True when this signature completed sponsorship and opened voting
+    pub voting_open: bool,
+    pub timestamp: i64,
 }
 
-#[derive(Accounts)]
-pub struct RecoverFundsExecute<'info> {
-    #[account(
-        mut,
-        seeds = [b"pool", pool.token_mint.as_ref()],
-        bump
-    )]
-    pub pool: Account<'info, Pool>,
+#[event]
+pub struct TemplateProposedEvent {
+    pub pool: Pubkey,
+    pub template: Pubkey,
+    pub template_id: u64,
+    pub proposal: Pubkey,
+    pub proposer: Pubkey,
+    pub timestamp: i64,
+}
 
-    #[account(mut, has_one = pool)]
-    pub recovery: Account<'info, RecoveryProposal>,
+#[event]
+pub struct TemplateResolvedEvent {
+    pub pool: Pubkey,
+    pub template: Pubkey,
+    pub status: TemplateStatus,
+    pub timestamp: i64,
+}
 
-    #[account(mut, constraint = pool_token_account.owner == pool.key())]
-    pub pool_token_account: Account<'info, TokenAccount>,
+#[event]
+pub struct ConfigChangeProposedEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub proposer: Pubkey,
+    pub params: ConfigChangeParams,
+    pub quorum_bps: u16,
+    pub deadline_ts: i64,
+    pub timestamp: i64,
+}
 
-    #[account(mut)]
-    pub destination_token_account: Account<'info, TokenAccount>,
+#[event]
+pub struct ConfigChangeExecutedEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub quorum_bps: u16,
+    pub protocol_fee_bps: u16,
+    pub min_proposer_deposit_lamports: u64,
+    pub proposal_delay_slots: u64,
+    pub fee_sink: FeeSink,
+    pub fee_sink_destination: Pubkey,
+    pub timestamp: i64,
+}
 
-    pub token_program: Program<'info, Token>,
-    #[account(mut)]
-    pub executor: Signer<'info>,
+#[event]
+pub struct MilestonesConfiguredEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub tranches: u8,
+    pub interval_seconds: i64,
+    pub timestamp: i64,
 }
 
-// ============= State =============
+#[event]
+pub struct MilestoneEscrowedEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub escrowed_pool_tokens: u64,
+    pub unlocked_pool_tokens: u64,
+    pub tranches: u8,
+    pub interval_seconds: i64,
+    pub timestamp: i64,
+}
 
-#[account]
-pub struct Pool {
-    pub token_mint: Pubkey,
-    pub authority: Pubkey,
-    pub creator: Pubkey,
+#[event]
+pub struct MilestoneConfirmedEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub tranche: u8,
+    pub weight: u64,
+    pub confirm_weight: u64,
+    pub timestamp: i64,
+}
 
-    // share accounting
-    pub total_shares: u64,
+#[event]
+pub struct MilestoneReleasedEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    // 1-based index of the tranche just paid
+    pub tranche: u8,
+    pub lamports_paid: u64,
+    pub protocol_fee: u64,
+    pub remaining_pool_tokens: u64,
+    pub timestamp: i64,
+}
 
-    // LST tokens held by the pool PDA (e.g. jitoSOL)
-    pub total_pool_tokens: u64,
-    pub reserved_pool_tokens: u64,
+#[event]
+pub struct MilestoneCancelledEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub tranches_released: u8,
+    pub unlocked_pool_tokens: u64,
+    pub timestamp: i64,
+}
 
-    // spend accounting
-    pub total_spent_lamports: u64,
+#[event]
+pub struct ProposalQueuedEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub yes_weight: u64,
+    pub no_weight: u64,
+    pub queued_until_ts: i64,
+    pub timestamp: i64,
+}
 
-    // governance/config
-    pub protocol_fee_bps: u16,
+#[event]
+pub struct StreamConfiguredEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokenPayoutConfiguredEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
     pub quorum_bps: u16,
-    pub min_proposer_deposit_lamports: u64,
+    pub timestamp: i64,
+}
 
-    // fee outputs
-    pub dev_fee_wallet: Pubkey,
-    pub burn_token_mint: Pubkey,
+#[event]
+pub struct BuybackConfiguredEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub token_mint: Pubkey,
+    pub venue: BurnVenue,
+    pub mode: BuybackMode,
+    pub timestamp: i64,
+}
 
-    // proposal tracking
-    pub active_proposal: Option<Pubkey>,
-    pub proposal_count: u64,
+#[event]
+pub struct LiquidityProvisionConfiguredEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub raydium_pool: Pubkey,
+    pub token_amount: u64,
+    pub timestamp: i64,
+}
 
-    // Abort governance cooldown + penalty configuration
-    pub proposal_cooldown_until_ts: i64,
-    pub base_penalty_lamports: u64,
+#[event]
+pub struct LiquidityQueuedEvent {
+    pub pool: Pubkey,
+    pub lamports: u64,
+    pub tokens: u64,
+    pub pending_liquidity_lamports: u64,
+    pub pending_liquidity_tokens: u64,
+    pub timestamp: i64,
+}
 
-    // recovery tracking
-    pub active_recovery: Option<Pubkey>,
-    pub recovery_count: u64,
+#[event]
+pub struct LiquidityProvidedEvent {
+    pub pool: Pubkey,
+    pub lamports_deposited: u64,
+    pub tokens_deposited: u64,
+    pub lp_received: u64,
+    pub lp_token_amount: u64,
+    pub timestamp: i64,
+}
 
-    // LST backend config
-    pub stake_pool_program: Pubkey,
-    pub stake_pool: Pubkey,
-    pub lst_mint: Pubkey,
+#[event]
+pub struct LiquidityRemovalProposedEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub proposer: Pubkey,
+    pub lp_amount: u64,
+    pub quorum_bps: u16,
+    pub deadline_ts: i64,
+    pub timestamp: i64,
+}
 
-    // PumpSwap buy&burn (post Pump.fun graduation)
-    pub pumpswap_enabled: bool,
-    pub pumpswap_pool_id: Pubkey,
-    pub pumpswap_base_vault: Pubkey,
-    pub pumpswap_quote_vault: Pubkey,
-    pub pumpswap_global_config: Pubkey,
-    pub pumpswap_fee_recipient: Pubkey,
-    pub pumpswap_version: u8, // H-05 FIX: Track expected PumpSwap version
+#[event]
+pub struct LiquidityRemovalApprovedEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub lp_amount: u64,
+    pub pending_lp_withdrawal: u64,
+    pub timestamp: i64,
+}
 
-    // Legacy raydium buy&burn (optional)
-    pub raydium_pool_id: Pubkey,
-    pub raydium_enabled: bool,
-    
-    // H-04 FIX: Reentrancy guard
-    pub locked: bool,
+#[event]
+pub struct LiquidityWithdrawnEvent {
+    pub pool: Pubkey,
+    pub lp_burned: u64,
+    pub lamports_received: u64,
+    pub tokens_received: u64,
+    pub lp_token_amount: u64,
+    pub timestamp: i64,
 }
 
-impl Pool {
-    pub const SIZE: usize =
-        32 + 32 + 32 + // token_mint, authority, creator
-        8 +            // total_shares
-        8 + 8 +        // total_pool_tokens, reserved_pool_tokens
-        8 +            // total_spent_lamports
-        2 + 2 + 8 +    // protocol_fee_bps, quorum_bps, min_proposer
-        32 + 32 +      // dev_fee_wallet, burn_token_mint
-        1 + 32 +       // active_proposal
-        8 +            // proposal_count
-        8 + 8 +        // proposal_cooldown_until_ts, base_penalty_lamports
-        1 + 32 +       // active_recovery
-        8 +            // recovery_count
-        32 + 32 + 32 + // stake_pool_program, stake_pool, lst_mint
-        1 +            // pumpswap_enabled
-        32 + 32 + 32 + 32 + 32 + // pumpswap_pool_id, base_vault, quote_vault, global_config, fee_recipient
-        1 +            // pumpswap_version (H-05 FIX)
-        32 + 1 +       // raydium_pool_id, raydium_enabled
-        1;             // locked (H-04 FIX: reentrancy guard)
+#[event]
+pub struct TokenPayoutExecutedEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub mint: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    /// pool's balance of the mint after the payout
+    pub remaining: u64,
+    pub timestamp: i64,
 }
 
-#[account]
-pub struct Donor {
+#[event]
+pub struct StreamEscrowedEvent {
     pub pool: Pubkey,
-    pub wallet: Pubkey,
-    pub shares: u64,
-    pub total_deposited_lamports: u64,
-    pub last_shares_change_slot: u64,
+    pub proposal: Pubkey,
+    pub escrowed_pool_tokens: u64,
+    pub unlocked_pool_tokens: u64,
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub timestamp: i64,
+}
 
-    // Abort/proposer penalty tracking (uncapped exponential fees)
-    pub abort_strike_count: u16,
-    pub propose_strike_count: u16,
-    pub non_abort_participation_count: u16,
-    pub non_propose_participation_count: u16,
+#[event]
+pub struct StreamClaimedEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub pool_tokens: u64,
+    pub lamports_paid: u64,
+    pub protocol_fee: u64,
+    pub claimed_pool_tokens: u64,
+    pub timestamp: i64,
 }
 
-impl Donor {
-    pub const SIZE: usize = 32 + 32 + 8 + 8 + 8 + 2 + 2 + 2 + 2;
+#[event]
+pub struct RecurringBudgetConfiguredEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub lamports_per_period: u64,
+    pub period_seconds: i64,
+    pub timestamp: i64,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
-pub enum ProposalStatus {
-    Active,
-    Failed,
-    Executed,
-    Aborted,
+#[event]
+pub struct BudgetDisbursedEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub period: u32,
+    pub pool_tokens: u64,
+    pub lamports_paid: u64,
+    pub protocol_fee: u64,
+    pub next_due_ts: i64,
+    pub timestamp: i64,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq)]
-pub enum ProposalKind {
-    Payout,
+#[event]
+pub struct RecurringBudgetCancelledEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub cancel_proposal: Pubkey,
+    pub periods_paid: u32,
+    pub timestamp: i64,
 }
 
-#[account]
-pub struct Proposal {
+#[event]
+pub struct PollCreatedEvent {
     pub pool: Pubkey,
-    pub kind: ProposalKind,
-
-    pub proposer_wallet: Pubkey,
-
-    pub requested_lamports: u64,
-    pub destination_wallet: Pubkey,
-
-    pub title: String,
-    pub description: String,
-
-    pub created_at_ts: i64,
+    pub proposal: Pubkey,
+    pub proposer: Pubkey,
     pub deadline_ts: i64,
+    pub quorum_bps: u16,
+    pub timestamp: i64,
+}
 
-    pub snapshot_slot: u64,
-    pub total_snapshot_shares: u64,
-
-    // pool tokens locked for this proposal
-    pub locked_pool_tokens: u64,
-
+#[event]
+pub struct PollFinalizedEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
     pub yes_weight: u64,
     pub no_weight: u64,
     pub abstain_weight: u64,
-    pub participation_weight: u64,
-
-    // Abort voting (weak emergency brake)
-    pub abort_voter_1: Pubkey,
-    pub abort_voter_2: Pubkey,
-    pub abort_count: u8,
-
-    pub status: ProposalStatus,
+    pub quorum_met: bool,
+    pub passed: bool,
+    pub timestamp: i64,
 }
 
-impl Proposal {
-    pub const TITLE_MAX: usize = 64;
-    pub const DESC_MAX: usize = 256;
-
-    pub const SIZE: usize =
-        32 + 1 +              // pool, kind
-        32 +                 // proposer_wallet
-        8 + 32 +              // requested, destination
-        4 + Self::TITLE_MAX + // title
-        4 + Self::DESC_MAX +  // description
-        8 + 8 +               // created_at, deadline
-        8 + 8 +               // snapshot_slot, total_snapshot_shares
-        8 +                   // locked_pool_tokens
-        8 + 8 + 8 + 8 +       // yes/no/abstain/participation
-        32 + 32 + 1 +         // abort_voter_1, abort_voter_2, abort_count
-        1;                    // status
+#[event]
+pub struct ProposalAbortedEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub abort_voters: [Pubkey; 2],
+    pub timestamp: i64,
 }
 
-#[account]
-pub struct RecoveryProposal {
+#[event]
+pub struct TokenBurnEvent {
     pub pool: Pubkey,
-    pub token_mint: Pubkey,
-    pub requested_amount: u64,
-    pub destination_wallet: Pubkey,
-
-    pub title: String,
-    pub description: String,
-
-    pub created_at_ts: i64,
-    pub deadline_ts: i64,
+    pub amount_sol: u64,
+    pub amount_ctop: u64,
+    pub timestamp: i64,
+}
 
-    pub snapshot_slot: u64,
-    pub total_snapshot_shares: u64,
+#[event]
+pub struct BuybackHeldEvent {
+    pub pool: Pubkey,
+    pub amount_sol: u64,
+    pub amount_ctop: u64,
+    pub held_ctop_total: u64,
+    pub timestamp: i64,
+}
 
-    pub yes_weight: u64,
-    pub no_weight: u64,
-    pub abstain_weight: u64,
-    pub participation_weight: u64,
+#[event]
+pub struct BurnAuctionFundedEvent {
+    pub pool: Pubkey,
+    pub amount_sol: u64,
+    pub lot_lamports: u64,
+    pub timestamp: i64,
+}
 
-    // Abort voting (weak emergency brake)
-    pub abort_voter_1: Pubkey,
-    pub abort_voter_2: Pubkey,
-    pub abort_count: u8,
+#[event]
+pub struct BurnAuctionFilledEvent {
+    pub pool: Pubkey,
+    pub filler: Pubkey,
+    pub amount_sol: u64,
+    pub amount_ctop: u64,
+    pub price_ctop_per_sol: u64,
+    pub remaining_lamports: u64,
+    pub timestamp: i64,
+}
 
-    pub status: ProposalStatus,
+#[event]
+pub struct BurnTargetChangedEvent {
+    pub pool: Pubkey,
+    pub previous_mint: Pubkey,
+    pub new_mint: Pubkey,
+    pub timestamp: i64,
 }
 
-impl RecoveryProposal {
-    pub const TITLE_MAX: usize = 64;
-    pub const DESC_MAX: usize = 256;
+#[event]
+pub struct FeeSinkConfiguredEvent {
+    pub pool: Pubkey,
+    pub sink: FeeSink,
+    pub destination: Pubkey,
+    pub timestamp: i64,
+}
 
-    pub const SIZE: usize =
-        32 + 32 + 8 + 32 +
-        4 + Self::TITLE_MAX +
-        4 + Self::DESC_MAX +
-        8 + 8 +
-        8 + 8 +
-        8 + 8 + 8 + 8 +
-        1;
+#[event]
+pub struct FeeSinkPaidEvent {
+    pub pool: Pubkey,
+    pub sink: FeeSink,
+    pub destination: Pubkey,
+    pub amount_sol: u64,
+    pub timestamp: i64,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
-pub enum VoteChoice {
-    Yes,
-    No,
-    Abstain,
-    Abort,
+#[event]
+pub struct DelegationChangedEvent {
+    pub pool: Pubkey,
+    pub delegator: Pubkey,
+    pub delegate: Pubkey,
+    pub shares: u64,
+    pub delegated: bool,
+    pub timestamp: i64,
 }
 
-#[account]
-pub struct VoteRecord {
+#[event]
+pub struct ProposalTiedEvent {
+    pub pool: Pubkey,
     pub proposal: Pubkey,
-    pub voter: Pubkey,
-    pub snapshot_weight: u64,
-    pub choice: VoteChoice,
-    pub initialized: bool,
+    pub weight: u64,
+    pub rule: TieBreakRule,
+    pub passed: bool,
+    pub extended: bool,
+    pub timestamp: i64,
 }
 
-impl VoteRecord {
-    pub const SIZE: usize = 32 + 32 + 8 + 1 + 1;
+#[event]
+pub struct QuorumGraceExtendedEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub participation_weight: u64,
+    pub total_snapshot_shares: u64,
+    pub new_deadline_ts: i64,
+    pub timestamp: i64,
 }
 
-// ============= Events =============
+#[event]
+pub struct ExecutorRentReimbursedEvent {
+    pub pool: Pubkey,
+    pub executor: Pubkey,
+    pub lamports: u64,
+    pub timestamp: i64,
+}
 
-// L-05 FIX: Comprehensive event emissions for state changes
+#[event]
+pub struct PoolSunsetStartedEvent {
+    pub pool: Pubkey,
+    pub last_engagement_ts: i64,
+    pub total_shares: u64,
+    pub timestamp: i64,
+}
 
 #[event]
-pub struct PoolCreatedEvent {
+pub struct SunsetProposedEvent {
     pub pool: Pubkey,
-    pub token_mint: Pubkey,
-    pub authority: Pubkey,
+    pub proposal: Pubkey,
+    pub proposer: Pubkey,
+    pub quorum_bps: u16,
+    pub deadline_ts: i64,
     pub timestamp: i64,
 }
 
 #[event]
-pub struct DonationEvent {
+pub struct SunsetDistributionEvent {
     pub pool: Pubkey,
     pub donor: Pubkey,
-    pub lamports_in: u64,
-    pub shares_minted: u64,
+    pub shares_burned: u64,
+    pub pool_tokens_out: u64,
+    pub custody: PoolCustody,
     pub timestamp: i64,
 }
 
 #[event]
-pub struct WithdrawalEvent {
+pub struct SunsetPoolUnstakedEvent {
     pub pool: Pubkey,
-    pub donor: Pubkey,
+    pub pool_tokens_in: u64,
     pub lamports_out: u64,
-    pub shares_burned: u64,
     pub timestamp: i64,
 }
 
 #[event]
-pub struct ProposalCreatedEvent {
+pub struct PoolClosedEvent {
     pub pool: Pubkey,
-    pub proposal: Pubkey,
-    pub proposer: Pubkey,
-    pub requested_lamports: u64,
+    pub burned_pool_tokens: u64,
     pub timestamp: i64,
 }
 
 #[event]
-pub struct VoteCastEvent {
+pub struct AuthorityHeartbeatEvent {
     pub pool: Pubkey,
-    pub proposal: Pubkey,
-    pub voter: Pubkey,
-    pub choice: VoteChoice,
-    pub weight: u64,
+    pub authority: Pubkey,
+    pub epoch: u64,
     pub timestamp: i64,
 }
 
 #[event]
-pub struct ProposalExecutedEvent {
+pub struct PoolMetadataUpdatedEvent {
     pub pool: Pubkey,
-    pub proposal: Pubkey,
-    pub lamports_paid: u64,
-    pub protocol_fee: u64,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    /// ConfigChange that approved the update; default for `create_pool_metadata`
+    pub change_proposal: Pubkey,
     pub timestamp: i64,
 }
 
 #[event]
-pub struct ProposalAbortedEvent {
+pub struct PoolPausedEvent {
     pub pool: Pubkey,
-    pub proposal: Pubkey,
-    pub abort_voters: [Pubkey; 2],
+    pub paused: bool,
     pub timestamp: i64,
 }
 
 #[event]
-pub struct TokenBurnEvent {
+pub struct AuthorityTransferProposedEvent {
     pub pool: Pubkey,
-    pub amount_sol: u64,
-    pub amount_ctop: u64,
+    pub authority: Pubkey,
+    /// Default pubkey when a pending transfer was cancelled
+    pub pending_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuthorityTransferredEvent {
+    pub pool: Pubkey,
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
     pub timestamp: i64,
 }
 
 #[event]
-pub struct SwapFailureEvent {
+pub struct BurnQueuedEvent {
     pub pool: Pubkey,
     pub amount_sol: u64,
-    pub error_code: u32,
+    pub pending_burn_lamports: u64,
     pub timestamp: i64,
 }
 
@@ -2611,6 +17163,90 @@ pub struct ProposalFailedEvent {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct ProposalArchivedEvent {
+    pub pool: Pubkey,
+    pub proposal: Pubkey,
+    pub archive: Pubkey,
+    pub page_index: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WithdrawalQueuedEvent {
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub index: u64,
+    pub shares: u64,
+    pub queued_shares: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WithdrawalRequestCancelledEvent {
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub index: u64,
+    pub shares: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WithdrawalQueueProcessedEvent {
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub index: u64,
+    pub shares: u64,
+    pub lamports_out: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StakeWithdrawalOpenedEvent {
+    pub pool: Pubkey,
+    pub stake_withdrawal: Pubkey,
+    pub stake_account: Pubkey,
+    pub recipient: Pubkey,
+    pub lamports: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PayoutCreditedEvent {
+    pub pool: Pubkey,
+    pub destination: Pubkey,
+    pub lamports: u64,
+    pub claimable_lamports: u64,
+    pub expires_ts: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PayoutClaimedEvent {
+    pub pool: Pubkey,
+    pub destination: Pubkey,
+    pub lamports: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PayoutClaimExpiredEvent {
+    pub pool: Pubkey,
+    pub destination: Pubkey,
+    pub lamports: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StakeWithdrawalClaimedEvent {
+    pub pool: Pubkey,
+    pub stake_withdrawal: Pubkey,
+    pub recipient: Pubkey,
+    pub lamports_out: u64,
+    pub protocol_fee: u64,
+    pub timestamp: i64,
+}
+
 // ============= Errors =============
 
 #[error_code]
@@ -2703,4 +17339,348 @@ pub enum CtoError {
     AbortVoterSlotsFull,
     #[msg("Abort not allowed on recovery proposals")]
     AbortNotAllowedOnRecovery,
+
+    // Proposal sizing
+    #[msg("Requested bps must be between 1 and 10000")]
+    InvalidRequestBps,
+    #[msg("Proposal exceeds the pool's maximum proposal size")]
+    ProposalTooLarge,
+    #[msg("Proposal is below the pool's minimum proposal amount")]
+    ProposalTooSmall,
+    #[msg("Invalid proposal limits")]
+    InvalidProposalLimits,
+    #[msg("Destination is cooling down after a failed proposal")]
+    DestinationCooldownActive,
+    #[msg("Proposal is still active")]
+    ProposalStillActive,
+    #[msg("Donor still has shares")]
+    DonorHasShares,
+    #[msg("Donor has outstanding penalty strikes")]
+    DonorHasStrikes,
+    #[msg("No surplus lamports to sweep")]
+    NoSurplus,
+    #[msg("Donation mints fewer than the minimum shares")]
+    DonationTooSmall,
+    #[msg("Donor balance is not dust")]
+    NotDust,
+    #[msg("Share balances must be migrated to the current scale")]
+    ShareScaleMigrationRequired,
+    #[msg("Full exit is unavailable while liquidity is reserved")]
+    FullExitBlockedByReservation,
+    #[msg("Rounding dust was folded too recently")]
+    DustFoldTooEarly,
+    #[msg("Invalid burn auction configuration")]
+    InvalidBurnAuctionConfig,
+    #[msg("Invalid burn schedule")]
+    InvalidBurnSchedule,
+    #[msg("Invalid burn target mint")]
+    InvalidBurnTarget,
+    #[msg("Burn target has held tokens or an open auction lot")]
+    BurnTargetInUse,
+    #[msg("Invalid fee sink configuration")]
+    InvalidFeeSink,
+    #[msg("Invalid delegate")]
+    InvalidDelegate,
+    #[msg("Undelegation already requested")]
+    UndelegationPending,
+    #[msg("Undelegation timelock has not passed")]
+    UndelegationLocked,
+    #[msg("Delegated weight cannot cast Abort votes")]
+    DelegatedAbortNotAllowed,
+    #[msg("Shares are delegated; undelegate first")]
+    SharesDelegated,
+    #[msg("Not enough undelegated shares")]
+    InsufficientShares,
+    #[msg("Tie approval is not enabled for this pool")]
+    TieApprovalNotEnabled,
+    #[msg("Voting period is outside the pool's bounds")]
+    InvalidVotingPeriod,
+    #[msg("Pool is sunsetting; withdrawals only")]
+    PoolSunsetting,
+    #[msg("Pool has not been inactive long enough to sunset")]
+    PoolNotAbandoned,
+    #[msg("Pool is not sunsetting")]
+    PoolNotSunsetting,
+    #[msg("Sunset grace period has not passed")]
+    SunsetGraceActive,
+    #[msg("Invalid dead-man switch window")]
+    InvalidDeadManSwitch,
+    #[msg("Pool authority has lapsed; use governance")]
+    AuthorityLapsed,
+    #[msg("Fill exceeds the burn auction lot")]
+    BurnAuctionLotExceeded,
+    #[msg("Title contains control characters")]
+    InvalidTitleCharacters,
+    #[msg("Description contains control characters other than newline or tab")]
+    InvalidDescriptionCharacters,
+    #[msg("Donor slippage tolerance exceeds the maximum")]
+    InvalidSlippageTolerance,
+    #[msg("Already voted on this proposal with vote_compact")]
+    AlreadyVotedCompact,
+    #[msg("Already voted on this proposal with a VoteRecord")]
+    AlreadyVotedWithRecord,
+    #[msg("Abort votes must use vote")]
+    CompactAbortNotAllowed,
+    #[msg("Account version is unknown or newer than this program")]
+    UnknownAccountVersion,
+    #[msg("Not enough compute budget left for the swap")]
+    InsufficientComputeForSwap,
+    #[msg("A poll is already open")]
+    ActivePollExists,
+    #[msg("Instruction does not apply to this proposal kind")]
+    WrongProposalKind,
+    #[msg("Polls cannot be aborted")]
+    PollAbortNotAllowed,
+    #[msg("Destination is not the vault of the given Squads multisig")]
+    InvalidSquadsVault,
+    #[msg("This pool does not accept donations of its token")]
+    TokenDonationsDisabled,
+    #[msg("Proposal uses Merkle weights; vote with vote_merkle")]
+    MerkleProofRequired,
+    #[msg("Proposal does not use Merkle weights")]
+    NotMerkleProposal,
+    #[msg("Weight root already posted")]
+    WeightRootAlreadyPosted,
+    #[msg("Weight root has not been posted yet")]
+    WeightRootNotPosted,
+    #[msg("Weight root must be non-zero")]
+    InvalidWeightRoot,
+    #[msg("Merkle proof does not match the weight root")]
+    InvalidMerkleProof,
+    #[msg("Abort votes must use vote")]
+    MerkleAbortNotAllowed,
+    #[msg("Template bounds are invalid")]
+    InvalidTemplateBounds,
+    #[msg("Template has not been adopted")]
+    TemplateNotActive,
+    #[msg("Request does not match the template")]
+    TemplateMismatch,
+    #[msg("Requested amount is outside the template bounds")]
+    OutsideTemplateBounds,
+    #[msg("Invalid co-sponsorship configuration")]
+    InvalidSponsorshipConfig,
+    #[msg("Proposal is still waiting for co-sponsors")]
+    AwaitingSponsors,
+    #[msg("Proposal already has all required sponsors")]
+    SponsorshipComplete,
+    #[msg("Sponsor window has closed")]
+    SponsorWindowClosed,
+    #[msg("Proposer cannot sponsor their own proposal")]
+    ProposerCannotSponsor,
+    #[msg("Sponsor holds too few shares")]
+    SponsorTooSmall,
+    #[msg("Cap-adjusted quorum is not enabled for this proposal")]
+    CapAdjustedQuorumDisabled,
+    #[msg("Donor's snapshot shares are not above the voter cap")]
+    NotAboveVoterCap,
+    #[msg("Invalid beneficiary")]
+    InvalidBeneficiary,
+    #[msg("Config change is empty or out of bounds")]
+    InvalidConfigChange,
+    #[msg("Proposal has not expired")]
+    ProposalNotExpired,
+    #[msg("SPL donations are disabled for this mint or pool")]
+    SplDonationsDisabled,
+    #[msg("SPL donation cap exceeded for this mint")]
+    SplDonationCapExceeded,
+    #[msg("The LST and pool token can't be accepted as SPL donations")]
+    InvalidSplDonationMint,
+    #[msg("Oracle price account invalid or too uncertain")]
+    InvalidOraclePrice,
+    #[msg("Oracle price is stale")]
+    StaleOraclePrice,
+    #[msg("Only the rent payer can close this before the grace period ends")]
+    CloseGraceActive,
+    #[msg("No pending burn is due yet")]
+    BurnNotDue,
+    #[msg("Destination wallet does not match the proposal")]
+    DestinationMismatch,
+    #[msg("Payout terms can only change before any sponsor or vote")]
+    PayoutTermsLocked,
+    #[msg("Invalid milestone schedule")]
+    InvalidMilestoneSchedule,
+    #[msg("Proposal has no escrowed payout")]
+    MilestoneNotEscrowed,
+    #[msg("Next milestone tranche is not due yet")]
+    MilestoneNotDue,
+    #[msg("Milestone confirmation window has not lapsed")]
+    MilestoneNotLapsed,
+    #[msg("Invalid stream schedule")]
+    InvalidStreamSchedule,
+    #[msg("Nothing has vested since the last claim")]
+    NothingVested,
+    #[msg("Quorum or voter cap out of bounds")]
+    InvalidGovernanceParams,
+    #[msg("Timelock out of bounds")]
+    InvalidTimelock,
+    #[msg("Pool has no execution timelock")]
+    TimelockDisabled,
+    #[msg("Proposal has not passed")]
+    ProposalNotPassed,
+    #[msg("Passed proposal must be queued before execution")]
+    ProposalMustBeQueued,
+    #[msg("Proposal timelock has not elapsed")]
+    TimelockActive,
+    #[msg("Only donors who voted No on the active proposal can rage-quit")]
+    RageQuitRequiresNoVote,
+    #[msg("Quorum tiers must ascend, stay within bounds and end with unused tiers")]
+    InvalidQuorumTiers,
+    #[msg("Fast-track quorum or voting window out of bounds")]
+    InvalidFastTrackConfig,
+    #[msg("Invalid recurring budget schedule")]
+    InvalidBudgetSchedule,
+    #[msg("Proposal is not an active recurring budget")]
+    BudgetNotRecurring,
+    #[msg("Next budget period is not due yet")]
+    BudgetPeriodNotDue,
+    #[msg("Config change does not cancel this budget")]
+    BudgetMismatch,
+    #[msg("Invalid Whirlpool config")]
+    InvalidWhirlpoolConfig,
+    #[msg("Invalid Whirlpool vault mints")]
+    InvalidWhirlpoolVaultMints,
+    #[msg("Invalid Whirlpool vault owner")]
+    InvalidWhirlpoolVaultOwner,
+    #[msg("Whirlpool math error")]
+    WhirlpoolMathError,
+    #[msg("Invalid Meteora DLMM config")]
+    InvalidMeteoraConfig,
+    #[msg("Invalid Meteora DLMM reserve mints")]
+    InvalidMeteoraReserveMints,
+    #[msg("Invalid Meteora DLMM reserve owner")]
+    InvalidMeteoraReserveOwner,
+    #[msg("Meteora DLMM math error")]
+    MeteoraMathError,
+    #[msg("Invalid Raydium CPMM config")]
+    InvalidRaydiumConfig,
+    #[msg("Invalid Raydium CPMM vault mints")]
+    InvalidRaydiumVaultMints,
+    #[msg("Invalid oracle guard configuration")]
+    InvalidOracleGuardConfig,
+    #[msg("Buy & burn executed outside the oracle price band")]
+    BurnPriceOutOfBand,
+    #[msg("LST exchange rate deviates abnormally from its last-epoch rate")]
+    LstRateDeviation,
+    #[msg("Stake pool reserve can't cover this withdrawal; supply the stake withdrawal accounts")]
+    ReserveDry,
+    #[msg("Stake pool reserve can cover this withdrawal; stake withdrawal accounts not allowed")]
+    ReserveNotDry,
+    #[msg("Stake program CPI failed")]
+    StakeProgramCpiFailed,
+    #[msg("Withdrawal stake account is still deactivating")]
+    StakeStillDeactivating,
+    #[msg("Withdrawal request already cancelled")]
+    WithdrawalRequestCancelled,
+    #[msg("Not enough free liquidity for the withdrawal queue's head request")]
+    WithdrawalQueueHeadNotFillable,
+    #[msg("Invalid campaign configuration")]
+    InvalidCampaignConfig,
+    #[msg("Campaign can only be configured before the first donation")]
+    CampaignAfterDonations,
+    #[msg("Campaign goal not reached")]
+    CampaignGoalNotReached,
+    #[msg("Campaign still running; withdrawals open once it reaches its goal or fails")]
+    CampaignInProgress,
+    #[msg("Campaign failed; donations are closed")]
+    CampaignFailed,
+    #[msg("Campaign has not failed")]
+    CampaignNotFailed,
+    #[msg("Donor LST token account required")]
+    MissingDonorLstAccount,
+    #[msg("Pool authority has expired")]
+    AuthorityExpired,
+    #[msg("Authority expiry must be in the future and no later than the current one")]
+    InvalidAuthorityExpiry,
+    #[msg("Call not approved by a passed ConfigChange")]
+    AdminCallNotApproved,
+    #[msg("Pool is paused")]
+    PoolPaused,
+    #[msg("Pool metadata too long or contains invalid characters")]
+    InvalidPoolMetadata,
+    #[msg("Pool metadata does not match an unapplied, executed ConfigChange")]
+    PoolMetadataMismatch,
+    #[msg("Donation memo or campaign tag too long or contains invalid characters")]
+    InvalidDonationMemo,
+    #[msg("Referral bps above the maximum, or a nonzero bps with no cap")]
+    InvalidReferralConfig,
+    #[msg("Referrer must be another wallet with a Donor account in this pool")]
+    InvalidReferrer,
+    #[msg("Invalid matching campaign ratio or window")]
+    InvalidMatchingCampaign,
+    #[msg("Matching campaign has ended")]
+    MatchingCampaignEnded,
+    #[msg("Matching campaign is still running")]
+    MatchingCampaignActive,
+    #[msg("Donation below the pool's minimum")]
+    DonationBelowMinimum,
+    #[msg("Donation would take the donor above the pool's maximum share")]
+    DonorShareCapExceeded,
+    #[msg("Maximum donor share must be at most 10000 bps")]
+    InvalidDonationLimits,
+    #[msg("Wallet is not on the pool's allowlist")]
+    NotAllowlisted,
+    #[msg("Proposer bond account required")]
+    MissingProposerBond,
+    #[msg("Proposer bond must be settled first")]
+    ProposerBondUnsettled,
+    #[msg("Spam threshold must be at most 10000 bps")]
+    InvalidProposerBondConfig,
+    #[msg("Donor must wait longer before creating another proposal")]
+    DonorProposalCooldownActive,
+    #[msg("Commit-reveal proposals take ballots through commit_vote and reveal_vote")]
+    CommitRevealVoting,
+    #[msg("Proposal does not use commit-reveal voting")]
+    NotCommitRevealProposal,
+    #[msg("Reveal window is not open")]
+    RevealWindowNotOpen,
+    #[msg("Revealed ballot does not match the commitment")]
+    CommitmentMismatch,
+    #[msg("Reveal window must be 0 or between 1 hour and 3 days")]
+    InvalidRevealWindow,
+    #[msg("Anti-sniping needs a 10 minute to 24 hour extension and 1 to 10 extensions, or both 0")]
+    InvalidAntiSnipingConfig,
+    #[msg("Vote weight ramp exceeds the maximum")]
+    InvalidVoteRamp,
+    #[msg("Token payout needs a non-LST mint, an amount the pool holds and a non-fast-track proposal")]
+    InvalidTokenPayout,
+    #[msg("Token payout needs the pool's and the destination's token accounts")]
+    MissingTokenPayoutAccounts,
+    #[msg("Buyback needs the pool's own token as burn target on a configured venue, and SOL from the reserve")]
+    InvalidBuyback,
+    #[msg("Liquidity provision needs a Raydium CPMM pool pairing WSOL with the pool's token, unreserved treasury tokens and SOL from the reserve")]
+    InvalidLiquidityProvision,
+    #[msg("Liquidity removal exceeds the LP tokens the pool holds")]
+    InvalidLiquidityRemoval,
+    #[msg("No liquidity queued for the AMM")]
+    NoPendingLiquidity,
+    #[msg("The pool holds or has queued liquidity in its current Raydium pool")]
+    LiquidityPositionOpen,
+    #[msg("SOL payouts need the destination's payout claim account")]
+    MissingPayoutClaim,
+    #[msg("Nothing to claim")]
+    NothingToClaim,
+    #[msg("Payout claim has not expired")]
+    PayoutClaimNotExpired,
+    #[msg("Payout claims are still outstanding")]
+    PayoutClaimsOutstanding,
+    #[msg("Upgrading a version-0 proposal needs its pool")]
+    MissingMigrationPool,
+    #[msg("The pool still holds queued, reserved or claimable assets")]
+    SunsetAssetsOutstanding,
+    #[msg("Pool is already in native custody")]
+    PoolAlreadyUnstaked,
+    #[msg("Invalid PumpSwap hop configuration")]
+    InvalidPumpSwapHopConfig,
+    #[msg("Weight root total must be non-zero and at most the snapshot total")]
+    InvalidWeightRootTotal,
+    #[msg("Proven Merkle weight exceeds the weight root's total")]
+    MerkleWeightExceedsTotal,
+    #[msg("Recovery token account does not hold the recovery's mint")]
+    RecoveryMintMismatch,
+    #[msg("Recovery not allowed for token accounts the pool tracks")]
+    RecoveryNotAllowedForTrackedAccount,
+    #[msg("Tokens of a donate_spl mint belong to its SplPosition holders")]
+    SplVaultTokensReserved,
+    #[msg("The ActivityLog must be passed exactly when upgrading a version-0 Pool")]
+    MigrationActivityLogMismatch,
 }
@@ -0,0 +1,337 @@
+//! Off-chain event replay (`client` feature).
+//!
+//! Folds a pool's historical events, in transaction order, into share balances, proposal history
+//! and burn totals. Every event that mints, burns, moves or rescales pool shares is folded in, so
+//! `total_shares` tracks the pool's (donor balances plus tokenized and queued shares), including
+//! legacy pools' `migrate_share_scale` rescaling. This is meant for audits and for rebuilding
+//! state whose accounts have since been closed (Donors, archived Proposals).
+//!
+//! Input is the raw event payload: 8-byte discriminator followed by the borsh body, i.e. the
+//! base64-decoded `Program data:` log line, or `emit_cpi!` inner-instruction data with the
+//! leading event-CPI tag removed.
+
+use std::collections::BTreeMap;
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use crate::{
+    BurnAuctionFilledEvent, BuybackHeldEvent, DonationEvent, DonationMatchedEvent, DustConsolidatedEvent,
+    ProposalAbortedEvent, ProposalCreatedEvent, ProposalExecutedEvent, ProposalFailedEvent, RageQuitEvent,
+    ReferralRewardEvent, ShareScaleMigratedEvent, ShareTokensRedeemedEvent, SharesTokenizedEvent,
+    SunsetDistributionEvent,
+    TokenBurnEvent, VoteCastEvent, VoteChoice, WithdrawalEvent, WithdrawalQueueProcessedEvent,
+    WithdrawalQueuedEvent, WithdrawalRequestCancelledEvent,
+};
+
+/// Outcome of a replayed proposal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReplayedStatus {
+    Active,
+    Executed,
+    Failed,
+    Aborted,
+}
+
+/// Proposal history rebuilt from events.
+#[derive(Clone, Debug)]
+pub struct ReplayedProposal {
+    pub proposer: Pubkey,
+    pub requested_lamports: u64,
+    pub locked_pool_tokens: u64,
+    pub created_at_ts: i64,
+    pub yes_weight: u64,
+    pub no_weight: u64,
+    pub abstain_weight: u64,
+    pub status: ReplayedStatus,
+    pub lamports_paid: u64,
+    pub protocol_fee: u64,
+    pub finalized_ts: i64,
+}
+
+/// Pool state reconstructed from its event stream.
+#[derive(Default)]
+pub struct PoolReplay {
+    pub pool: Pubkey,
+    pub shares: BTreeMap<Pubkey, u64>,
+    pub total_shares: u64,
+    /// Shares held as share tokens (out of every Donor, still in `total_shares`).
+    pub tokenized_shares: u64,
+    /// Shares parked in withdrawal-queue requests (out of every Donor, still in `total_shares`).
+    pub queued_shares: u64,
+    pub proposals: BTreeMap<Pubkey, ReplayedProposal>,
+    /// Proposal keys in creation order.
+    pub proposal_order: Vec<Pubkey>,
+    pub ctop_burned: u64,
+    pub ctop_held: u64,
+    pub sol_spent_on_buybacks: u64,
+    pub events_applied: u64,
+    // latest (choice, weight) per (proposal, voter); re-votes replace rather than add
+    votes: BTreeMap<(Pubkey, Pubkey), (VoteChoice, u64)>,
+}
+
+impl PoolReplay {
+    pub fn new(pool: Pubkey) -> Self {
+        Self {
+            pool,
+            ..Self::default()
+        }
+    }
+
+    /// Applies one event payload. Returns false for unknown events and other pools' events.
+    pub fn apply(&mut self, data: &[u8]) -> bool {
+        let applied = self.apply_inner(data);
+        if applied {
+            self.events_applied = self.events_applied.saturating_add(1);
+        }
+        applied
+    }
+
+    /// Applies payloads in order; returns how many were applied.
+    pub fn apply_all<'a>(&mut self, events: impl IntoIterator<Item = &'a [u8]>) -> u64 {
+        events.into_iter().filter(|data| self.apply(data)).count() as u64
+    }
+
+    /// Shares held by `donor` after the replayed events.
+    pub fn shares_of(&self, donor: &Pubkey) -> u64 {
+        self.shares.get(donor).copied().unwrap_or(0)
+    }
+
+    fn apply_inner(&mut self, data: &[u8]) -> bool {
+        if let Some(e) = decode::<DonationEvent>(data) {
+            if e.pool != self.pool {
+                return false;
+            }
+            self.mint_shares(e.donor, e.shares_minted);
+            return true;
+        }
+        if let Some(e) = decode::<DonationMatchedEvent>(data) {
+            if e.pool != self.pool {
+                return false;
+            }
+            self.mint_shares(e.donor, e.matched_shares);
+            return true;
+        }
+        if let Some(e) = decode::<ReferralRewardEvent>(data) {
+            if e.pool != self.pool {
+                return false;
+            }
+            self.mint_shares(e.referrer, e.bonus_shares);
+            return true;
+        }
+        if let Some(e) = decode::<WithdrawalEvent>(data) {
+            if e.pool != self.pool {
+                return false;
+            }
+            self.burn_shares(e.donor, e.shares_burned);
+            return true;
+        }
+        if let Some(e) = decode::<DustConsolidatedEvent>(data) {
+            if e.pool != self.pool {
+                return false;
+            }
+            self.burn_shares(e.donor, e.shares_burned);
+            return true;
+        }
+        if let Some(e) = decode::<RageQuitEvent>(data) {
+            if e.pool != self.pool {
+                return false;
+            }
+            self.burn_shares(e.donor, e.shares_burned);
+            return true;
+        }
+        if let Some(e) = decode::<SunsetDistributionEvent>(data) {
+            if e.pool != self.pool {
+                return false;
+            }
+            self.burn_shares(e.donor, e.shares_burned);
+            return true;
+        }
+        if let Some(e) = decode::<SharesTokenizedEvent>(data) {
+            if e.pool != self.pool {
+                return false;
+            }
+            self.take_shares(e.wallet, e.shares);
+            self.tokenized_shares = self.tokenized_shares.saturating_add(e.shares);
+            return true;
+        }
+        if let Some(e) = decode::<ShareTokensRedeemedEvent>(data) {
+            if e.pool != self.pool {
+                return false;
+            }
+            self.tokenized_shares = self.tokenized_shares.saturating_sub(e.shares);
+            self.give_shares(e.wallet, e.shares);
+            return true;
+        }
+        if let Some(e) = decode::<WithdrawalQueuedEvent>(data) {
+            if e.pool != self.pool {
+                return false;
+            }
+            self.take_shares(e.owner, e.shares);
+            self.queued_shares = self.queued_shares.saturating_add(e.shares);
+            return true;
+        }
+        if let Some(e) = decode::<WithdrawalRequestCancelledEvent>(data) {
+            if e.pool != self.pool {
+                return false;
+            }
+            self.queued_shares = self.queued_shares.saturating_sub(e.shares);
+            self.give_shares(e.owner, e.shares);
+            return true;
+        }
+        if let Some(e) = decode::<WithdrawalQueueProcessedEvent>(data) {
+            if e.pool != self.pool {
+                return false;
+            }
+            // Cancelled entries are processed with zero shares.
+            self.queued_shares = self.queued_shares.saturating_sub(e.shares);
+            self.total_shares = self.total_shares.saturating_sub(e.shares);
+            return true;
+        }
+        if let Some(e) = decode::<ShareScaleMigratedEvent>(data) {
+            if e.pool != self.pool {
+                return false;
+            }
+            // Legacy (1x) balances are multiplied up when the pool, then each donor, is migrated.
+            self.total_shares = self.total_shares.saturating_mul(e.pool_factor);
+            if let Some(balance) = self.shares.get_mut(&e.wallet) {
+                *balance = balance.saturating_mul(e.donor_factor);
+            }
+            return true;
+        }
+        if let Some(e) = decode::<ProposalCreatedEvent>(data) {
+            if e.pool != self.pool {
+                return false;
+            }
+            self.proposal_order.push(e.proposal);
+            self.proposals.insert(
+                e.proposal,
+                ReplayedProposal {
+                    proposer: e.proposer,
+                    requested_lamports: e.requested_lamports,
+                    locked_pool_tokens: e.locked_pool_tokens,
+                    created_at_ts: e.timestamp,
+                    yes_weight: 0,
+                    no_weight: 0,
+                    abstain_weight: 0,
+                    status: ReplayedStatus::Active,
+                    lamports_paid: 0,
+                    protocol_fee: 0,
+                    finalized_ts: 0,
+                },
+            );
+            return true;
+        }
+        if let Some(e) = decode::<VoteCastEvent>(data) {
+            if e.pool != self.pool {
+                return false;
+            }
+            if let Some((choice, weight)) = self.votes.insert((e.proposal, e.voter), (e.choice, e.weight)) {
+                self.tally(e.proposal, choice, weight, false);
+            }
+            self.tally(e.proposal, e.choice, e.weight, true);
+            return true;
+        }
+        if let Some(e) = decode::<ProposalExecutedEvent>(data) {
+            if e.pool != self.pool {
+                return false;
+            }
+            if let Some(p) = self.proposals.get_mut(&e.proposal) {
+                p.status = ReplayedStatus::Executed;
+                p.lamports_paid = e.lamports_paid;
+                p.protocol_fee = e.protocol_fee;
+                p.finalized_ts = e.timestamp;
+            }
+            return true;
+        }
+        if let Some(e) = decode::<ProposalFailedEvent>(data) {
+            if e.pool != self.pool {
+                return false;
+            }
+            self.finalize(e.proposal, ReplayedStatus::Failed, e.timestamp);
+            return true;
+        }
+        if let Some(e) = decode::<ProposalAbortedEvent>(data) {
+            if e.pool != self.pool {
+                return false;
+            }
+            self.finalize(e.proposal, ReplayedStatus::Aborted, e.timestamp);
+            return true;
+        }
+        if let Some(e) = decode::<TokenBurnEvent>(data) {
+            if e.pool != self.pool {
+                return false;
+            }
+            self.ctop_burned = self.ctop_burned.saturating_add(e.amount_ctop);
+            self.sol_spent_on_buybacks = self.sol_spent_on_buybacks.saturating_add(e.amount_sol);
+            return true;
+        }
+        if let Some(e) = decode::<BuybackHeldEvent>(data) {
+            if e.pool != self.pool {
+                return false;
+            }
+            self.ctop_held = e.held_ctop_total;
+            self.sol_spent_on_buybacks = self.sol_spent_on_buybacks.saturating_add(e.amount_sol);
+            return true;
+        }
+        if let Some(e) = decode::<BurnAuctionFilledEvent>(data) {
+            if e.pool != self.pool {
+                return false;
+            }
+            self.ctop_burned = self.ctop_burned.saturating_add(e.amount_ctop);
+            self.sol_spent_on_buybacks = self.sol_spent_on_buybacks.saturating_add(e.amount_sol);
+            return true;
+        }
+        false
+    }
+
+    fn mint_shares(&mut self, donor: Pubkey, shares: u64) {
+        self.give_shares(donor, shares);
+        self.total_shares = self.total_shares.saturating_add(shares);
+    }
+
+    /// Adds `shares` to a donor's balance without changing `total_shares`.
+    fn give_shares(&mut self, donor: Pubkey, shares: u64) {
+        let balance = self.shares.entry(donor).or_default();
+        *balance = balance.saturating_add(shares);
+    }
+
+    fn burn_shares(&mut self, donor: Pubkey, shares: u64) {
+        self.take_shares(donor, shares);
+        self.total_shares = self.total_shares.saturating_sub(shares);
+    }
+
+    /// Takes `shares` out of a donor's balance without changing `total_shares`.
+    fn take_shares(&mut self, donor: Pubkey, shares: u64) {
+        let balance = self.shares.entry(donor).or_default();
+        *balance = balance.saturating_sub(shares);
+        if *balance == 0 {
+            self.shares.remove(&donor);
+        }
+    }
+
+    fn tally(&mut self, proposal: Pubkey, choice: VoteChoice, weight: u64, add: bool) {
+        let Some(p) = self.proposals.get_mut(&proposal) else {
+            return;
+        };
+        let slot = match choice {
+            VoteChoice::Yes => &mut p.yes_weight,
+            VoteChoice::No => &mut p.no_weight,
+            VoteChoice::Abstain => &mut p.abstain_weight,
+            VoteChoice::Abort => return,
+        };
+        *slot = if add { slot.saturating_add(weight) } else { slot.saturating_sub(weight) };
+    }
+
+    fn finalize(&mut self, proposal: Pubkey, status: ReplayedStatus, ts: i64) {
+        if let Some(p) = self.proposals.get_mut(&proposal) {
+            p.status = status;
+            p.finalized_ts = ts;
+        }
+    }
+}
+
+fn decode<T: AnchorDeserialize + Discriminator>(data: &[u8]) -> Option<T> {
+    let body = data.strip_prefix(T::DISCRIMINATOR)?;
+    T::try_from_slice(body).ok()
+}